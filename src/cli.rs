@@ -1,7 +1,9 @@
+use anyhow::{bail, Context, Result};
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 use crate::commands::{
-    add, cat_file, checkout, commit, commit_tree, hash_object, init, log, ls_files, ls_tree, merge, rev_parse, rm, show_ref, status, write_tree
+    add, blame, cat_file, check_ignore, checkout, cherry_pick, clean, clone, commit, commit_tree, config, count_objects, describe, diff, export, fetch, fsck, gc, hash_object, init, log, ls_files, ls_tree, merge, mv, push, reflog, remote, reset, restore, rev_list, rev_parse, revert, rm, show, show_ref, stash, status, switch, tag, write_tree
 };
 
 #[derive(Parser)]
@@ -12,9 +14,66 @@ use crate::commands::{
     about = "A Git implementation in Rust like Guts"
 )]
 pub struct Cli {
+    /// Run as if guts was started in this directory
+    #[arg(short = 'C', value_name = "path")]
+    pub chdir: Option<PathBuf>,
+
+    /// The `.git` directory of the repository
+    #[arg(long, value_name = "path")]
+    pub git_dir: Option<PathBuf>,
+
+    /// The working tree of the repository
+    #[arg(long, value_name = "path")]
+    pub work_tree: Option<PathBuf>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
+
+impl Cli {
+    /// Moves the process to where `-C`, `--git-dir` and `--work-tree` say the repository
+    /// is, so that every command finds it from the current directory. `-C` applies
+    /// first, like in git. A guts repository always is `<work tree>/.git`: the two other
+    /// flags have to agree with that.
+    pub fn enter_repository(&self) -> Result<()> {
+        if let Some(path) = &self.chdir {
+            std::env::set_current_dir(path)
+                .with_context(|| format!("fatal: cannot change to '{}'", path.display()))?;
+        }
+
+        // Relative to the directory guts runs in, before moving to the working tree
+        let git_dir = match &self.git_dir {
+            Some(path) => Some(
+                path.canonicalize()
+                    .with_context(|| format!("fatal: not a git repository: '{}'", path.display()))?,
+            ),
+            None => None,
+        };
+        let work_tree = match (&self.work_tree, &git_dir) {
+            (Some(path), _) => path.clone(),
+            (None, Some(git_dir)) if git_dir.file_name().is_some_and(|name| name == ".git") => {
+                git_dir.parent().map(PathBuf::from).unwrap_or_default()
+            }
+            (None, Some(git_dir)) => {
+                bail!("fatal: '{}' is not the .git directory of a working tree", git_dir.display())
+            }
+            (None, None) => return Ok(()),
+        };
+
+        std::env::set_current_dir(&work_tree)
+            .with_context(|| format!("fatal: cannot change to '{}'", work_tree.display()))?;
+        if let Some(git_dir) = git_dir {
+            if std::env::current_dir()?.join(".git").canonicalize().ok() != Some(git_dir.clone()) {
+                bail!(
+                    "fatal: '{}' is not the .git directory of '{}'",
+                    git_dir.display(),
+                    work_tree.display()
+                );
+            }
+        }
+        Ok(())
+    }
+}
 /// we add the functions we're going to call and put in the main.rs commands
 #[derive(Subcommand)]
 pub enum Commands {
@@ -66,6 +125,81 @@ pub enum Commands {
     // Merge 2 branch together
     Merge(merge::MergeArgs),
 
+    /// Show changes between HEAD and the working tree or index
+    Diff(diff::DiffArgs),
+
+    /// Reset the current branch to a commit
+    Reset(reset::ResetArgs),
+
+    /// Get and set repository options
+    Config(config::ConfigArgs),
+
+    /// Show a commit and the changes it introduced
+    Show(show::ShowArgs),
+
+    /// Show the movements of HEAD or a branch
+    Reflog(reflog::ReflogArgs),
+
+    /// Create a commit undoing the changes of another commit
+    Revert(revert::RevertArgs),
+
+    /// Apply the changes of a commit on top of HEAD
+    CherryPick(cherry_pick::CherryPickArgs),
+
+    /// Move or rename a tracked file
+    Mv(mv::MvArgs),
+
+    /// Remove untracked files from the working tree
+    Clean(clean::CleanArgs),
+
+    /// Save working tree changes away and restore them later
+    Stash(stash::StashArgs),
+
+    /// Copy a local repository into a new directory
+    Clone(clone::CloneArgs),
+
+    /// Name a commit after the nearest tag
+    Describe(describe::DescribeArgs),
+
+    /// Show the commit that introduced each line of a file
+    Blame(blame::BlameArgs),
+
+    /// Remove unreachable loose objects
+    Gc(gc::GcArgs),
+
+    /// Verify the integrity of the object store
+    Fsck(fsck::FsckArgs),
+
+    /// Switch to another branch / create a new branch with -c
+    Switch(switch::SwitchArgs),
+
+    /// Restore files to their committed version
+    Restore(restore::RestoreArgs),
+
+    /// List, add or remove remote repositories
+    Remote(remote::RemoteArgs),
+
+    /// Download objects and branches from a local remote
+    Fetch(fetch::FetchArgs),
+
+    /// Upload a branch and its objects to a local remote
+    Push(push::PushArgs),
+
+    /// List tags, or create a lightweight or annotated (-a -m) tag
+    Tag(tag::TagArgs),
+
+    /// Count the loose objects and their size on disk
+    CountObjects(count_objects::CountObjectsArgs),
+
+    /// List the commits reachable from a commit
+    RevList(rev_list::RevListArgs),
+
+    /// Show which ignore rule matches each path
+    CheckIgnore(check_ignore::CheckIgnoreArgs),
+
+    /// Check out a commit into another directory
+    Export(export::ExportArgs),
+
     /// Launch graphical terminal UI
     Tui,
 }
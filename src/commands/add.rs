@@ -2,7 +2,11 @@ use crate::core::{ignore::IgnoreMatcher, simple_index};
 use anyhow::{anyhow, Result};
 use clap::Args;
 use std::fs;
-use std::path::{PathBuf};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// Below this many files `add` is quick enough not to show its progress
+const PROGRESS_THRESHOLD: usize = 200;
 
 /// Arguments for the `guts add` command
 #[derive(Args)]
@@ -11,16 +15,24 @@ pub struct AddArgs {
     #[arg(required = true)]
     pub files: Vec<PathBuf>,
 
-    /// Current directory for the operation (injected by TUI)
-    #[arg(last = true)]
-    pub dir: Option<PathBuf>,
+    /// Only show what would be added, leave the index untouched
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+
+    /// Also list the files skipped because they are ignored
+    #[arg(short = 'v', long)]
+    pub verbose: bool,
+
+    /// Do not show the progress of large additions
+    #[arg(short = 'q', long)]
+    pub quiet: bool,
 }
 
 /// Recursively collect all files from a directory (excludes .git)
 fn collect_files_recursively(dir: &PathBuf) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
 
-    if dir.is_file() {
+    if dir.is_file() || simple_index::is_symlink(dir) {
         files.push(dir.clone());
         return Ok(files);
     }
@@ -35,7 +47,8 @@ fn collect_files_recursively(dir: &PathBuf) -> Result<Vec<PathBuf>> {
             continue;
         }
 
-        if path.is_file() {
+        // Symlinks are staged as links, never followed
+        if path.is_file() || simple_index::is_symlink(&path) {
             files.push(path);
         } else if path.is_dir() {
             let mut sub_files = collect_files_recursively(&path)?;
@@ -46,88 +59,121 @@ fn collect_files_recursively(dir: &PathBuf) -> Result<Vec<PathBuf>> {
     Ok(files)
 }
 
+/// Path as the user would type it: relative to the current directory
+fn display_path(path: &Path, current_dir: &Path) -> String {
+    path.strip_prefix(current_dir).unwrap_or(path).display().to_string()
+}
+
 /// Main function for the `guts add` command
 /// Adds files to the staging area (index)
 pub fn run(args: &AddArgs) -> Result<String> {
-    // Set current directory context for TUI
-    let original_dir = std::env::current_dir()?;
-    if let Some(dir) = &args.dir {
-        std::env::set_current_dir(dir)?;
+    // Check if we're in a git repository
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
     }
-    
-    let result = || -> Result<String> {
-        // Check if we're in a git repository
-        if !simple_index::is_git_repository()? {
-            return Err(anyhow!("fatal: not a git repository"));
-        }
-
-        let mut added_files = Vec::new();
-        let mut output = String::new();
-        let current_dir = std::env::current_dir()?;
-
-        // Load .gutsignore matcher
-        let matcher = IgnoreMatcher::from_gutsignore(&current_dir)
-            .unwrap_or_else(|_| IgnoreMatcher::empty());
-
-        // Process each requested file
-        for file_path in &args.files {
-            // Support for "." - add all files from current directory
-            if file_path.to_string_lossy() == "." {
-                let files = collect_files_recursively(&current_dir)?;
-                for file in files {
-                    if matcher.is_ignored(&file, &current_dir) {
-                        continue;
-                    }
-                    simple_index::add_file_to_index(&file)?;
-                    added_files.push(file.display().to_string());
-                }
-                continue;
-            }
 
-            // Basic checks
-            if !file_path.exists() {
+    let mut added_files = Vec::new();
+    let mut to_stage = Vec::new();
+    let mut skipped = Vec::new();
+    let mut output = String::new();
+    let current_dir = std::env::current_dir()?;
+
+    // Load .gutsignore matcher
+    let matcher = IgnoreMatcher::from_gutsignore(&current_dir)
+        .unwrap_or_else(|_| IgnoreMatcher::empty());
+
+    // Process each requested file
+    for file_path in &args.files {
+        // Support for "." - add all files from current directory
+        let candidates = if file_path.to_string_lossy() == "." {
+            collect_files_recursively(&current_dir)?
+        } else {
+            // Basic checks (a dangling symlink still exists as a link)
+            if !file_path.exists() && !simple_index::is_symlink(file_path) {
                 return Err(anyhow!(
                     "pathspec '{}' did not match any files",
                     file_path.display()
                 ));
             }
 
-            if file_path.is_dir() {
+            if file_path.is_dir() && !simple_index::is_symlink(file_path) {
                 // If it's a directory, add all files recursively
-                let files = collect_files_recursively(file_path)?;
-                for file in files {
-                    if matcher.is_ignored(&file, &current_dir) {
-                        continue;
-                    }
-                    simple_index::add_file_to_index(&file)?;
-                    added_files.push(file.display().to_string());
-                }
+                collect_files_recursively(file_path)?
             } else {
-                // Skip if ignored
-                if matcher.is_ignored(file_path, &current_dir) {
-                    continue;
-                }
-                // Add the file to the JSON index
-                simple_index::add_file_to_index(file_path)?;
-                added_files.push(file_path.display().to_string());
+                vec![file_path.clone()]
             }
-        }
+        };
 
-        // Confirmation message
-        if added_files.len() == 1 {
-            output.push_str(&format!("Added: {}", added_files[0]));
-        } else {
-            output.push_str(&format!("Added {} files:", added_files.len()));
-            for file in &added_files {
-                output.push_str(&format!("\n  - {}", file));
+        for file in candidates {
+            if matcher.is_ignored(&file, &current_dir) {
+                skipped.push(file);
+                continue;
             }
+            added_files.push(file.display().to_string());
+            to_stage.push(file);
+        }
+    }
+
+    if args.verbose {
+        for file in &skipped {
+            output.push_str(&format!("skip '{}' (ignored)\n", display_path(file, &current_dir)));
         }
+    }
+
+    if args.dry_run {
+        for file in &to_stage {
+            output.push_str(&format!("add '{}'\n", display_path(file, &current_dir)));
+        }
+        return Ok(output);
+    }
+
+    // An `Adding N/M files` line rewritten in place on stderr, only for a person watching
+    let total = to_stage.len();
+    let show_progress = shows_progress(args.quiet, total, std::io::stderr().is_terminal());
+    let progress = |done: usize| {
+        if show_progress && (done.is_multiple_of(64) || done == total) {
+            let mut stderr = std::io::stderr().lock();
+            let _ = write!(stderr, "\rAdding {}/{} files", done, total);
+            let _ = stderr.flush();
+        }
+    };
+
+    // Hash everything in parallel and write the JSON index once
+    let mut index = simple_index::SimpleIndex::load()?;
+    index.add_files_with_progress(&to_stage, progress)?;
+    index.save()?;
+    if show_progress {
+        eprintln!();
+    }
+
+    // Confirmation message
+    if added_files.len() == 1 {
+        output.push_str(&format!("Added: {}", added_files[0]));
+    } else {
+        output.push_str(&format!("Added {} files:", added_files.len()));
+        for file in &added_files {
+            output.push_str(&format!("\n  - {}", file));
+        }
+    }
+
+    Ok(output)
+}
 
-        Ok(output)
-    }();
-    
-    // Restore original directory
-    std::env::set_current_dir(&original_dir)?;
-    
-    result
+/// Whether `add` reports its progress: not with --quiet, not for a few files,
+/// and only when stderr is a terminal
+fn shows_progress(quiet: bool, total: usize, is_terminal: bool) -> bool {
+    !quiet && total > PROGRESS_THRESHOLD && is_terminal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_only_for_large_additions_on_a_terminal() {
+        assert!(shows_progress(false, PROGRESS_THRESHOLD + 1, true));
+        assert!(!shows_progress(false, PROGRESS_THRESHOLD, true));
+        assert!(!shows_progress(true, PROGRESS_THRESHOLD + 1, true));
+        assert!(!shows_progress(false, PROGRESS_THRESHOLD + 1, false));
+    }
 }
@@ -0,0 +1,141 @@
+use crate::commands::diff::read_blob;
+use crate::commands::log::parse_timezone;
+use crate::core::cat;
+use crate::core::diff::{diff_lines, DiffLine};
+use crate::core::object::Commit;
+use crate::core::resolve_parse::resolve_ref;
+use crate::core::simple_index;
+use anyhow::{anyhow, Result};
+use chrono::{FixedOffset, TimeZone};
+use clap::Args;
+use std::path::{Path, PathBuf};
+
+/// Arguments for the `guts blame` command
+#[derive(Args)]
+pub struct BlameArgs {
+    /// File to annotate, as committed in HEAD
+    pub file: PathBuf,
+}
+
+/// Main function for the `guts blame` command
+/// Shows, for each line of a file in HEAD, the commit that introduced it.
+/// Follows first parents only and matches lines exactly (no rename detection).
+pub fn run(args: &BlameArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    let repo_root = simple_index::find_repo_root()?;
+    let git_dir = repo_root.join(".git");
+    let path = std::env::current_dir()?
+        .join(&args.file)
+        .strip_prefix(&repo_root)
+        .map_err(|_| anyhow!("fatal: '{}' is outside repository", args.file.display()))?
+        .to_string_lossy()
+        .to_string();
+
+    let head = resolve_ref(&git_dir, "HEAD")
+        .map_err(|_| anyhow!("fatal: no commits yet"))?;
+    let mut commit = cat::read_commit(&git_dir, &head)?;
+    let content = file_at(&git_dir, &commit, &path)?
+        .ok_or_else(|| anyhow!("fatal: no such path '{}' in HEAD", path))?;
+    let final_lines: Vec<String> = content.lines().map(str::to_string).collect();
+
+    // Commit each final line comes from, once known
+    let mut owners: Vec<Option<(String, Commit)>> = vec![None; final_lines.len()];
+    // Text of the file in `commit`, each line with its index in the final version
+    let mut current: Vec<(String, Option<usize>)> = final_lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| (line.clone(), Some(i)))
+        .collect();
+    let mut sha = head;
+
+    while current.iter().any(|(_, origin)| origin.is_some()) {
+        let parent_sha = commit.parent.as_ref().and_then(|parents| parents.first()).cloned();
+        let parent = parent_sha.as_deref().map(|p| cat::read_commit(&git_dir, p)).transpose()?;
+        let parent_content = match &parent {
+            Some(parent) => file_at(&git_dir, parent, &path)?,
+            None => None,
+        };
+
+        let Some(parent_content) = parent_content else {
+            // The file did not exist before: every remaining line comes from this commit
+            for (_, origin) in &current {
+                if let Some(i) = origin {
+                    owners[*i] = Some((sha.clone(), commit.clone()));
+                }
+            }
+            break;
+        };
+
+        let parent_lines: Vec<&str> = parent_content.lines().collect();
+        let current_lines: Vec<&str> = current.iter().map(|(line, _)| line.as_str()).collect();
+        let mut previous = Vec::with_capacity(parent_lines.len());
+        let mut position = 0;
+        for line in diff_lines(&parent_lines, &current_lines) {
+            match line {
+                // Unchanged: the parent gets the blame for it
+                DiffLine::Context(text) => {
+                    previous.push((text.to_string(), current[position].1));
+                    position += 1;
+                }
+                // Added by this commit
+                DiffLine::Added(_) => {
+                    if let Some(i) = current[position].1 {
+                        owners[i] = Some((sha.clone(), commit.clone()));
+                    }
+                    position += 1;
+                }
+                DiffLine::Removed(text) => previous.push((text.to_string(), None)),
+            }
+        }
+
+        current = previous;
+        sha = parent_sha.unwrap_or_default();
+        commit = parent.ok_or_else(|| anyhow!("missing parent commit"))?;
+    }
+
+    let author_width = owners
+        .iter()
+        .flatten()
+        .map(|(_, commit)| author_name(&commit.author).len())
+        .max()
+        .unwrap_or(0);
+    let mut output = String::new();
+    for (line, owner) in final_lines.iter().zip(&owners) {
+        let (sha, commit) = owner.as_ref().ok_or_else(|| anyhow!("line '{}' could not be blamed", line))?;
+        output.push_str(&format!(
+            "{} ({:<width$} {}) {}\n",
+            cat::shortest_unique_prefix(&git_dir, sha, 7)?,
+            author_name(&commit.author),
+            format_date(commit.author_date, &commit.author_timezone),
+            line,
+            width = author_width,
+        ));
+    }
+
+    Ok(output)
+}
+
+/// Content of `path` in a commit, None when the commit does not have it
+fn file_at(git_dir: &Path, commit: &Commit, path: &str) -> Result<Option<String>> {
+    let files = simple_index::get_files_from_tree(git_dir, &commit.tree, "")?;
+    match files.get(path) {
+        Some(blob_sha) => Ok(Some(String::from_utf8_lossy(&read_blob(git_dir, blob_sha)?).into_owned())),
+        None => Ok(None),
+    }
+}
+
+/// `Name` out of `Name <email>`
+fn author_name(identity: &str) -> &str {
+    identity.split(" <").next().unwrap_or(identity)
+}
+
+/// `2024-01-31 12:00:00 +0100`, in the commit's own timezone
+fn format_date(timestamp: i64, timezone: &str) -> String {
+    let offset = parse_timezone(timezone).unwrap_or(FixedOffset::east_opt(0).unwrap());
+    match offset.timestamp_opt(timestamp, 0).single() {
+        Some(date) => format!("{} {}", date.format("%Y-%m-%d %H:%M:%S"), timezone),
+        None => format!("{} {}", timestamp, timezone),
+    }
+}
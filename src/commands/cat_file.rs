@@ -1,49 +1,83 @@
 use crate::core::cat;
 use crate::core::cat::ParsedObject;
-use anyhow::{anyhow, Context, Result};
+use crate::core::hash;
+use crate::core::object::GitObject;
+use crate::core::simple_index;
+use anyhow::{anyhow, Result};
 use clap::Args;
-use std::env;
-use std::fs;
+use std::io::Read;
+use std::path::Path;
 
 #[derive(Args)]
 pub struct CatFileArgs {
-    pub sha: String,
-    /// Current directory for the operation (injected by TUI)
-    pub dir: Option<std::path::PathBuf>,
+    /// Show the object type
+    #[arg(short = 't', conflicts_with_all = ["size", "pretty"])]
+    pub show_type: bool,
+
+    /// Show the object size
+    #[arg(short = 's', conflicts_with = "pretty")]
+    pub size: bool,
+
+    /// Pretty-print the object content (default)
+    #[arg(short = 'p')]
+    pub pretty: bool,
+
+    /// Read SHAs from stdin, one per line, and print each object framed by a
+    /// `<sha> <type> <size>` header line
+    #[arg(long, conflicts_with_all = ["show_type", "size", "pretty"])]
+    pub batch: bool,
+
+    #[arg(required_unless_present = "batch")]
+    pub sha: Option<String>,
 }
 
 pub fn run(args: &CatFileArgs) -> Result<String> {
-    let sha = &args.sha;
-
-    if sha.len() < 4 {
-        return Err(anyhow!("SHA is too small (need at least 4 characters)"));
+    if args.batch {
+        return Ok(String::from_utf8_lossy(&run_batch(args)?).into_owned());
     }
+    let sha = args.sha.as_ref().ok_or_else(|| anyhow!("fatal: an object name is required"))?;
 
-    // Determine current directory to use
-    let current_dir = args
-        .dir
-        .clone()
-        .unwrap_or_else(|| env::current_dir().expect("Failed to get current directory"));
-    let git_dir = current_dir.join(".git");
+    let sha = hash::normalize_sha(sha)?;
 
-    if !git_dir.exists() {
-        return Err(anyhow!("no git directory found in current path"));
-    }
+    // The object store of the repository containing the current directory
+    let git_dir = simple_index::find_repo_root()
+        .map_err(|_| anyhow!("fatal: not a git repository"))?
+        .join(".git");
+
+    // Abbreviated SHAs are expanded to the one object they name
+    let sha = if sha.len() < 40 {
+        cat::resolve_object_prefix(&git_dir, &sha)?
+    } else {
+        sha
+    };
 
-    let object_path = cat::get_object_path(&git_dir, sha);
-    let content = fs::read(&object_path)
-        .with_context(|| format!("Failed to read object file at {}", object_path.display()))?;
+    let decompressed = cat::read_raw_object(&git_dir, &sha)?;
+
+    // -t and -s only need the header, the content is never parsed
+    if args.show_type || args.size {
+        let header = cat::parse_header(&decompressed)?;
+        let value = if args.show_type {
+            header.obj_type
+        } else {
+            header.size.to_string()
+        };
+        return Ok(format!("{}\n", value));
+    }
 
-    let decompressed = decompress_object(&content)?;
     let result = match cat::parse_object(&decompressed)? {
         ParsedObject::Tree(entries) => entries
             .iter()
             .map(|entry| {
                 let hash_hex: String = entry.hash.iter().map(|b| format!("{:02x}", b)).collect();
-                format!("{} {} {}", entry.mode, entry.name, hash_hex)
+                // Like git: <mode> <type> <hash>\t<name>, the mode padded to six digits
+                let object_type = match entry.mode.trim_start_matches('0') {
+                    "40000" => "tree",
+                    "160000" => "commit",
+                    _ => "blob",
+                };
+                format!("{:0>6} {} {}\t{}\n", entry.mode, object_type, hash_hex, entry.name)
             })
-            .collect::<Vec<String>>()
-            .join("\n"),
+            .collect::<String>(),
         ParsedObject::Blob(data) => String::from_utf8_lossy(&data).to_string(),
         ParsedObject::Commit(data) => {
             let mut out = String::new();
@@ -53,8 +87,14 @@ pub fn run(args: &CatFileArgs) -> Result<String> {
                     out += &format!("parent {}\n", p);
                 }
             }
-            out += &format!("author {} {} +0000\n", data.author, data.author_date);
-            out += &format!("committer {} {} +0000\n", data.committer, data.committer_date);
+            out += &format!(
+                "author {} {} {}\n",
+                data.author, data.author_date, data.author_timezone
+            );
+            out += &format!(
+                "committer {} {} {}\n",
+                data.committer, data.committer_date, data.committer_timezone
+            );
             out += "\n";
             out += &data.message;
             if !data.message.ends_with('\n') {
@@ -62,6 +102,8 @@ pub fn run(args: &CatFileArgs) -> Result<String> {
             }
             out
         }
+        // The tag object is already text in the pretty format
+        ParsedObject::Tag(tag) => String::from_utf8_lossy(&tag.content()).to_string(),
         ParsedObject::Other(obj_type, _) => {
             format!("Unsupported object type: {}", obj_type)
         }
@@ -70,12 +112,48 @@ pub fn run(args: &CatFileArgs) -> Result<String> {
     Ok(result)
 }
 
-fn decompress_object(data: &[u8]) -> Result<Vec<u8>> {
-    use std::io::Read;
-    let mut decoder = flate2::read::ZlibDecoder::new(data);
-    let mut decompressed = Vec::new();
-    match decoder.read_to_end(&mut decompressed) {
-        Ok(_) => Ok(decompressed),
-        Err(_) => Ok(data.to_vec()), // If decompression fails, assume data is already uncompressed
+/// `guts cat-file --batch`: answers every SHA read from stdin in one process.
+/// Returns raw bytes so that binary blobs keep their exact size.
+pub fn run_batch(_args: &CatFileArgs) -> Result<Vec<u8>> {
+    let git_dir = simple_index::find_repo_root()
+        .map_err(|_| anyhow!("fatal: not a git repository"))?
+        .join(".git");
+
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    Ok(batch_output(&git_dir, &input))
+}
+
+/// For each requested object: `<sha> <type> <size>\n<content>\n`, or
+/// `<name> missing\n` when it cannot be found
+fn batch_output(git_dir: &Path, input: &str) -> Vec<u8> {
+    let mut output = Vec::new();
+    for name in input.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let found = || -> Result<(String, Vec<u8>)> {
+            let sha = hash::normalize_sha(name)?;
+            let sha = if sha.len() < 40 {
+                cat::resolve_object_prefix(git_dir, &sha)?
+            } else {
+                sha
+            };
+            Ok((sha.clone(), cat::read_raw_object(git_dir, &sha)?))
+        }();
+
+        let (sha, raw) = match found {
+            Ok(found) => found,
+            Err(_) => {
+                output.extend_from_slice(format!("{} missing\n", name).as_bytes());
+                continue;
+            }
+        };
+        match cat::parse_header(&raw) {
+            Ok(header) => {
+                output.extend_from_slice(format!("{} {} {}\n", sha, header.obj_type, header.size).as_bytes());
+                output.extend_from_slice(&raw[header.body_start..]);
+                output.push(b'\n');
+            }
+            Err(_) => output.extend_from_slice(format!("{} missing\n", name).as_bytes()),
+        }
     }
+    output
 }
@@ -0,0 +1,46 @@
+use crate::core::{ignore::IgnoreMatcher, simple_index};
+use anyhow::{anyhow, Result};
+use clap::Args;
+
+/// Arguments for the `guts check-ignore` command
+#[derive(Args)]
+pub struct CheckIgnoreArgs {
+    /// Paths to check
+    #[arg(required = true)]
+    pub paths: Vec<String>,
+
+    /// Also print the ignore file, line number and pattern that matched
+    #[arg(short = 'v', long)]
+    pub verbose: bool,
+}
+
+/// Main function for the `guts check-ignore` command
+/// Prints the given paths that are ignored. With `-v` every matching rule is shown as
+/// `<source>:<line>:<pattern>\t<path>`, `!` patterns included, like git.
+pub fn run(args: &CheckIgnoreArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    let repo_root = simple_index::find_repo_root()?;
+    let current_dir = std::env::current_dir()?;
+    let matcher = IgnoreMatcher::from_gutsignore(&repo_root)?;
+
+    let mut output = String::new();
+    for path in &args.paths {
+        let Some(rule) = matcher.matching_rule(&current_dir.join(path), &repo_root) else {
+            continue;
+        };
+        if args.verbose {
+            output.push_str(&format!(
+                "{}:{}:{}\t{}\n",
+                rule.source.display(),
+                rule.line_number,
+                rule.pattern,
+                path
+            ));
+        } else if !rule.is_negation {
+            output.push_str(&format!("{}\n", path));
+        }
+    }
+    Ok(output)
+}
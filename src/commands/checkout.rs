@@ -1,12 +1,16 @@
 use anyhow::{Context, Result};
 use clap::Args;
+use crate::core::attributes::{Attributes, ATTRIBUTES_FILES};
+use crate::core::read_head::{current_head, Head};
 use crate::core::resolve_parse::resolve_ref;
 use flate2::read::ZlibDecoder;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use crate::core::parse_tree::{parse_tree};
+use crate::core::{hash, reflog};
+use crate::core::simple_index::{self, SimpleIndex};
 
 #[derive(Args)]
 pub struct CheckoutObject {
@@ -15,18 +19,90 @@ pub struct CheckoutObject {
     #[arg(short = 'b', long)]
     pub branch_name: Option<String>,
 
+    /// Files to restore from HEAD (or from <name>) instead of switching branches
     #[arg(last = true)]
-    pub dir: Option<PathBuf>,
+    pub paths: Vec<PathBuf>,
 }
 
 pub fn run(args: &CheckoutObject) -> Result<String> {
+    if args.paths.is_empty() {
+        switch(args)
+    } else {
+        restore_paths(args)
+    }
+}
 
-    let original_dir = std::env::current_dir()?;
+/// `guts checkout [<commit>] -- <paths>`: overwrite only the given files with their
+/// committed version, and stage that version
+fn restore_paths(args: &CheckoutObject) -> Result<String> {
+    let current_dir = std::env::current_dir().context("Cannot get the current directory")?;
+    let git_dir = current_dir.join(".git");
 
-    if let Some(dir) = &args.dir {
-        std::env::set_current_dir(dir)?;
+    let tree_sha = match &args.name {
+        Some(name) => {
+            let sha = resolve_ref(&git_dir, name)?;
+            let commit_content = read_and_parse_git_object(&git_dir, &sha)?;
+            let commit_str = std::str::from_utf8(&commit_content)
+                .context("Commit content is not valid UTF-8")?;
+            extract_tree_sha(commit_str)?
+        }
+        None => read_head_tree_sha(&git_dir)?,
+    };
+
+    // Resolve every path first so that nothing is written if one of them is not tracked
+    let mut restores = Vec::new();
+    for path in &args.paths {
+        let absolute_path = current_dir.join(path);
+        let relative_path = absolute_path
+            .strip_prefix(&current_dir)
+            .map_err(|_| anyhow::anyhow!("'{}' is outside repository", path.display()))?
+            .to_path_buf();
+
+        let (blob_sha, mode) = find_blob_sha_in_tree(&git_dir, &tree_sha, &relative_path)?
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "error: pathspec '{}' did not match any file(s) known to guts",
+                    path.display()
+                )
+            })?;
+        restores.push((relative_path, blob_sha, mode));
     }
 
+    let attributes = Attributes::load(&current_dir)?;
+    let mut index = SimpleIndex::load()?;
+    for (relative_path, blob_sha, mode) in &restores {
+        let full_path = current_dir.join(relative_path);
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = read_and_parse_git_object(&git_dir, blob_sha)?;
+        // Never write through an existing symlink, and make room for a link over a file
+        if simple_index::is_symlink(&full_path) || (mode == simple_index::MODE_SYMLINK && full_path.is_file()) {
+            fs::remove_file(&full_path)?;
+        }
+        if mode == simple_index::MODE_SYMLINK {
+            create_symlink(&content, &full_path)?;
+        } else {
+            fs::write(&full_path, attributes.to_worktree(&relative_path.to_string_lossy(), content))
+                .with_context(|| format!("Failed to restore {:?}", full_path))?;
+            set_file_mode(&full_path, mode)?;
+        }
+        let relative_path = relative_path.to_string_lossy().to_string();
+        // Only executables and symlinks have their mode kept in the index
+        if mode == simple_index::MODE_FILE {
+            index.modes.remove(&relative_path);
+        } else {
+            index.modes.insert(relative_path.clone(), mode.clone());
+        }
+        index.files.insert(relative_path, blob_sha.clone());
+    }
+    index.save()?;
+
+    Ok(format!("Updated {} path(s) from {}", restores.len(), args.name.as_deref().unwrap_or("HEAD")))
+}
+
+/// Switch the working directory to another branch or commit
+fn switch(args: &CheckoutObject) -> Result<String> {
     let current_dir = std::env::current_dir().context("Cannot get the current directory")?;
     let git_dir = current_dir.join(".git");
 
@@ -45,10 +121,32 @@ pub fn run(args: &CheckoutObject) -> Result<String> {
         .context("Commit content is not valid UTF-8")?;
     let tree_sha = extract_tree_sha(commit_str)?;
     
-    if has_uncommitted_changes(&git_dir, &current_dir, &tree_sha)? {
+    if has_uncommitted_changes(&git_dir, &current_dir)? {
         anyhow::bail!("You have uncommitted changes. Commit or stash them before switching branches.");
     } else {
-        if let Some(branch_name) = &args.branch_name {
+        // Read before HEAD moves: it tells which files are tracked right now
+        let old_tree_sha = head_tree_sha(&git_dir)?;
+
+        let conflicts = untracked_conflicts(&current_dir, &git_dir, old_tree_sha.as_deref(), &tree_sha)?;
+        if !conflicts.is_empty() {
+            let list: Vec<String> = conflicts
+                .iter()
+                .map(|path| format!("\t{}", path.display()))
+                .collect();
+            anyhow::bail!(
+                "The following untracked working tree files would be overwritten by checkout:\n{}\nPlease move or remove them before you switch branches.",
+                list.join("\n")
+            );
+        }
+
+        // Where HEAD comes from, for the reflog
+        let old_head = resolve_ref(&git_dir, "HEAD").ok();
+        let from = match read_head_ref(&git_dir)? {
+            Some(branch) => branch,
+            None => old_head.clone().unwrap_or_default(),
+        };
+
+        let to = if let Some(branch_name) = &args.branch_name {
             let refs_path = git_dir.join("refs").join("heads").join(branch_name);
             if refs_path.exists() {
                 anyhow::bail!("Branch '{}' already exists", branch_name);
@@ -60,23 +158,47 @@ pub fn run(args: &CheckoutObject) -> Result<String> {
             std::fs::write(&head_path, format!("ref: refs/heads/{}\n", branch_name))
                 .with_context(|| format!("failed to update HEAD to point to {}", branch_name))?;
 
+            reflog::append(
+                &git_dir,
+                &format!("refs/heads/{}", branch_name),
+                None,
+                &sha,
+                &format!("branch: Created from {}", target_ref),
+            )?;
+            branch_name.clone()
         } else {
             let possible_branch_path = git_dir.join("refs").join("heads").join(&target_ref);
+            let head_path = git_dir.join("HEAD");
             if possible_branch_path.exists() {
-                let head_path = git_dir.join("HEAD");
                 std::fs::write(&head_path, format!("ref: refs/heads/{}\n", &target_ref))
                     .with_context(|| format!("failed to update HEAD to point to {}", &target_ref))?;
+            } else {
+                // Not a branch: HEAD is detached at the commit itself
+                std::fs::write(&head_path, format!("{}\n", sha))
+                    .with_context(|| format!("failed to detach HEAD at {}", sha))?;
             }
-        }
-
-        clean_working_directory(&current_dir, &git_dir, &tree_sha)?;
+            target_ref.clone()
+        };
+
+        reflog::append(
+            &git_dir,
+            "HEAD",
+            old_head.as_deref(),
+            &sha,
+            &format!("checkout: moving from {} to {}", from, to),
+        )?;
+
+        clean_working_directory(&current_dir, &git_dir, old_tree_sha.as_deref(), &tree_sha)?;
     
     
         let tree_content = read_and_parse_git_object(&git_dir, &tree_sha)?;
         parse_tree_object(&git_dir, &tree_content, current_dir)?;
-    
-        std::env::set_current_dir(&original_dir)?;
-        
+
+        // The index follows the checked-out tree, whose files were all just written
+        let mut index = SimpleIndex::from_tree(&git_dir, &tree_sha)?;
+        index.hashed = index.files.keys().cloned().collect();
+        index.save()?;
+
         Ok(tree_sha)
     }
 }
@@ -108,158 +230,213 @@ fn read_git_object(path: &Path) -> Result<Vec<u8>> {
     Ok(decompressed)
 }
 
+/// Writes every file of a tree below `target_dir`, with the line endings asked for by
+/// the attributes file the tree holds
 pub fn parse_tree_object(git_dir: &PathBuf, tree_bytes: &[u8], target_dir: PathBuf) -> Result<()> {
-    for entry in parse_tree(&tree_bytes)? {
+    let entries = parse_tree(tree_bytes)?;
+    let attributes = match ATTRIBUTES_FILES
+        .iter()
+        .find_map(|name| entries.iter().find(|entry| entry.filename == *name && entry.mode != "40000"))
+    {
+        Some(entry) => Attributes::parse(&String::from_utf8_lossy(&read_and_parse_git_object(git_dir, &entry.sha)?))?,
+        None => Attributes::empty(),
+    };
+    write_tree_entries(git_dir, tree_bytes, &target_dir, "", &attributes)
+}
+
+/// `prefix` is the path of the tree relative to the root tree being written
+fn write_tree_entries(
+    git_dir: &PathBuf,
+    tree_bytes: &[u8],
+    target_dir: &Path,
+    prefix: &str,
+    attributes: &Attributes,
+) -> Result<()> {
+    for entry in parse_tree(tree_bytes)? {
         let full_path = target_dir.join(&entry.filename);
+        let relative_path = format!("{}{}", prefix, entry.filename);
 
         if entry.mode == "40000" {
             fs::create_dir_all(&full_path)?;
-            let sub_tree_content = read_and_parse_git_object(&git_dir, &entry.sha)?;
-            parse_tree_object(&git_dir, &sub_tree_content, full_path)?;
+            let sub_tree_content = read_and_parse_git_object(git_dir, &entry.sha)?;
+            write_tree_entries(git_dir, &sub_tree_content, &full_path, &format!("{}/", relative_path), attributes)?;
         } else {
-            let blob_content = read_and_parse_git_object(&git_dir, &entry.sha)?;
-            fs::create_dir_all(&full_path.parent().unwrap())?;
+            let blob_content = read_and_parse_git_object(git_dir, &entry.sha)?;
+            fs::create_dir_all(full_path.parent().unwrap())?;
+            // Never write through an existing symlink
+            if simple_index::is_symlink(&full_path) {
+                fs::remove_file(&full_path)?;
+            }
+            if entry.mode == simple_index::MODE_SYMLINK {
+                create_symlink(&blob_content, &full_path)?;
+                continue;
+            }
             let mut file = File::create(&full_path)?;
-            file.write_all(&blob_content)?;
+            file.write_all(&attributes.to_worktree(&relative_path, blob_content))?;
+            set_file_mode(&full_path, &entry.mode)?;
         }
     }
 
     Ok(())
 }
 
-fn read_head_ref(git_dir: &Path) -> Result<Option<String>> {
-    let head_path = git_dir.join("HEAD");
-    let content = fs::read_to_string(&head_path)
-        .with_context(|| format!("Failed to read {:?}", head_path))?;
-
-    if let Some(stripped) = content.strip_prefix("ref: ") {
-        let name = Path::new(stripped.trim())
-            .file_name()
-            .and_then(|s| s.to_str())
-            .map(|s| s.to_string());
-        Ok(name)
-    } else {
-        Ok(None)
+/// Recreates a symlink from the target stored in its blob.
+/// Without symlink support the target is written as a plain file, like git does.
+fn create_symlink(target: &[u8], path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        let target = String::from_utf8_lossy(target).into_owned();
+        std::os::unix::fs::symlink(target, path)
+            .with_context(|| format!("Failed to create symlink {:?}", path))?;
     }
-}
+    #[cfg(not(unix))]
+    fs::write(path, target).with_context(|| format!("Failed to write {:?}", path))?;
 
-pub fn clean_working_directory(current_dir: &Path, git_dir: &Path, tree_sha: &str) -> Result<()> {
-    let mut tracked_paths = HashSet::new();
-    collect_tracked_paths(git_dir, tree_sha, PathBuf::new(), &mut tracked_paths)?;
-
-    for entry in fs::read_dir(current_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-
-        if path == *git_dir {
-            continue;
-        }
-
-        let relative_path = path.strip_prefix(current_dir).unwrap();
-
-        if tracked_paths.contains(relative_path) {
-            continue;
-        }
+    Ok(())
+}
 
-        if path.is_dir() {
-            fs::remove_dir_all(&path)
-                .with_context(|| format!("Failed to remove directory {:?}", path))?;
+/// Sets or clears the execute bits to match a tree entry mode (Unix only)
+pub fn set_file_mode(path: &Path, mode: &str) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut permissions = fs::metadata(path)?.permissions();
+        let bits = if mode == "100755" {
+            permissions.mode() | 0o111
         } else {
-            fs::remove_file(&path)
-                .with_context(|| format!("Failed to remove file {:?}", path))?;
-        }
+            permissions.mode() & !0o111
+        };
+        permissions.set_mode(bits);
+        fs::set_permissions(path, permissions)
+            .with_context(|| format!("Failed to set the mode of {:?}", path))?;
     }
+    #[cfg(not(unix))]
+    let _ = (path, mode);
 
     Ok(())
 }
 
-fn collect_tracked_paths(
+fn read_head_ref(git_dir: &Path) -> Result<Option<String>> {
+    match current_head(git_dir)? {
+        Head::Branch(name) => Ok(Some(name)),
+        Head::Detached(_) => Ok(None),
+    }
+}
+
+/// Removes the files tracked in `old_tree_sha` that are absent from `new_tree_sha`,
+/// then the directories they leave empty. Untracked files are never touched.
+pub fn clean_working_directory(
+    current_dir: &Path,
     git_dir: &Path,
-    tree_sha: &str,
-    base_path: PathBuf,
-    paths: &mut HashSet<PathBuf>,
+    old_tree_sha: Option<&str>,
+    new_tree_sha: &str,
 ) -> Result<()> {
-    let tree_content = read_and_parse_git_object(&git_dir, &tree_sha)?;
+    let Some(old_tree_sha) = old_tree_sha else {
+        return Ok(());
+    };
 
-    for entry in parse_tree(&tree_content)? {
-        let mut full_path = base_path.clone();
-        full_path.push(&entry.filename);
+    let old_files = list_files_in_tree(git_dir, old_tree_sha)?;
+    let new_files = list_files_in_tree(git_dir, new_tree_sha)?;
 
-        paths.insert(full_path.clone());
+    for relative_path in old_files.difference(&new_files) {
+        let path = current_dir.join(relative_path);
+        if path.is_file() || simple_index::is_symlink(&path) {
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove file {:?}", path))?;
+        }
 
-        if entry.mode == "40000" {
-            collect_tracked_paths(git_dir, &entry.sha, full_path, paths)?;
+        // Walk up removing directories as long as they are empty
+        let mut parent = path.parent();
+        while let Some(dir) = parent {
+            if dir == current_dir || fs::remove_dir(dir).is_err() {
+                break;
+            }
+            parent = dir.parent();
         }
     }
 
     Ok(())
 }
 
-fn has_uncommitted_changes(git_dir: &Path, current_dir: &Path, tree_sha: &str) -> Result<bool> {
-    
-    let current_head_tree = read_head_tree_sha(git_dir)?;
-    
-    let tracked_files = list_files_in_tree(git_dir, &current_head_tree)?;
-    
-    let mut changed = false;
-    check_tree_for_changes(git_dir, current_dir, current_dir, &tracked_files, &mut changed)?;
-
-    Ok(changed)
-}
-
-fn check_tree_for_changes(
-    git_dir: &Path,
+/// Untracked files that checking out `new_tree_sha` would overwrite with different content
+pub fn untracked_conflicts(
     current_dir: &Path,
-    path_prefix: &Path,
-    tracked_files: &HashSet<PathBuf>,
-    changed: &mut bool,
-) -> Result<()> {
-    for entry in fs::read_dir(path_prefix)? {
-        let entry = entry?;
-        let path = entry.path();
+    git_dir: &Path,
+    old_tree_sha: Option<&str>,
+    new_tree_sha: &str,
+) -> Result<Vec<PathBuf>> {
+    let old_files = match old_tree_sha {
+        Some(old_tree_sha) => list_files_in_tree(git_dir, old_tree_sha)?,
+        None => HashSet::new(),
+    };
+    let new_files = list_files_in_tree(git_dir, new_tree_sha)?;
+    let attributes = Attributes::load(current_dir)?;
 
-        if path == *git_dir {
+    let mut conflicts = Vec::new();
+    for relative_path in new_files.difference(&old_files) {
+        let path = current_dir.join(relative_path);
+        if !path.is_file() && !simple_index::is_symlink(&path) {
             continue;
         }
 
-        let relative_path = path.strip_prefix(current_dir).unwrap().to_path_buf();
-
-        if path.is_dir() {
-            check_tree_for_changes(git_dir, current_dir, &path, tracked_files, changed)?;
-        } else {
-            let is_tracked = tracked_files.contains(&relative_path);
-
-            if is_tracked {
-                if let Some(blob_sha) = find_blob_sha_for_path(git_dir, &relative_path)? {
-                    let blob_path = git_dir.join("objects").join(&blob_sha[..2]).join(&blob_sha[2..]);
-                    let blob_bytes = read_git_object(&blob_path)?;
-                    let (_header, content) = split_header_and_content(&blob_bytes)?;
-                    let current_content = fs::read(&path)?;
-
-                    if current_content != content {
-                        *changed = true;
-                    }
-                } else {
-                    println!("DEBUG: Could not find blob SHA for tracked file: {:?}", relative_path);
-                }
-            } else {
-                *changed = true;
+        let blob_sha = find_blob_sha_in_tree(git_dir, new_tree_sha, relative_path)?;
+        let same_content = match blob_sha {
+            Some((blob_sha, _)) => {
+                simple_index::read_worktree_file(&path, &relative_path.to_string_lossy(), &attributes)?
+                    == read_and_parse_git_object(git_dir, &blob_sha)?
             }
+            None => false,
+        };
+        if !same_content {
+            conflicts.push(relative_path.clone());
         }
     }
 
-    for tracked_file in tracked_files {
-        let full_path = current_dir.join(tracked_file);
-        if !full_path.exists() {
-            *changed = true;
+    conflicts.sort();
+    Ok(conflicts)
+}
+
+/// Returns true when the index differs from HEAD (a staged new file included) or a
+/// file tracked in the HEAD tree was modified or deleted.
+/// Untracked files are not changes: they are left alone by checkout.
+pub fn has_uncommitted_changes(git_dir: &Path, current_dir: &Path) -> Result<bool> {
+    let index = simple_index::SimpleIndex::load()?;
+    let Some(current_head_tree) = head_tree_sha(git_dir)? else {
+        // Nothing committed yet: only staged files can be lost
+        return Ok(!index.files.is_empty());
+    };
+
+    // Staged changes only live in the index, rebuilding it from a tree would drop them
+    let head_index = simple_index::SimpleIndex::from_tree(git_dir, &current_head_tree)?;
+    if index.files != head_index.files || index.modes != head_index.modes {
+        return Ok(true);
+    }
+
+    // One hash per tracked file
+    let attributes = Attributes::load(current_dir)?;
+    for (relative_path, blob_sha) in &head_index.files {
+        let path = current_dir.join(relative_path);
+        if !path.is_file() && !simple_index::is_symlink(&path) {
+            // Deleted (or replaced by a directory)
+            return Ok(true);
+        }
+
+        let content = simple_index::read_worktree_file(&path, relative_path, &attributes)?;
+        if hash::hash_blob(&content)? != *blob_sha {
+            return Ok(true);
         }
     }
 
-    Ok(())
+    Ok(false)
 }
 
 fn list_files_in_tree(git_dir: &Path, tree_sha: &str) -> Result<HashSet<PathBuf>> {
-    let mut files = HashSet::new();
+    Ok(tree_file_shas(git_dir, tree_sha)?.into_keys().collect())
+}
+
+/// Every file of a tree (recursively) with its blob SHA
+fn tree_file_shas(git_dir: &Path, tree_sha: &str) -> Result<HashMap<PathBuf, String>> {
+    let mut files = HashMap::new();
     list_files_recursive(git_dir, tree_sha, PathBuf::new(), &mut files)?;
     Ok(files)
 }
@@ -268,9 +445,9 @@ fn list_files_recursive(
     git_dir: &Path,
     tree_sha: &str,
     prefix: PathBuf,
-    files: &mut HashSet<PathBuf>,
+    files: &mut HashMap<PathBuf, String>,
 ) -> Result<()> {
-    let tree_content = read_and_parse_git_object(git_dir, &tree_sha)?;
+    let tree_content = read_and_parse_git_object(git_dir, tree_sha)?;
 
     for entry in parse_tree(&tree_content)? {
         let current_path = prefix.join(&entry.filename);
@@ -278,56 +455,58 @@ fn list_files_recursive(
         if entry.mode == "40000" {
             list_files_recursive(git_dir, &entry.sha, current_path, files)?;
         } else {
-            files.insert(current_path);
+            files.insert(current_path, entry.sha);
         }
     }
-    
+
     Ok(())
 }
 
-fn find_blob_sha_for_path(git_dir: &Path, relative_path: &Path) -> Result<Option<String>> {
-    let mut current_tree_sha = read_head_tree_sha(git_dir)?;
+/// Looks up the blob SHA and mode of a file path inside a tree, descending through subtrees
+fn find_blob_sha_in_tree(git_dir: &Path, tree_sha: &str, relative_path: &Path) -> Result<Option<(String, String)>> {
+    let mut current_tree_sha = tree_sha.to_string();
+    let component_count = relative_path.components().count();
 
-    for component in relative_path.components() {
+    for (i, component) in relative_path.components().enumerate() {
         let component_str = component.as_os_str().to_string_lossy();
 
         let tree_content = read_and_parse_git_object(git_dir, &current_tree_sha)?;
 
         let entries = parse_tree(&tree_content)?;
 
-        let found_entry = entries.iter().find(|entry| entry.filename == component_str);
+        let Some(entry) = entries.iter().find(|entry| entry.filename == component_str) else {
+            return Ok(None);
+        };
 
-        if let Some(entry) = found_entry {
-            if component == relative_path.components().last().unwrap() {
-                return Ok(Some(entry.sha.clone()));
-            } else if entry.mode == "40000" {
-                    current_tree_sha = entry.sha.clone();
-                } else {
-                    return Ok(None);
-                }
-            } else {
-                return Ok(None);
+        let is_dir = entry.mode == "40000";
+        if i + 1 == component_count {
+            // The last component must be a file
+            return Ok(if is_dir { None } else { Some((entry.sha.clone(), entry.mode.clone())) });
+        } else if is_dir {
+            current_tree_sha = entry.sha.clone();
+        } else {
+            return Ok(None);
         }
     }
 
     Ok(None)
 }
 
-fn read_head_tree_sha(git_dir: &Path) -> Result<String> {
-    let head_ref = read_head_ref(git_dir)?
-        .ok_or_else(|| anyhow::anyhow!("HEAD is detached or invalid"))?;
-
-    let ref_path = git_dir.join("refs").join("heads").join(&head_ref);
-    let commit_sha = fs::read_to_string(&ref_path)
-        .context("Failed to read HEAD ref file")?;
-    let commit_sha = commit_sha.trim();
+/// Tree of the commit HEAD points to (branch or detached), or None before the first commit
+pub fn head_tree_sha(git_dir: &Path) -> Result<Option<String>> {
+    let Ok(commit_sha) = resolve_ref(git_dir, "HEAD") else {
+        return Ok(None);
+    };
 
     let commit_content = read_and_parse_git_object(git_dir, &commit_sha)?;
-
     let commit_str = std::str::from_utf8(&commit_content)
         .context("Commit content is not valid UTF-8")?;
 
-    extract_tree_sha(commit_str)
+    extract_tree_sha(commit_str).map(Some)
+}
+
+fn read_head_tree_sha(git_dir: &Path) -> Result<String> {
+    head_tree_sha(git_dir)?.ok_or_else(|| anyhow::anyhow!("HEAD does not point to a commit yet"))
 }
 
 
@@ -0,0 +1,37 @@
+use crate::commands::sequencer::{self, Replay};
+use crate::core::simple_index;
+use anyhow::{anyhow, Result};
+use clap::Args;
+
+/// Arguments for the `guts cherry-pick` command
+#[derive(Args)]
+pub struct CherryPickArgs {
+    /// Commit whose changes should be applied on top of HEAD
+    pub commit: String,
+}
+
+/// Entry point for the `guts cherry-pick` command
+/// Replays the changes of one commit on top of HEAD as a new commit with the same message
+pub fn run(args: &CherryPickArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+
+    let repo_root = simple_index::find_repo_root()?;
+    std::env::set_current_dir(&repo_root)?;
+    let git_dir = repo_root.join(".git");
+
+    let (sha, picked, parent_tree) = sequencer::read_replayed_commit(&git_dir, &args.commit, Replay::CherryPick)?;
+
+    // The picked commit's parent is the base, HEAD one side and the picked commit the other.
+    // Like git, the original author is kept and the current user becomes the committer.
+    sequencer::apply_tree_change(
+        &repo_root,
+        Replay::CherryPick,
+        &sha,
+        &parent_tree,
+        &picked.tree,
+        &picked.message,
+        Some(&picked),
+    )
+}
@@ -0,0 +1,111 @@
+use crate::commands::status;
+use crate::core::simple_index::{self, SimpleIndex};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Arguments for the `guts clean` command
+#[derive(Args)]
+pub struct CleanArgs {
+    /// Actually delete the untracked files
+    #[arg(short = 'f', long)]
+    pub force: bool,
+
+    /// Only show what would be deleted
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+
+    /// Also delete untracked directories
+    #[arg(short = 'd')]
+    pub directories: bool,
+}
+
+/// Main function for the `guts clean` command
+/// Deletes the files `status` reports as untracked; ignored files are never touched
+pub fn run(args: &CleanArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    // Like git with clean.requireForce, nothing happens by accident
+    if !args.force && !args.dry_run {
+        return Err(anyhow!("fatal: refusing to clean without -f or -n"));
+    }
+
+    let repo_root = simple_index::find_repo_root()?;
+    std::env::set_current_dir(&repo_root)?;
+
+    let untracked = status::list_untracked_files(&repo_root)?;
+    let tracked_dirs = tracked_directories()?;
+
+    // What is reported (`file` or `dir/`) -> files to delete for it
+    let mut removals: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for path in untracked {
+        match untracked_parent(&path, &tracked_dirs) {
+            Some(dir) if args.directories => {
+                removals.entry(format!("{}/", dir)).or_default().push(path)
+            }
+            // Without -d, files inside an untracked directory are left alone
+            Some(_) => {}
+            None => removals.entry(path.clone()).or_default().push(path),
+        }
+    }
+
+    let mut output = String::new();
+    for (shown, files) in &removals {
+        if args.dry_run {
+            output.push_str(&format!("Would remove {}\n", shown));
+            continue;
+        }
+
+        for file in files {
+            fs::remove_file(repo_root.join(file))
+                .map_err(|e| anyhow!("failed to remove '{}': {}", file, e))?;
+        }
+        if let Some(dir) = shown.strip_suffix('/') {
+            remove_empty_dirs(&repo_root.join(dir));
+        }
+        output.push_str(&format!("Removing {}\n", shown));
+    }
+
+    Ok(output)
+}
+
+/// Every directory that holds a file of the index or of HEAD, relative to the repo root
+fn tracked_directories() -> Result<HashSet<String>> {
+    let index = SimpleIndex::load()?;
+    let committed_files = simple_index::get_committed_files()?;
+
+    let mut dirs = HashSet::new();
+    for path in index.files.keys().chain(committed_files.keys()) {
+        let mut parent = Path::new(path).parent();
+        while let Some(dir) = parent.filter(|dir| !dir.as_os_str().is_empty()) {
+            dirs.insert(dir.to_string_lossy().to_string());
+            parent = dir.parent();
+        }
+    }
+    Ok(dirs)
+}
+
+/// Outermost directory above `path` that holds no tracked file, if any
+fn untracked_parent(path: &str, tracked_dirs: &HashSet<String>) -> Option<String> {
+    Path::new(path)
+        .ancestors()
+        .skip(1)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .map(|dir| dir.to_string_lossy().to_string())
+        .filter(|dir| !tracked_dirs.contains(dir))
+        .last()
+}
+
+/// Deletes the directories left empty below `dir`, deepest first.
+/// A directory still holding ignored files is not empty and stays.
+fn remove_empty_dirs(dir: &Path) {
+    for entry in WalkDir::new(dir).contents_first(true).into_iter().filter_map(|e| e.ok()) {
+        if entry.file_type().is_dir() {
+            let _ = fs::remove_dir(entry.path());
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use crate::commands::checkout::{head_tree_sha, parse_tree_object, read_and_parse_git_object};
+use crate::core::{config, repo};
+use crate::core::resolve_parse::resolve_ref;
+use crate::core::simple_index::SimpleIndex;
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Arguments for the `guts clone` command
+#[derive(Args)]
+pub struct CloneArgs {
+    /// Path of the local repository to copy
+    pub source: PathBuf,
+
+    /// Directory to create for the copy
+    pub destination: PathBuf,
+}
+
+/// Main function for the `guts clone` command
+/// Copies the objects and refs of a local repository and checks out its current branch
+pub fn run(args: &CloneArgs) -> Result<String> {
+    let source_git_dir = args.source.join(".git");
+    if !source_git_dir.is_dir() {
+        return Err(anyhow!("fatal: repository '{}' does not exist", args.source.display()));
+    }
+    if args.destination.exists() {
+        return Err(anyhow!(
+            "fatal: destination path '{}' already exists",
+            args.destination.display()
+        ));
+    }
+    let head = resolve_ref(&source_git_dir, "HEAD").map_err(|_| {
+        anyhow!("fatal: repository '{}' has no commits to clone", args.source.display())
+    })?;
+
+    // Same branch as the source, or a detached HEAD on the same commit
+    let source_head = fs::read_to_string(source_git_dir.join("HEAD"))
+        .with_context(|| "failed to read the source HEAD")?;
+    let branch = source_head
+        .trim()
+        .strip_prefix("ref: refs/heads/")
+        .map(|branch| branch.to_string());
+
+    fs::create_dir_all(&args.destination)
+        .with_context(|| format!("failed to create {:?}", args.destination))?;
+    let destination = args.destination.canonicalize()?;
+    let branch_or_default = match &branch {
+        Some(branch) => branch.clone(),
+        None => config::default_branch()?,
+    };
+    repo::init_with_branch(&destination, &branch_or_default)?;
+    let git_dir = destination.join(".git");
+    if branch.is_none() {
+        fs::write(git_dir.join("HEAD"), format!("{}\n", head))
+            .with_context(|| "failed to write HEAD file")?;
+    }
+
+    copy_dir(&source_git_dir.join("objects"), &git_dir.join("objects"))?;
+    copy_dir(&source_git_dir.join("refs"), &git_dir.join("refs"))?;
+
+    // The index is found from the current directory
+    std::env::set_current_dir(&destination)?;
+    let tree_sha = head_tree_sha(&git_dir)?
+        .ok_or_else(|| anyhow!("fatal: HEAD of '{}' is not a commit", args.source.display()))?;
+    let tree_content = read_and_parse_git_object(&git_dir, &tree_sha)?;
+    parse_tree_object(&git_dir, &tree_content, destination.clone())?;
+    SimpleIndex::from_tree(&git_dir, &tree_sha)?.save()?;
+
+    Ok(format!("Cloning into '{}'... done.", args.destination.display()))
+}
+
+/// Recursively copies the files of `from` into `to`, creating directories as needed
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to).with_context(|| format!("failed to create {:?}", to))?;
+    for entry in fs::read_dir(from).with_context(|| format!("failed to read {:?}", from))? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)
+                .with_context(|| format!("failed to copy {:?}", entry.path()))?;
+        }
+    }
+    Ok(())
+}
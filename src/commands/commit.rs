@@ -1,81 +1,119 @@
-use crate::commands::{commit_tree, write_tree};
-use crate::core::simple_index;
+use crate::commands::{commit_tree, status, write_tree};
+use crate::core::object::{local_timezone, Commit};
+use crate::core::color::ColorWhen;
+use crate::core::read_head::{self, Head};
+use crate::core::{cat, config, reflog, simple_index};
 use anyhow::Result;
 use clap::Args;
-use std::env;
-use std::path::PathBuf;
 
 #[derive(Args)]
 pub struct CommitArgs {
     /// Commit message
-    #[arg(short = 'm', long)]
-    pub message: String,
-    
-    /// Current directory for the operation (injected by TUI)
-    pub dir: Option<PathBuf>,
-}
-
-pub fn run(args: &CommitArgs) -> Result<String> {
-    let current_dir = args
-        .dir
-        .clone()
-        .unwrap_or_else(|| env::current_dir().expect("could not get the current dir"));
+    #[arg(short = 'm', long, required_unless_present = "amend")]
+    pub message: Option<String>,
 
-    // Change to the specified directory for this operation
-    let original_dir = env::current_dir()?;
-    env::set_current_dir(&current_dir)?;
+    /// Replace the last commit instead of adding a new one (its message is kept without -m)
+    #[arg(long)]
+    pub amend: bool,
 
-    let result = run_commit(args);
+    /// Override the commit author, as `Name <email>` (the committer stays the configured identity)
+    #[arg(long, value_name = "author")]
+    pub author: Option<String>,
 
-    // Restore original directory
-    env::set_current_dir(original_dir)?;
-
-    result
+    /// Record a commit even if its tree is the same as HEAD's
+    #[arg(long)]
+    pub allow_empty: bool,
 }
 
-fn run_commit(args: &CommitArgs) -> Result<String> {
+pub fn run(args: &CommitArgs) -> Result<String> {
     // Check if we're in a git repository
     if !simple_index::is_git_repository()? {
         return Err(anyhow::anyhow!("fatal: not a git repository"));
     }
 
+    // Refuse a malformed --author before writing anything
+    let author = args.author.as_deref().map(config::parse_identity).transpose()?;
+
     // Load the index to check if there are staged files
     let index = simple_index::SimpleIndex::load()?;
-    if index.files.is_empty() {
-        return Err(anyhow::anyhow!("nothing to commit, working tree clean"));
+    if index.files.is_empty() && !args.amend && !args.allow_empty {
+        return Err(nothing_to_commit()?);
     }
 
     // 1. Create tree from staged files using write-tree
-    let write_tree_args = write_tree::WriteTreeArgs { dir: None };
+    let write_tree_args = write_tree::WriteTreeArgs { from_dir: None };
     let tree_hash = write_tree::run(&write_tree_args)?;
 
     // 2. Get the current HEAD commit (parent) if it exists
-    let parent = match get_current_head()? {
-        Some(p) => Some(vec![p]),
-        None => None,
-    };
-
+    let head = get_current_head()?;
+    let identity = config::user_identity()?;
 
     // 3. Create commit object using commit-tree
-    let commit_tree_args = commit_tree::CommitObject {
-        tree: tree_hash.clone(),
-        parent: parent,
-        message: args.message.clone(),
-        author: "guts <guts@example.com>".to_string(),
-        committer: "guts <guts@example.com>".to_string(),
-        author_date: None,
-        committer_date: None,
-        dir: None,
+    let (commit_tree_args, action) = if args.amend {
+        // The amended commit takes the place of HEAD: same parents, same author
+        let head_sha = head
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("fatal: You have nothing to amend."))?;
+        let amended = read_commit(head_sha)?;
+        let commit_tree_args = commit_tree::CommitObject {
+            tree: tree_hash.clone(),
+            parent: amended.parent.filter(|parents| !parents.is_empty()),
+            message: Some(args.message.clone().unwrap_or(amended.message)),
+            author: author.unwrap_or(amended.author),
+            committer: identity,
+            author_date: Some(amended.author_date),
+            committer_date: Some(chrono::Utc::now().timestamp()),
+            author_timezone: Some(amended.author_timezone),
+            committer_timezone: Some(local_timezone()),
+        };
+        (commit_tree_args, "commit (amend)")
+    } else {
+        // The index is kept after each commit, so an unchanged tree means nothing was staged
+        if let (Some(head_sha), false) = (&head, args.allow_empty) {
+            if read_commit(head_sha)?.tree == tree_hash {
+                return Err(nothing_to_commit()?);
+            }
+        }
+
+        let commit_tree_args = commit_tree::CommitObject {
+            tree: tree_hash.clone(),
+            parent: head.clone().map(|p| vec![p]),
+            message: Some(args.message.clone().unwrap_or_default()),
+            author: author.unwrap_or_else(|| identity.clone()),
+            committer: identity,
+            author_date: None,
+            committer_date: None,
+            author_timezone: None,
+            committer_timezone: None,
+        };
+        (commit_tree_args, if head.is_some() { "commit" } else { "commit (initial)" })
     };
+    let message = commit_tree_args.message.clone().unwrap_or_default();
     let commit_hash = commit_tree::run(&commit_tree_args)?;
 
     // 4. Update HEAD to point to the new commit
     update_head(&commit_hash)?;
 
-    // 5. Clear the index (staged files become committed)
-    clear_index()?;
+    let subject = message.lines().next().unwrap_or("");
+    reflog::record_head_update(
+        std::path::Path::new(".git"),
+        head.as_deref(),
+        &commit_hash,
+        &format!("{}: {}", action, subject),
+    )?;
 
-    Ok(format!("[{}] {}", &commit_hash[..7], args.message))
+    Ok(format!("[{}] {}", &commit_hash[..7], message))
+}
+
+/// Error for a commit with nothing staged, telling apart a clean working tree
+/// from changes that were simply not added
+fn nothing_to_commit() -> Result<anyhow::Error> {
+    let short_status = status::run(&status::StatusObject { short: true, color: ColorWhen::Never, ignored: false })?;
+    if short_status.trim().is_empty() {
+        Ok(anyhow::anyhow!("nothing to commit, working tree clean"))
+    } else {
+        Ok(anyhow::anyhow!("no changes added to commit (use \"guts add\" to stage changes)"))
+    }
 }
 
 /// Get the current HEAD commit hash, or None if this is the first commit
@@ -109,35 +147,34 @@ fn get_current_head() -> Result<Option<String>> {
 }
 
 /// Update HEAD to point to the new commit
-fn update_head(commit_hash: &str) -> Result<()> {
-    let head_path = std::path::Path::new(".git/HEAD");
-    let head_content = std::fs::read_to_string(head_path)?;
-    let head_content = head_content.trim();
+pub(crate) fn update_head(commit_hash: &str) -> Result<()> {
+    let git_dir = std::path::Path::new(".git");
 
-    if head_content.starts_with("ref: ") {
-        // HEAD points to a branch, update the branch ref
-        let ref_path = head_content.strip_prefix("ref: ")
-            .ok_or_else(|| anyhow::anyhow!("malformed HEAD reference: {}", head_content))?;
-        let ref_file = std::path::Path::new(".git").join(ref_path);
-        
-        // Create parent directories if they don't exist
-        if let Some(parent) = ref_file.parent() {
-            std::fs::create_dir_all(parent)?;
+    match read_head::current_head(git_dir)? {
+        Head::Branch(name) => {
+            // HEAD points to a branch, update the branch ref
+            let ref_file = git_dir.join("refs").join("heads").join(name);
+
+            // Create parent directories if they don't exist
+            if let Some(parent) = ref_file.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            std::fs::write(ref_file, format!("{}\n", commit_hash))?;
+        }
+        Head::Detached(_) => {
+            // Detached HEAD, update HEAD directly
+            std::fs::write(git_dir.join("HEAD"), format!("{}\n", commit_hash))?;
         }
-        
-        std::fs::write(ref_file, format!("{}\n", commit_hash))?;
-    } else {
-        // Detached HEAD, update HEAD directly
-        std::fs::write(head_path, format!("{}\n", commit_hash))?;
     }
 
     Ok(())
 }
 
-/// Clear the staging area after successful commit
-fn clear_index() -> Result<()> {
-    let mut index = simple_index::SimpleIndex::load()?;
-    index.files.clear();
-    index.save()?;
-    Ok(())
+/// Read a commit object
+fn read_commit(commit_hash: &str) -> Result<Commit> {
+    match cat::read_object(std::path::Path::new(".git"), commit_hash)? {
+        cat::ParsedObject::Commit(commit) => Ok(commit),
+        _ => Err(anyhow::anyhow!("fatal: HEAD {} is not a commit", commit_hash)),
+    }
 }
\ No newline at end of file
@@ -1,17 +1,20 @@
+use crate::core::cat::{self, ParsedObject};
 use crate::core::hash;
-use crate::core::object::Commit;
+use crate::core::object::{local_timezone, Commit};
 use anyhow::Result;
 use clap::Args;
 use std::env;
-use std::path::PathBuf;
+use std::io::Read;
 
 #[derive(Args)]
 pub struct CommitObject {
     pub tree: String,
+    /// Parent commit, repeat for a merge commit
     #[arg(short = 'p', long)]
     pub parent: Option<Vec<String>>,
+    /// Commit message (read from stdin when omitted)
     #[arg(short = 'm', long)]
-    pub message: String,
+    pub message: Option<String>,
     /// Author name and email in format "Name <email>"
     #[arg(long, default_value = "guts <guts@example.com>")]
     pub author: String,
@@ -24,15 +27,16 @@ pub struct CommitObject {
     /// Unix timestamp for committer date
     #[arg(long)]
     pub committer_date: Option<i64>,
-    /// Current directory for the operation (injected by TUI)
-    pub dir: Option<PathBuf>,
+    /// UTC offset of the author date, e.g. "+0200" (defaults to the local timezone)
+    #[arg(long, allow_hyphen_values = true)]
+    pub author_timezone: Option<String>,
+    /// UTC offset of the committer date (defaults to the local timezone)
+    #[arg(long, allow_hyphen_values = true)]
+    pub committer_timezone: Option<String>,
 }
 
 pub fn run(args: &CommitObject) -> Result<String> {
-    let current_dir = args
-        .dir
-        .clone()
-        .unwrap_or_else(|| env::current_dir().expect("could not get the current dir"));
+    let current_dir = env::current_dir()?;
 
     let git_dir = current_dir.join(".git");
 
@@ -43,24 +47,58 @@ pub fn run(args: &CommitObject) -> Result<String> {
     let now = chrono::Utc::now().timestamp();
     let author_date = args.author_date.unwrap_or(now);
     let committer_date = args.committer_date.unwrap_or(author_date);
+    let author_timezone = match &args.author_timezone {
+        Some(timezone) => validate_timezone(timezone)?,
+        None => local_timezone(),
+    };
+    if let Some(timezone) = &args.committer_timezone {
+        validate_timezone(timezone)?;
+    }
 
     let parent = match &args.parent {
         Some(vec) if !vec.is_empty() => Some(vec.clone()),
         _ => None,
     };
+    for sha in parent.iter().flatten() {
+        match cat::read_object(&git_dir, sha) {
+            Ok(ParsedObject::Commit(_)) => {}
+            Ok(_) => anyhow::bail!("fatal: {} is not a valid 'commit' object", sha),
+            Err(_) => anyhow::bail!("fatal: not a valid object name {}", sha),
+        }
+    }
 
-
+    let message = match &args.message {
+        Some(message) => message.clone(),
+        None => {
+            let mut message = String::new();
+            std::io::stdin().read_to_string(&mut message)?;
+            message
+        }
+    };
 
     let commit = Commit {
         tree: args.tree.clone(),
         parent: parent.clone(),
-        message: args.message.clone(),
+        message,
         author: args.author.clone(),
         committer: args.committer.clone(),
         author_date,
         committer_date,
+        author_timezone: author_timezone.clone(),
+        committer_timezone: args.committer_timezone.clone().unwrap_or(author_timezone),
     };
 
     let oid = hash::write_object(&commit)?;
     Ok(oid)
 }
+
+/// Checks a `+HHMM` / `-HHMM` offset
+fn validate_timezone(timezone: &str) -> Result<String> {
+    let valid = timezone.len() == 5
+        && (timezone.starts_with('+') || timezone.starts_with('-'))
+        && timezone[1..].chars().all(|c| c.is_ascii_digit());
+    if !valid {
+        anyhow::bail!("invalid timezone '{}', expected +HHMM or -HHMM", timezone);
+    }
+    Ok(timezone.to_string())
+}
@@ -0,0 +1,60 @@
+use crate::core::config::{self, Config};
+use crate::core::simple_index;
+use anyhow::{anyhow, Result};
+use clap::Args;
+
+/// Arguments for the `guts config` command
+#[derive(Args)]
+pub struct ConfigArgs {
+    /// List every key/value pair of ~/.gutsconfig and .git/config
+    #[arg(short = 'l', long, conflicts_with_all = ["key", "value"])]
+    pub list: bool,
+
+    /// Key to read or write, e.g. `user.name`
+    #[arg(required_unless_present = "list")]
+    pub key: Option<String>,
+
+    /// New value for the key; the key is only read when omitted
+    pub value: Option<String>,
+}
+
+/// Entry point for the `guts config` command
+/// Reads or writes keys in the repository config file
+pub fn run(args: &ConfigArgs) -> Result<String> {
+    if args.list {
+        return list();
+    }
+
+    let key = args.key.as_deref().unwrap_or_default();
+    match &args.value {
+        Some(value) => {
+            if !simple_index::is_git_repository()? {
+                return Err(anyhow!("fatal: not a git repository"));
+            }
+            let path = config::repo_config_path()?;
+            let mut repo_config = Config::load(&path)?;
+            repo_config.set(key, value)?;
+            repo_config.save(&path)?;
+            Ok(String::new())
+        }
+        None => config::get(key)?.ok_or_else(|| anyhow!("error: key '{}' is not set", key)),
+    }
+}
+
+/// Dumps `key=value` lines, user-wide config first like git
+fn list() -> Result<String> {
+    let mut lines = Vec::new();
+
+    if let Some(path) = config::global_config_path() {
+        for (key, value) in Config::load(&path)?.entries() {
+            lines.push(format!("{}={}", key, value));
+        }
+    }
+    if let Ok(path) = config::repo_config_path() {
+        for (key, value) in Config::load(&path)?.entries() {
+            lines.push(format!("{}={}", key, value));
+        }
+    }
+
+    Ok(lines.join("\n"))
+}
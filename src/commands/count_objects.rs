@@ -0,0 +1,48 @@
+use crate::commands::gc::loose_objects;
+use crate::core::cat::{self, ParsedObject};
+use crate::core::simple_index;
+use anyhow::{anyhow, Result};
+use clap::Args;
+use std::collections::BTreeMap;
+use std::fs;
+
+/// Arguments for the `guts count-objects` command
+#[derive(Args)]
+pub struct CountObjectsArgs {
+    /// Also count the objects of each type
+    #[arg(short = 'v', long)]
+    pub verbose: bool,
+}
+
+/// Main function for the `guts count-objects` command
+/// Counts the loose objects and the disk space their (compressed) files take
+pub fn run(args: &CountObjectsArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    let git_dir = simple_index::find_repo_root()?.join(".git");
+
+    let objects = loose_objects(&git_dir)?;
+    let mut size = 0;
+    let mut types: BTreeMap<String, usize> = BTreeMap::new();
+    for sha in &objects {
+        size += fs::metadata(cat::get_object_path(&git_dir, sha))?.len();
+        if !args.verbose {
+            continue;
+        }
+        let object_type = match cat::parse_object(&cat::read_raw_object(&git_dir, sha)?)? {
+            ParsedObject::Blob(_) => "blob".to_string(),
+            ParsedObject::Tree(_) => "tree".to_string(),
+            ParsedObject::Commit(_) => "commit".to_string(),
+            ParsedObject::Tag(_) => "tag".to_string(),
+            ParsedObject::Other(kind, _) => kind,
+        };
+        *types.entry(object_type).or_default() += 1;
+    }
+
+    let mut output = format!("count: {}, size: {} bytes\n", objects.len(), size);
+    for (object_type, count) in &types {
+        output.push_str(&format!("{}: {}\n", object_type, count));
+    }
+    Ok(output)
+}
@@ -0,0 +1,81 @@
+use crate::core::cat::{self, ParsedObject};
+use crate::core::resolve_parse::{peel_tags, resolve_ref};
+use crate::core::simple_index;
+use anyhow::{anyhow, Result};
+use clap::Args;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Arguments for the `guts describe` command
+#[derive(Args)]
+pub struct DescribeArgs {
+    /// Commit to describe (defaults to HEAD)
+    #[arg(default_value = "HEAD")]
+    pub commit: String,
+}
+
+/// Main function for the `guts describe` command
+/// Names a commit after the nearest tag on its first-parent history:
+/// `<tag>` on the tagged commit itself, `<tag>-<n>-g<short sha>` n commits later
+pub fn run(args: &DescribeArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    let git_dir = simple_index::find_repo_root()?.join(".git");
+
+    let sha = resolve_ref(&git_dir, &args.commit)?;
+    let tags = tags_by_commit(&git_dir)?;
+
+    let mut current = sha.clone();
+    let mut distance = 0;
+    loop {
+        if let Some(tag) = tags.get(&current) {
+            if distance == 0 {
+                return Ok(tag.clone());
+            }
+            let short = cat::shortest_unique_prefix(&git_dir, &sha, 7)?;
+            return Ok(format!("{}-{}-g{}", tag, distance, short));
+        }
+
+        let parent = match cat::read_object(&git_dir, &current)? {
+            ParsedObject::Commit(commit) => commit.parent.and_then(|parents| parents.into_iter().next()),
+            _ => return Err(anyhow!("fatal: {} is not a commit", current)),
+        };
+        match parent {
+            Some(parent) => {
+                current = parent;
+                distance += 1;
+            }
+            None => return Err(anyhow!("fatal: No tags can describe '{}'.", sha)),
+        }
+    }
+}
+
+/// Commit SHA -> tag name, for every tag under `refs/tags`.
+/// When several tags point to the same commit, the first one by name wins.
+fn tags_by_commit(git_dir: &Path) -> Result<HashMap<String, String>> {
+    let tags_dir = git_dir.join("refs").join("tags");
+    let mut tags = HashMap::new();
+    if !tags_dir.exists() {
+        return Ok(tags);
+    }
+
+    for entry in WalkDir::new(&tags_dir).sort_by_file_name() {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let name = entry
+            .path()
+            .strip_prefix(&tags_dir)?
+            .to_string_lossy()
+            .to_string();
+        // Annotated tags name the commit their tag object points to
+        let sha = peel_tags(git_dir, fs::read_to_string(entry.path())?.trim())?;
+        tags.entry(sha).or_insert(name);
+    }
+
+    Ok(tags)
+}
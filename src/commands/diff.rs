@@ -0,0 +1,185 @@
+use crate::commands::checkout::head_tree_sha;
+use crate::core::cat::{self, ParsedObject};
+use crate::core::resolve_parse::resolve_ref;
+use crate::core::attributes::Attributes;
+use crate::core::{diff, simple_index};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+/// Arguments for the `guts diff` command
+#[derive(Args)]
+pub struct DiffArgs {
+    /// Compare the staged files against HEAD instead of the working tree
+    #[arg(long)]
+    pub cached: bool,
+
+    /// Only list the paths of the changed files
+    #[arg(long, conflicts_with = "name_status")]
+    pub name_only: bool,
+
+    /// List the changed paths, each prefixed with A (added), M (modified) or D (deleted)
+    #[arg(long)]
+    pub name_status: bool,
+
+    /// Commits to compare: `<a> <b>` or `<a>..<b>`. A single commit is compared with
+    /// the working tree (or the index with `--cached`), none means HEAD.
+    pub commits: Vec<String>,
+}
+
+/// A changed path with its old and new content (None when the file is absent) and the
+/// mode of the side that exists
+type Change<'a> = (&'a String, Option<Vec<u8>>, Option<Vec<u8>>, &'a str);
+
+/// Entry point for the `guts diff` command
+/// Shows a unified diff between HEAD and the working tree (or the index with `--cached`),
+/// or between two commits
+pub fn run(args: &DiffArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+
+    let repo_root = simple_index::find_repo_root()?;
+    let git_dir = repo_root.join(".git");
+
+    let (old_revision, new_revision) = split_revisions(&args.commits)?;
+    let (old_files, old_modes) = match old_revision {
+        Some(revision) => files_at(&git_dir, revision)?,
+        None => {
+            let modes = head_tree_sha(&git_dir)?
+                .map(|tree| simple_index::get_modes_from_tree(&git_dir, &tree))
+                .transpose()?
+                .unwrap_or_default();
+            (simple_index::get_committed_files()?, modes)
+        }
+    };
+    let new_tree = new_revision.map(|revision| files_at(&git_dir, revision)).transpose()?;
+    let (new_files, new_modes) = match new_tree {
+        Some((files, modes)) => (Some(files), Some(modes)),
+        None => (None, None),
+    };
+    let index = simple_index::SimpleIndex::load()?;
+    let attributes = Attributes::load(&repo_root)?;
+
+    let mut changes: Vec<Change> = Vec::new();
+    let new_paths = new_files.as_ref().unwrap_or(&index.files);
+    let paths: BTreeSet<&String> = old_files.keys().chain(new_paths.keys()).collect();
+
+    for path in paths {
+        let old_hash = old_files.get(path);
+        let mut new_mode = mode_in(new_modes.as_ref().unwrap_or(&index.modes), path);
+        let new = if new_files.is_some() || args.cached {
+            // Another commit, or the index: compare the blob SHAs first
+            let new_hash = new_paths.get(path);
+            if old_hash == new_hash {
+                continue;
+            }
+            new_hash.map(|sha| read_blob(&git_dir, sha)).transpose()?
+        } else {
+            // Working tree, for every committed or staged path
+            let work_path = repo_root.join(path);
+            if work_path.is_file() || simple_index::is_symlink(&work_path) {
+                new_mode = simple_index::file_mode(&work_path)?;
+                Some(simple_index::read_worktree_file(&work_path, path, &attributes)?)
+            } else {
+                None
+            }
+        };
+        let old = old_hash.map(|sha| read_blob(&git_dir, sha)).transpose()?;
+
+        if old != new {
+            let mode = if new.is_some() { new_mode } else { mode_in(&old_modes, path) };
+            changes.push((path, old, new, mode));
+        }
+    }
+
+    let mut output = String::new();
+    for (path, old, new, mode) in changes {
+        if args.name_only {
+            output.push_str(&format!("{}\n", path));
+        } else if args.name_status {
+            let status = match (&old, &new) {
+                (None, _) => 'A',
+                (_, None) => 'D',
+                _ => 'M',
+            };
+            output.push_str(&format!("{}\t{}\n", status, path));
+        } else {
+            output.push_str(&diff::diff_file(path, old.as_deref(), new.as_deref(), mode));
+        }
+    }
+
+    Ok(output)
+}
+
+/// Old and new revisions given on the command line; None stands for HEAD (old side)
+/// or the working tree/index (new side). `a..b` is the same as `a b`, a missing side
+/// of the range being HEAD.
+fn split_revisions(commits: &[String]) -> Result<(Option<&str>, Option<&str>)> {
+    match commits {
+        [] => Ok((None, None)),
+        [range] if range.contains("..") => {
+            let (old, new) = range.split_once("..").unwrap_or_default();
+            let old = if old.is_empty() { "HEAD" } else { old };
+            let new = if new.is_empty() { "HEAD" } else { new };
+            Ok((Some(old), Some(new)))
+        }
+        [commit] => Ok((Some(commit.as_str()), None)),
+        [old, new] => Ok((Some(old.as_str()), Some(new.as_str()))),
+        _ => Err(anyhow!("fatal: diff takes at most two commits")),
+    }
+}
+
+/// Every file of a commit's tree, as `path -> blob SHA`, with the modes other than `100644`
+fn files_at(git_dir: &Path, revision: &str) -> Result<(HashMap<String, String>, HashMap<String, String>)> {
+    let sha = resolve_ref(git_dir, revision)?;
+    match cat::read_object(git_dir, &sha)? {
+        ParsedObject::Commit(commit) => Ok((
+            simple_index::get_files_from_tree(git_dir, &commit.tree, "")?,
+            simple_index::get_modes_from_tree(git_dir, &commit.tree)?,
+        )),
+        _ => Err(anyhow!("fatal: {} is not a commit", revision)),
+    }
+}
+
+/// Mode of a path in a `path -> mode` map that only lists the modes other than `100644`
+fn mode_in<'a>(modes: &'a HashMap<String, String>, path: &str) -> &'a str {
+    modes.get(path).map_or(simple_index::MODE_FILE, String::as_str)
+}
+
+/// Renders the diff between two file lists (`path -> blob sha`), e.g. two commits' trees,
+/// with their modes other than `100644`
+pub fn diff_trees(
+    git_dir: &Path,
+    old_files: &HashMap<String, String>,
+    old_modes: &HashMap<String, String>,
+    new_files: &HashMap<String, String>,
+    new_modes: &HashMap<String, String>,
+) -> Result<String> {
+    let paths: BTreeSet<&String> = old_files.keys().chain(new_files.keys()).collect();
+    let mut output = String::new();
+
+    for path in paths {
+        let old_hash = old_files.get(path);
+        let new_hash = new_files.get(path);
+        if old_hash == new_hash {
+            continue;
+        }
+
+        let old = old_hash.map(|sha| read_blob(git_dir, sha)).transpose()?;
+        let new = new_hash.map(|sha| read_blob(git_dir, sha)).transpose()?;
+        let mode = if new.is_some() { mode_in(new_modes, path) } else { mode_in(old_modes, path) };
+        output.push_str(&diff::diff_file(path, old.as_deref(), new.as_deref(), mode));
+    }
+
+    Ok(output)
+}
+
+/// Reads the content of a blob object
+pub fn read_blob(git_dir: &Path, sha: &str) -> Result<Vec<u8>> {
+    match cat::read_object(git_dir, sha)? {
+        ParsedObject::Blob(data) => Ok(data),
+        _ => Err(anyhow!("fatal: object {} is not a blob", sha)),
+    }
+}
@@ -0,0 +1,52 @@
+use crate::commands::checkout::{parse_tree_object, read_and_parse_git_object};
+use crate::core::cat::{self, ParsedObject};
+use crate::core::resolve_parse::resolve_ref;
+use crate::core::simple_index;
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use std::fs;
+use std::path::PathBuf;
+
+/// Arguments for the `guts export` command
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Commit (or tree) to check out (branch, tag, SHA, HEAD~n...)
+    pub commit: String,
+
+    /// Directory to write the files to: created if needed, it must be empty
+    pub directory: PathBuf,
+}
+
+/// Main function for the `guts export` command
+/// Writes the files of a commit into another directory, to look at an old state side
+/// by side with the working tree. HEAD, the index and the working tree are untouched.
+pub fn run(args: &ExportArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    let git_dir = simple_index::find_repo_root()?.join(".git");
+
+    let sha = resolve_ref(&git_dir, &args.commit)?;
+    let tree_sha = match cat::read_object(&git_dir, &sha)? {
+        ParsedObject::Commit(commit) => commit.tree,
+        ParsedObject::Tree(_) => sha.clone(),
+        _ => return Err(anyhow!("fatal: '{}' is not a commit or a tree", args.commit)),
+    };
+
+    if args.directory.exists() {
+        let is_empty = fs::read_dir(&args.directory)
+            .with_context(|| format!("fatal: '{}' is not a directory", args.directory.display()))?
+            .next()
+            .is_none();
+        if !is_empty {
+            return Err(anyhow!("fatal: '{}' is not empty", args.directory.display()));
+        }
+    }
+    fs::create_dir_all(&args.directory)
+        .with_context(|| format!("failed to create {:?}", args.directory))?;
+
+    let tree_content = read_and_parse_git_object(&git_dir, &tree_sha)?;
+    parse_tree_object(&git_dir, &tree_content, args.directory.clone())?;
+
+    Ok(format!("Checked out {} into '{}'", &sha[..7], args.directory.display()))
+}
@@ -0,0 +1,152 @@
+use crate::core::cat::{self, ParsedObject};
+use crate::core::config::{self, Config};
+use crate::core::simple_index;
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Arguments for the `guts fetch` command
+#[derive(Args)]
+pub struct FetchArgs {
+    /// Remote recorded with `guts remote add`
+    #[arg(default_value = "origin")]
+    pub remote: String,
+}
+
+/// Main function for the `guts fetch` command
+/// Copies the objects of the remote's branches that are missing here and points
+/// `refs/remotes/<remote>/<branch>` at them. Only local repositories are supported.
+pub fn run(args: &FetchArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    let repo_root = simple_index::find_repo_root()?;
+    let git_dir = repo_root.join(".git");
+
+    let url = Config::load(&config::repo_config_path()?)?
+        .get(&format!("remote.{}.url", args.remote))
+        .map(str::to_string)
+        .ok_or_else(|| anyhow!("fatal: '{}' does not appear to be a guts repository", args.remote))?;
+    let remote_git_dir = remote_git_dir(&repo_root, &url)?;
+
+    let branches = list_branches(&remote_git_dir)?;
+    copy_missing_objects(&remote_git_dir, &git_dir, branches.values())?;
+
+    let mut output = String::new();
+    for (branch, sha) in &branches {
+        let tracking = format!("{}/{}", args.remote, branch);
+        let ref_path = git_dir.join("refs/remotes").join(&tracking);
+        let old = fs::read_to_string(&ref_path).ok().map(|s| s.trim().to_string());
+        if old.as_deref() == Some(sha.as_str()) {
+            continue;
+        }
+
+        if let Some(parent) = ref_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&ref_path, format!("{}\n", sha))
+            .with_context(|| format!("failed to write {}", ref_path.display()))?;
+
+        match old {
+            Some(old) => output.push_str(&format!("   {}..{}  {} -> {}\n", &old[..7], &sha[..7], branch, tracking)),
+            None => output.push_str(&format!(" * [new branch]      {} -> {}\n", branch, tracking)),
+        }
+    }
+
+    if output.is_empty() {
+        return Ok(output);
+    }
+    Ok(format!("From {}\n{}", url, output))
+}
+
+/// `.git` directory of the repository a remote URL points to; relative paths are
+/// taken from the repository root, and bare repositories are accepted
+pub(crate) fn remote_git_dir(repo_root: &Path, url: &str) -> Result<PathBuf> {
+    let path = repo_root.join(url.strip_prefix("file://").unwrap_or(url));
+    if path.join(".git").is_dir() {
+        return Ok(path.join(".git"));
+    }
+    if path.join("objects").is_dir() && path.join("refs").is_dir() {
+        return Ok(path);
+    }
+    Err(anyhow!("fatal: '{}' does not appear to be a local guts repository", url))
+}
+
+/// Every branch of a repository with the SHA it points to
+pub(crate) fn list_branches(git_dir: &Path) -> Result<BTreeMap<String, String>> {
+    let heads = git_dir.join("refs/heads");
+    let mut branches = BTreeMap::new();
+    for entry in WalkDir::new(&heads).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let name = entry.path().strip_prefix(&heads)?.to_string_lossy().replace('\\', "/");
+        let sha = fs::read_to_string(entry.path())?.trim().to_string();
+        branches.insert(name, sha);
+    }
+    Ok(branches)
+}
+
+/// Copies the loose objects reachable from `tips` in `from` that `to` lacks.
+///
+/// The whole set is read before anything is copied, so a missing or corrupt object
+/// copies nothing. Objects are then copied before those pointing to them: an object
+/// present in `to` always comes with everything it points to, which is why the walk
+/// stops there, even after an interrupted copy. Returns the number of objects copied.
+pub(crate) fn copy_missing_objects<'a>(
+    from: &Path,
+    to: &Path,
+    tips: impl IntoIterator<Item = &'a String>,
+) -> Result<usize> {
+    // (sha, whether the objects it points to were all listed already)
+    let mut pending: Vec<(String, bool)> = tips.into_iter().map(|sha| (sha.clone(), false)).collect();
+    let mut seen = HashSet::new();
+    // Every missing object, after the objects it points to
+    let mut missing = Vec::new();
+
+    while let Some((sha, children_listed)) = pending.pop() {
+        if children_listed {
+            missing.push(sha);
+            continue;
+        }
+        if !seen.insert(sha.clone()) || cat::get_object_path(to, &sha).exists() {
+            continue;
+        }
+
+        let object = cat::read_object(from, &sha).with_context(|| format!("fatal: missing object {} in the remote", sha))?;
+        pending.push((sha, true));
+        match object {
+            ParsedObject::Commit(commit) => {
+                pending.push((commit.tree, false));
+                pending.extend(commit.parent.unwrap_or_default().into_iter().map(|parent| (parent, false)));
+            }
+            ParsedObject::Tree(entries) => pending.extend(
+                entries
+                    .into_iter()
+                    // Submodule commits live in another repository
+                    .filter(|entry| entry.mode != "160000")
+                    .map(|entry| (hex::encode(entry.hash), false)),
+            ),
+            ParsedObject::Tag(tag) => pending.push((tag.object, false)),
+            _ => {}
+        }
+    }
+
+    for sha in &missing {
+        let source = cat::get_object_path(from, sha);
+        let target = cat::get_object_path(to, sha);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Objects are compressed the same way on both sides: the file is copied as is,
+        // under a temporary name so that a partial copy never passes for the object
+        let partial = target.with_file_name(format!("tmp_obj_{}", sha));
+        fs::copy(&source, &partial).with_context(|| format!("failed to copy object {}", sha))?;
+        fs::rename(&partial, &target).with_context(|| format!("failed to copy object {}", sha))?;
+    }
+
+    Ok(missing.len())
+}
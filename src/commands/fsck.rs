@@ -0,0 +1,101 @@
+use crate::commands::gc::{loose_objects, reachable_objects};
+use crate::core::cat::{self, ParsedObject};
+use crate::core::simple_index;
+use anyhow::{anyhow, Result};
+use clap::Args;
+use sha1::{Digest, Sha1};
+use std::collections::{BTreeMap, HashSet};
+
+/// Arguments for the `guts fsck` command
+#[derive(Args)]
+pub struct FsckArgs {}
+
+/// Main function for the `guts fsck` command
+/// Checks every loose object: it must inflate, hash to its own name and parse, and
+/// the objects it points to must exist. Unreachable objects nothing points to are
+/// reported as dangling. Fails when a corrupt or missing object is found.
+pub fn run(_args: &FsckArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    let git_dir = simple_index::find_repo_root()?.join(".git");
+
+    let objects = loose_objects(&git_dir)?;
+    let mut errors = Vec::new();
+    // Type of every sound object
+    let mut types: BTreeMap<String, String> = BTreeMap::new();
+    // Objects pointed to by another object, with the type they should have
+    let mut referenced: BTreeMap<String, &str> = BTreeMap::new();
+
+    for sha in &objects {
+        let raw = match cat::read_raw_object(&git_dir, sha) {
+            Ok(raw) => raw,
+            Err(_) => {
+                errors.push(format!("error: corrupt object {} (cannot be inflated)", sha));
+                continue;
+            }
+        };
+        let computed = hex::encode(Sha1::digest(&raw));
+        if computed != *sha {
+            errors.push(format!("error: corrupt object {} (hash mismatch, content hashes to {})", sha, computed));
+            continue;
+        }
+        let object = match cat::parse_object(&raw) {
+            Ok(object) => object,
+            Err(e) => {
+                errors.push(format!("error: corrupt object {} ({})", sha, e));
+                continue;
+            }
+        };
+
+        let object_type = match object {
+            ParsedObject::Blob(_) => "blob".to_string(),
+            ParsedObject::Tree(entries) => {
+                for entry in entries {
+                    let kind = match entry.mode.as_str() {
+                        "40000" => "tree",
+                        // Submodule commits live in another repository
+                        "160000" => continue,
+                        _ => "blob",
+                    };
+                    referenced.insert(hex::encode(entry.hash), kind);
+                }
+                "tree".to_string()
+            }
+            ParsedObject::Commit(commit) => {
+                referenced.insert(commit.tree, "tree");
+                for parent in commit.parent.unwrap_or_default() {
+                    referenced.insert(parent, "commit");
+                }
+                "commit".to_string()
+            }
+            ParsedObject::Tag(tag) => {
+                referenced.insert(tag.object, "object");
+                "tag".to_string()
+            }
+            ParsedObject::Other(kind, _) => kind,
+        };
+        types.insert(sha.clone(), object_type);
+    }
+
+    let present: HashSet<&String> = objects.iter().collect();
+    for (sha, kind) in &referenced {
+        if !present.contains(sha) {
+            errors.push(format!("missing {} {}", kind, sha));
+        }
+    }
+
+    // Unreadable objects are already reported as corrupt or missing above
+    let (reachable, _) = reachable_objects(&git_dir)?;
+    let mut output = String::new();
+    for (sha, object_type) in &types {
+        if !reachable.contains(sha) && !referenced.contains_key(sha) {
+            output.push_str(&format!("dangling {} {}\n", object_type, sha));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(anyhow!("{}{}", output, errors.join("\n")));
+    }
+    Ok(output)
+}
@@ -0,0 +1,168 @@
+use crate::core::cat::{self, ParsedObject};
+use crate::core::reflog::{self, NULL_SHA};
+use crate::core::resolve_parse::resolve_ref;
+use crate::core::simple_index::{self, SimpleIndex};
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Arguments for the `guts gc` command
+#[derive(Args)]
+pub struct GcArgs {
+    /// Only list the unreachable objects, delete nothing
+    #[arg(short = 'n', long)]
+    pub dry_run: bool,
+}
+
+/// Main function for the `guts gc` command
+/// Deletes the loose objects that nothing points to any more
+pub fn run(args: &GcArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    let git_dir = simple_index::find_repo_root()?.join(".git");
+
+    // Whatever an unreadable object points to is unknown: deleting anything could
+    // destroy history that is still in use
+    let (reachable, mut unreadable) = reachable_objects(&git_dir)?;
+    if !unreadable.is_empty() {
+        unreadable.sort();
+        return Err(anyhow!(
+            "fatal: cannot read reachable object(s) {}, nothing was removed",
+            unreadable.join(", ")
+        ));
+    }
+
+    let mut output = String::new();
+    for sha in loose_objects(&git_dir)? {
+        if reachable.contains(&sha) {
+            continue;
+        }
+        if args.dry_run {
+            output.push_str(&format!("Would remove {}\n", sha));
+            continue;
+        }
+
+        let path = cat::get_object_path(&git_dir, &sha);
+        fs::remove_file(&path).with_context(|| format!("failed to remove {:?}", path))?;
+        // Drop the `objects/xx` directory once it is empty
+        if let Some(parent) = path.parent() {
+            let _ = fs::remove_dir(parent);
+        }
+        output.push_str(&format!("Removing {}\n", sha));
+    }
+
+    Ok(output)
+}
+
+/// Every object reachable from HEAD, the refs, the reflogs, the stash and the index,
+/// and those of them that could not be read (missing, corrupt or packed): the walk
+/// does not go past them.
+pub(crate) fn reachable_objects(git_dir: &Path) -> Result<(HashSet<String>, Vec<String>)> {
+    let mut pending: Vec<String> = Vec::new();
+
+    if let Ok(head) = resolve_ref(git_dir, "HEAD") {
+        pending.push(head);
+    }
+
+    let refs_dir = git_dir.join("refs");
+    for entry in WalkDir::new(&refs_dir).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let content = fs::read_to_string(entry.path())?;
+        match content.trim().strip_prefix("ref: ") {
+            // Symbolic refs point to a ref that is walked anyway
+            Some(_) => {}
+            None => pending.push(content.trim().to_string()),
+        }
+    }
+
+    // Objects a reflog can bring back (e.g. `HEAD@{1}` after an amend) are kept
+    let logs_dir = git_dir.join("logs");
+    for entry in WalkDir::new(&logs_dir).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let ref_name = entry.path().strip_prefix(&logs_dir)?.to_string_lossy().to_string();
+        for log_entry in reflog::read(git_dir, &ref_name)? {
+            pending.push(log_entry.old_sha);
+            pending.push(log_entry.new_sha);
+        }
+    }
+
+    // Stash entries hold their base commit and the saved tree
+    if let Ok(content) = fs::read_to_string(git_dir.join("stash")) {
+        let entries: Vec<serde_json::Value> =
+            serde_json::from_str(&content).with_context(|| "invalid JSON in stash")?;
+        for entry in entries {
+            for field in ["base", "tree"] {
+                if let Some(sha) = entry[field].as_str() {
+                    pending.push(sha.to_string());
+                }
+            }
+        }
+    }
+
+    // Staged but not committed yet
+    let index = SimpleIndex::load()?;
+    pending.extend(
+        index
+            .files
+            .iter()
+            .filter(|(path, _)| index.mode_of(path) != "160000")
+            .map(|(_, sha)| sha.clone()),
+    );
+
+    let mut reachable = HashSet::new();
+    let mut unreadable = Vec::new();
+    while let Some(sha) = pending.pop() {
+        if sha == NULL_SHA || !reachable.insert(sha.clone()) {
+            continue;
+        }
+        let Ok(object) = cat::read_object(git_dir, &sha) else {
+            unreadable.push(sha);
+            continue;
+        };
+        match object {
+            ParsedObject::Commit(commit) => {
+                pending.push(commit.tree);
+                pending.extend(commit.parent.unwrap_or_default());
+            }
+            ParsedObject::Tree(entries) => pending.extend(
+                entries
+                    .into_iter()
+                    // Submodule commits live in another repository
+                    .filter(|entry| entry.mode != "160000")
+                    .map(|entry| hex::encode(entry.hash)),
+            ),
+            ParsedObject::Tag(tag) => pending.push(tag.object),
+            _ => {}
+        }
+    }
+
+    Ok((reachable, unreadable))
+}
+
+/// SHAs of every loose object in `objects/xx/yyyy...`, sorted
+pub(crate) fn loose_objects(git_dir: &Path) -> Result<Vec<String>> {
+    let mut objects = Vec::new();
+    for dir in fs::read_dir(git_dir.join("objects"))? {
+        let dir = dir?;
+        let prefix = dir.file_name().to_string_lossy().to_string();
+        if prefix.len() != 2 || !prefix.chars().all(|c| c.is_ascii_hexdigit()) || !dir.path().is_dir() {
+            continue;
+        }
+        for file in fs::read_dir(dir.path())? {
+            let rest = file?.file_name().to_string_lossy().to_string();
+            if rest.len() == 38 && rest.chars().all(|c| c.is_ascii_hexdigit()) {
+                objects.push(format!("{}{}", prefix, rest));
+            }
+        }
+    }
+    objects.sort();
+    Ok(objects)
+}
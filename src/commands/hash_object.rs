@@ -1,31 +1,84 @@
-use crate::core::{blob, hash};
+use crate::core::object::GitObject;
+use crate::core::{hash, simple_index};
 use anyhow::{anyhow, Context, Result};
 use clap::Args;
+use std::io::Read;
 use std::path::PathBuf;
 
 #[derive(Args)]
 pub struct HashObjectArgs {
-    /// Path to the file to hash
-    pub file: PathBuf,
-    /// Current directory for the operation (injected by TUI)
-    pub dir: Option<PathBuf>,
+    /// Paths to the files to hash, one SHA is printed per file
+    #[arg(required_unless_present = "stdin")]
+    pub files: Vec<PathBuf>,
+
+    /// Hash the content read from standard input (before the files)
+    #[arg(long)]
+    pub stdin: bool,
+
+    /// Write the objects to the object database, not only print their SHA
+    #[arg(short = 'w')]
+    pub write: bool,
+
+    /// Type of object to create
+    #[arg(short = 't', value_name = "type", default_value = "blob",
+          value_parser = ["blob", "tree", "commit", "tag"])]
+    pub object_type: String,
+}
+
+/// Any object built from raw bytes, `-t` choosing its type
+struct RawObject<'a> {
+    object_type: &'a str,
+    data: Vec<u8>,
+}
+
+impl GitObject for RawObject<'_> {
+    fn object_type(&self) -> &str {
+        self.object_type
+    }
+
+    fn content(&self) -> Vec<u8> {
+        self.data.clone()
+    }
 }
 
 pub fn run(args: &HashObjectArgs) -> Result<String> {
-    let path = &args.file;
+    let mut contents = Vec::new();
 
-    if !path.exists() {
-        return Err(anyhow!("file {:?} does not exist", path));
+    if args.stdin {
+        let mut data = Vec::new();
+        std::io::stdin()
+            .read_to_end(&mut data)
+            .with_context(|| "failed to read standard input")?;
+        contents.push(data);
     }
 
-    if path.is_dir() {
-        return Err(anyhow!("path {:?} is a directory", path));
+    for path in &args.files {
+        if !path.exists() {
+            return Err(anyhow!("file {:?} does not exist", path));
+        }
+
+        if path.is_dir() {
+            return Err(anyhow!("path {:?} is a directory", path));
+        }
+
+        contents.push(std::fs::read(path).with_context(|| format!("failed to read file {:?}", path))?);
     }
 
-    let data = std::fs::read(path).with_context(|| format!("failed to read file {:?}", path))?;
+    // Objects are written relative to the repository root
+    if args.write {
+        std::env::set_current_dir(simple_index::find_repo_root()?)?;
+    }
 
-    let blob = blob::Blob::new(data);
-    let oid = hash::write_object(&blob)?;
+    let mut oids = Vec::new();
+    for data in contents {
+        let object = RawObject { object_type: &args.object_type, data };
+        let oid = if args.write {
+            hash::write_object(&object)?
+        } else {
+            hash::hash_object(&object)
+        };
+        oids.push(oid);
+    }
 
-    Ok(oid)
+    Ok(oids.join("\n"))
 }
@@ -1,10 +1,20 @@
-use crate::core::repo;
+use crate::core::{config, repo};
 use anyhow::{anyhow, Context, Result};
 use clap::Args;
 use std::path::PathBuf;
 
 #[derive(Args)]
 pub struct InitArgs {
+    /// Name of the branch HEAD points to in the new repository (defaults to
+    /// `init.defaultBranch`, else `main`)
+    #[arg(short = 'b', long, value_name = "name")]
+    pub initial_branch: Option<String>,
+
+    /// Create a bare repository (e.g. a push target): the repository files go directly
+    /// in the directory and there is no working tree
+    #[arg(long)]
+    pub bare: bool,
+
     /// Directory to initialize the repository in (defaults to current directory)
     pub dir: Option<PathBuf>,
 }
@@ -14,6 +24,19 @@ pub fn run(args: &InitArgs) -> Result<String> {
         .dir
         .clone()
         .unwrap_or_else(|| std::env::current_dir().expect("failed to get current directory"));
+    let initial_branch = match &args.initial_branch {
+        Some(branch) => branch.clone(),
+        None => config::default_branch()?,
+    };
+
+    if args.bare {
+        if dir.join("HEAD").exists() {
+            return Err(anyhow!("a repository already exists in {:?}", dir));
+        }
+        repo::init_bare(&dir, &initial_branch)
+            .with_context(|| format!("failed to initialize repository in {:?}", dir))?;
+        return Ok(format!("Initialized empty Guts repository in {:?}", dir));
+    }
 
     let git_dir = dir.join(".git");
 
@@ -21,7 +44,7 @@ pub fn run(args: &InitArgs) -> Result<String> {
         return Err(anyhow!(".git directory already exists in {:?}", dir));
     }
 
-    repo::init(&dir).with_context(|| format!("failed to initialize repository in {:?}", dir))?;
+    repo::init_with_branch(&dir, &initial_branch).with_context(|| format!("failed to initialize repository in {:?}", dir))?;
     Ok(format!(
         "Initialized empty Guts repository in {:?}",
         git_dir
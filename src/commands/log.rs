@@ -1,105 +1,454 @@
-use crate::core::cat::{get_object_path, parse_object, ParsedObject};
+use crate::commands::rev_list::reachable_commits;
+use crate::core::cat::{self, ParsedObject};
+use crate::core::object::Commit;
+use crate::core::color::{paint, ColorWhen, YELLOW};
+use crate::core::read_head::{current_head, Head};
 use crate::core::simple_index;
 use anyhow::{anyhow, Result};
+use chrono::{FixedOffset, TimeZone};
 use clap::Args;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
+use walkdir::WalkDir;
 
 /// Arguments for the `guts log` command
 #[derive(Args)]
 pub struct LogArgs {
-    /// Current directory for the operation (injected by TUI)
-    pub dir: Option<PathBuf>,
+    /// Show each commit on one line with an abbreviated SHA
+    #[arg(long)]
+    pub oneline: bool,
+
+    /// Draw the history as an ASCII graph, following every parent
+    #[arg(long)]
+    pub graph: bool,
+
+    /// Show every commit reachable from HEAD or any ref, through all parents, each
+    /// once and newest first
+    #[arg(long, conflicts_with = "graph")]
+    pub all: bool,
+
+    /// Stop after showing this many commits
+    #[arg(short = 'n', long = "max-count")]
+    pub max_count: Option<usize>,
+
+    /// Visit at most this many commits, nearest to HEAD first: the walk itself stops
+    /// there, older history is never read
+    #[arg(long)]
+    pub depth: Option<usize>,
+
+    /// Print each commit with a custom format: %H, %h, %s, %an, %ae, %ad, %P, %n and %%
+    #[arg(long, value_name = "string")]
+    pub format: Option<String>,
+
+    /// Highlight commit SHAs
+    #[arg(long, value_enum, default_value_t = ColorWhen::Auto, value_name = "when")]
+    pub color: ColorWhen,
 }
 
 /// Entry point for the `guts log` command
-/// Traverses the commit chain from HEAD to root, printing each commit's SHA and first line of message.
+/// Traverses the commit chain from HEAD to root, printing each commit's SHA, author, date and message
+/// (or only the abbreviated SHA and first line of the message with `--oneline`).
 pub fn run(args: &LogArgs) -> Result<String> {
-    // Set current directory context for TUI
-    let original_dir = std::env::current_dir()?;
-    if let Some(dir) = &args.dir {
-        std::env::set_current_dir(dir)?;
+    // Check if we're in a git repository
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
     }
-    
-    let result = || -> Result<String> {
-        // Check if we're in a git repository
-        if !simple_index::is_git_repository()? {
-            return Err(anyhow!("fatal: not a git repository"));
-        }
-        
-        let current_dir = std::env::current_dir()?;
 
-    // Use the standard .git directory
-    let git_dir = current_dir.join(".git");
+    let current_dir = std::env::current_dir()?;
 
-    // Read HEAD to get current commit
-    let head_path = git_dir.join("HEAD");
-    if !head_path.exists() {
-        return Err(anyhow!("fatal: not a git repository (HEAD missing)"));
-    }
+// Use the standard .git directory
+let git_dir = current_dir.join(".git");
 
-    let head_content = fs::read_to_string(&head_path)?.trim().to_string();
+// Read HEAD to get current commit
+let head_path = git_dir.join("HEAD");
+if !head_path.exists() {
+    return Err(anyhow!("fatal: not a git repository (HEAD missing)"));
+}
 
-    // Get the commit hash
-    let commit_hash = if head_content.starts_with("ref: ") {
+// Get the commit hash
+let commit_hash = match current_head(&git_dir)? {
+    Head::Branch(name) => {
         // HEAD points to a branch
-        let ref_path = head_content.strip_prefix("ref: ").unwrap();
-        let ref_file = git_dir.join(ref_path);
+        let ref_file = git_dir.join("refs").join("heads").join(name);
         if !ref_file.exists() {
             return Err(anyhow!("fatal: branch exists but no commits yet"));
         }
         fs::read_to_string(ref_file)?.trim().to_string()
+    }
+    // Detached HEAD, direct commit hash
+    Head::Detached(sha) => sha,
+};
+
+let max_count = args.max_count.unwrap_or(usize::MAX);
+let depth = args.depth.unwrap_or(usize::MAX);
+let color = args.color.enabled();
+
+if args.graph {
+    let mut commits = graph_order(&git_dir, &commit_hash, depth)?;
+    commits.truncate(max_count);
+    return Ok(render_graph(&commits, args, color));
+}
+
+if args.all {
+    let mut starts = vec![commit_hash];
+    starts.extend(ref_commits(&git_dir)?);
+    let mut entries = Vec::new();
+    for sha in reachable_commits(&git_dir, &starts, max_count.min(depth))? {
+        entries.push(format_entry(&sha, &read_commit(&git_dir, &sha)?, args, color));
+    }
+    let separator = if is_compact(args) { "\n" } else { "\n\n" };
+    return Ok(entries.join(separator));
+}
+
+// Traverse commit chain
+let mut entries = Vec::new();
+let mut current_hash = commit_hash;
+while entries.len() < max_count.min(depth) {
+    let commit = read_commit(&git_dir, &current_hash)?;
+
+    entries.push(format_entry(&current_hash, &commit, args, color));
+
+    // Merge commits have several parents: follow the first one (the branch merged into)
+    if let Some(parent_hash) = commit.parent {
+        current_hash = parent_hash[0].clone();
     } else {
-        // Detached HEAD, direct commit hash
-        head_content
+        break;
+    }
+}
+
+    // The verbose format separates commits with a blank line
+    let separator = if is_compact(args) { "\n" } else { "\n\n" };
+    Ok(entries.join(separator))
+}
+
+/// Reads a commit object, failing if the SHA points to anything else
+fn read_commit(git_dir: &Path, sha: &str) -> Result<Commit> {
+    if !cat::get_object_path(git_dir, sha).exists() {
+        return Err(anyhow!("fatal: commit object {} not found", sha));
+    }
+    match cat::read_object_cached(git_dir, sha)? {
+        ParsedObject::Commit(commit) => Ok(commit),
+        _ => Err(anyhow!("fatal: object {} is not a commit", sha)),
+    }
+}
+
+/// The commits the refs of `refs/` point to, annotated tags peeled. Symbolic refs and
+/// tags of anything but a commit are skipped.
+fn ref_commits(git_dir: &Path) -> Result<Vec<String>> {
+    let mut commits = Vec::new();
+    for entry in WalkDir::new(git_dir.join("refs")).sort_by_file_name() {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let mut sha = fs::read_to_string(entry.path())?.trim().to_string();
+        if sha.starts_with("ref: ") {
+            continue;
+        }
+        loop {
+            match cat::read_object_cached(git_dir, &sha) {
+                Ok(ParsedObject::Commit(_)) => {
+                    commits.push(sha);
+                    break;
+                }
+                Ok(ParsedObject::Tag(tag)) => sha = tag.object,
+                _ => break,
+            }
+        }
+    }
+    Ok(commits)
+}
+
+/// True when each commit is printed without the blank line separating verbose entries
+fn is_compact(args: &LogArgs) -> bool {
+    args.oneline || args.format.is_some()
+}
+
+/// Formats one commit as requested by the options, without trailing newline
+fn format_entry(sha: &str, commit: &Commit, args: &LogArgs, color: bool) -> String {
+    match &args.format {
+        Some(format) => format_placeholders(sha, commit, format),
+        None => format_commit(sha, commit, args.oneline, color),
+    }
+}
+
+/// Expands the `--format` placeholders of a commit. Unknown placeholders are kept as they are.
+pub fn format_placeholders(sha: &str, commit: &Commit, format: &str) -> String {
+    // Author is stored as `Name <email>`
+    let (author_name, author_email) = match commit.author.split_once(" <") {
+        Some((name, email)) => (name, email.trim_end_matches('>')),
+        None => (commit.author.as_str(), ""),
     };
 
-    // Traverse commit chain
     let mut output = String::new();
-    let mut current_hash = commit_hash;
-    loop {
-        let commit_obj_path = get_object_path(&git_dir, &current_hash);
-        if !commit_obj_path.exists() {
-            return Err(anyhow!("fatal: commit object {} not found", current_hash));
+    let mut rest = format;
+    while let Some(position) = rest.find('%') {
+        output.push_str(&rest[..position]);
+        rest = &rest[position..];
+
+        let (expansion, len) = if rest.starts_with("%an") {
+            (author_name.to_string(), 3)
+        } else if rest.starts_with("%ae") {
+            (author_email.to_string(), 3)
+        } else if rest.starts_with("%ad") {
+            (format_date(commit.author_date, &commit.author_timezone), 3)
+        } else {
+            match rest.chars().nth(1) {
+                Some('H') => (sha.to_string(), 2),
+                Some('h') => (sha[..7].to_string(), 2),
+                Some('s') => (commit.message.lines().next().unwrap_or("").to_string(), 2),
+                Some('P') => (commit.parent.iter().flatten().cloned().collect::<Vec<_>>().join(" "), 2),
+                Some('n') => ("\n".to_string(), 2),
+                Some('%') => ("%".to_string(), 2),
+                // Not a placeholder: keep the `%` literally
+                _ => ("%".to_string(), 1),
+            }
+        };
+        output.push_str(&expansion);
+        rest = &rest[len..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Formats one commit, without trailing newline.
+///
+/// `--oneline` gives `<short sha> <subject>`, otherwise git's default format:
+/// ```text
+/// commit <sha>
+/// Author: Name <email>
+/// Date:   Tue Nov 14 22:13:20 2023 +0000
+///
+///     <message, indented>
+/// ```
+pub fn format_commit(sha: &str, commit: &Commit, oneline: bool, color: bool) -> String {
+    if oneline {
+        let subject = commit.message.lines().next().unwrap_or("");
+        return format!("{} {}", paint(&sha[..7], YELLOW, color), subject);
+    }
+
+    let mut output = format!(
+        "{}\nAuthor: {}\nDate:   {}\n",
+        paint(&format!("commit {}", sha), YELLOW, color),
+        commit.author,
+        format_date(commit.author_date, &commit.author_timezone)
+    );
+    for line in commit.message.lines() {
+        output.push('\n');
+        if !line.is_empty() {
+            output.push_str("    ");
+            output.push_str(line);
         }
+    }
+    output
+}
+
+/// Formats a Unix timestamp in the commit's own timezone, like `git log`
+fn format_date(timestamp: i64, timezone: &str) -> String {
+    let offset = parse_timezone(timezone).unwrap_or(FixedOffset::east_opt(0).unwrap());
+    match offset.timestamp_opt(timestamp, 0).single() {
+        Some(date) => format!("{} {}", date.format("%a %b %-d %H:%M:%S %Y"), timezone),
+        None => format!("{} {}", timestamp, timezone),
+    }
+}
+
+/// Parses a `+HHMM` / `-HHMM` offset
+pub(crate) fn parse_timezone(timezone: &str) -> Option<FixedOffset> {
+    let sign = match timezone.get(..1)? {
+        "+" => 1,
+        "-" => -1,
+        _ => return None,
+    };
+    let hours: i32 = timezone.get(1..3)?.parse().ok()?;
+    let minutes: i32 = timezone.get(3..5)?.parse().ok()?;
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Lists every commit reachable from `head` so that a commit always comes before its parents.
+///
+/// Among the commits whose children are all shown, the most recent one is picked next;
+/// on equal dates the last discovered wins, which shows the merged branch right after
+/// the merge commit like git does.
+fn graph_order(git_dir: &Path, head: &str, depth: usize) -> Result<Vec<(String, Commit)>> {
+    // Load the reachable history breadth-first, up to `depth` commits
+    let mut commits: HashMap<String, Commit> = HashMap::new();
+    let mut queue = VecDeque::from([head.to_string()]);
+    let mut truncated = false;
+    while let Some(sha) = queue.pop_front() {
+        if commits.contains_key(&sha) {
+            continue;
+        }
+        if commits.len() == depth {
+            truncated = true;
+            break;
+        }
+        let commit = read_commit(git_dir, &sha)?;
+        queue.extend(commit.parent.iter().flatten().cloned());
+        commits.insert(sha, commit);
+    }
+
+    // Past the depth the history is cut, like in a shallow clone: parents that were
+    // not loaded are dropped and their children drawn as roots
+    if truncated {
+        let loaded: HashSet<String> = commits.keys().cloned().collect();
+        for commit in commits.values_mut() {
+            if let Some(parents) = &mut commit.parent {
+                parents.retain(|parent| loaded.contains(parent));
+            }
+        }
+    }
+
+    // Number of children of each commit that are not printed yet
+    let mut pending_children: HashMap<&str, usize> = HashMap::new();
+    for commit in commits.values() {
+        for parent in commit.parent.iter().flatten() {
+            *pending_children.entry(parent.as_str()).or_default() += 1;
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(commits.len());
+    let mut ready: Vec<&str> = vec![head];
+    while !ready.is_empty() {
+        let next = (0..ready.len())
+            .max_by_key(|&i| (commits[ready[i]].committer_date, i))
+            .unwrap();
+        let sha = ready.remove(next);
+        let commit = &commits[sha];
+
+        for parent in commit.parent.iter().flatten() {
+            let count = pending_children.get_mut(parent.as_str()).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.push(parent);
+            }
+        }
+        ordered.push(sha.to_string());
+    }
 
-        let commit_data = fs::read(&commit_obj_path)?;
-        let decompressed = decompress_object(&commit_data)?;
-        let parsed = parse_object(&decompressed)?;
+    Ok(ordered
+        .into_iter()
+        .map(|sha| {
+            let commit = commits.remove(&sha).unwrap();
+            (sha, commit)
+        })
+        .collect())
+}
 
-        let (parent, message) = match parsed {
-            ParsedObject::Commit(ref commit) => (commit.parent.clone(), commit.message.clone()),
-            _ => return Err(anyhow!("fatal: object {} is not a commit", current_hash)),
+/// Draws commits (in `graph_order`) with one column per line of history being followed.
+///
+/// ```text
+/// * merge
+/// |\
+/// | * right
+/// * | left
+/// |/
+/// * base
+/// ```
+fn render_graph(commits: &[(String, Commit)], args: &LogArgs, color: bool) -> String {
+    // SHA expected next in each column
+    let mut columns: Vec<String> = Vec::new();
+    let mut output = String::new();
+
+    for (index, (sha, commit)) in commits.iter().enumerate() {
+        let col = match columns.iter().position(|c| c == sha) {
+            Some(col) => col,
+            None => {
+                columns.push(sha.clone());
+                columns.len() - 1
+            }
         };
 
-        let first_line = message.lines().next().unwrap_or("");
-        output.push_str(&format!("{} {}\n", current_hash, first_line));
+        // Commit line: `*` in its column, `|` in the others
+        let mut line: Vec<char> = vec![' '; 2 * columns.len() - 1];
+        for i in 0..columns.len() {
+            line[2 * i] = if i == col { '*' } else { '|' };
+        }
+        let line: String = line.into_iter().collect();
+
+        let parents: Vec<&String> = commit.parent.iter().flatten().collect();
 
-        if let Some(parent_hash) = parent {
-            current_hash = parent_hash[0].clone();
-        } else {
+        // Lines after the first keep the columns going below the commit
+        let mut continuation: Vec<char> = vec![' '; 2 * columns.len() - 1];
+        for i in 0..columns.len() {
+            if i != col || !parents.is_empty() {
+                continuation[2 * i] = '|';
+            }
+        }
+        let continuation: String = continuation.into_iter().collect();
+
+        let mut text = format_entry(sha, commit, args, color);
+        if !is_compact(args) && index + 1 < commits.len() {
+            // Blank separator line between verbose entries
+            text.push('\n');
+        }
+        for (i, text_line) in text.split('\n').enumerate() {
+            let prefix = if i == 0 { &line } else { &continuation };
+            output.push_str(format!("{} {}", prefix, text_line).trim_end());
+            output.push('\n');
+        }
+
+        // Nothing is drawn below the last commit shown, even if it has parents
+        if index + 1 == commits.len() {
             break;
         }
+
+        let Some(first_parent) = parents.first() else {
+            // Root commit: its column ends here
+            columns.remove(col);
+            continue;
+        };
+
+        // First parent already followed in another column: the two lines join
+        if let Some(other) = columns.iter().position(|c| c == *first_parent) {
+            let removed = col.max(other);
+            columns[col.min(other)] = first_parent.to_string();
+            output.push_str(&join_line(columns.len(), removed));
+            columns.remove(removed);
+        } else {
+            columns[col] = first_parent.to_string();
+        }
+
+        // Other parents fork new columns to the right of the commit
+        let col = columns.iter().position(|c| c == *first_parent).unwrap();
+        let mut inserted = 0;
+        for parent in &parents[1..] {
+            if !columns.contains(parent) {
+                inserted += 1;
+                columns.insert(col + inserted, parent.to_string());
+                output.push_str(&fork_line(columns.len() - 1, col + inserted - 1));
+            }
+        }
     }
 
-        Ok(output)
-    }();
-    
-    // Restore original directory
-    std::env::set_current_dir(&original_dir)?;
-    
-    result
+    output.trim_end_matches('\n').to_string()
 }
 
+/// Line drawn when column `removed` (out of `width`) merges into its left neighbour
+fn join_line(width: usize, removed: usize) -> String {
+    let mut line: Vec<char> = vec![' '; 2 * width - 1];
+    for i in 0..width {
+        if i < removed {
+            line[2 * i] = '|';
+        } else {
+            line[2 * i - 1] = '/';
+        }
+    }
+    let line: String = line.into_iter().collect();
+    format!("{}\n", line.trim_end())
+}
 
-/// Decompress Git object data (Git uses zlib compression)
-/// But our simple implementation stores objects uncompressed, so try both
-fn decompress_object(data: &[u8]) -> Result<Vec<u8>> {
-    use std::io::Read;
-    let mut decoder = flate2::read::ZlibDecoder::new(data);
-    let mut decompressed = Vec::new();
-    match decoder.read_to_end(&mut decompressed) {
-        Ok(_) => Ok(decompressed),
-        Err(_) => Ok(data.to_vec()), // If decompression fails, assume data is already uncompressed
+/// Line drawn when a new column opens right after column `col` (out of `width` before the fork)
+fn fork_line(width: usize, col: usize) -> String {
+    let mut line: Vec<char> = vec![' '; 2 * width + 1];
+    for i in 0..width {
+        if i <= col {
+            line[2 * i] = '|';
+        } else {
+            line[2 * i + 1] = '\\';
+        }
     }
+    line[2 * col + 1] = '\\';
+    let line: String = line.into_iter().collect();
+    format!("{}\n", line.trim_end())
 }
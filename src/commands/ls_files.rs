@@ -1,4 +1,6 @@
-use crate::core::simple_index;
+use crate::commands::status::{calculate_file_hash, list_working_dir_files};
+use crate::core::attributes::Attributes;
+use crate::core::{ignore::IgnoreMatcher, simple_index};
 use anyhow::Result;
 use clap::Args;
 use std::collections::HashSet;
@@ -6,42 +8,81 @@ use std::collections::HashSet;
 /// Arguments for the `guts ls-files` command
 #[derive(Args)]
 pub struct LsFilesArgs {
-    // Placeholder for future options if needed
+    /// Show the mode, blob SHA and stage number of each entry
+    #[arg(short = 's', long)]
+    pub stage: bool,
+
+    /// Show untracked files (ignored files are skipped)
+    #[arg(short = 'o', long)]
+    pub others: bool,
+
+    /// Show tracked files whose working tree content differs from the index
+    #[arg(short = 'm', long)]
+    pub modified: bool,
 }
 
 /// List all files in the index
-pub fn run(_args: &LsFilesArgs) -> Result<String> {
-    // Get all tracked files (both from current index and from last commit)
-    let mut tracked_files = HashSet::new();
-    
-    // Get currently staged files
+/// With `-m` and/or `-o`, list only the modified and/or untracked files instead
+pub fn run(args: &LsFilesArgs) -> Result<String> {
+    // The index holds every tracked file (it is kept in sync with HEAD after commits)
     let index = simple_index::SimpleIndex::load()?;
-    for file_path in index.get_staged_files() {
-        tracked_files.insert(file_path.clone());
-    }
-    
-    // Get files from the last commit
-    match simple_index::get_committed_files() {
-        Ok(committed_files) => {
-            for file_path in committed_files.keys() {
-                tracked_files.insert(file_path.clone());
+    let mut tracked_files: Vec<String> = index.get_staged_files().into_iter().cloned().collect();
+    let mut others = Vec::new();
+
+    if args.modified || args.others {
+        let repo_root = simple_index::find_repo_root()?;
+
+        if args.modified {
+            // Deleted files count as modified, like in git
+            let mut modified = Vec::new();
+            let attributes = Attributes::load(&repo_root)?;
+            for path in tracked_files {
+                let work_path = repo_root.join(&path);
+                let changed = (!work_path.is_file() && !simple_index::is_symlink(&work_path))
+                    || calculate_file_hash(&work_path, &path, &attributes)? != index.files[&path];
+                if changed {
+                    modified.push(path);
+                }
+            }
+            tracked_files = modified;
+        } else {
+            tracked_files.clear();
+        }
+
+        if args.others {
+            let matcher = IgnoreMatcher::from_gutsignore(&repo_root)
+                .unwrap_or_else(|_| IgnoreMatcher::empty());
+            let tracked: HashSet<&String> = index.files.keys().collect();
+            for work_file in list_working_dir_files(&repo_root, &matcher)? {
+                let path = work_file
+                    .strip_prefix(&repo_root)?
+                    .to_string_lossy()
+                    .to_string();
+                if !tracked.contains(&path) {
+                    others.push(path);
+                }
             }
-        },
-        Err(_) => {
-            // No commits yet, only show staged files
         }
     }
-    
-    if tracked_files.is_empty() {
-        return Ok(String::new());
-    }
-    
+
     // Sort the files for consistent output
-    let mut sorted_files: Vec<String> = tracked_files.into_iter().collect();
-    sorted_files.sort();
-    
+    tracked_files.sort();
+    others.sort();
+
+    // `--stage`: `<mode> <sha> <stage>\t<path>`, the stage is always 0 without merge conflicts
+    if args.stage {
+        tracked_files = tracked_files
+            .into_iter()
+            .map(|path| format!("{} {} 0\t{}", index.mode_of(&path), index.files[&path], path))
+            .collect();
+    }
+
     // Join all files with newlines
-    let output = sorted_files.join("\n");
-    
+    let output = tracked_files
+        .into_iter()
+        .chain(others)
+        .collect::<Vec<String>>()
+        .join("\n");
+
     Ok(output)
 }
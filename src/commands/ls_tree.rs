@@ -1,21 +1,28 @@
-use crate::core::cat;
+use crate::core::{cat, hash};
 use anyhow::{anyhow, Result};
 use clap::Args;
-use std::path::PathBuf;
+use std::path::Path;
 
 #[derive(Args)]
 pub struct LsTreeArgs {
     /// Tree SHA to list contents of
     pub tree_sha: String,
-    /// Current directory for the operation (injected by TUI)
-    pub dir: Option<PathBuf>,
+
+    /// Recurse into subtrees, printing full paths of blobs
+    #[arg(short = 'r', long)]
+    pub recursive: bool,
+
+    /// Show tree entries even when recursing into them
+    #[arg(short = 't')]
+    pub show_trees: bool,
+
+    /// Only list these paths of the tree (after `--`); a directory lists its contents
+    #[arg(last = true)]
+    pub paths: Vec<String>,
 }
 
 pub fn run(args: &LsTreeArgs) -> Result<String> {
-    let current_dir = args
-        .dir
-        .clone()
-        .unwrap_or_else(|| std::env::current_dir().expect("could not get the current dir"));
+    let current_dir = std::env::current_dir()?;
 
     let git_dir = current_dir.join(".git");
 
@@ -23,38 +30,106 @@ pub fn run(args: &LsTreeArgs) -> Result<String> {
         return Err(anyhow!("fatal: not a git repository"));
     }
 
-    // Get the object path
-    let object_path = cat::get_object_path(&git_dir, &args.tree_sha);
+    // Abbreviated SHAs are expanded to the one object they name
+    let tree_sha = hash::normalize_sha(&args.tree_sha)?;
+    let tree_sha = if tree_sha.len() < 40 {
+        cat::resolve_object_prefix(&git_dir, &tree_sha)?
+    } else {
+        tree_sha
+    };
 
-    if !object_path.exists() {
-        return Err(anyhow!("fatal: not a valid object name {}", args.tree_sha));
+    if !cat::get_object_path(&git_dir, &tree_sha).exists() {
+        return Err(anyhow!("fatal: not a valid object name: {}", args.tree_sha));
     }
 
-    // Read and parse the object
-    let object_data = std::fs::read(&object_path)?;
-    let parsed_object = cat::parse_object(&object_data)?;
-
-    match parsed_object {
-        cat::ParsedObject::Tree(entries) => {
-            let mut output = Vec::new();
-
-            for entry in entries {
-                // Convert 20-byte hash to hex string
-                let hash_hex = hex::encode(&entry.hash);
-                
-                // Format: <mode> <type> <hash><TAB><name>
-                // We need to determine the object type (blob/tree) from the mode
-                let object_type = if entry.mode.starts_with("040") {
-                    "tree"
-                } else {
-                    "blob"
-                };
-
-                output.push(format!("{} {} {}\t{}", entry.mode, object_type, hash_hex, entry.name));
-            }
-
-            Ok(output.join("\n"))
+    let mut output = Vec::new();
+    if args.paths.is_empty() {
+        list_tree(&git_dir, &tree_sha, "", args, &mut output)?;
+    }
+    for path in &args.paths {
+        list_path(&git_dir, &tree_sha, path, args, &mut output)?;
+    }
+
+    Ok(output.iter().map(|line| format!("{}\n", line)).collect())
+}
+
+/// Descends from the tree `tree_sha` to `path` and lists it: the contents of a
+/// directory, or the single entry of a file. A path the tree does not hold lists nothing.
+fn list_path(
+    git_dir: &Path,
+    tree_sha: &str,
+    path: &str,
+    args: &LsTreeArgs,
+    output: &mut Vec<String>,
+) -> Result<()> {
+    let path = path.trim_matches('/');
+    if path.is_empty() {
+        return list_tree(git_dir, tree_sha, "", args, output);
+    }
+
+    let mut current = tree_sha.to_string();
+    let components: Vec<&str> = path.split('/').collect();
+    for (i, component) in components.iter().enumerate() {
+        let entries = match cat::read_object(git_dir, &current)? {
+            cat::ParsedObject::Tree(entries) => entries,
+            // A file in the middle of the path
+            _ => return Ok(()),
+        };
+        let Some(entry) = entries.into_iter().find(|entry| entry.name == *component) else {
+            return Ok(());
+        };
+        current = hex::encode(entry.hash);
+
+        if i + 1 == components.len() && !is_tree_mode(&entry.mode) {
+            output.push(format_entry(&entry.mode, &current, path));
+            return Ok(());
         }
-        _ => Err(anyhow!("fatal: not a tree object")),
     }
+
+    list_tree(git_dir, &current, &format!("{}/", path), args, output)
+}
+
+/// Directories are stored with mode "40000" (or "040000")
+fn is_tree_mode(mode: &str) -> bool {
+    mode.trim_start_matches('0') == "40000"
+}
+
+/// Format: <mode> <type> <hash><TAB><name>
+fn format_entry(mode: &str, hash_hex: &str, path: &str) -> String {
+    let object_type = if is_tree_mode(mode) { "tree" } else { "blob" };
+    format!("{:0>6} {} {}\t{}", mode, object_type, hash_hex, path)
+}
+
+/// Appends one line per entry of the tree `tree_sha`, descending into subtrees with `-r`.
+/// `prefix` is the path of the tree relative to the root tree being listed.
+fn list_tree(
+    git_dir: &Path,
+    tree_sha: &str,
+    prefix: &str,
+    args: &LsTreeArgs,
+    output: &mut Vec<String>,
+) -> Result<()> {
+    let mut entries = match cat::read_object(git_dir, tree_sha)? {
+        cat::ParsedObject::Tree(entries) => entries,
+        _ => return Err(anyhow!("fatal: not a tree object")),
+    };
+    // Trees are written sorted already, but don't rely on it for the output order
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    for entry in entries {
+        // Convert 20-byte hash to hex string
+        let hash_hex = hex::encode(entry.hash);
+        let path = format!("{}{}", prefix, entry.name);
+
+        let is_tree = is_tree_mode(&entry.mode);
+        if !is_tree || !args.recursive || args.show_trees {
+            output.push(format_entry(&entry.mode, &hash_hex, &path));
+        }
+
+        if is_tree && args.recursive {
+            list_tree(git_dir, &hash_hex, &format!("{}/", path), args, output)?;
+        }
+    }
+
+    Ok(())
 }
@@ -1,24 +1,29 @@
 use anyhow::{bail, Context, Result};
 use clap::Args;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashSet, VecDeque};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::commands::checkout::{
-    clean_working_directory, extract_tree_sha, parse_tree_object, read_and_parse_git_object,
+    clean_working_directory, extract_tree_sha, has_uncommitted_changes, parse_tree_object,
+    read_and_parse_git_object,
 };
-use crate::core::parse_tree::{parse_tree, TreeEntry};
-use crate::core::object::{Commit, Tree, TreeEntry as ObjectTreeEntry};
-use crate::core::hash::write_object;
+use crate::core::merge_engine::{self, MergeOutcome};
+use crate::core::object::{local_timezone, Commit};
+use crate::core::hash::{self, write_object};
+use crate::core::config;
+use crate::core::read_head::{current_head, Head};
+use crate::core::reflog;
+use crate::core::simple_index::SimpleIndex;
 
 /// Command line arguments for the merge operation
 #[derive(Args)]
 pub struct MergeArgs {
     /// Name of the branch to merge into the current branch
     pub name: String,
-    /// Optional directory path where the git repository is located
-    #[arg(last = true)]
-    pub dir: Option<PathBuf>,
+    /// Always create a merge commit, even when HEAD could simply be fast-forwarded
+    #[arg(long)]
+    pub no_ff: bool,
 }
 
 /// Context structure that holds all the necessary information for a merge operation
@@ -40,13 +45,12 @@ impl MergeContext {
     /// Creates a new MergeContext by reading the current repository state
     /// 
     /// # Arguments
-    /// * `args` - Command line arguments containing branch name and optional directory
+    /// * `args` - Command line arguments containing the branch name
     /// 
     /// # Returns
     /// * `Result<Self>` - A new MergeContext or an error if the repository state is invalid
     fn new(args: &MergeArgs) -> Result<Self> {
-        // Use provided directory or current working directory
-        let current_dir = args.dir.clone().unwrap_or_else(|| std::env::current_dir().unwrap());
+        let current_dir = std::env::current_dir()?;
         let git_dir = current_dir.join(".git");
 
         // Read which branch HEAD points to
@@ -71,15 +75,14 @@ impl MergeContext {
     /// # Returns
     /// * `Result<String>` - The branch reference (e.g., "refs/heads/main") or error if detached HEAD
     fn read_head_ref(git_dir: &Path) -> Result<String> {
-        let head_path = git_dir.join("HEAD");
-        let head_content = fs::read_to_string(&head_path)
-            .with_context(|| format!("Failed to read HEAD from {}", head_path.display()))?;
-        
-        // HEAD should contain "ref: refs/heads/branch_name"
-        head_content
-            .strip_prefix("ref: ")
-            .map(|s| s.trim().to_string())
-            .ok_or_else(|| anyhow::anyhow!("Detached HEAD"))
+        // The merge commit has to go on a branch
+        match current_head(git_dir)? {
+            Head::Branch(name) => Ok(format!("refs/heads/{}", name)),
+            Head::Detached(sha) => bail!(
+                "fatal: HEAD is detached at {}. Switch to a branch before merging.",
+                &sha[..sha.len().min(7)]
+            ),
+        }
     }
 
     /// Reads the commit SHAs for both the current branch and the branch to be merged
@@ -106,6 +109,12 @@ impl MergeContext {
         // Read the commit SHAs from the reference files
         let current_commit = fs::read_to_string(&current_commit_path)?.trim().to_string();
         let other_commit = fs::read_to_string(&other_commit_path)?.trim().to_string();
+        // A damaged ref is reported before any object is looked up
+        for sha in [&current_commit, &other_commit] {
+            if !hash::is_valid_sha(sha) {
+                bail!("fatal: not a valid object name: {}", sha);
+            }
+        }
 
         Ok((current_commit, other_commit))
     }
@@ -157,11 +166,37 @@ impl MergeTrees {
 /// * `args` - Command line arguments containing branch name and optional directory
 /// 
 /// # Returns
-/// * `Result<String>` - Empty string on success, or error if merge fails
+/// * `Result<String>` - What was done (fast-forward, merge commit or nothing), or error if merge fails
 pub fn run(args: &MergeArgs) -> Result<String> {
     // Initialize merge context with repository state
     let ctx = MergeContext::new(args)?;
     
+    // The other branch is already part of HEAD: nothing to merge
+    if is_ancestor(&ctx.git_dir, &ctx.other_commit, &ctx.current_commit)? {
+        return Ok("Already up to date.".to_string());
+    }
+
+    // The merge rewrites the working tree, so local changes would be lost
+    if has_uncommitted_changes(&ctx.git_dir, &ctx.current_dir)? {
+        bail!("error: your local changes would be overwritten by merge. Commit or stash them first.");
+    }
+
+    // HEAD is behind the other branch: move it forward without a merge commit
+    if !args.no_ff && is_ancestor(&ctx.git_dir, &ctx.current_commit, &ctx.other_commit)? {
+        let head_tree = extract_tree_sha(&read_commit_content_as_string(&ctx.git_dir, &ctx.current_commit)?)?;
+        let other_tree = extract_tree_sha(&read_commit_content_as_string(&ctx.git_dir, &ctx.other_commit)?)?;
+
+        apply_merge_to_working_dir(&ctx, &head_tree, &other_tree)?;
+        SimpleIndex::from_tree(&ctx.git_dir, &other_tree)?.save()?;
+        update_head_ref(&ctx, &ctx.other_commit, &format!("merge {}: Fast-forward", args.name))?;
+
+        return Ok(format!(
+            "Updating {}..{}\nFast-forward",
+            &ctx.current_commit[..7],
+            &ctx.other_commit[..7]
+        ));
+    }
+
     // Find the common ancestor (merge base) of the two branches
     let merge_base = find_merge_base(&ctx.git_dir, &ctx.current_commit, &ctx.other_commit)?
         .context("No common ancestor found")?;
@@ -169,20 +204,33 @@ pub fn run(args: &MergeArgs) -> Result<String> {
     // Load the tree objects for the 3-way merge
     let trees = MergeTrees::load_from_commits(&ctx.git_dir, &merge_base, &ctx.current_commit, &ctx.other_commit)?;
     
-    // Perform the actual merge of the trees
-    let merged_tree_sha = merge_trees(&ctx.git_dir, &trees.base_tree, &trees.head_tree, &trees.other_tree)?;
+    // Perform the actual merge of the trees. Every conflict is found here, before the
+    // working directory or the index are touched, so a failed merge changes nothing.
+    let merged_tree_sha = match merge_engine::merge_trees(&ctx.git_dir, &trees.base_tree, &trees.head_tree, &trees.other_tree)? {
+        MergeOutcome::Clean(tree_sha) => tree_sha,
+        MergeOutcome::Conflicts(paths) => bail!(
+            "{}\nAutomatic merge failed; the working tree and index were left untouched.",
+            conflict_summary(&paths)
+        ),
+    };
 
     // Apply the merged tree to the working directory
-    apply_merge_to_working_dir(&ctx, &merged_tree_sha)?;
+    apply_merge_to_working_dir(&ctx, &trees.head_tree, &merged_tree_sha)?;
+
+    // Stage the merged tree so the index matches the merge commit
+    SimpleIndex::from_tree(&ctx.git_dir, &merged_tree_sha)?.save()?;
     
     // Create the merge commit with two parents
     let new_commit_sha = create_merge_commit(&ctx, &merged_tree_sha, &args.name)?;
     
     // Update the current branch to point to the new merge commit
-    update_head_ref(&ctx, &new_commit_sha)?;
+    update_head_ref(
+        &ctx,
+        &new_commit_sha,
+        &format!("merge {}: Merge made by the 'recursive' strategy.", args.name),
+    )?;
 
-    println!("Merged '{}' into '{}'. New commit: {}", args.name, ctx.head_ref, new_commit_sha);
-    Ok(String::new())
+    Ok(format!("Merged '{}' into '{}'. New commit: {}", args.name, ctx.head_ref, new_commit_sha))
 }
 
 /// Applies the merged tree to the working directory
@@ -190,13 +238,14 @@ pub fn run(args: &MergeArgs) -> Result<String> {
 /// 
 /// # Arguments
 /// * `ctx` - Merge context containing repository paths
+/// * `head_tree_sha` - SHA of the tree checked out before the merge
 /// * `merged_tree_sha` - SHA of the merged tree to apply
 /// 
 /// # Returns
 /// * `Result<()>` - Success or error
-fn apply_merge_to_working_dir(ctx: &MergeContext, merged_tree_sha: &str) -> Result<()> {
-    // Clean the working directory and checkout the merged tree
-    clean_working_directory(&ctx.current_dir, &ctx.git_dir, merged_tree_sha)?;
+fn apply_merge_to_working_dir(ctx: &MergeContext, head_tree_sha: &str, merged_tree_sha: &str) -> Result<()> {
+    // Remove the files the merge deleted and checkout the merged tree
+    clean_working_directory(&ctx.current_dir, &ctx.git_dir, Some(head_tree_sha), merged_tree_sha)?;
     let tree_content = read_and_parse_git_object(&ctx.git_dir, merged_tree_sha)?;
     parse_tree_object(&ctx.git_dir, &tree_content, ctx.current_dir.clone())?;
     Ok(())
@@ -214,15 +263,18 @@ fn apply_merge_to_working_dir(ctx: &MergeContext, merged_tree_sha: &str) -> Resu
 /// * `Result<String>` - SHA of the newly created merge commit
 fn create_merge_commit(ctx: &MergeContext, merged_tree_sha: &str, branch_name: &str) -> Result<String> {
     let now = chrono::Utc::now().timestamp();
-    
+    let identity = config::user_identity()?;
+
     let commit = Commit {
         tree: merged_tree_sha.to_string(),
         // Two parents: current commit and the commit being merged
         parent: Some(vec![ctx.current_commit.clone(), ctx.other_commit.clone()]),
-        author: "Your Name <you@example.com>".into(),
-        committer: "Your Name <you@example.com>".into(),
+        author: identity.clone(),
+        committer: identity,
         author_date: now,
         committer_date: now,
+        author_timezone: local_timezone(),
+        committer_timezone: local_timezone(),
         message: format!("Merge branch '{}' into {}", branch_name, ctx.head_ref),
     };
 
@@ -235,196 +287,47 @@ fn create_merge_commit(ctx: &MergeContext, merged_tree_sha: &str, branch_name: &
 /// 
 /// # Arguments
 /// * `ctx` - Merge context containing the HEAD reference path
-/// * `new_commit_sha` - SHA of the new merge commit
+/// * `new_commit_sha` - SHA of the merge commit, or of the other branch for a fast-forward
+/// * `message` - Reflog message describing the merge
 /// 
 /// # Returns
 /// * `Result<()>` - Success or error
-fn update_head_ref(ctx: &MergeContext, new_commit_sha: &str) -> Result<()> {
+fn update_head_ref(ctx: &MergeContext, new_commit_sha: &str, message: &str) -> Result<()> {
     fs::write(ctx.git_dir.join(&ctx.head_ref), new_commit_sha)?;
+    reflog::record_head_update(&ctx.git_dir, Some(&ctx.current_commit), new_commit_sha, message)?;
     Ok(())
 }
 
-/// Enumeration of possible merge decisions for a file
-/// This represents the outcome of comparing a file across the three trees
-#[derive(Debug)]
-enum MergeDecision {
-    /// Take the version from the current branch (HEAD)
-    TakeHead,
-    /// Take the version from the branch being merged
-    TakeOther,
-    /// There's a conflict that requires manual resolution
-    Conflict,
-}
-
-/// Decides what action to take for a file during merge based on 3-way comparison
-/// This implements the core Git merge logic for individual files
-/// 
-/// # Arguments
-/// * `base` - File entry from the merge base (common ancestor)
-/// * `head` - File entry from the current branch (HEAD)
-/// * `other` - File entry from the branch being merged
-/// 
-/// # Returns
-/// * `MergeDecision` - The decision for how to handle this file
-fn decide_merge_action(base: Option<&TreeEntry>, head: Option<&TreeEntry>, other: Option<&TreeEntry>) -> MergeDecision {
-    match (base, head, other) {
-        // Both branches have the same content - no conflict
-        (Some(_), Some(h), Some(o)) if h.sha == o.sha => MergeDecision::TakeHead,
-        // Current branch unchanged, other branch modified - take other
-        (Some(b), Some(h), Some(o)) if b.sha == h.sha => MergeDecision::TakeOther,
-        // Other branch unchanged, current branch modified - take head
-        (Some(b), Some(h), Some(o)) if b.sha == o.sha => MergeDecision::TakeHead,
-        // New file added in both branches with same content - no conflict
-        (None, Some(h), Some(o)) if h.sha == o.sha => MergeDecision::TakeHead,
-        // File only exists in current branch - keep it
-        (_, Some(_), None) => MergeDecision::TakeHead,
-        // File only exists in other branch - take it
-        (_, None, Some(_)) => MergeDecision::TakeOther,
-        // All other cases are conflicts (different changes to same file)
-        _ => MergeDecision::Conflict,
-    }
-}
-
-/// Converts a TreeEntry from the parse_tree module to an ObjectTreeEntry for the object module
-/// This handles the conversion between different internal representations of tree entries
-/// 
-/// # Arguments
-/// * `entry` - TreeEntry from parse_tree with string SHA
-/// 
-/// # Returns
-/// * `Result<ObjectTreeEntry>` - ObjectTreeEntry with binary hash or conversion error
-fn convert_to_object_tree_entry(entry: &TreeEntry) -> Result<ObjectTreeEntry> {
-    // Validate SHA string length (should be 40 hex characters)
-    let hex_str = if entry.sha.len() == 40 {
-        &entry.sha
-    } else {
-        return Err(anyhow::anyhow!("Invalid SHA length: {}", entry.sha.len()));
-    };
-    
-    // Convert hex string to bytes
-    let bytes = hex::decode(hex_str)?;
-    if bytes.len() != 20 {
-        return Err(anyhow::anyhow!("SHA should be 20 bytes"));
-    }
-    
-    // Convert to fixed-size array
-    let mut hash = [0u8; 20];
-    hash.copy_from_slice(&bytes);
-    
-    Ok(ObjectTreeEntry {
-        mode: entry.mode.clone(),
-        name: entry.filename.clone(),
-        hash,
-    })
-}
-
-/// Performs a 3-way merge of Git trees
-/// This is the core merge algorithm that combines changes from three tree states
-/// 
+/// Tells whether `ancestor` can be reached from `descendant` by following parents
+/// (a commit counts as its own ancestor)
+///
 /// # Arguments
 /// * `git_dir` - Path to the .git directory
-/// * `base` - SHA of the base tree (common ancestor)
-/// * `head` - SHA of the current branch's tree
-/// * `other` - SHA of the other branch's tree
-/// 
+/// * `ancestor` - SHA of the possible ancestor
+/// * `descendant` - SHA of the commit whose history is walked
+///
 /// # Returns
-/// * `Result<String>` - SHA of the newly created merged tree
-fn merge_trees(git_dir: &Path, base: &str, head: &str, other: &str) -> Result<String> {
-    // Load all three trees into flat maps for easier comparison
-    let base_entries = load_tree_map(git_dir, base)?;
-    let head_entries = load_tree_map(git_dir, head)?;
-    let other_entries = load_tree_map(git_dir, other)?;
-
-    let mut merged_entries: Vec<ObjectTreeEntry> = Vec::new();
-
-    // Collect all unique file paths from all three trees
-    let all_paths: HashSet<PathBuf> = base_entries.keys()
-        .chain(head_entries.keys())
-        .chain(other_entries.keys())
-        .cloned()
-        .collect();
-
-    // Process each file path
-    for path in all_paths {
-        let base_entry = base_entries.get(&path);
-        let head_entry = head_entries.get(&path);
-        let other_entry = other_entries.get(&path);
-
-        // Decide what to do with this file based on 3-way comparison
-        let decision = decide_merge_action(base_entry, head_entry, other_entry);
-        
-        match decision {
-            MergeDecision::TakeHead => {
-                if let Some(entry) = head_entry {
-                    merged_entries.push(convert_to_object_tree_entry(entry)?);
-                }
-            },
-            MergeDecision::TakeOther => {
-                if let Some(entry) = other_entry {
-                    merged_entries.push(convert_to_object_tree_entry(entry)?);
-                }
-            },
-            MergeDecision::Conflict => {
-                anyhow::bail!("Merge conflict on file: {:?}", path);
-            },
-        }
-    }
-
-    // Create and write the new merged tree object
-    let tree_obj = Tree { entries: merged_entries };
-    let tree_sha = write_object(&tree_obj)?;
-    Ok(tree_sha)
-}
-
-/// Loads a Git tree into a flat HashMap mapping file paths to tree entries
-/// This recursively traverses the tree structure and flattens it for easier processing
-/// 
-/// # Arguments
-/// * `git_dir` - Path to the .git directory
-/// * `sha` - SHA of the tree object to load
-/// 
-/// # Returns
-/// * `Result<HashMap<PathBuf, TreeEntry>>` - Map of file paths to tree entries
-fn load_tree_map(git_dir: &Path, sha: &str) -> Result<HashMap<PathBuf, TreeEntry>> {
-    let mut map = HashMap::new();
-    load_tree_map_recursive(git_dir, sha, PathBuf::new(), &mut map)?;
-    Ok(map)
-}
+/// * `Result<bool>` - True when `ancestor` is in the history of `descendant`
+pub(crate) fn is_ancestor(git_dir: &Path, ancestor: &str, descendant: &str) -> Result<bool> {
+    let mut visited = HashSet::new();
+    let mut pending = vec![descendant.to_string()];
 
-/// Recursively loads tree entries into a flat map
-/// This handles the recursive nature of Git trees (directories contain subtrees)
-/// 
-/// # Arguments
-/// * `git_dir` - Path to the .git directory
-/// * `sha` - SHA of the current tree object
-/// * `prefix` - Current path prefix for nested directories
-/// * `map` - Mutable reference to the map being built
-/// 
-/// # Returns
-/// * `Result<()>` - Success or error
-fn load_tree_map_recursive(
-    git_dir: &Path,
-    sha: &str,
-    prefix: PathBuf,
-    map: &mut HashMap<PathBuf, TreeEntry>,
-) -> Result<()> {
-    let content = read_and_parse_git_object(git_dir, sha)?;
-    for entry in parse_tree(&content)? {
-        let full_path = prefix.join(&entry.filename);
-        
-        if entry.mode == "40000" {
-            // Directory entry - recurse into subtree
-            load_tree_map_recursive(git_dir, &entry.sha, full_path, map)?;
-        } else {
-            // File entry - add to map
-            map.insert(full_path, entry.clone());
+    while let Some(commit) = pending.pop() {
+        if commit == ancestor {
+            return Ok(true);
+        }
+        if !visited.insert(commit.clone()) {
+            continue;
         }
+        pending.extend(commit_parents(git_dir, &commit)?);
     }
-    Ok(())
+    Ok(false)
 }
 
-/// Finds the merge base (common ancestor) of two commits using a breadth-first search
-/// This implements a simplified version of Git's merge base algorithm
+/// Finds the merge base (best common ancestor) of two commits
+/// A common ancestor is reachable from both sides; the best ones are those no other
+/// common ancestor descends from. Histories with merge commits can reach the same commit
+/// twice from one side, so each side's ancestors are collected separately.
 /// 
 /// # Arguments
 /// * `git_dir` - Path to the .git directory
@@ -434,36 +337,54 @@ fn load_tree_map_recursive(
 /// # Returns
 /// * `Result<Option<String>>` - SHA of the merge base commit, or None if no common ancestor
 fn find_merge_base(git_dir: &Path, a: &str, b: &str) -> Result<Option<String>> {
-    /// Helper function to get parent commits of a given commit
-    fn get_parents(git_dir: &Path, commit: &str) -> Result<Vec<String>> {
-        let content = read_and_parse_git_object(git_dir, commit)?;
-        let content_str = std::str::from_utf8(&content)?;
-        // Parse parent lines from commit object
-        Ok(content_str
-            .lines()
-            .filter_map(|l| l.strip_prefix("parent "))
-            .map(|s| s.to_string())
-            .collect())
-    }
+    let reachable_from_a = ancestors(git_dir, a)?;
 
+    // Walk back from `b`, stopping at the first commits `a` also reaches
+    let mut candidates = Vec::new();
     let mut visited = HashSet::new();
-    let mut queue = VecDeque::new();
-    
-    // Start BFS from both commits simultaneously
-    queue.push_back(a.to_string());
-    queue.push_back(b.to_string());
-
+    let mut queue = VecDeque::from([b.to_string()]);
     while let Some(current) = queue.pop_front() {
-        // If we've seen this commit before, it's a common ancestor
         if !visited.insert(current.clone()) {
-            return Ok(Some(current));
+            continue;
         }
-        // Add all parents to the queue for further exploration
-        for parent in get_parents(git_dir, &current)? {
-            queue.push_back(parent);
+        if reachable_from_a.contains(&current) {
+            candidates.push(current);
+            continue;
         }
+        queue.extend(commit_parents(git_dir, &current)?);
+    }
+
+    // Drop the candidates another candidate descends from
+    let mut below_others = HashSet::new();
+    for candidate in &candidates {
+        let mut reachable = ancestors(git_dir, candidate)?;
+        reachable.remove(candidate);
+        below_others.extend(reachable);
     }
-    Ok(None)
+    Ok(candidates.into_iter().find(|candidate| !below_others.contains(candidate)))
+}
+
+/// Every commit reachable from `start`, itself included
+fn ancestors(git_dir: &Path, start: &str) -> Result<HashSet<String>> {
+    let mut reachable = HashSet::new();
+    let mut pending = vec![start.to_string()];
+    while let Some(commit) = pending.pop() {
+        if reachable.insert(commit.clone()) {
+            pending.extend(commit_parents(git_dir, &commit)?);
+        }
+    }
+    Ok(reachable)
+}
+
+/// Parent SHAs listed in a commit object
+fn commit_parents(git_dir: &Path, commit: &str) -> Result<Vec<String>> {
+    let content = read_commit_content_as_string(git_dir, commit)?;
+    Ok(content
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.strip_prefix("parent "))
+        .map(str::to_string)
+        .collect())
 }
 
 /// Reads a commit object and returns its content as a UTF-8 string
@@ -480,4 +401,13 @@ fn read_commit_content_as_string(git_dir: &Path, sha: &str) -> Result<String> {
     let content_str = std::str::from_utf8(&content)
         .context("Invalid UTF-8 in commit object content")?;
     Ok(content_str.to_string())
-}
\ No newline at end of file
+}
+
+/// One `CONFLICT` line per path, as printed when a merge stops
+pub(crate) fn conflict_summary(paths: &[String]) -> String {
+    paths
+        .iter()
+        .map(|path| format!("CONFLICT (content): Merge conflict in {}", path))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -15,4 +15,30 @@ pub mod write_tree; // Version JSON (nouvelle)
 pub mod write_tree_filesystem_version; // Version filesystem (préservée)
 pub mod rev_parse;
 pub mod checkout;
-pub mod merge;
\ No newline at end of file
+pub mod merge;
+pub mod diff;
+pub mod reset;
+pub mod config;
+pub mod show;
+pub mod reflog;
+pub mod revert;
+pub mod cherry_pick;
+pub mod sequencer;
+pub mod mv;
+pub mod clean;
+pub mod stash;
+pub mod clone;
+pub mod describe;
+pub mod blame;
+pub mod gc;
+pub mod fsck;
+pub mod switch;
+pub mod restore;
+pub mod remote;
+pub mod fetch;
+pub mod push;
+pub mod tag;
+pub mod count_objects;
+pub mod rev_list;
+pub mod check_ignore;
+pub mod export;
\ No newline at end of file
@@ -0,0 +1,79 @@
+use crate::core::simple_index::{self, repo_relative, SimpleIndex};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use std::fs;
+use std::path::PathBuf;
+
+/// Arguments for the `guts mv` command
+#[derive(Args)]
+pub struct MvArgs {
+    /// Tracked file or directory to move
+    pub source: PathBuf,
+
+    /// New path, or an existing directory (or a path ending with `/`) to move into
+    pub destination: PathBuf,
+}
+
+/// Main function for the `guts mv` command
+/// Moves a tracked file on disk and in the index, keeping its blob (no rehash)
+pub fn run(args: &MvArgs) -> Result<String> {
+    // Check if we're in a git repository
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    let repo_root = simple_index::find_repo_root()?;
+
+    // `guts mv a.txt dir/` keeps the file name
+    let into_dir = args.destination.is_dir() || args.destination.to_string_lossy().ends_with('/');
+    let destination = if into_dir {
+        let file_name = args
+            .source
+            .file_name()
+            .ok_or_else(|| anyhow!("fatal: bad source '{}'", args.source.display()))?;
+        args.destination.join(file_name)
+    } else {
+        args.destination.clone()
+    };
+
+    if fs::symlink_metadata(&destination).is_ok() {
+        return Err(anyhow!("fatal: destination exists, source={}, destination={}",
+            args.source.display(), destination.display()));
+    }
+
+    let old_path = repo_relative(&repo_root, &args.source)?;
+    let new_path = repo_relative(&repo_root, &destination)?;
+    let old_prefix = format!("{}/", old_path);
+
+    // Every index entry for the source: the file itself, or everything below a directory
+    let mut index = SimpleIndex::load()?;
+    let moved: Vec<String> = index
+        .files
+        .keys()
+        .filter(|path| **path == old_path || path.starts_with(&old_prefix))
+        .cloned()
+        .collect();
+    if moved.is_empty() {
+        return Err(anyhow!("fatal: not under version control, source={}, destination={}",
+            args.source.display(), destination.display()));
+    }
+
+    if let Some(parent) = destination.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(&args.source, &destination).map_err(|e| {
+        anyhow!("failed to move '{}' to '{}': {}", args.source.display(), destination.display(), e)
+    })?;
+
+    for path in moved {
+        let renamed = format!("{}{}", new_path, &path[old_path.len()..]);
+        if let Some(sha) = index.files.remove(&path) {
+            index.files.insert(renamed.clone(), sha);
+        }
+        if let Some(mode) = index.modes.remove(&path) {
+            index.modes.insert(renamed, mode);
+        }
+    }
+    index.save()?;
+
+    Ok(format!("Renamed '{}' to '{}'", args.source.display(), destination.display()))
+}
@@ -0,0 +1,114 @@
+use crate::commands::fetch::{copy_missing_objects, remote_git_dir};
+use crate::commands::merge::is_ancestor;
+use crate::core::cat;
+use crate::core::config::{self, Config};
+use crate::core::read_head::{current_head, Head};
+use crate::core::simple_index;
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use std::fs;
+
+/// Arguments for the `guts push` command
+#[derive(Args)]
+pub struct PushArgs {
+    /// Remote recorded with `guts remote add`, or the path of a local repository
+    #[arg(default_value = "origin")]
+    pub remote: String,
+
+    /// Branch to push (defaults to the current branch)
+    pub branch: Option<String>,
+
+    /// Overwrite the remote branch even if it is not an ancestor of the local one
+    #[arg(short = 'f', long)]
+    pub force: bool,
+}
+
+/// Main function for the `guts push` command
+/// Copies the objects of a local branch that the remote lacks and moves the remote
+/// branch to it. Only local repositories are supported.
+pub fn run(args: &PushArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    let repo_root = simple_index::find_repo_root()?;
+    let git_dir = repo_root.join(".git");
+
+    let branch = match &args.branch {
+        Some(branch) => branch.clone(),
+        None => match current_head(&git_dir)? {
+            Head::Branch(name) => name,
+            Head::Detached(_) => {
+                return Err(anyhow!("fatal: You are not currently on a branch. Name the branch to push."))
+            }
+        },
+    };
+    let local_sha = fs::read_to_string(git_dir.join("refs/heads").join(&branch))
+        .map_err(|_| anyhow!("error: src refspec {} does not match any", branch))?
+        .trim()
+        .to_string();
+
+    // A configured remote, or else a path
+    let configured_url = Config::load(&config::repo_config_path()?)?
+        .get(&format!("remote.{}.url", args.remote))
+        .map(str::to_string);
+    let url = configured_url.clone().unwrap_or_else(|| args.remote.clone());
+    let remote_git_dir = remote_git_dir(&repo_root, &url)?;
+
+    // Moving the branch checked out over there would leave its working tree behind
+    let is_bare = remote_git_dir.file_name().is_none_or(|name| name != ".git");
+    if !is_bare && current_head(&remote_git_dir)? == Head::Branch(branch.clone()) {
+        return Err(anyhow!(
+            "error: refusing to update checked out branch: refs/heads/{}",
+            branch
+        ));
+    }
+
+    let ref_path = remote_git_dir.join("refs/heads").join(&branch);
+    let remote_sha = fs::read_to_string(&ref_path).ok().map(|s| s.trim().to_string());
+    if remote_sha.as_deref() == Some(local_sha.as_str()) {
+        return Ok("Everything up-to-date\n".to_string());
+    }
+
+    // Commits only the remote has are unknown here, so they cannot be ancestors
+    let fast_forward = match &remote_sha {
+        Some(remote_sha) => {
+            cat::get_object_path(&git_dir, remote_sha).exists()
+                && is_ancestor(&git_dir, remote_sha, &local_sha)?
+        }
+        None => true,
+    };
+    if !fast_forward && !args.force {
+        return Err(anyhow!(
+            " ! [rejected]        {} -> {} (non-fast-forward)\nerror: failed to push some refs to '{}'\nhint: fetch and merge the remote changes first, or use -f to overwrite them",
+            branch,
+            branch,
+            url
+        ));
+    }
+
+    copy_missing_objects(&git_dir, &remote_git_dir, [&local_sha])?;
+
+    if let Some(parent) = ref_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&ref_path, format!("{}\n", local_sha))
+        .with_context(|| format!("failed to write {}", ref_path.display()))?;
+
+    // Keep the remote-tracking branch in step when pushing to a named remote
+    if configured_url.is_some() {
+        let tracking = git_dir.join("refs/remotes").join(&args.remote).join(&branch);
+        if let Some(parent) = tracking.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&tracking, format!("{}\n", local_sha))?;
+    }
+
+    let line = match &remote_sha {
+        None => format!(" * [new branch]      {} -> {}", branch, branch),
+        Some(old) if !fast_forward => {
+            format!(" + {}...{} {} -> {} (forced update)", &old[..7], &local_sha[..7], branch, branch)
+        }
+        Some(old) => format!("   {}..{}  {} -> {}", &old[..7], &local_sha[..7], branch, branch),
+    };
+    Ok(format!("To {}\n{}\n", url, line))
+}
@@ -0,0 +1,40 @@
+use crate::core::reflog;
+use crate::core::simple_index;
+use anyhow::{anyhow, Result};
+use clap::Args;
+
+/// Arguments for the `guts reflog` command
+#[derive(Args)]
+pub struct ReflogArgs {
+    /// Ref whose movements to show (defaults to HEAD)
+    #[arg(default_value = "HEAD")]
+    pub reference: String,
+}
+
+/// Entry point for the `guts reflog` command
+/// Lists the movements of a ref, newest first, as `<short sha> HEAD@{n}: action: message`
+pub fn run(args: &ReflogArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+
+    let git_dir = simple_index::find_repo_root()?.join(".git");
+
+    // Branch names are stored under refs/heads
+    let ref_name = if args.reference == "HEAD" || args.reference.starts_with("refs/") {
+        args.reference.clone()
+    } else {
+        format!("refs/heads/{}", args.reference)
+    };
+
+    let lines: Vec<String> = reflog::read(&git_dir, &ref_name)?
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(n, entry)| {
+            format!("{} {}@{{{}}}: {}", &entry.new_sha[..7], args.reference, n, entry.message)
+        })
+        .collect();
+
+    Ok(lines.join("\n"))
+}
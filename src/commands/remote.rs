@@ -0,0 +1,102 @@
+use crate::core::config::{self, Config};
+use crate::core::simple_index;
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, ValueEnum};
+use std::fs;
+
+/// What `guts remote` should do besides listing
+#[derive(Clone, Copy, ValueEnum)]
+pub enum RemoteAction {
+    /// Record a new remote: `guts remote add <name> <url>`
+    Add,
+    /// Forget a remote and its remote-tracking branches: `guts remote remove <name>`
+    #[value(alias = "rm")]
+    Remove,
+}
+
+/// Arguments for the `guts remote` command
+#[derive(Args)]
+pub struct RemoteArgs {
+    /// Without an action the remotes are listed
+    #[arg(value_enum)]
+    pub action: Option<RemoteAction>,
+
+    /// Name of the remote, e.g. `origin`
+    #[arg(required_if_eq_any = [("action", "add"), ("action", "remove"), ("action", "rm")])]
+    pub name: Option<String>,
+
+    /// URL or path of the remote repository
+    #[arg(required_if_eq("action", "add"))]
+    pub url: Option<String>,
+
+    /// Show the URL next to each remote name
+    #[arg(short = 'v', long)]
+    pub verbose: bool,
+}
+
+/// Entry point for the `guts remote` command
+/// Remotes live in `.git/config` as `[remote "<name>"]` sections
+pub fn run(args: &RemoteArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    let path = config::repo_config_path()?;
+    let mut repo_config = Config::load(&path)?;
+    let name = args.name.as_deref().unwrap_or_default();
+
+    match args.action {
+        None => Ok(list(&repo_config, args.verbose)),
+        Some(RemoteAction::Add) => {
+            if !is_valid_name(name) {
+                return Err(anyhow!("fatal: '{}' is not a valid remote name", name));
+            }
+            if repo_config.subsections("remote").iter().any(|n| n == name) {
+                return Err(anyhow!("error: remote {} already exists.", name));
+            }
+            let url = args.url.as_deref().unwrap_or_default();
+            repo_config.set(&format!("remote.{}.url", name), url)?;
+            repo_config.set(
+                &format!("remote.{}.fetch", name),
+                &format!("+refs/heads/*:refs/remotes/{}/*", name),
+            )?;
+            repo_config.save(&path)?;
+            Ok(String::new())
+        }
+        Some(RemoteAction::Remove) => {
+            if !repo_config.remove_section(&format!("remote.{}", name)) {
+                return Err(anyhow!("error: No such remote: '{}'", name));
+            }
+            repo_config.save(&path)?;
+
+            let tracking = simple_index::find_repo_root()?.join(".git/refs/remotes").join(name);
+            if tracking.exists() {
+                fs::remove_dir_all(&tracking)
+                    .with_context(|| format!("failed to remove {}", tracking.display()))?;
+            }
+            Ok(String::new())
+        }
+    }
+}
+
+/// One remote name per line, or `<name>\t<url> (fetch|push)` lines with -v
+fn list(repo_config: &Config, verbose: bool) -> String {
+    let mut output = String::new();
+    for name in repo_config.subsections("remote") {
+        if verbose {
+            let url = repo_config.get(&format!("remote.{}.url", name)).unwrap_or_default();
+            output.push_str(&format!("{}\t{} (fetch)\n{}\t{} (push)\n", name, url, name, url));
+        } else {
+            output.push_str(&format!("{}\n", name));
+        }
+    }
+    output
+}
+
+/// Remote names end up in ref paths (`refs/remotes/<name>/...`)
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && !name.starts_with('.')
+        && !name.contains("..")
+        && !name.chars().any(|c| c.is_whitespace() || c.is_control() || "\\\"[]:?*~^".contains(c))
+}
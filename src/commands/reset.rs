@@ -0,0 +1,179 @@
+use crate::commands::checkout::{
+    clean_working_directory, has_uncommitted_changes, head_tree_sha, parse_tree_object,
+    read_and_parse_git_object,
+};
+use crate::core::cat::{self, ParsedObject};
+use crate::core::reflog;
+use crate::core::resolve_parse::resolve_ref;
+use crate::core::simple_index::{self, repo_relative, SimpleIndex};
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Arguments for the `guts reset` command
+#[derive(Args)]
+pub struct ResetArgs {
+    /// Commit to reset the current branch to (defaults to HEAD)
+    #[arg(default_value = "HEAD")]
+    pub commit: String,
+
+    /// Files to unstage: their index entries go back to the commit, HEAD and the
+    /// working tree are untouched
+    pub paths: Vec<PathBuf>,
+
+    /// Only move the branch, keep the index and the working tree
+    #[arg(long, conflicts_with_all = ["mixed", "hard"])]
+    pub soft: bool,
+
+    /// Move the branch and reset the index, keep the working tree (default)
+    #[arg(long, conflicts_with = "hard")]
+    pub mixed: bool,
+
+    /// Move the branch and reset both the index and the working tree
+    #[arg(long)]
+    pub hard: bool,
+
+    /// With --hard, discard uncommitted changes without asking
+    #[arg(short = 'f', long)]
+    pub force: bool,
+}
+
+/// Entry point for the `guts reset` command
+/// Moves the current branch to another commit and optionally resets the index and working tree
+pub fn run(args: &ResetArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+
+    let repo_root = simple_index::find_repo_root()?;
+    let git_dir = repo_root.join(".git");
+
+    // `guts reset <file>`: a lone argument naming no commit but a file is a path
+    let is_lone_path = args.paths.is_empty()
+        && resolve_ref(&git_dir, &args.commit).is_err()
+        && (Path::new(&args.commit).exists()
+            || SimpleIndex::load()?.contains_file(&repo_relative(&repo_root, Path::new(&args.commit))?));
+    if (is_lone_path || !args.paths.is_empty()) && (args.soft || args.hard) {
+        return Err(anyhow!("fatal: Cannot do {} reset with paths.", if args.soft { "soft" } else { "hard" }));
+    }
+    if is_lone_path {
+        return reset_paths(&git_dir, &repo_root, "HEAD", &[PathBuf::from(&args.commit)]);
+    }
+    if !args.paths.is_empty() {
+        return reset_paths(&git_dir, &repo_root, &args.commit, &args.paths);
+    }
+
+    let target = resolve_ref(&git_dir, &args.commit)?;
+    let commit = match cat::read_object(&git_dir, &target)? {
+        ParsedObject::Commit(commit) => commit,
+        _ => return Err(anyhow!("fatal: {} is not a commit", args.commit)),
+    };
+
+    // Check before anything moves: the comparison is made against the current HEAD
+    if args.hard && !args.force && has_uncommitted_changes(&git_dir, &repo_root)? {
+        return Err(anyhow!(
+            "You have uncommitted changes that would be lost. Use -f to discard them."
+        ));
+    }
+
+    // Files tracked before the move are the ones --hard may delete
+    let old_tree_sha = head_tree_sha(&git_dir)?;
+    let old_head = resolve_ref(&git_dir, "HEAD").ok();
+
+    // 1. Move the branch (every mode)
+    update_branch_ref(&git_dir, &target)?;
+    reflog::record_head_update(
+        &git_dir,
+        old_head.as_deref(),
+        &target,
+        &format!("reset: moving to {}", args.commit),
+    )?;
+
+    // 2. Reset the index to the target tree (mixed and hard)
+    if !args.soft {
+        SimpleIndex::from_tree(&git_dir, &commit.tree)?.save()?;
+    }
+
+    // 3. Restore the working directory (hard only)
+    if args.hard {
+        clean_working_directory(&repo_root, &git_dir, old_tree_sha.as_deref(), &commit.tree)?;
+        let tree_content = read_and_parse_git_object(&git_dir, &commit.tree)?;
+        parse_tree_object(&git_dir, &tree_content, repo_root.clone())?;
+    }
+
+    let subject = commit.message.lines().next().unwrap_or("");
+    Ok(format!("HEAD is now at {} {}", &target[..7], subject))
+}
+
+/// `guts reset [<commit>] <paths>`: the index entries of the paths (or of the files
+/// below them) go back to their blob in the commit, or are dropped when the commit does
+/// not have them. On an unborn branch, HEAD has no file at all.
+fn reset_paths(git_dir: &Path, repo_root: &Path, commit_name: &str, paths: &[PathBuf]) -> Result<String> {
+    let (committed, committed_modes) = match resolve_ref(git_dir, commit_name) {
+        Ok(sha) => match cat::read_object(git_dir, &sha)? {
+            ParsedObject::Commit(commit) => (
+                simple_index::get_files_from_tree(git_dir, &commit.tree, "")?,
+                simple_index::get_modes_from_tree(git_dir, &commit.tree)?,
+            ),
+            _ => return Err(anyhow!("fatal: {} is not a commit", commit_name)),
+        },
+        Err(_) if commit_name == "HEAD" => Default::default(),
+        Err(e) => return Err(e),
+    };
+
+    let mut index = SimpleIndex::load()?;
+    // Resolve every path first so that the index is left alone if one matches nothing
+    let mut unstaged = Vec::new();
+    for path in paths {
+        let relative_path = repo_relative(repo_root, path)?;
+        let is_below = |file: &String| {
+            relative_path.is_empty() || *file == relative_path || file.starts_with(&format!("{}/", relative_path))
+        };
+        let mut matches: Vec<String> =
+            index.files.keys().chain(committed.keys()).filter(|file| is_below(file)).cloned().collect();
+        if matches.is_empty() {
+            return Err(anyhow!(
+                "error: pathspec '{}' did not match any file(s) known to guts",
+                path.display()
+            ));
+        }
+        unstaged.append(&mut matches);
+    }
+    unstaged.sort();
+    unstaged.dedup();
+
+    for file in &unstaged {
+        match committed.get(file) {
+            Some(sha) => {
+                index.files.insert(file.clone(), sha.clone());
+                match committed_modes.get(file) {
+                    Some(mode) => index.modes.insert(file.clone(), mode.clone()),
+                    None => index.modes.remove(file),
+                };
+            }
+            None => {
+                index.files.remove(file);
+                index.modes.remove(file);
+            }
+        }
+    }
+    index.save()?;
+
+    Ok(format!("Unstaged {} path(s)", unstaged.len()))
+}
+
+/// Point the current branch (or HEAD itself when detached) at `commit_hash`
+fn update_branch_ref(git_dir: &Path, commit_hash: &str) -> Result<()> {
+    let head_path = git_dir.join("HEAD");
+    let head_content = fs::read_to_string(&head_path).context("cannot read HEAD")?;
+
+    let ref_path = match head_content.trim().strip_prefix("ref: ") {
+        Some(ref_name) => git_dir.join(ref_name),
+        None => head_path,
+    };
+
+    fs::write(&ref_path, format!("{}\n", commit_hash))
+        .with_context(|| format!("failed to update {:?}", ref_path))?;
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use crate::commands::checkout::{self, CheckoutObject};
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+
+/// Arguments for the `guts restore` command
+#[derive(Args)]
+pub struct RestoreArgs {
+    /// Files to restore
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+
+    /// Commit or branch to take the files from (defaults to HEAD)
+    #[arg(short = 's', long)]
+    pub source: Option<String>,
+}
+
+/// Main function for the `guts restore` command
+/// The file half of `checkout`: `guts restore <paths>` is `guts checkout -- <paths>`
+/// and never moves HEAD
+pub fn run(args: &RestoreArgs) -> Result<String> {
+    checkout::run(&CheckoutObject {
+        name: args.source.clone(),
+        branch_name: None,
+        paths: args.paths.clone(),
+    })
+}
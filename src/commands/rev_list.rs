@@ -0,0 +1,86 @@
+use crate::core::cat::{self, ParsedObject};
+use crate::core::resolve_parse::resolve_ref;
+use crate::core::simple_index;
+use anyhow::{anyhow, Result};
+use clap::Args;
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Arguments for the `guts rev-list` command
+#[derive(Args)]
+pub struct RevListArgs {
+    /// Commit to start from (branch, tag, SHA, HEAD~n...)
+    pub commit: String,
+
+    /// Print the number of commits instead of their SHAs
+    #[arg(long)]
+    pub count: bool,
+
+    /// Stop after this many commits
+    #[arg(short = 'n', long = "max-count")]
+    pub max_count: Option<usize>,
+}
+
+/// Main function for the `guts rev-list` command
+/// Lists the commits reachable from a commit through all of its parents, newest first
+pub fn run(args: &RevListArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    let git_dir = simple_index::find_repo_root()?.join(".git");
+
+    let sha = resolve_ref(&git_dir, &args.commit)?;
+    let commits = reachable_commits(&git_dir, &[sha], args.max_count.unwrap_or(usize::MAX))?;
+
+    if args.count {
+        return Ok(format!("{}\n", commits.len()));
+    }
+    Ok(commits.iter().map(|sha| format!("{}\n", sha)).collect())
+}
+
+/// Up to `limit` commits reachable from any of `starts`, each listed once, in git's
+/// default order: the most recent committer date first, earlier discovered first on
+/// equal dates
+pub(crate) fn reachable_commits(git_dir: &Path, starts: &[String], limit: usize) -> Result<Vec<String>> {
+    let mut seen = HashSet::new();
+    // (committer date, discovery order, sha) of the commits waiting to be listed
+    let mut pending = Vec::new();
+    let mut discovered = 0;
+    for start in starts {
+        if seen.insert(start.clone()) {
+            pending.push((commit_date(git_dir, start)?, discovered, start.clone()));
+            discovered += 1;
+        }
+    }
+    let mut listed = Vec::new();
+
+    while listed.len() < limit {
+        let Some(next) = (0..pending.len()).max_by_key(|&i| (pending[i].0, std::cmp::Reverse(pending[i].1))) else {
+            break;
+        };
+        let (_, _, sha) = pending.swap_remove(next);
+
+        let ParsedObject::Commit(commit) = cat::read_object_cached(git_dir, &sha)? else {
+            return Err(anyhow!("fatal: object {} is not a commit", sha));
+        };
+        for parent in commit.parent.unwrap_or_default() {
+            if seen.insert(parent.clone()) {
+                pending.push((commit_date(git_dir, &parent)?, discovered, parent));
+                discovered += 1;
+            }
+        }
+        listed.push(sha);
+    }
+
+    Ok(listed)
+}
+
+fn commit_date(git_dir: &Path, sha: &str) -> Result<i64> {
+    if !cat::get_object_path(git_dir, sha).exists() {
+        return Err(anyhow!("fatal: commit object {} not found", sha));
+    }
+    match cat::read_object_cached(git_dir, sha)? {
+        ParsedObject::Commit(commit) => Ok(commit.committer_date),
+        _ => Err(anyhow!("fatal: object {} is not a commit", sha)),
+    }
+}
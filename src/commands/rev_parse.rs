@@ -1,18 +1,21 @@
 use clap::Args;
 use anyhow::{Context, Result};
+use crate::core::{cat, hash};
 use crate::core::read_head::read_head; 
 use crate::core::resolve_parse::resolve_ref;
 
+/// Length of `--short` without a value
+const DEFAULT_SHORT_LEN: usize = 7;
+
 // CLI arguments for the `rev-parse` command
 #[derive(Args)]
 pub struct RevParse {
     // The reference to resolve (e.g., "HEAD", "main", a SHA hash)
     pub head: String,
-}
 
-// Checks whether the input string looks like a full SHA-1 hash (40 hex digits)
-fn looks_like_sha(s: &str) -> bool {
-    s.len() == 40 && s.chars().all(|c| c.is_ascii_hexdigit())
+    /// Abbreviate the SHA: shortest unique prefix of at least 7 chars, or exactly n chars with `--short=n`
+    #[arg(long, value_name = "n", num_args = 0..=1, require_equals = true)]
+    pub short: Option<Option<usize>>,
 }
 
 // Main entry point for `gut rev-parse` command
@@ -21,19 +24,22 @@ pub fn run(head_input: &RevParse) -> Result<String> {
     let current_dir = std::env::current_dir().context("Cannot get current directory")?;
     let gits_dir = current_dir.join(".git"); 
 
-    match head_input.head.as_str() {
+    let sha = match head_input.head.as_str() {
         // If the user requested "HEAD", resolve it with read_head()
-        "HEAD" => {
-            let sha = read_head(&gits_dir, &head_input.head)?; 
-            Ok(sha)
-        }
+        "HEAD" => read_head(&gits_dir, &head_input.head)?,
 
-        // If it looks like a valid SHA, return it directly
-        s if looks_like_sha(s) => {
-            Ok(s.to_string())
-        }
+        // A full SHA is returned directly
+        s if hash::is_valid_sha(s) => hash::normalize_sha(s)?,
 
         // Otherwise, try to resolve the ref (e.g., a branch name)
-        other => resolve_ref(&gits_dir, other)
+        other => resolve_ref(&gits_dir, other)?,
+    };
+
+    match head_input.short {
+        None => Ok(sha),
+        // `--short=n`: exactly n chars
+        Some(Some(len)) => Ok(sha[..len.clamp(cat::MIN_PREFIX_LEN, sha.len())].to_string()),
+        // `--short`: grow past 7 chars until no other object shares the prefix
+        Some(None) => cat::shortest_unique_prefix(&gits_dir, &sha, DEFAULT_SHORT_LEN),
     }
 }
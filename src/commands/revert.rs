@@ -0,0 +1,30 @@
+use crate::commands::sequencer::{self, Replay};
+use crate::core::simple_index;
+use anyhow::{anyhow, Result};
+use clap::Args;
+
+/// Arguments for the `guts revert` command
+#[derive(Args)]
+pub struct RevertArgs {
+    /// Commit whose changes should be undone
+    pub commit: String,
+}
+
+/// Entry point for the `guts revert` command
+/// Creates a new commit on top of HEAD that undoes the changes introduced by a commit
+pub fn run(args: &RevertArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+
+    let repo_root = simple_index::find_repo_root()?;
+    std::env::set_current_dir(&repo_root)?;
+    let git_dir = repo_root.join(".git");
+
+    let (sha, reverted, parent_tree) = sequencer::read_replayed_commit(&git_dir, &args.commit, Replay::Revert)?;
+
+    // Undoing a commit is merging its parent into HEAD, with the commit itself as base
+    let subject = reverted.message.lines().next().unwrap_or("");
+    let message = format!("Revert \"{}\"\n\nThis reverts commit {}.", subject, sha);
+    sequencer::apply_tree_change(&repo_root, Replay::Revert, &sha, &reverted.tree, &parent_tree, &message, None)
+}
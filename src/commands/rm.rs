@@ -3,6 +3,7 @@ use anyhow::{anyhow, Result};
 use clap::Args;
 use std::fs;
 use std::path::PathBuf;
+use walkdir::WalkDir;
 
 /// Arguments for the `guts rm` command
 #[derive(Args)]
@@ -10,9 +11,14 @@ pub struct RmArgs {
     /// File(s) to remove from working directory and index
     #[arg(required = true)]
     pub files: Vec<PathBuf>,
-    /// Current directory for the operation (injected by TUI)
-    #[arg(last = true)]
-    pub dir: Option<PathBuf>,
+
+    /// Allow removing directories: every tracked file below them is removed
+    #[arg(short = 'r', long)]
+    pub recursive: bool,
+
+    /// Only remove from the index, keep the files in the working directory
+    #[arg(long)]
+    pub cached: bool,
 }
 
 /// Convert absolute path to relative path from repo root
@@ -45,73 +51,120 @@ fn remove_file_from_index(file_path: &PathBuf) -> Result<bool> {
     }
 }
 
+/// Remove every tracked file below a directory, from the index and (unless `cached`) the working tree.
+/// Untracked files are left alone and the directories are only deleted once empty.
+/// Returns the removed paths, relative to the repo root.
+fn remove_directory(dir_path: &PathBuf, cached: bool) -> Result<Vec<String>> {
+    let repo_root = simple_index::find_repo_root()?;
+    let relative_dir = get_relative_path(dir_path)?;
+    let prefix = if relative_dir.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", relative_dir)
+    };
+
+    let mut index = simple_index::SimpleIndex::load()?;
+    let mut removed: Vec<String> = index
+        .files
+        .keys()
+        .filter(|path| path.starts_with(&prefix))
+        .cloned()
+        .collect();
+    removed.sort();
+
+    for path in &removed {
+        index.files.remove(path);
+        let work_path = repo_root.join(path);
+        if !cached && work_path.exists() {
+            fs::remove_file(&work_path)
+                .map_err(|e| anyhow!("failed to remove '{}': {}", path, e))?;
+        }
+    }
+    index.save()?;
+
+    if cached {
+        return Ok(removed);
+    }
+
+    // Deepest directories first; those still holding untracked files stay
+    for entry in WalkDir::new(dir_path).contents_first(true) {
+        let entry = entry?;
+        if entry.file_type().is_dir() {
+            let _ = fs::remove_dir(entry.path());
+        }
+    }
+
+    Ok(removed)
+}
+
 /// Main function for the `guts rm` command
 /// Removes files from working directory and index
 pub fn run(args: &RmArgs) -> Result<String> {
-    // Set current directory context for TUI
-    let original_dir = std::env::current_dir()?;
-    if let Some(dir) = &args.dir {
-        std::env::set_current_dir(dir)?;
+    // Check if we're in a git repository
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
     }
-    
-    let result = || -> Result<String> {
-        // Check if we're in a git repository
-        if !simple_index::is_git_repository()? {
-            return Err(anyhow!("fatal: not a git repository"));
-        }
 
-    let mut removed_files = Vec::new();
-    let mut output = String::new();
-
-    // Process each requested file
-    for file_path in &args.files {
-        // Basic checks
-        if !file_path.exists() {
-            return Err(anyhow!(
-                "pathspec '{}' did not match any files",
-                file_path.display()
-            ));
-        }
+let mut removed_files = Vec::new();
+let mut output = String::new();
+
+// Process each requested file
+for file_path in &args.files {
+    // Basic checks (with --cached the file may already be gone from disk)
+    if !file_path.exists() && !args.cached {
+        return Err(anyhow!(
+            "pathspec '{}' did not match any files",
+            file_path.display()
+        ));
+    }
 
-        if file_path.is_dir() {
+    if file_path.is_dir() {
+        if !args.recursive {
             return Err(anyhow!(
                 "fatal: not removing '{}' recursively without -r",
                 file_path.display()
             ));
         }
 
-        // Remove from index
-        let was_in_index = remove_file_from_index(file_path)?;
-
-        if !was_in_index {
+        let removed = remove_directory(file_path, args.cached)?;
+        if removed.is_empty() {
             return Err(anyhow!(
                 "fatal: pathspec '{}' did not match any files",
                 file_path.display()
             ));
         }
+        removed_files.extend(removed);
+        continue;
+    }
+
+    // Remove from index
+    let was_in_index = remove_file_from_index(file_path)?;
 
-        // Remove from working directory
+    if !was_in_index {
+        return Err(anyhow!(
+            "fatal: pathspec '{}' did not match any files",
+            file_path.display()
+        ));
+    }
+
+    // Remove from working directory
+    if !args.cached {
         fs::remove_file(file_path)
             .map_err(|e| anyhow!("failed to remove '{}': {}", file_path.display(), e))?;
-
-        removed_files.push(file_path.display().to_string());
     }
 
-    // Confirmation message
-    if removed_files.len() == 1 {
-        output.push_str(&format!("rm '{}'", removed_files[0]));
-    } else {
-        for file in &removed_files {
-            output.push_str(&format!("rm '{}'\n", file));
-        }
-        output.pop(); // Remove last newline
+    removed_files.push(file_path.display().to_string());
+}
+
+// Confirmation message
+if removed_files.len() == 1 {
+    output.push_str(&format!("rm '{}'", removed_files[0]));
+} else {
+    for file in &removed_files {
+        output.push_str(&format!("rm '{}'\n", file));
     }
+    output.pop(); // Remove last newline
+}
 
-        Ok(output)
-    }();
-    
-    // Restore original directory
-    std::env::set_current_dir(&original_dir)?;
-    
-    result
+    Ok(output)
 }
@@ -0,0 +1,141 @@
+use crate::commands::checkout::{
+    clean_working_directory, has_uncommitted_changes, parse_tree_object, read_and_parse_git_object,
+};
+use crate::commands::{commit, commit_tree, merge};
+use crate::core::cat::read_commit;
+use crate::core::hash;
+use crate::core::merge_engine::{self, MergeOutcome};
+use crate::core::object::{local_timezone, Commit, Tree};
+use crate::core::resolve_parse::resolve_ref;
+use crate::core::simple_index::SimpleIndex;
+use crate::core::{config, reflog};
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+/// The commands that replay the change of one commit on top of HEAD
+#[derive(Clone, Copy)]
+pub(crate) enum Replay {
+    CherryPick,
+    Revert,
+}
+
+impl Replay {
+    /// Command name, as written in messages and the reflog
+    fn name(self) -> &'static str {
+        match self {
+            Replay::CherryPick => "cherry-pick",
+            Replay::Revert => "revert",
+        }
+    }
+
+    /// What could not be done when the change conflicts, e.g. `could not apply`
+    fn failure(self) -> &'static str {
+        match self {
+            Replay::CherryPick => "could not apply",
+            Replay::Revert => "could not revert",
+        }
+    }
+
+    /// State of a change HEAD already has, e.g. `already applied`
+    fn done(self) -> &'static str {
+        match self {
+            Replay::CherryPick => "already applied",
+            Replay::Revert => "already undone",
+        }
+    }
+}
+
+/// Resolves the commit to replay and the tree of its parent.
+/// A root commit is compared with an empty tree; merge commits are refused.
+pub(crate) fn read_replayed_commit(git_dir: &Path, revision: &str, replay: Replay) -> Result<(String, Commit, String)> {
+    let sha = resolve_ref(git_dir, revision)?;
+    let commit = read_commit(git_dir, &sha)?;
+    let parent_tree = match commit.parent.as_deref() {
+        None | Some([]) => hash::write_object(&Tree { entries: Vec::new() })?,
+        Some([parent]) => read_commit(git_dir, parent)?.tree,
+        Some(_) => {
+            return Err(anyhow!(
+                "error: commit {} is a merge, {} it is not supported",
+                &sha[..7],
+                match replay {
+                    Replay::CherryPick => "cherry-picking",
+                    Replay::Revert => "reverting",
+                }
+            ))
+        }
+    };
+    Ok((sha, commit, parent_tree))
+}
+
+/// Applies the change from `base` to `theirs` on top of HEAD and commits the result.
+/// Nothing is touched when the working tree is dirty or the change conflicts; then the
+/// working tree and the index are brought to the new tree and HEAD moves to the commit.
+/// `author` keeps the author of another commit (the current user is always the committer),
+/// `source` is the commit the change comes from. Returns the `[<sha>] <subject>` line.
+pub(crate) fn apply_tree_change(
+    repo_root: &Path,
+    replay: Replay,
+    source: &str,
+    base: &str,
+    theirs: &str,
+    message: &str,
+    author: Option<&Commit>,
+) -> Result<String> {
+    let git_dir = repo_root.join(".git");
+
+    let head = resolve_ref(&git_dir, "HEAD")
+        .map_err(|_| anyhow!("fatal: no commit to {} onto", replay.name()))?;
+    let head_tree = read_commit(&git_dir, &head)?.tree;
+
+    if has_uncommitted_changes(&git_dir, repo_root)? {
+        return Err(anyhow!(
+            "error: your local changes would be overwritten by {}. Commit them first.",
+            replay.name()
+        ));
+    }
+
+    let new_tree = match merge_engine::merge_trees(&git_dir, base, &head_tree, theirs)? {
+        MergeOutcome::Clean(tree_sha) => tree_sha,
+        MergeOutcome::Conflicts(paths) => {
+            return Err(anyhow!(
+                "{}\nerror: {} {}",
+                merge::conflict_summary(&paths),
+                replay.failure(),
+                &source[..7]
+            ))
+        }
+    };
+    if new_tree == head_tree {
+        return Err(anyhow!("nothing to commit: the changes of {} are {}", &source[..7], replay.done()));
+    }
+
+    // Bring the working tree and the index to the new state
+    clean_working_directory(repo_root, &git_dir, Some(&head_tree), &new_tree)?;
+    let tree_content = read_and_parse_git_object(&git_dir, &new_tree)?;
+    parse_tree_object(&git_dir, &tree_content, repo_root.to_path_buf())?;
+    SimpleIndex::from_tree(&git_dir, &new_tree)?.save()?;
+
+    let identity = config::user_identity()?;
+    let commit_hash = commit_tree::run(&commit_tree::CommitObject {
+        tree: new_tree,
+        parent: Some(vec![head.clone()]),
+        message: Some(message.to_string()),
+        author: author.map_or_else(|| identity.clone(), |commit| commit.author.clone()),
+        committer: identity,
+        author_date: author.map(|commit| commit.author_date),
+        committer_date: author.map(|_| chrono::Utc::now().timestamp()),
+        author_timezone: author.map(|commit| commit.author_timezone.clone()),
+        committer_timezone: author.map(|_| local_timezone()),
+    })?;
+
+    let subject = message.lines().next().unwrap_or("");
+    commit::update_head(&commit_hash)?;
+    reflog::record_head_update(
+        &git_dir,
+        Some(&head),
+        &commit_hash,
+        &format!("{}: {}", replay.name(), subject),
+    )?;
+
+    Ok(format!("[{}] {}", &commit_hash[..7], subject))
+}
@@ -0,0 +1,56 @@
+use crate::commands::{diff, log};
+use crate::core::cat::{self, ParsedObject};
+use crate::core::resolve_parse::resolve_ref;
+use crate::core::simple_index;
+use anyhow::{anyhow, Result};
+use clap::Args;
+use std::collections::HashMap;
+
+/// Arguments for the `guts show` command
+#[derive(Args)]
+pub struct ShowArgs {
+    /// Commit to show (defaults to HEAD)
+    #[arg(default_value = "HEAD")]
+    pub commit: String,
+}
+
+/// Entry point for the `guts show` command
+/// Prints a commit like `guts log` does, followed by the diff it introduced
+pub fn run(args: &ShowArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+
+    let git_dir = simple_index::find_repo_root()?.join(".git");
+
+    let sha = resolve_ref(&git_dir, &args.commit)?;
+    let commit = match cat::read_object(&git_dir, &sha)? {
+        ParsedObject::Commit(commit) => commit,
+        _ => return Err(anyhow!("fatal: {} is not a commit", args.commit)),
+    };
+
+    // Compare against the first parent; a root commit adds every file
+    let (parent_files, parent_modes) = match commit.parent.as_ref().and_then(|p| p.first()) {
+        Some(parent) => match cat::read_object(&git_dir, parent)? {
+            ParsedObject::Commit(parent_commit) => (
+                simple_index::get_files_from_tree(&git_dir, &parent_commit.tree, "")?,
+                simple_index::get_modes_from_tree(&git_dir, &parent_commit.tree)?,
+            ),
+            _ => return Err(anyhow!("fatal: parent {} is not a commit", parent)),
+        },
+        None => (HashMap::new(), HashMap::new()),
+    };
+    let files = simple_index::get_files_from_tree(&git_dir, &commit.tree, "")?;
+    let modes = simple_index::get_modes_from_tree(&git_dir, &commit.tree)?;
+
+    let mut output = log::format_commit(&sha, &commit, false, false);
+    output.push('\n');
+
+    let changes = diff::diff_trees(&git_dir, &parent_files, &parent_modes, &files, &modes)?;
+    if !changes.is_empty() {
+        output.push('\n');
+        output.push_str(&changes);
+    }
+
+    Ok(output)
+}
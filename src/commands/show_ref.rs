@@ -2,24 +2,29 @@ use anyhow::Result;
 use clap::Args;
 use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
 use walkdir::WalkDir;
 
 /// CLI arguments for the `show-ref` command.
 #[derive(Args)]
 pub struct ShowRefArgs {
-    /// Current directory for the operation (injected by TUI)
-    pub dir: Option<PathBuf>,
+    /// Only show branches (`refs/heads/`)
+    #[arg(long)]
+    pub heads: bool,
+
+    /// Only show tags (`refs/tags/`)
+    #[arg(long)]
+    pub tags: bool,
+
+    /// Show only this fully qualified ref (e.g. `refs/heads/main`), failing if it does not exist
+    #[arg(long, value_name = "ref", conflicts_with_all = ["heads", "tags"])]
+    pub verify: Option<String>,
 }
 
 /// Entry point for the `guts show-ref` command
 /// Lists all refs and their hashes
 pub fn run(args: &ShowRefArgs) -> Result<String> {
-    // Determine current directory to use
-    let current_dir = args
-        .dir
-        .clone()
-        .unwrap_or_else(|| std::env::current_dir().expect("failed to get current directory"));
+    let current_dir = std::env::current_dir()?;
 
     // Find .git directory
     let git_dir = current_dir.join(".git");
@@ -28,6 +33,10 @@ pub fn run(args: &ShowRefArgs) -> Result<String> {
         return Ok("fatal: not a git repository".to_string());
     }
 
+    if let Some(name) = &args.verify {
+        return verify(&git_dir, name);
+    }
+
     let refs_dir = git_dir.join("refs");
     if !refs_dir.exists() {
         return Ok("".to_string()); // No refs yet
@@ -78,10 +87,32 @@ pub fn run(args: &ShowRefArgs) -> Result<String> {
     let mut refs_vec: Vec<(String, String)> = refs.into_iter().collect();
     refs_vec.sort_by(|a, b| a.1.cmp(&b.1));
 
+    // --heads and --tags each let their namespace through; without either, everything is shown
+    if args.heads || args.tags {
+        refs_vec.retain(|(_, name)| {
+            (args.heads && name.starts_with("refs/heads/")) || (args.tags && name.starts_with("refs/tags/"))
+        });
+    }
+
     // Format output: hash ref_name
     for (hash, ref_name) in refs_vec {
         output.push_str(&format!("{} {}\n", hash, ref_name));
     }
 
     Ok(output)
-}
\ No newline at end of file
+}
+
+/// `--verify`: the one ref named exactly, as `<sha> <ref>`
+fn verify(git_dir: &Path, name: &str) -> Result<String> {
+    let not_valid = || anyhow::anyhow!("fatal: '{}' - not a valid ref", name);
+    if name != "HEAD" && !name.starts_with("refs/") {
+        return Err(not_valid());
+    }
+
+    let mut content = fs::read_to_string(git_dir.join(name)).map_err(|_| not_valid())?;
+    // Follow symbolic refs such as HEAD
+    while let Some(target) = content.trim().strip_prefix("ref: ") {
+        content = fs::read_to_string(git_dir.join(target.trim())).map_err(|_| not_valid())?;
+    }
+    Ok(format!("{} {}\n", content.trim(), name))
+}
@@ -0,0 +1,198 @@
+use crate::commands::checkout::{
+    clean_working_directory, has_uncommitted_changes, head_tree_sha, parse_tree_object,
+    read_and_parse_git_object, untracked_conflicts,
+};
+use crate::commands::merge;
+use crate::core::attributes::Attributes;
+use crate::core::build_tree;
+use crate::core::cat::{self, ParsedObject};
+use crate::core::merge_engine::{self, MergeOutcome};
+use crate::core::read_head;
+use crate::core::resolve_parse::resolve_ref;
+use crate::core::simple_index::{self, SimpleIndex};
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What `guts stash` should do
+#[derive(Clone, Copy, ValueEnum)]
+pub enum StashAction {
+    /// Save the working tree changes and reset it to HEAD
+    Push,
+    /// Re-apply the most recent stash entry and drop it
+    Pop,
+}
+
+/// Arguments for the `guts stash` command
+#[derive(Args)]
+pub struct StashArgs {
+    #[arg(value_enum, default_value = "push")]
+    pub action: StashAction,
+}
+
+/// One saved state in `.git/stash`
+#[derive(Serialize, Deserialize)]
+struct StashEntry {
+    /// Commit HEAD pointed to when the changes were saved
+    base: String,
+    /// Tree holding every tracked file as it was in the working tree
+    tree: String,
+    /// `WIP on <branch>: <short sha> <subject>`
+    message: String,
+}
+
+/// Entry point for the `guts stash` command
+pub fn run(args: &StashArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+
+    // Objects are written relative to the current directory
+    let repo_root = simple_index::find_repo_root()?;
+    std::env::set_current_dir(&repo_root)?;
+    let git_dir = repo_root.join(".git");
+
+    match args.action {
+        StashAction::Push => push(&repo_root, &git_dir),
+        StashAction::Pop => pop(&repo_root, &git_dir),
+    }
+}
+
+/// Saves the tracked files of the working tree as a tree, then resets them to HEAD
+fn push(repo_root: &Path, git_dir: &Path) -> Result<String> {
+    let head = resolve_ref(git_dir, "HEAD")
+        .map_err(|_| anyhow!("fatal: you do not have the initial commit yet"))?;
+    let head_tree = head_tree_sha(git_dir)?
+        .ok_or_else(|| anyhow!("fatal: you do not have the initial commit yet"))?;
+
+    // Every file of the index, with its content on disk; deleted files are left out
+    let index = SimpleIndex::load()?;
+    let attributes = Attributes::load(repo_root)?;
+    let mut files = HashMap::new();
+    let mut modes = HashMap::new();
+    for path in index.files.keys() {
+        let full_path = repo_root.join(path);
+        if !full_path.is_file() && !simple_index::is_symlink(&full_path) {
+            continue;
+        }
+        files.insert(path.clone(), simple_index::write_worktree_blob(&full_path, path, &attributes)?);
+        let mode = simple_index::file_mode(&full_path)?;
+        if mode != simple_index::MODE_FILE {
+            modes.insert(path.clone(), mode.to_string());
+        }
+    }
+    let stash_tree = build_tree::write_tree_from_files(&files, &modes)?;
+    if stash_tree == head_tree {
+        return Ok("No local changes to save".to_string());
+    }
+
+    let branch = read_head::get_current_branch().unwrap_or_else(|_| "HEAD".to_string());
+    let subject = match cat::read_object(git_dir, &head)? {
+        ParsedObject::Commit(commit) => commit.message.lines().next().unwrap_or("").to_string(),
+        _ => String::new(),
+    };
+    let message = format!("WIP on {}: {} {}", branch, &head[..7], subject);
+
+    let mut entries = load_entries(git_dir)?;
+    entries.push(StashEntry { base: head, tree: stash_tree.clone(), message: message.clone() });
+    save_entries(git_dir, &entries)?;
+
+    // Back to HEAD, removing the files that were only added to the index
+    clean_working_directory(repo_root, git_dir, Some(&stash_tree), &head_tree)?;
+    let tree_content = read_and_parse_git_object(git_dir, &head_tree)?;
+    parse_tree_object(&git_dir.to_path_buf(), &tree_content, repo_root.to_path_buf())?;
+    SimpleIndex::from_tree(git_dir, &head_tree)?.save()?;
+
+    Ok(format!("Saved working directory and index state {}", message))
+}
+
+/// Merges the latest stash entry into the working tree and drops it from the list
+fn pop(repo_root: &Path, git_dir: &Path) -> Result<String> {
+    let mut entries = load_entries(git_dir)?;
+    let Some(entry) = entries.last() else {
+        return Err(anyhow!("No stash entries found."));
+    };
+
+    let head_tree = head_tree_sha(git_dir)?
+        .ok_or_else(|| anyhow!("fatal: you do not have the initial commit yet"))?;
+    if has_uncommitted_changes(git_dir, repo_root)? {
+        return Err(anyhow!(
+            "error: your local changes would be overwritten by stash pop. Commit or stash them first."
+        ));
+    }
+
+    // The commit the changes were made on is the base: HEAD may have moved since
+    let base_tree = cat::read_commit(git_dir, &entry.base)?.tree;
+    let new_tree = match merge_engine::merge_trees(git_dir, &base_tree, &head_tree, &entry.tree)? {
+        MergeOutcome::Clean(tree_sha) => tree_sha,
+        MergeOutcome::Conflicts(paths) => {
+            return Err(anyhow!(
+                "{}\nerror: could not apply stash@{{0}}, the stash entry is kept",
+                merge::conflict_summary(&paths)
+            ))
+        }
+    };
+
+    let conflicts = untracked_conflicts(repo_root, git_dir, Some(&head_tree), &new_tree)?;
+    if !conflicts.is_empty() {
+        let list: Vec<String> = conflicts.iter().map(|path| format!("\t{}", path.display())).collect();
+        return Err(anyhow!(
+            "error: the following untracked working tree files would be overwritten by stash pop:\n{}",
+            list.join("\n")
+        ));
+    }
+
+    clean_working_directory(repo_root, git_dir, Some(&head_tree), &new_tree)?;
+    let tree_content = read_and_parse_git_object(git_dir, &new_tree)?;
+    parse_tree_object(&git_dir.to_path_buf(), &tree_content, repo_root.to_path_buf())?;
+
+    // Like git, modifications come back unstaged; files new to HEAD stay in the index
+    let mut index = SimpleIndex::from_tree(git_dir, &head_tree)?;
+    let restored = SimpleIndex::from_tree(git_dir, &new_tree)?;
+    for (path, sha) in restored.files {
+        if !index.files.contains_key(&path) {
+            if let Some(mode) = restored.modes.get(&path) {
+                index.modes.insert(path.clone(), mode.clone());
+            }
+            index.files.insert(path, sha);
+        }
+    }
+    index.save()?;
+
+    let message = entry.message.clone();
+    entries.pop();
+    save_entries(git_dir, &entries)?;
+
+    Ok(format!("Dropped stash@{{0}} ({})", message))
+}
+
+/// Path of the stash list: `.git/stash`
+fn stash_path(git_dir: &Path) -> PathBuf {
+    git_dir.join("stash")
+}
+
+/// Stash entries, oldest first. No file means an empty stash.
+fn load_entries(git_dir: &Path) -> Result<Vec<StashEntry>> {
+    let path = stash_path(git_dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).with_context(|| format!("unable to read {:?}", path))?;
+    serde_json::from_str(&content).with_context(|| "invalid JSON in stash")
+}
+
+/// Writes the stash list back, removing the file once it is empty
+fn save_entries(git_dir: &Path, entries: &[StashEntry]) -> Result<()> {
+    let path = stash_path(git_dir);
+    if entries.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path).with_context(|| format!("unable to remove {:?}", path))?;
+        }
+        return Ok(());
+    }
+    let content = serde_json::to_string_pretty(entries).with_context(|| "unable to serialize stash")?;
+    fs::write(&path, content).with_context(|| format!("unable to write {:?}", path))
+}
@@ -1,61 +1,77 @@
-use crate::core::{ignore::IgnoreMatcher, simple_index, read_head};
+use crate::core::{hash, ignore::IgnoreMatcher, simple_index, read_head};
+use crate::core::attributes::Attributes;
+use crate::core::cat::{self, ParsedObject};
+use crate::core::color::{paint, ColorWhen, GREEN, RED};
+use crate::core::config::{self, Config};
+use crate::core::object::{Tree, TreeEntry};
+use crate::core::read_head::Head;
+use crate::core::resolve_parse::resolve_ref;
+use crate::core::status_binary_index::{self, FileStat};
 use anyhow::Result;
 use clap::Args;
-use std::collections::HashMap;
-use std::path::{PathBuf};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use walkdir::WalkDir;
 
+/// Number of working files hashed by `calculate_file_hash`, to measure what status reads
+static FILES_HASHED: AtomicUsize = AtomicUsize::new(0);
+
 /// CLI arguments for the `status` command.
 #[derive(Args)]
 pub struct StatusObject {
-    /// Current directory for the operation (injected by TUI)
-    pub dir: Option<PathBuf>,
+    /// Give the output in the short two-column format (`XY path`)
+    #[arg(short = 's', long)]
+    pub short: bool,
+
+    /// Color staged changes green and unstaged or untracked ones red
+    #[arg(long, value_enum, default_value_t = ColorWhen::Auto, value_name = "when")]
+    pub color: ColorWhen,
+
+    /// Also list the ignored files
+    #[arg(long)]
+    pub ignored: bool,
 }
 
 /// Entry point for the `guts status` command
 pub fn run(args: &StatusObject) -> Result<String> {
-    // Set current directory context for TUI
-    let original_dir = std::env::current_dir()?;
-    if let Some(dir) = &args.dir {
-        std::env::set_current_dir(dir)?;
+    if !simple_index::is_git_repository()? {
+        return Ok("fatal: not a git repository".to_string());
     }
-    
-    let result = || -> Result<String> {
-        if !simple_index::is_git_repository()? {
-            return Ok("fatal: not a git repository".to_string());
-        }
 
-        let current_dir = std::env::current_dir()?;
-        let matcher = IgnoreMatcher::from_gutsignore(&current_dir)
-            .unwrap_or_else(|_| IgnoreMatcher::empty());
+    let current_dir = std::env::current_dir()?;
+    let matcher = IgnoreMatcher::from_gutsignore(&current_dir)
+        .unwrap_or_else(|_| IgnoreMatcher::empty());
 
-        let committed_files = simple_index::get_committed_files()?;
-        let index = simple_index::SimpleIndex::load()?;
+    let committed_files = simple_index::get_committed_files()?;
+    let index = simple_index::SimpleIndex::load()?;
+    let attributes = Attributes::load(&current_dir)?;
     let work_files = list_working_dir_files(&current_dir, &matcher)?;
 
-    let current_branch = read_head::get_current_branch()
-        .unwrap_or_else(|_| "main".to_string());
-    
-    let mut output = String::new();
-    output.push_str(&format!("On branch {}\n", current_branch));
-
-    if committed_files.is_empty() {
-        output.push_str("\nNo commits yet\n");
-    }
-    output.push_str("\n");
-
     let mut work_files_map = HashMap::new();
     for work_file in &work_files {
         let relative_path = get_relative_path(work_file, &current_dir)?;
         work_files_map.insert(relative_path, work_file.clone());
     }
 
+    // The index holds the full next commit: HEAD vs index gives the staged changes,
+    // index vs working tree gives the unstaged ones
     let staged_files = &index.files;
     let mut staged_changes = Vec::new();
     let mut unstaged_changes = Vec::new();
     let mut untracked_files = Vec::new();
 
+    // Nothing to report, and nothing to hash, below a directory that still holds its
+    // committed tree in both the index and the working tree
+    let git_dir = simple_index::find_repo_root()?.join(".git");
+    let unchanged_dirs = unchanged_directories(&git_dir, &work_files_map, &index)?;
+
     for (work_path, work_file_path) in &work_files_map {
+        if parent_directories(work_path).any(|dir| unchanged_dirs.contains(dir)) {
+            continue;
+        }
+
         let committed_hash = committed_files.get(work_path as &str);
         let staged_hash = staged_files.get(work_path as &str);
 
@@ -63,46 +79,85 @@ pub fn run(args: &StatusObject) -> Result<String> {
             (None, None) => {
                 untracked_files.push(work_path.clone());
             }
-            (None, Some(_)) => {
-                staged_changes.push((work_path.clone(), "new file"));
-            }
-            (Some(committed_hash), Some(staged_hash)) => {
-                if committed_hash != staged_hash {
-                    staged_changes.push((work_path.clone(), "modified"));
+            (_, Some(staged_hash)) => {
+                match committed_hash {
+                    None => staged_changes.push((work_path.clone(), "new file")),
+                    Some(committed_hash) if committed_hash != staged_hash => {
+                        staged_changes.push((work_path.clone(), "modified"))
+                    }
+                    _ => {}
                 }
-            }
-            (Some(committed_hash), None) => {
-                let work_hash = calculate_file_hash(work_file_path)?;
-                if &work_hash != committed_hash {
+                if &calculate_file_hash(work_file_path, work_path, &attributes)? != staged_hash {
                     unstaged_changes.push((work_path.clone(), "modified"));
                 }
             }
+            (Some(_), None) => {
+                // Removed from the index but still on disk
+                staged_changes.push((work_path.clone(), "deleted"));
+                untracked_files.push(work_path.clone());
+            }
         }
     }
 
     for file_path in committed_files.keys() {
         if !work_files_map.contains_key(file_path) {
             if staged_files.contains_key(file_path) {
-                staged_changes.push((file_path.clone(), "deleted"));
-            } else {
                 unstaged_changes.push((file_path.clone(), "deleted"));
+            } else {
+                staged_changes.push((file_path.clone(), "deleted"));
             }
         }
     }
 
     for file_path in staged_files.keys() {
         if !work_files_map.contains_key(file_path) && !committed_files.contains_key(file_path) {
-            staged_changes.push((file_path.clone(), "deleted"));
+            staged_changes.push((file_path.clone(), "new file"));
+            unstaged_changes.push((file_path.clone(), "deleted"));
+        }
+    }
+
+    detect_renames(&mut staged_changes, &committed_files, staged_files);
+
+    // HashMap iteration order is random: sort for a stable output
+    staged_changes.sort();
+    unstaged_changes.sort();
+    untracked_files.sort();
+
+    let mut ignored_files = Vec::new();
+    if args.ignored {
+        ignored_files = list_ignored_files(&current_dir, &matcher)?;
+        // Tracked files stay tracked whatever the patterns say
+        ignored_files.retain(|path| !committed_files.contains_key(path) && !staged_files.contains_key(path));
+    }
+
+    let color = args.color.enabled();
+    if args.short {
+        return Ok(format_short(&staged_changes, &unstaged_changes, &untracked_files, &ignored_files, color));
+    }
+
+    let mut output = String::new();
+    match read_head::current_head(&git_dir) {
+        Ok(head @ Head::Detached(_)) => output.push_str(&format!("HEAD detached at {}\n", head.short_name())),
+        Ok(Head::Branch(name)) => {
+            output.push_str(&format!("On branch {}\n", name));
+            output.push_str(&upstream_summary(&git_dir, &name)?);
         }
+        Err(_) => output.push_str(&format!("On branch {}\n", config::default_branch()?)),
+    }
+
+    if committed_files.is_empty() {
+        output.push_str("\nNo commits yet\n");
     }
+    output.push('\n');
 
     if !staged_changes.is_empty() {
         output.push_str("Changes to be committed:\n");
         output.push_str("  (use \"git reset HEAD <file>...\" to unstage)\n");
         for (file_path, change_type) in &staged_changes {
-            output.push_str(&format!("        {}:   {}\n", change_type, file_path));
+            let line = format!("{}:   {}", change_type, file_path);
+            output.push_str(&format!("        {}\n", paint(&line, GREEN, color)));
         }
-        output.push_str("\n");
+        output.push('\n');
     }
 
     if !unstaged_changes.is_empty() {
@@ -110,35 +165,209 @@ pub fn run(args: &StatusObject) -> Result<String> {
         output.push_str("  (use \"git add <file>...\" to update what will be committed)\n");
         output.push_str("  (use \"git checkout -- <file>...\" to discard changes in working directory)\n");
         for (file_path, change_type) in &unstaged_changes {
-            output.push_str(&format!("        {}:   {}\n", change_type, file_path));
+            let line = format!("{}:   {}", change_type, file_path);
+            output.push_str(&format!("        {}\n", paint(&line, RED, color)));
         }
-        output.push_str("\n");
+        output.push('\n');
     }
 
     if !untracked_files.is_empty() {
         output.push_str("Untracked files:\n");
         output.push_str("  (use \"git add <file>...\" to include in what will be committed)\n");
         for file in &untracked_files {
-            output.push_str(&format!("        {}\n", file));
+            output.push_str(&format!("        {}\n", paint(file, RED, color)));
         }
-        output.push_str("\n");
+        output.push('\n');
     }
 
-        if staged_changes.is_empty() && unstaged_changes.is_empty() && untracked_files.is_empty() {
-            output.push_str("nothing to commit, working tree clean\n");
+    if !ignored_files.is_empty() {
+        output.push_str("Ignored files:\n");
+        output.push_str("  (use \"git add -f <file>...\" to include in what will be committed)\n");
+        for file in &ignored_files {
+            output.push_str(&format!("        {}\n", paint(file, RED, color)));
         }
+        output.push('\n');
+    }
+
+    if staged_changes.is_empty() && unstaged_changes.is_empty() && untracked_files.is_empty() {
+        output.push_str("nothing to commit, working tree clean\n");
+    }
 
-        Ok(output)
-    }();
-    
-    // Restore original directory
-    std::env::set_current_dir(&original_dir)?;
-    
-    result
+    Ok(output)
+}
+
+/// "Your branch is ahead of 'origin/main' by 2 commits." and the like, when the branch
+/// has an upstream (`branch.<name>.remote` and `branch.<name>.merge`); empty otherwise
+fn upstream_summary(git_dir: &Path, branch: &str) -> Result<String> {
+    let repo_config = Config::load(&git_dir.join("config"))?;
+    let (Some(remote), Some(merge)) = (
+        repo_config.get(&format!("branch.{}.remote", branch)),
+        repo_config.get(&format!("branch.{}.merge", branch)),
+    ) else {
+        return Ok(String::new());
+    };
+    let upstream = format!("{}/{}", remote, merge.strip_prefix("refs/heads/").unwrap_or(merge));
+
+    let Ok(upstream_sha) = fs::read_to_string(git_dir.join("refs/remotes").join(&upstream)) else {
+        return Ok(format!(
+            "Your branch is based on '{}', but the upstream is gone.\n  (use \"git branch --unset-upstream\" to fixup)\n",
+            upstream
+        ));
+    };
+    let Ok(local_sha) = fs::read_to_string(git_dir.join("refs/heads").join(branch)) else {
+        // No commit on the branch yet
+        return Ok(String::new());
+    };
+
+    let local = ancestors(git_dir, local_sha.trim())?;
+    let remote = ancestors(git_dir, upstream_sha.trim())?;
+    let ahead = local.difference(&remote).count();
+    let behind = remote.difference(&local).count();
+    let commits = |n: usize| if n == 1 { "commit" } else { "commits" };
+
+    Ok(match (ahead, behind) {
+        (0, 0) => format!("Your branch is up to date with '{}'.\n", upstream),
+        (ahead, 0) => format!(
+            "Your branch is ahead of '{}' by {} {}.\n  (use \"git push\" to publish your local commits)\n",
+            upstream,
+            ahead,
+            commits(ahead)
+        ),
+        (0, behind) => format!(
+            "Your branch is behind '{}' by {} {}, and can be fast-forwarded.\n  (use \"git pull\" to update your local branch)\n",
+            upstream,
+            behind,
+            commits(behind)
+        ),
+        (ahead, behind) => format!(
+            "Your branch and '{}' have diverged,\nand have {} and {} different commits each, respectively.\n",
+            upstream, ahead, behind
+        ),
+    })
+}
+
+/// A commit and every commit in its history. Commits missing from the object
+/// store (e.g. not fetched) end the walk on their side.
+fn ancestors(git_dir: &Path, sha: &str) -> Result<HashSet<String>> {
+    let mut seen = HashSet::new();
+    let mut pending = vec![sha.to_string()];
+    while let Some(sha) = pending.pop() {
+        if !cat::get_object_path(git_dir, &sha).exists() || !seen.insert(sha.clone()) {
+            continue;
+        }
+        if let ParsedObject::Commit(commit) = cat::read_object_cached(git_dir, &sha)? {
+            pending.extend(commit.parent.unwrap_or_default());
+        }
+    }
+    Ok(seen)
+}
+
+/// Pairs each staged deletion with a staged new file holding exactly the same blob,
+/// and reports the pair as a single `renamed: old -> new` change
+fn detect_renames(
+    staged_changes: &mut Vec<(String, &str)>,
+    committed_files: &HashMap<String, String>,
+    staged_files: &HashMap<String, String>,
+) {
+    let mut deleted: Vec<String> = staged_changes
+        .iter()
+        .filter(|(_, change_type)| *change_type == "deleted")
+        .map(|(path, _)| path.clone())
+        .collect();
+    let mut added: Vec<String> = staged_changes
+        .iter()
+        .filter(|(_, change_type)| *change_type == "new file")
+        .map(|(path, _)| path.clone())
+        .collect();
+    // Sorted so that duplicates of the same content are paired deterministically
+    deleted.sort();
+    added.sort();
+
+    for new_path in added {
+        let Some(new_hash) = staged_files.get(&new_path) else {
+            continue;
+        };
+        let Some(position) = deleted
+            .iter()
+            .position(|old_path| committed_files.get(old_path) == Some(new_hash))
+        else {
+            continue;
+        };
+        let old_path = deleted.remove(position);
+
+        staged_changes.retain(|(path, change_type)| {
+            !((*path == old_path && *change_type == "deleted") || (*path == new_path && *change_type == "new file"))
+        });
+        staged_changes.push((format!("{} -> {}", old_path, new_path), "renamed"));
+    }
+}
+
+/// Renders the short format: one `XY path` line per file, X being the index status
+/// and Y the working tree status, followed by `?? path` for untracked files
+fn format_short(
+    staged_changes: &[(String, &str)],
+    unstaged_changes: &[(String, &str)],
+    untracked_files: &[String],
+    ignored_files: &[String],
+    color: bool,
+) -> String {
+    let mut codes: BTreeMap<&str, (char, char)> = BTreeMap::new();
+    for (path, change_type) in staged_changes {
+        codes.entry(path.as_str()).or_insert((' ', ' ')).0 = short_code(change_type);
+    }
+    for (path, change_type) in unstaged_changes {
+        codes.entry(path.as_str()).or_insert((' ', ' ')).1 = short_code(change_type);
+    }
+
+    // Blank columns are left uncolored
+    let column = |code: char, ansi: &str| match code {
+        ' ' => " ".to_string(),
+        _ => paint(&code.to_string(), ansi, color),
+    };
+
+    let mut output = String::new();
+    for (path, (index_code, work_code)) in codes {
+        output.push_str(&format!("{}{} {}\n", column(index_code, GREEN), column(work_code, RED), path));
+    }
+    for path in untracked_files {
+        output.push_str(&format!("{} {}\n", paint("??", RED, color), path));
+    }
+    for path in ignored_files {
+        output.push_str(&format!("{} {}\n", paint("!!", RED, color), path));
+    }
+    output
+}
+
+/// Letter used by the short format for a change type
+fn short_code(change_type: &str) -> char {
+    match change_type {
+        "new file" => 'A',
+        "deleted" => 'D',
+        "renamed" => 'R',
+        _ => 'M',
+    }
+}
+
+/// Paths (relative to the repo root, sorted) of the files below `repo_root` that are
+/// neither in the index nor in HEAD, ignored files excluded. Same rule as `status`.
+pub(crate) fn list_untracked_files(repo_root: &Path) -> Result<Vec<String>> {
+    let matcher = IgnoreMatcher::from_gutsignore(repo_root).unwrap_or_else(|_| IgnoreMatcher::empty());
+    let committed_files = simple_index::get_committed_files()?;
+    let index = simple_index::SimpleIndex::load()?;
+
+    let mut untracked_files = Vec::new();
+    for work_file in list_working_dir_files(repo_root, &matcher)? {
+        let work_path = get_relative_path(&work_file, repo_root)?;
+        if !committed_files.contains_key(&work_path) && !index.files.contains_key(&work_path) {
+            untracked_files.push(work_path);
+        }
+    }
+    untracked_files.sort();
+    Ok(untracked_files)
 }
 
 /// List all working directory files, excluding ignored and .git files
-fn list_working_dir_files(current_dir: &PathBuf, matcher: &IgnoreMatcher) -> Result<Vec<PathBuf>> {
+pub(crate) fn list_working_dir_files(current_dir: &Path, matcher: &IgnoreMatcher) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
 
     let walker = WalkDir::new(current_dir).into_iter().filter_entry(|e| {
@@ -149,12 +378,12 @@ fn list_working_dir_files(current_dir: &PathBuf, matcher: &IgnoreMatcher) -> Res
             return false;
         }
 
-        !matcher.is_ignored(path, &current_dir)
+        !matcher.is_ignored(path, current_dir)
     });
 
     for entry in walker {
         let entry = entry?;
-        if entry.file_type().is_file() && !matcher.is_ignored(entry.path(), &current_dir) {
+        if (entry.file_type().is_file() || entry.file_type().is_symlink()) && !matcher.is_ignored(entry.path(), current_dir) {
             files.push(entry.into_path());
         }
     }
@@ -162,7 +391,38 @@ fn list_working_dir_files(current_dir: &PathBuf, matcher: &IgnoreMatcher) -> Res
     Ok(files)
 }
 
-fn get_relative_path(file_path: &PathBuf, _current_dir: &PathBuf) -> Result<String> {
+/// Ignored paths below `current_dir` (relative to the repo root, sorted). A directory
+/// ignored as a whole is listed once with a trailing `/`, like git.
+fn list_ignored_files(current_dir: &Path, matcher: &IgnoreMatcher) -> Result<Vec<String>> {
+    let mut ignored = Vec::new();
+    let mut walker = WalkDir::new(current_dir).min_depth(1).into_iter();
+
+    while let Some(entry) = walker.next() {
+        let entry = entry?;
+        let is_dir = entry.file_type().is_dir();
+        if entry.file_name() == ".git" {
+            if is_dir {
+                walker.skip_current_dir();
+            }
+            continue;
+        }
+        if !matcher.is_ignored(entry.path(), current_dir) {
+            continue;
+        }
+
+        let mut path = get_relative_path(entry.path(), current_dir)?;
+        if is_dir {
+            walker.skip_current_dir();
+            path.push('/');
+        }
+        ignored.push(path);
+    }
+
+    ignored.sort();
+    Ok(ignored)
+}
+
+fn get_relative_path(file_path: &Path, _current_dir: &Path) -> Result<String> {
     // Find repo root from current working directory
     let repo_root = simple_index::find_repo_root()?;
     let relative = file_path
@@ -171,11 +431,132 @@ fn get_relative_path(file_path: &PathBuf, _current_dir: &PathBuf) -> Result<Stri
     Ok(relative.to_string_lossy().to_string())
 }
 
-fn calculate_file_hash(file_path: &PathBuf) -> Result<String> {
-    use crate::core::{blob, hash};
-    use std::fs;
+/// Directories (relative to the repo root, `""` for the root) whose tree is the one of
+/// HEAD both in the index and in the working tree. A working file keeps the SHA staged in
+/// `.git/index` while its stat data is the one recorded there, so the working trees are
+/// recomputed bottom-up without reading any file; a directory holding a file changed
+/// since, or an untracked one, never matches.
+fn unchanged_directories(
+    git_dir: &Path,
+    work_files: &HashMap<String, PathBuf>,
+    index: &simple_index::SimpleIndex,
+) -> Result<HashSet<String>> {
+    let mut committed = HashMap::new();
+    if let Ok(head) = resolve_ref(git_dir, "HEAD") {
+        if let ParsedObject::Commit(commit) = cat::read_object_cached(git_dir, &head)? {
+            committed_trees(git_dir, &commit.tree, "", &mut committed)?;
+        }
+    }
+    if committed.is_empty() {
+        return Ok(HashSet::new());
+    }
+
+    let recorded: HashMap<PathBuf, status_binary_index::IndexEntry> = status_binary_index::parse_git_index(git_dir)
+        .map(|entries| entries.into_iter().map(|entry| (entry.path.clone(), entry)).collect())
+        .unwrap_or_default();
+    let work_blobs: HashMap<String, Option<(String, String)>> = work_files
+        .iter()
+        .map(|(path, full_path)| {
+            let blob = recorded
+                .get(Path::new(path))
+                .filter(|entry| entry.stat != FileStat::default())
+                .filter(|entry| index.files.get(path) == Some(&entry.blob_hash))
+                .filter(|entry| {
+                    fs::symlink_metadata(full_path).is_ok_and(|metadata| FileStat::from_metadata(&metadata) == entry.stat)
+                })
+                .map(|entry| (format!("{:o}", entry.mode), entry.blob_hash.clone()));
+            (path.clone(), blob)
+        })
+        .collect();
+    let staged_blobs: HashMap<String, Option<(String, String)>> = index
+        .files
+        .iter()
+        .map(|(path, sha)| (path.clone(), Some((index.mode_of(path).to_string(), sha.clone()))))
+        .collect();
+
+    let work_trees = directory_trees(&work_blobs);
+    let staged_trees = directory_trees(&staged_blobs);
+    Ok(committed
+        .into_iter()
+        .filter(|(dir, sha)| {
+            work_trees.get(dir).is_some_and(|tree| tree.as_ref() == Some(sha))
+                && staged_trees.get(dir).is_some_and(|tree| tree.as_ref() == Some(sha))
+        })
+        .map(|(dir, _)| dir)
+        .collect())
+}
+
+/// Tree SHA of a committed directory and of every directory below it
+fn committed_trees(git_dir: &Path, tree_sha: &str, prefix: &str, trees: &mut HashMap<String, String>) -> Result<()> {
+    trees.insert(prefix.to_string(), tree_sha.to_string());
+    let ParsedObject::Tree(entries) = cat::read_object_cached(git_dir, tree_sha)? else {
+        return Ok(());
+    };
+    for entry in entries.iter().filter(|entry| entry.mode == "40000") {
+        let path = if prefix.is_empty() { entry.name.clone() } else { format!("{}/{}", prefix, entry.name) };
+        committed_trees(git_dir, &hex::encode(entry.hash), &path, trees)?;
+    }
+    Ok(())
+}
+
+/// Tree SHA of every directory of a `path -> (mode, blob SHA)` listing, computed bottom-up
+/// like `write-tree` but without writing anything. A directory holding a file whose SHA
+/// is unknown has none.
+fn directory_trees(blobs: &HashMap<String, Option<(String, String)>>) -> HashMap<String, Option<String>> {
+    // Entries of each directory: (name, mode, SHA)
+    let mut children: HashMap<String, Vec<(String, String, Option<String>)>> = HashMap::new();
+    for (path, blob) in blobs {
+        let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+        let (mode, sha) = match blob {
+            Some((mode, sha)) => (mode.clone(), Some(sha.clone())),
+            None => (String::new(), None),
+        };
+        children.entry(dir.to_string()).or_default().push((name.to_string(), mode, sha));
+        for parent in parent_directories(dir) {
+            children.entry(parent.to_string()).or_default();
+        }
+    }
+
+    // Deepest first, so that every subtree is known before its parent
+    let mut dirs: Vec<String> = children.keys().cloned().collect();
+    dirs.sort_by_key(|dir| std::cmp::Reverse(if dir.is_empty() { 0 } else { dir.matches('/').count() + 1 }));
+
+    let mut trees = HashMap::new();
+    for dir in dirs {
+        let mut entries = children.remove(&dir).unwrap_or_default();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        let tree = entries
+            .into_iter()
+            .map(|(name, mode, sha)| {
+                let hash = hex::decode(sha?).ok()?.try_into().ok()?;
+                Some(TreeEntry { mode, name, hash })
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|entries| hash::hash_object(&Tree { entries }));
+
+        if let Some((parent, name)) = dir.rsplit_once('/').or((!dir.is_empty()).then_some(("", dir.as_str()))) {
+            if let Some(siblings) = children.get_mut(parent) {
+                siblings.push((name.to_string(), "40000".to_string(), tree.clone()));
+            }
+        }
+        trees.insert(dir, tree);
+    }
+    trees
+}
+
+/// The directories above a path, the repository root (`""`) first
+fn parent_directories(path: &str) -> impl Iterator<Item = &str> {
+    std::iter::once("").chain(path.match_indices('/').map(move |(i, _)| &path[..i]))
+}
+
+/// How many working files have been hashed by this process
+pub fn files_hashed() -> usize {
+    FILES_HASHED.load(Ordering::Relaxed)
+}
 
-    let content = fs::read(file_path)?;
-    let blob = blob::Blob::new(content);
-    hash::write_object(&blob)
-}
\ No newline at end of file
+/// Hash a working file as a blob without writing it to the object store
+pub(crate) fn calculate_file_hash(file_path: &Path, relative_path: &str, attributes: &Attributes) -> Result<String> {
+    FILES_HASHED.fetch_add(1, Ordering::Relaxed);
+    let content = simple_index::read_worktree_file(file_path, relative_path, attributes)?;
+    hash::hash_blob(&content)
+}
@@ -40,7 +40,7 @@ pub fn run(_args: &StatusObject) -> Result<String> {
     let mut output = String::new();
     output.push_str("On branch main\n");
     output.push_str("Your branch is up to date with 'origin/main'.\n");
-    output.push_str("\n");
+    output.push('\n');
 
     // Detect untracked files (present in working dir but not in index)
     // if !work_files.is_empty() {
@@ -51,7 +51,7 @@ pub fn run(_args: &StatusObject) -> Result<String> {
     //             output.push_str(&format!("        {}\n", file.display()));
     //         }
     //     }
-    //     output.push_str("\n");
+    //     output.push('\n');
     // }
 
     // Show files that have been modified since last index
@@ -61,7 +61,7 @@ pub fn run(_args: &StatusObject) -> Result<String> {
         for file in &modified_files {
             output.push_str(&format!("        modified:   {}\n", file.display()));
         }
-        output.push_str("\n");
+        output.push('\n');
     }
 
     // Show files that are in the index but missing in the working directory (deleted)
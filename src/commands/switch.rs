@@ -0,0 +1,42 @@
+use crate::commands::checkout::{self, CheckoutObject};
+use anyhow::{anyhow, Result};
+use clap::Args;
+
+/// Arguments for the `guts switch` command
+#[derive(Args)]
+pub struct SwitchArgs {
+    /// Branch to switch to, or the start point of the branch created with -c
+    #[arg(required_unless_present = "create")]
+    pub name: Option<String>,
+
+    /// Create a new branch and switch to it
+    #[arg(short = 'c', long, value_name = "NEW_BRANCH")]
+    pub create: Option<String>,
+}
+
+/// Main function for the `guts switch` command
+/// The branch half of `checkout`: it never touches single files, and only accepts
+/// branches so that `switch <file>` cannot be mistaken for something else
+pub fn run(args: &SwitchArgs) -> Result<String> {
+    if let (Some(name), None) = (&args.name, &args.create) {
+        if !std::env::current_dir()?.join(".git/refs/heads").join(name).is_file() {
+            return Err(anyhow!(
+                "fatal: invalid reference: {}\nhint: use 'guts checkout {}' to check out a commit",
+                name,
+                name
+            ));
+        }
+    }
+
+    checkout::run(&CheckoutObject {
+        name: args.name.clone(),
+        branch_name: args.create.clone(),
+        paths: Vec::new(),
+    })?;
+
+    Ok(match (&args.create, &args.name) {
+        (Some(branch), _) => format!("Switched to a new branch '{}'", branch),
+        (None, Some(branch)) => format!("Switched to branch '{}'", branch),
+        (None, None) => unreachable!("clap requires a branch name"),
+    })
+}
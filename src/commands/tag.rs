@@ -0,0 +1,98 @@
+use crate::core::cat::{self, ParsedObject};
+use crate::core::config;
+use crate::core::hash::write_object;
+use crate::core::object::{local_timezone, Tag};
+use crate::core::resolve_parse::resolve_ref;
+use crate::core::simple_index;
+use anyhow::{anyhow, Context, Result};
+use clap::Args;
+use std::fs;
+use walkdir::WalkDir;
+
+/// Arguments for the `guts tag` command
+#[derive(Args)]
+pub struct TagArgs {
+    /// Name of the tag to create; without it the tags are listed
+    pub name: Option<String>,
+
+    /// Commit to tag (defaults to HEAD)
+    #[arg(requires = "name")]
+    pub commit: Option<String>,
+
+    /// Create an annotated tag object instead of a plain ref
+    #[arg(short = 'a', long, requires = "message")]
+    pub annotate: bool,
+
+    /// Message of the annotated tag (implies -a)
+    #[arg(short = 'm', long, requires = "name")]
+    pub message: Option<String>,
+}
+
+/// Main function for the `guts tag` command
+/// Lists tags, or creates `refs/tags/<name>` pointing at a commit (lightweight tag)
+/// or at a new tag object holding a message (annotated tag)
+pub fn run(args: &TagArgs) -> Result<String> {
+    if !simple_index::is_git_repository()? {
+        return Err(anyhow!("fatal: not a git repository"));
+    }
+    // Objects are written below the current directory
+    let repo_root = simple_index::find_repo_root()?;
+    std::env::set_current_dir(&repo_root)?;
+    let git_dir = repo_root.join(".git");
+    let tags_dir = git_dir.join("refs").join("tags");
+
+    let Some(name) = &args.name else {
+        let mut output = String::new();
+        for entry in WalkDir::new(&tags_dir).sort_by_file_name().into_iter().filter_map(|e| e.ok()) {
+            if entry.file_type().is_file() {
+                output.push_str(&format!("{}\n", entry.path().strip_prefix(&tags_dir)?.to_string_lossy()));
+            }
+        }
+        return Ok(output);
+    };
+
+    if name.is_empty()
+        || name.starts_with('-')
+        || name.contains("..")
+        || name.chars().any(|c| c.is_whitespace() || "~^:?*[\\".contains(c))
+    {
+        return Err(anyhow!("fatal: '{}' is not a valid tag name.", name));
+    }
+    let ref_path = tags_dir.join(name);
+    if ref_path.exists() {
+        return Err(anyhow!("fatal: tag '{}' already exists", name));
+    }
+
+    let revision = args.commit.as_deref().unwrap_or("HEAD");
+    let target = resolve_ref(&git_dir, revision)
+        .map_err(|_| anyhow!("fatal: Failed to resolve '{}' as a valid ref.", revision))?;
+
+    let tag_sha = match &args.message {
+        Some(message) => {
+            let target_type = match cat::read_object(&git_dir, &target)? {
+                ParsedObject::Blob(_) => "blob",
+                ParsedObject::Tree(_) => "tree",
+                ParsedObject::Commit(_) => "commit",
+                ParsedObject::Tag(_) => "tag",
+                ParsedObject::Other(_, _) => return Err(anyhow!("fatal: cannot tag object {}", target)),
+            };
+            write_object(&Tag {
+                object: target.clone(),
+                target_type: target_type.to_string(),
+                name: name.clone(),
+                tagger: config::user_identity()?,
+                tagger_date: chrono::Utc::now().timestamp(),
+                tagger_timezone: local_timezone(),
+                message: message.clone(),
+            })?
+        }
+        None => target,
+    };
+
+    if let Some(parent) = ref_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&ref_path, format!("{}\n", tag_sha))
+        .with_context(|| format!("failed to write {}", ref_path.display()))?;
+    Ok(String::new())
+}
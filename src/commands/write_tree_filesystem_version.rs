@@ -1,4 +1,4 @@
-use crate::core::{build_tree, hash};
+use crate::core::build_tree;
 use anyhow::Result;
 use clap::Args;
 use std::path::PathBuf;
@@ -6,18 +6,14 @@ use std::path::PathBuf;
 #[derive(Args)]
 pub struct WriteTreeArgs {
     pub dir: Option<PathBuf>,
-    /// Current directory for the operation (injected by TUI)
-    pub tui_dir: Option<PathBuf>,
 }
 
+/// Filesystem version of write-tree: hashes a directory as it is on disk, index untouched
 pub fn run(args: &WriteTreeArgs) -> Result<String> {
     let root = args
         .dir
         .clone()
         .unwrap_or_else(|| std::env::current_dir().expect("failed to get the current directory"));
 
-    let tree = build_tree::build_tree(&root)?;
-    let oid = hash::write_object(&tree)?;
-
-    Ok(oid)
+    build_tree::write_tree_from_dir(&root)
 }
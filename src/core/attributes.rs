@@ -0,0 +1,196 @@
+use crate::core::ignore::compile_pattern;
+use anyhow::{Context, Result};
+use globset::GlobMatcher;
+use std::fs;
+use std::path::Path;
+
+/// Attribute files read at the root of the working tree, `.gutsattributes` first
+pub const ATTRIBUTES_FILES: [&str; 2] = [".gutsattributes", ".gitattributes"];
+
+/// State of the `text` attribute of a path
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Text {
+    /// `text`: always normalized
+    Set,
+    /// `text=auto`: normalized unless the content looks binary
+    Auto,
+    /// `-text` or `binary`: stored verbatim
+    Unset,
+}
+
+/// Line ending written to the working tree for normalized files
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Eol {
+    Lf,
+    Crlf,
+}
+
+/// One line of an attributes file: a pattern and the attributes it sets
+struct AttributeRule {
+    glob: GlobMatcher,
+    text: Option<Text>,
+    eol: Option<Eol>,
+}
+
+/// Minimal `.gitattributes` support: only `text`, `text=auto`, `-text`, `binary` and
+/// `eol=lf|crlf` are understood, from the file at the root of the repository
+pub struct Attributes {
+    rules: Vec<AttributeRule>,
+}
+
+impl Attributes {
+    pub fn empty() -> Self {
+        Attributes { rules: Vec::new() }
+    }
+
+    /// Reads the attributes file at the root of the working tree, if there is one
+    pub fn load(repo_root: &Path) -> Result<Self> {
+        for name in ATTRIBUTES_FILES {
+            let path = repo_root.join(name);
+            if path.is_file() {
+                let content = fs::read_to_string(&path).with_context(|| format!("unable to read {:?}", path))?;
+                return Self::parse(&content);
+            }
+        }
+        Ok(Self::empty())
+    }
+
+    /// Parses `<pattern> <attr>...` lines. Patterns follow the ignore file rules;
+    /// unknown attributes are skipped.
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+        for line in content.lines() {
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            if pattern.starts_with('#') {
+                continue;
+            }
+
+            let mut rule = AttributeRule {
+                glob: compile_pattern(pattern).with_context(|| format!("invalid attribute pattern '{}'", pattern))?,
+                text: None,
+                eol: None,
+            };
+            for attribute in fields {
+                match attribute {
+                    "text" => rule.text = Some(Text::Set),
+                    "text=auto" => rule.text = Some(Text::Auto),
+                    "-text" | "binary" => rule.text = Some(Text::Unset),
+                    "eol=lf" => rule.eol = Some(Eol::Lf),
+                    "eol=crlf" => rule.eol = Some(Eol::Crlf),
+                    _ => {}
+                }
+            }
+            rules.push(rule);
+        }
+        Ok(Attributes { rules })
+    }
+
+    /// `text` and `eol` of a path: for each attribute the last matching line wins
+    fn lookup(&self, relative_path: &str) -> (Option<Text>, Option<Eol>) {
+        let (mut text, mut eol) = (None, None);
+        for rule in self.rules.iter().filter(|rule| rule.glob.is_match(relative_path)) {
+            text = rule.text.or(text);
+            eol = rule.eol.or(eol);
+        }
+        // Setting `eol` marks the path as text, like git
+        if text.is_none() && eol.is_some() {
+            text = Some(Text::Set);
+        }
+        (text, eol)
+    }
+
+    fn is_normalized(&self, relative_path: &str, content: &[u8]) -> (bool, Option<Eol>) {
+        let (text, eol) = self.lookup(relative_path);
+        let normalized = match text {
+            Some(Text::Set) => true,
+            Some(Text::Auto) => !looks_binary(content),
+            Some(Text::Unset) | None => false,
+        };
+        (normalized, eol)
+    }
+
+    /// Whether `to_blob` converts the line endings of a file, `content_start` being at
+    /// least its first 8000 bytes (all of it when shorter). For files too large to be
+    /// read at once.
+    pub fn normalizes_to_blob(&self, relative_path: &str, content_start: &[u8]) -> bool {
+        self.is_normalized(relative_path, content_start).0
+    }
+
+    /// Content of a working tree file as it is stored in a blob: CRLF becomes LF for text files
+    pub fn to_blob(&self, relative_path: &str, content: Vec<u8>) -> Vec<u8> {
+        match self.is_normalized(relative_path, &content) {
+            (true, _) => crlf_to_lf(&content),
+            (false, _) => content,
+        }
+    }
+
+    /// Content of a blob as it is written to the working tree: text files get the
+    /// `eol` line ending, or the native one of the platform
+    pub fn to_worktree(&self, relative_path: &str, content: Vec<u8>) -> Vec<u8> {
+        let native = if cfg!(windows) { Eol::Crlf } else { Eol::Lf };
+        match self.is_normalized(relative_path, &content) {
+            (true, eol) if eol.unwrap_or(native) == Eol::Crlf => lf_to_crlf(&content),
+            _ => content,
+        }
+    }
+}
+
+/// Git's heuristic: a NUL byte in the first 8000 bytes means binary
+fn looks_binary(content: &[u8]) -> bool {
+    content.iter().take(8000).any(|&byte| byte == 0)
+}
+
+fn crlf_to_lf(content: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(content.len());
+    for (i, &byte) in content.iter().enumerate() {
+        if byte == b'\r' && content.get(i + 1) == Some(&b'\n') {
+            continue;
+        }
+        output.push(byte);
+    }
+    output
+}
+
+/// `crlf_to_lf` for content read in chunks: a CR ending a chunk waits for the next one
+#[derive(Default)]
+pub struct CrlfToLf {
+    pending_cr: bool,
+}
+
+impl CrlfToLf {
+    /// Appends the converted `chunk` to `output`
+    pub fn convert(&mut self, chunk: &[u8], output: &mut Vec<u8>) {
+        for &byte in chunk {
+            if std::mem::take(&mut self.pending_cr) && byte != b'\n' {
+                output.push(b'\r');
+            }
+            if byte == b'\r' {
+                self.pending_cr = true;
+            } else {
+                output.push(byte);
+            }
+        }
+    }
+
+    /// Appends what is left once the content ends
+    pub fn finish(self, output: &mut Vec<u8>) {
+        if self.pending_cr {
+            output.push(b'\r');
+        }
+    }
+}
+
+/// Adds a CR before every LF that does not already have one
+fn lf_to_crlf(content: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(content.len() + content.len() / 16);
+    for (i, &byte) in content.iter().enumerate() {
+        if byte == b'\n' && (i == 0 || content[i - 1] != b'\r') {
+            output.push(b'\r');
+        }
+        output.push(byte);
+    }
+    output
+}
@@ -3,8 +3,11 @@ use std::path::Path;
 
 use anyhow::{Context, Result};
 
+use crate::core::attributes::Attributes;
+use crate::core::ignore::IgnoreMatcher;
 use crate::core::object::{Tree, TreeEntry};
-use crate::core::{blob, hash};
+use crate::core::{hash, simple_index};
+use walkdir::WalkDir;
 
 /// Recursively builds a Git tree object from a directory on the filesystem.
 ///
@@ -18,6 +21,7 @@ use crate::core::{blob, hash};
 /// hashes all files as blobs, and collects their info as tree entries.
 pub fn build_tree(dir: &Path) -> Result<Tree> {
     let mut entries = Vec::new(); // Container for the tree entries (files)
+    let attributes = Attributes::load(dir)?;
 
     // Iterate over directory entries, return error if directory can't be read
     for entry in fs::read_dir(dir)? {
@@ -33,18 +37,12 @@ pub fn build_tree(dir: &Path) -> Result<Tree> {
             continue;
         }
 
-        if path.is_file() {
-            // For files only (ignore directories for now)
+        if path.is_file() || simple_index::is_symlink(&path) {
+            // For files and symlinks only (ignore directories for now)
 
-            // Read the file content as bytes
-            let data =
-                fs::read(&path).with_context(|| format!("failed to read file {:?}", path))?;
-
-            // Create a Blob Git object from the file content
-            let blob = blob::Blob::new(data);
-
-            // Write the blob object and get its SHA1 hash in hex format
-            let oid_hex = hash::write_object(&blob)?;
+            // Write the file content (the target path for a symlink) as a blob object
+            // and get its SHA1 hash in hex format
+            let oid_hex = simple_index::write_worktree_blob(&path, &name, &attributes)?;
 
             // Decode the hex SHA1 hash into raw bytes (20 bytes for SHA1)
             let hash_bin = hex::decode(&oid_hex)
@@ -54,9 +52,9 @@ pub fn build_tree(dir: &Path) -> Result<Tree> {
             let mut hash = [0u8; 20];
             hash.copy_from_slice(&hash_bin);
 
-            // Create a tree entry for this file
+            // Create a tree entry for this file, keeping the executable bit or symlink
             entries.push(TreeEntry {
-                mode: "100644".to_string(), // File mode for a normal file
+                mode: simple_index::file_mode(&path)?.to_string(),
                 name,
                 hash,
             });
@@ -66,3 +64,121 @@ pub fn build_tree(dir: &Path) -> Result<Tree> {
     // Return a Tree Git object containing all collected entries
     Ok(Tree { entries })
 }
+
+/// Writes the tree (and its subtrees) of a directory on disk, without going through the index.
+/// `.git` and the paths ignored by `.gutsignore`/`.gitignore` are skipped; returns the root tree SHA.
+pub fn write_tree_from_dir(dir: &Path) -> Result<String> {
+    let matcher = IgnoreMatcher::from_gutsignore(dir).unwrap_or_else(|_| IgnoreMatcher::empty());
+    let attributes = Attributes::load(dir)?;
+    let mut files = std::collections::HashMap::new();
+    let mut modes = std::collections::HashMap::new();
+
+    let walker = WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git" && !matcher.is_ignored(e.path(), dir));
+    for entry in walker {
+        let entry = entry?;
+        if !entry.file_type().is_file() && !entry.file_type().is_symlink() {
+            continue;
+        }
+
+        let path = entry.path();
+        let relative_path = path.strip_prefix(dir)?.to_string_lossy().to_string();
+        let mode = simple_index::file_mode(path)?;
+        if mode != simple_index::MODE_FILE {
+            modes.insert(relative_path.clone(), mode.to_string());
+        }
+        let sha = simple_index::write_worktree_blob(path, &relative_path, &attributes)?;
+        files.insert(relative_path, sha);
+    }
+
+    write_tree_from_files(&files, &modes)
+}
+
+/// Writes the tree (and its subtrees) for a `path -> blob SHA` map, returns the root tree SHA.
+/// `modes` holds the paths that are not plain `100644` files.
+pub fn write_tree_from_files(
+    files: &std::collections::HashMap<String, String>,
+    modes: &std::collections::HashMap<String, String>,
+) -> Result<String> {
+    let tree = build_tree_from_files(files, modes, "")?;
+    hash::write_object(&tree)
+}
+
+/// Recursively build a tree for a given directory path
+/// 
+/// Simple algorithm:
+/// 1. Filter files that belong to current directory level
+/// 2. For direct files: create blob entries  
+/// 3. For subdirectories: collect files, recurse, create tree entries
+pub fn build_tree_from_files(
+    all_files: &std::collections::HashMap<String, String>, 
+    modes: &std::collections::HashMap<String, String>,
+    prefix: &str
+) -> Result<Tree> {
+    use std::collections::HashMap;
+    
+    let mut entries = Vec::new();
+    let mut subdirs: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    
+    // Process each file to see if it belongs in this directory level
+    for (file_path, file_hash) in all_files {
+        // Skip files not in our prefix
+        let relative_path = if prefix.is_empty() {
+            file_path.as_str()
+        } else if file_path.starts_with(prefix) && file_path.len() > prefix.len() && file_path.chars().nth(prefix.len()) == Some('/') {
+            &file_path[prefix.len() + 1..] // +1 to skip the '/'
+        } else {
+            continue; // Not in this directory
+        };
+        
+        if let Some(slash_pos) = relative_path.find('/') {
+            // File is in a subdirectory
+            let subdir_name = &relative_path[..slash_pos];
+            subdirs.entry(subdir_name.to_string())
+                   .or_default()
+                   .push((file_path.clone(), file_hash.clone()));
+        } else {
+            // File is directly in this directory
+            let hash_bin = hex::decode(file_hash)
+                .map_err(|_| anyhow::anyhow!("invalid SHA-1 hash: {}", file_hash))?;
+            let mut hash = [0u8; 20];
+            hash.copy_from_slice(&hash_bin);
+            
+            entries.push(TreeEntry {
+                mode: modes
+                    .get(file_path)
+                    .cloned()
+                    .unwrap_or_else(|| simple_index::MODE_FILE.to_string()),
+                name: relative_path.to_string(),
+                hash,
+            });
+        }
+    }
+    
+    // Create subtrees for each subdirectory
+    for (subdir_name, _) in subdirs {
+        let subdir_prefix = if prefix.is_empty() {
+            subdir_name.clone()
+        } else {
+            format!("{}/{}", prefix, subdir_name)
+        };
+        
+        let subtree = build_tree_from_files(all_files, modes, &subdir_prefix)?;
+        let subtree_hash = hash::write_object(&subtree)?;
+        let hash_bin = hex::decode(&subtree_hash)?;
+        let mut hash = [0u8; 20];
+        hash.copy_from_slice(&hash_bin);
+        
+        entries.push(TreeEntry {
+            mode: "40000".to_string(), // Directory mode (Git uses 40000, not 040000)
+            name: subdir_name,
+            hash,
+        });
+    }
+    
+    // Sort entries by name (required by Git)
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    
+    Ok(Tree { entries })
+}
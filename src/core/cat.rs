@@ -1,17 +1,24 @@
-use crate::core::object::Commit;
+use crate::core::hash;
+use crate::core::object::{Commit, Tag};
 use crate::core::object::TreeEntry;
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
 
 /// Enum representing different parsed Git object types.
 /// - Blob holds raw file content bytes.
 /// - Tree holds a list of `TreeEntry` structs representing files/directories.
 /// - Commit holds a parsed commit object with metadata.
+/// - Tag holds a parsed annotated tag.
 /// - Other holds unknown object types with their raw bytes.
 pub enum ParsedObject {
     Blob(Vec<u8>),
     Tree(Vec<TreeEntry>),
     Commit(Commit),
+    Tag(Tag),
     Other(String, Vec<u8>),
 }
 
@@ -26,20 +33,234 @@ pub fn get_object_path(guts_dir: &Path, sha: &str) -> PathBuf {
     guts_dir.join("objects").join(dir).join(file)
 }
 
-/// Parses raw Git object data into a structured `ParsedObject`.
+/// Minimum length of an abbreviated SHA
+pub const MIN_PREFIX_LEN: usize = hash::MIN_SHA_PREFIX_LEN;
+
+/// Expands an abbreviated SHA (at least 4 hex chars) to the full SHA of the only
+/// object whose name starts with it. Zero or several matches are errors.
+pub fn resolve_object_prefix(guts_dir: &Path, prefix: &str) -> Result<String> {
+    let prefix = hash::normalize_sha(prefix)?;
+    let (dir, rest) = prefix.split_at(2);
+
+    let mut matches = Vec::new();
+    if let Ok(entries) = fs::read_dir(guts_dir.join("objects").join(dir)) {
+        for entry in entries {
+            let name = entry?.file_name().to_string_lossy().to_string();
+            if name.len() == 38 && name.starts_with(rest) {
+                matches.push(format!("{}{}", dir, name));
+            }
+        }
+    }
+
+    if matches.is_empty() {
+        if let [sha] = packed_objects(guts_dir).iter().filter(|sha| sha.starts_with(&prefix)).collect::<Vec<_>>()[..] {
+            return Err(packed_object_error(sha));
+        }
+    }
+
+    match matches.len() {
+        0 => Err(anyhow!("no object matches '{}'", prefix)),
+        1 => Ok(matches.remove(0)),
+        n => Err(anyhow!("short SHA '{}' is ambiguous ({} objects match)", prefix, n)),
+    }
+}
+
+/// Shortest prefix of `sha`, at least `min_len` chars long, naming no other object
+pub fn shortest_unique_prefix(guts_dir: &Path, sha: &str, min_len: usize) -> Result<String> {
+    let (dir, rest) = sha.split_at(2);
+    let mut others = Vec::new();
+    if let Ok(entries) = fs::read_dir(guts_dir.join("objects").join(dir)) {
+        for entry in entries {
+            let name = entry?.file_name().to_string_lossy().to_string();
+            if name.len() == 38 && name != rest {
+                others.push(name);
+            }
+        }
+    }
+
+    let mut len = min_len.clamp(MIN_PREFIX_LEN, 40);
+    while len < 40 && others.iter().any(|name| name.starts_with(&rest[..len - 2])) {
+        len += 1;
+    }
+    Ok(sha[..len].to_string())
+}
+
+/// Reads an object from the object store, decompresses it and parses it.
 ///
-/// Git object format:
-///   "<type> <size>\0<content>"
+/// This is the one-call way to get at an object when only its SHA is known.
+pub fn read_object(guts_dir: &Path, sha: &str) -> Result<ParsedObject> {
+    parse_object(&read_raw_object(guts_dir, sha)?)
+}
+
+/// Reads a commit object, failing if the SHA points to anything else
+pub fn read_commit(guts_dir: &Path, sha: &str) -> Result<Commit> {
+    match read_object(guts_dir, sha)? {
+        ParsedObject::Commit(commit) => Ok(commit),
+        _ => Err(anyhow!("fatal: {} is not a commit", sha)),
+    }
+}
+
+/// Reads an object from the object store and returns its decompressed bytes
+/// (header included), without parsing them.
+pub fn read_raw_object(guts_dir: &Path, sha: &str) -> Result<Vec<u8>> {
+    let object_path = get_object_path(guts_dir, sha);
+    if !object_path.exists() && packed_objects(guts_dir).iter().any(|packed| packed == sha) {
+        return Err(packed_object_error(sha));
+    }
+    let data = fs::read(&object_path)
+        .with_context(|| format!("Failed to read object file at {}", object_path.display()))?;
+
+    decompress(sha, &data)
+}
+
+fn packed_object_error(sha: &str) -> anyhow::Error {
+    anyhow!("fatal: object {} is in a packfile (unsupported)", sha)
+}
+
+/// Names of the objects listed by the pack indexes of `.git/objects/pack`.
+/// Packs cannot be read: this only tells a packed object from a missing one.
+pub fn packed_objects(guts_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(guts_dir.join("objects").join("pack")) else {
+        return Vec::new();
+    };
+
+    let mut objects = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().is_some_and(|ext| ext == "idx") {
+            if let Ok(index) = fs::read(&path) {
+                objects.extend(pack_index_objects(&index));
+            }
+        }
+    }
+    objects
+}
+
+/// Object names of a pack index. Version 2 starts with `\377tOc` and its version, then
+/// the 256-entry fan-out table and the sorted names; version 1 has the fan-out table
+/// first, then a 4-byte offset before each name.
+fn pack_index_objects(index: &[u8]) -> Vec<String> {
+    let read_u32 = |pos: usize| index.get(pos..pos + 4).map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]));
+    let (fanout, first_name, stride) = if index.starts_with(b"\xfftOc") {
+        if read_u32(4) != Some(2) {
+            return Vec::new();
+        }
+        (8, 8 + 256 * 4, 20)
+    } else {
+        (0, 256 * 4 + 4, 24)
+    };
+
+    let Some(count) = read_u32(fanout + 255 * 4) else {
+        return Vec::new();
+    };
+    (0..count as usize)
+        .map_while(|i| index.get(first_name + i * stride..first_name + i * stride + 20))
+        .map(hex::encode)
+        .collect()
+}
+
+/// Most objects the object cache keeps at a time
+const CACHE_CAPACITY: usize = 4096;
+
+/// Small least-recently-used cache of decompressed objects, keyed by object file path
+/// (so that two repositories never share an entry). Objects are immutable, so an
+/// entry never goes stale while its file exists.
+struct ObjectCache {
+    entries: HashMap<PathBuf, (Arc<Vec<u8>>, u64)>,
+    /// The paths of `entries` by the stamp of their last use, oldest first
+    recent: BTreeMap<u64, PathBuf>,
+    /// Incremented on every access; an entry's stamp tells when it was last used
+    clock: u64,
+}
+
+impl ObjectCache {
+    fn get(&mut self, path: &Path) -> Option<Arc<Vec<u8>>> {
+        self.clock += 1;
+        let clock = self.clock;
+        let (data, used) = self.entries.get_mut(path)?;
+        if let Some(path) = self.recent.remove(used) {
+            self.recent.insert(clock, path);
+        }
+        *used = clock;
+        Some(Arc::clone(data))
+    }
+
+    fn insert(&mut self, path: PathBuf, data: Arc<Vec<u8>>) {
+        if let Some((_, used)) = self.entries.remove(&path) {
+            self.recent.remove(&used);
+        }
+        if self.entries.len() >= CACHE_CAPACITY {
+            if let Some((_, oldest)) = self.recent.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.clock += 1;
+        self.recent.insert(self.clock, path.clone());
+        self.entries.insert(path, (data, self.clock));
+    }
+}
+
+fn object_cache() -> &'static Mutex<ObjectCache> {
+    static CACHE: OnceLock<Mutex<ObjectCache>> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        Mutex::new(ObjectCache {
+            entries: HashMap::new(),
+            recent: BTreeMap::new(),
+            clock: 0,
+        })
+    })
+}
+
+/// Same as `read_raw_object`, but served from an in-process cache after the first read.
+/// The data is shared with the cache, not copied.
 ///
-/// Steps:
-/// - Find the first null byte to split header and content.
-/// - Parse header for object type and size (size is parsed but not verified).
-/// - Based on type, parse the body:
-///     - "tree": parse as list of TreeEntry structs
-///     - "blob": raw bytes returned as-is
-///     - "commit": parse as Commit struct
-///     - others: return type and raw bytes unchanged
-pub fn parse_object(data: &[u8]) -> Result<ParsedObject> {
+/// Meant for paths that read the same trees and commits over and over (tree walks,
+/// history walks). Commands checking the object files themselves, like `fsck`, must
+/// keep using `read_raw_object`.
+pub fn read_raw_object_cached(guts_dir: &Path, sha: &str) -> Result<Arc<Vec<u8>>> {
+    let object_path = get_object_path(guts_dir, sha);
+    if let Some(data) = object_cache().lock().unwrap_or_else(|e| e.into_inner()).get(&object_path) {
+        return Ok(data);
+    }
+
+    let data = Arc::new(read_raw_object(guts_dir, sha)?);
+    object_cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(object_path, Arc::clone(&data));
+    Ok(data)
+}
+
+/// Cached counterpart of `read_object`
+pub fn read_object_cached(guts_dir: &Path, sha: &str) -> Result<ParsedObject> {
+    parse_object(&read_raw_object_cached(guts_dir, sha)?)
+}
+
+/// Decompresses the zlib data of an object file.
+///
+/// Objects are always written compressed, so data that does not inflate completely
+/// (e.g. a truncated file) is reported as a corrupt object.
+pub fn decompress(sha: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let mut decoder = flate2::read::ZlibDecoder::new(data);
+    let mut decompressed = Vec::new();
+    decoder
+        .read_to_end(&mut decompressed)
+        .map_err(|e| anyhow!("object {} is corrupt: {}", sha, e))?;
+    Ok(decompressed)
+}
+
+/// Header of a Git object: `"<type> <size>\0"`
+pub struct ObjectHeader {
+    /// Object type, e.g. "blob", "tree" or "commit"
+    pub obj_type: String,
+    /// Content size declared in the header, in bytes
+    pub size: usize,
+    /// Offset of the first content byte, right after the null separator
+    pub body_start: usize,
+}
+
+/// Parses only the header of raw (decompressed) object data,
+/// without looking at the content. The declared size must match the content length.
+pub fn parse_header(data: &[u8]) -> Result<ObjectHeader> {
     // Find the position of the null byte separating header from body
     let null_pos = data
         .iter()
@@ -48,8 +269,6 @@ pub fn parse_object(data: &[u8]) -> Result<ParsedObject> {
 
     // Interpret header bytes as UTF-8 string
     let header = std::str::from_utf8(&data[..null_pos])?;
-    // The remainder after the null byte is the body/content
-    let body = &data[null_pos + 1..];
 
     // Header format: "<type> <size>"
     let mut parts = header.split(' ');
@@ -59,7 +278,42 @@ pub fn parse_object(data: &[u8]) -> Result<ParsedObject> {
     let size_str = parts
         .next()
         .ok_or_else(|| anyhow!("Invalid header format"))?;
-    let _size: usize = size_str.parse()?; // Size parsed but not strictly enforced here
+    let size: usize = size_str.parse()?;
+
+    let actual_size = data.len() - (null_pos + 1);
+    if actual_size != size {
+        return Err(anyhow!(
+            "object is corrupt: header declares {} bytes but the content has {}",
+            size,
+            actual_size
+        ));
+    }
+
+    Ok(ObjectHeader {
+        obj_type: obj_type.to_string(),
+        size,
+        body_start: null_pos + 1,
+    })
+}
+
+/// Parses raw Git object data into a structured `ParsedObject`.
+///
+/// Git object format:
+///   "<type> <size>\0<content>"
+///
+/// Steps:
+/// - Parse the header with `parse_header` (which verifies the size).
+/// - Based on type, parse the body:
+///     - "tree": parse as list of TreeEntry structs
+///     - "blob": raw bytes returned as-is
+///     - "commit": parse as Commit struct
+///     - "tag": parse as Tag struct
+///     - others: return type and raw bytes unchanged
+pub fn parse_object(data: &[u8]) -> Result<ParsedObject> {
+    let header = parse_header(data)?;
+    // The remainder after the null byte is the body/content
+    let body = &data[header.body_start..];
+    let obj_type = header.obj_type.as_str();
 
     // Dispatch parsing based on object type
     match obj_type {
@@ -77,6 +331,7 @@ pub fn parse_object(data: &[u8]) -> Result<ParsedObject> {
             let commit = parse_commit_body(body)?;
             Ok(ParsedObject::Commit(commit))
         }
+        "tag" => Ok(ParsedObject::Tag(parse_tag_body(body)?)),
         _ => {
             // Unknown or unsupported object type: keep raw data and type
             Ok(ParsedObject::Other(obj_type.to_string(), body.to_vec()))
@@ -134,7 +389,9 @@ pub fn parse_tree_body(data: &[u8]) -> Result<Vec<TreeEntry>> {
 ///
 /// Commit body format is plaintext with lines:
 ///   tree <tree SHA>
-///   parent <parent SHA>  (optional)
+///   parent <parent SHA>  (zero or more)
+///   author <name> <<email>> <timestamp> <timezone>
+///   committer <name> <<email>> <timestamp> <timezone>
 ///   <empty line>
 ///   <commit message>
 ///
@@ -143,56 +400,21 @@ fn parse_commit_body(body: &[u8]) -> Result<Commit> {
     let text = std::str::from_utf8(body)?;
     let mut tree = String::new();
     let mut parent = Vec::new();
-    let mut message = String::new();
-    let mut author = String::new();
-    let mut committer = String::new();
-    let mut author_date = 0i64;
-    let mut committer_date = 0i64;
-    let mut in_message = false;
-
-    for line in text.lines() {
-        if line.trim().is_empty() {
-            // Empty line marks start of commit message
-            in_message = true;
-            continue;
-        }
+    let mut author = None;
+    let mut committer = None;
 
-        if in_message {
-            // Accumulate commit message lines
-            message.push_str(line);
-            message.push('\n');
-            continue;
-        }
+    // Headers end at the first empty line, the message is everything after it
+    let (headers, message) = text.split_once("\n\n").unwrap_or((text, ""));
 
-        // Parse header lines
+    for line in headers.lines() {
         if let Some(rest) = line.strip_prefix("tree ") {
             tree = rest.to_string();
         } else if let Some(rest) = line.strip_prefix("parent ") {
             parent.push(rest.to_string());
         } else if let Some(rest) = line.strip_prefix("author ") {
-            // Format: "Name <email> timestamp timezone"
-            let parts: Vec<&str> = rest.rsplitn(2, ' ').collect();
-            if parts.len() == 2 {
-                // parts[0] is timezone, parts[1] is "Name <email> timestamp"
-                let name_email_timestamp = parts[1];
-                let timestamp_parts: Vec<&str> = name_email_timestamp.rsplitn(2, ' ').collect();
-                if timestamp_parts.len() == 2 {
-                    author_date = timestamp_parts[0].parse().unwrap_or(0);
-                    author = timestamp_parts[1].to_string();
-                }
-            }
+            author = Some(parse_signature(rest)?);
         } else if let Some(rest) = line.strip_prefix("committer ") {
-            // Format: "Name <email> timestamp timezone"
-            let parts: Vec<&str> = rest.rsplitn(2, ' ').collect();
-            if parts.len() == 2 {
-                // parts[0] is timezone, parts[1] is "Name <email> timestamp"
-                let name_email_timestamp = parts[1];
-                let timestamp_parts: Vec<&str> = name_email_timestamp.rsplitn(2, ' ').collect();
-                if timestamp_parts.len() == 2 {
-                    committer_date = timestamp_parts[0].parse().unwrap_or(0);
-                    committer = timestamp_parts[1].to_string();
-                }
-            }
+            committer = Some(parse_signature(rest)?);
         }
     }
 
@@ -202,13 +424,189 @@ fn parse_commit_body(body: &[u8]) -> Result<Commit> {
         return Err(anyhow!("commit object missing 'tree' field"));
     }
 
+    let unknown = || Signature {
+        identity: "Unknown <unknown@example.com>".to_string(),
+        timestamp: 0,
+        timezone: "+0000".to_string(),
+    };
+    let author = author.unwrap_or_else(unknown);
+    let committer = committer.unwrap_or_else(unknown);
+
     Ok(Commit {
         tree,
         parent,
         message: message.trim_end().to_string(),
-        author: if author.is_empty() { "Unknown <unknown@example.com>".to_string() } else { author },
-        committer: if committer.is_empty() { "Unknown <unknown@example.com>".to_string() } else { committer },
-        author_date,
-        committer_date,
+        author: author.identity,
+        committer: committer.identity,
+        author_date: author.timestamp,
+        committer_date: committer.timestamp,
+        author_timezone: author.timezone,
+        committer_timezone: committer.timezone,
+    })
+}
+
+/// Parses the body of an annotated tag: `object`, `type`, `tag` and `tagger` header
+/// lines, a blank line, then the message
+fn parse_tag_body(body: &[u8]) -> Result<Tag> {
+    let text = std::str::from_utf8(body).context("tag object is not valid UTF-8")?;
+    let (headers, message) = text.split_once("\n\n").unwrap_or((text, ""));
+
+    let mut object = None;
+    let mut target_type = None;
+    let mut name = None;
+    let mut tagger = None;
+    for line in headers.lines() {
+        match line.split_once(' ') {
+            Some(("object", value)) => object = Some(value.to_string()),
+            Some(("type", value)) => target_type = Some(value.to_string()),
+            Some(("tag", value)) => name = Some(value.to_string()),
+            Some(("tagger", value)) => tagger = Some(parse_signature(value)?),
+            _ => {}
+        }
+    }
+
+    let tagger = tagger.ok_or_else(|| anyhow!("tag object has no tagger line"))?;
+    Ok(Tag {
+        object: object.ok_or_else(|| anyhow!("tag object has no object line"))?,
+        target_type: target_type.ok_or_else(|| anyhow!("tag object has no type line"))?,
+        name: name.ok_or_else(|| anyhow!("tag object has no tag line"))?,
+        tagger: tagger.identity,
+        tagger_date: tagger.timestamp,
+        tagger_timezone: tagger.timezone,
+        message: message.to_string(),
+    })
+}
+
+/// Author or committer line of a commit: "Name <email> timestamp timezone"
+struct Signature {
+    identity: String,
+    timestamp: i64,
+    timezone: String,
+}
+
+/// Parses the part of an author/committer line after the keyword
+fn parse_signature(line: &str) -> Result<Signature> {
+    let invalid = || anyhow!("invalid signature line: {}", line);
+
+    // The identity may contain spaces, so split from the right
+    let mut parts = line.rsplitn(3, ' ');
+    let timezone = parts.next().ok_or_else(invalid)?;
+    let timestamp = parts.next().ok_or_else(invalid)?;
+    let identity = parts.next().ok_or_else(invalid)?;
+
+    Ok(Signature {
+        identity: identity.to_string(),
+        timestamp: timestamp.parse().map_err(|_| invalid())?,
+        timezone: timezone.to_string(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+    use std::time::Instant;
+
+    /// Writes `content` as a loose object of type `kind` and returns its SHA
+    fn write_loose(git_dir: &Path, kind: &str, content: &[u8]) -> String {
+        use sha1::{Digest, Sha1};
+        let mut raw = format!("{} {}\0", kind, content.len()).into_bytes();
+        raw.extend_from_slice(content);
+        let sha = hex::encode(Sha1::digest(&raw));
+
+        let path = get_object_path(git_dir, &sha);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&raw).unwrap();
+        fs::write(path, encoder.finish().unwrap()).unwrap();
+        sha
+    }
+
+    /// Tree `depth` levels deep, each level holding one file and the next level
+    fn deep_tree(git_dir: &Path, depth: usize) -> String {
+        let blob = write_loose(git_dir, "blob", b"leaf\n");
+        let mut tree: Option<String> = None;
+        for level in 0..depth {
+            let mut content = format!("100644 file{}\0", level).into_bytes();
+            content.extend_from_slice(&hex::decode(&blob).unwrap());
+            if let Some(sub) = &tree {
+                content.extend_from_slice(b"40000 sub\0");
+                content.extend_from_slice(&hex::decode(sub).unwrap());
+            }
+            tree = Some(write_loose(git_dir, "tree", &content));
+        }
+        tree.unwrap()
+    }
+
+    #[test]
+    fn cached_read_returns_the_same_bytes() {
+        let temp = tempfile::tempdir().unwrap();
+        let sha = write_loose(temp.path(), "blob", b"cached content\n");
+
+        let uncached = read_raw_object(temp.path(), &sha).unwrap();
+        let first = read_raw_object_cached(temp.path(), &sha).unwrap();
+        assert_eq!(*first, uncached);
+        // Second read comes from the cache, without a copy
+        let second = read_raw_object_cached(temp.path(), &sha).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn cache_evicts_the_least_recently_used_entry() {
+        let mut cache = ObjectCache {
+            entries: HashMap::new(),
+            recent: BTreeMap::new(),
+            clock: 0,
+        };
+        for i in 0..CACHE_CAPACITY {
+            cache.insert(PathBuf::from(i.to_string()), Arc::new(Vec::new()));
+        }
+
+        // Using the oldest entry makes the second one the oldest
+        assert!(cache.get(Path::new("0")).is_some());
+        cache.insert(PathBuf::from("new"), Arc::new(Vec::new()));
+
+        assert_eq!(cache.entries.len(), CACHE_CAPACITY);
+        assert_eq!(cache.recent.len(), CACHE_CAPACITY);
+        assert!(cache.get(Path::new("0")).is_some());
+        assert!(cache.get(Path::new("1")).is_none());
+        assert!(cache.get(Path::new("new")).is_some());
+    }
+
+    #[test]
+    fn cache_is_per_repository() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        let sha = write_loose(first.path(), "blob", b"only in the first repository\n");
+
+        read_raw_object_cached(first.path(), &sha).unwrap();
+        assert!(read_raw_object_cached(second.path(), &sha).is_err());
+    }
+
+    /// Micro-benchmark: `cargo test --release cached_tree_walk -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn cached_tree_walk_benchmark() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = deep_tree(temp.path(), 200);
+
+        let walk = |read: fn(&Path, &str) -> Result<ParsedObject>| {
+            let start = Instant::now();
+            for _ in 0..20 {
+                let mut sha = root.clone();
+                while let ParsedObject::Tree(entries) = read(temp.path(), &sha).unwrap() {
+                    match entries.iter().find(|entry| entry.mode == "40000") {
+                        Some(sub) => sha = hex::encode(sub.hash),
+                        None => break,
+                    }
+                }
+            }
+            start.elapsed()
+        };
+
+        let uncached = walk(read_object);
+        let cached = walk(read_object_cached);
+        println!("deep tree walk: uncached {:?}, cached {:?}", uncached, cached);
+    }
+}
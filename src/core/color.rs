@@ -0,0 +1,39 @@
+use clap::ValueEnum;
+use std::io::IsTerminal;
+
+/// ANSI code of the color used for staged changes
+pub const GREEN: &str = "32";
+/// ANSI code of the color used for unstaged and untracked changes
+pub const RED: &str = "31";
+/// ANSI code of the color used for commit SHAs
+pub const YELLOW: &str = "33";
+
+/// When to colorize output, as given to `--color`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorWhen {
+    /// Only when stdout is a terminal
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorWhen {
+    /// Whether escape codes should be written
+    pub fn enabled(self) -> bool {
+        match self {
+            ColorWhen::Auto => std::io::stdout().is_terminal(),
+            ColorWhen::Always => true,
+            ColorWhen::Never => false,
+        }
+    }
+}
+
+/// Wraps `text` in the escape codes of `color` when `enabled`, returns it as is otherwise
+pub fn paint(text: &str, color: &str, enabled: bool) -> String {
+    if enabled {
+        format!("\x1b[{}m{}\x1b[m", color, text)
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,275 @@
+use crate::core::{repo, simple_index};
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Identity used when no `user.name` is configured
+pub const DEFAULT_USER_NAME: &str = "guts";
+/// Identity used when no `user.email` is configured
+pub const DEFAULT_USER_EMAIL: &str = "guts@example.com";
+
+/// One `[section]` or `[section "subsection"]` block of a config file
+#[derive(Debug, Clone)]
+struct Section {
+    /// Lowercased section name, with the subsection appended: `user`, `remote.origin`
+    name: String,
+    /// Key/value pairs in file order, keys lowercased
+    entries: Vec<(String, String)>,
+}
+
+/// A parsed git-style INI config file (`.git/config`, `~/.gutsconfig`)
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    sections: Vec<Section>,
+}
+
+impl Config {
+    /// Parses the content of a config file.
+    ///
+    /// Supports `[section]` and `[section "subsection"]` headers, `key = value` lines,
+    /// `#`/`;` comments and double-quoted values. Anything else is ignored.
+    pub fn parse(content: &str) -> Self {
+        let mut sections: Vec<Section> = Vec::new();
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let name = match header.split_once(' ') {
+                    Some((section, subsection)) => format!(
+                        "{}.{}",
+                        section.trim().to_lowercase(),
+                        subsection.trim().trim_matches('"')
+                    ),
+                    None => header.trim().to_lowercase(),
+                };
+                sections.push(Section { name, entries: Vec::new() });
+                continue;
+            }
+
+            // Key/value lines before any section header are ignored
+            let Some(section) = sections.last_mut() else {
+                continue;
+            };
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), unquote(value.trim())),
+                // A bare key is a boolean set to true
+                None => (line, "true".to_string()),
+            };
+            section.entries.push((key.to_lowercase(), value));
+        }
+
+        Config { sections }
+    }
+
+    /// Loads a config file, an absent file being an empty config
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        Ok(Config::parse(&content))
+    }
+
+    /// Looks up a dotted key such as `user.name` or `remote.origin.url`.
+    /// When a key is set several times the last value wins, like git.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let (section, name) = split_key(key)?;
+        self.sections
+            .iter()
+            .filter(|s| s.name == section)
+            .flat_map(|s| s.entries.iter())
+            .filter(|(k, _)| *k == name)
+            .map(|(_, v)| v.as_str())
+            .next_back()
+    }
+
+    /// Sets a dotted key, replacing the last existing value or appending it
+    /// to its section (created at the end of the file if needed)
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let (section, name) =
+            split_key(key).ok_or_else(|| anyhow!("error: key does not contain a section: {}", key))?;
+
+        if let Some(entry) = self
+            .sections
+            .iter_mut()
+            .filter(|s| s.name == section)
+            .flat_map(|s| s.entries.iter_mut())
+            .rfind(|(k, _)| *k == name)
+        {
+            entry.1 = value.to_string();
+            return Ok(());
+        }
+
+        match self.sections.iter_mut().rfind(|s| s.name == section) {
+            Some(existing) => existing.entries.push((name, value.to_string())),
+            None => self.sections.push(Section {
+                name: section,
+                entries: vec![(name, value.to_string())],
+            }),
+        }
+        Ok(())
+    }
+
+    /// Names of the `[section "<subsection>"]` blocks of a section, in file order,
+    /// e.g. the remote names for `remote`
+    pub fn subsections(&self, section: &str) -> Vec<String> {
+        let prefix = format!("{}.", section.to_lowercase());
+        let mut names: Vec<String> = Vec::new();
+        for s in &self.sections {
+            if let Some(name) = s.name.strip_prefix(&prefix) {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names
+    }
+
+    /// Removes every block of a section (`remote.origin`); false if there was none
+    pub fn remove_section(&mut self, section: &str) -> bool {
+        let name = match section.split_once('.') {
+            Some((section, subsection)) => format!("{}.{}", section.to_lowercase(), subsection),
+            None => section.to_lowercase(),
+        };
+        let before = self.sections.len();
+        self.sections.retain(|s| s.name != name);
+        self.sections.len() != before
+    }
+
+    /// All key/value pairs in file order, keys in dotted form
+    pub fn entries(&self) -> Vec<(String, String)> {
+        self.sections
+            .iter()
+            .flat_map(|s| {
+                s.entries
+                    .iter()
+                    .map(move |(k, v)| (format!("{}.{}", s.name, k), v.clone()))
+            })
+            .collect()
+    }
+
+    /// Renders the config back to git's INI format
+    pub fn serialize(&self) -> String {
+        let mut output = String::new();
+        for section in &self.sections {
+            match section.name.split_once('.') {
+                Some((name, subsection)) => {
+                    output.push_str(&format!("[{} \"{}\"]\n", name, subsection))
+                }
+                None => output.push_str(&format!("[{}]\n", section.name)),
+            }
+            for (key, value) in &section.entries {
+                output.push_str(&format!("\t{} = {}\n", key, quote(value)));
+            }
+        }
+        output
+    }
+
+    /// Writes the config to `path`
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, self.serialize())
+            .with_context(|| format!("failed to write config file {}", path.display()))
+    }
+}
+
+/// Splits `section[.subsection].name` into the section part and the lowercased name
+fn split_key(key: &str) -> Option<(String, String)> {
+    let (section, name) = key.rsplit_once('.')?;
+    let section = match section.split_once('.') {
+        Some((section, subsection)) => format!("{}.{}", section.to_lowercase(), subsection),
+        None => section.to_lowercase(),
+    };
+    Some((section, name.to_lowercase()))
+}
+
+/// Strips surrounding double quotes from a value and unescapes `\"` and `\\`
+fn unquote(value: &str) -> String {
+    let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+        return value.to_string();
+    };
+
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.extend(chars.next()),
+            _ => unquoted.push(c),
+        }
+    }
+    unquoted
+}
+
+/// Quotes a value when it would not survive a round trip unquoted
+fn quote(value: &str) -> String {
+    if value.trim() != value || value.contains(['#', ';', '"']) {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Path of the user-wide config file, `~/.gutsconfig`
+pub fn global_config_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".gutsconfig"))
+}
+
+/// Path of the config file of the current repository, `.git/config`
+pub fn repo_config_path() -> Result<PathBuf> {
+    Ok(simple_index::find_repo_root()?.join(".git").join("config"))
+}
+
+/// Reads a key from the repository config, falling back to `~/.gutsconfig`
+pub fn get(key: &str) -> Result<Option<String>> {
+    if let Ok(path) = repo_config_path() {
+        if let Some(value) = Config::load(&path)?.get(key) {
+            return Ok(Some(value.to_string()));
+        }
+    }
+
+    if let Some(path) = global_config_path() {
+        if let Some(value) = Config::load(&path)?.get(key) {
+            return Ok(Some(value.to_string()));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Configured `user.name`, or the default identity
+pub fn user_name() -> Result<String> {
+    Ok(get("user.name")?.unwrap_or_else(|| DEFAULT_USER_NAME.to_string()))
+}
+
+/// Configured `user.email`, or the default identity
+pub fn user_email() -> Result<String> {
+    Ok(get("user.email")?.unwrap_or_else(|| DEFAULT_USER_EMAIL.to_string()))
+}
+
+/// Branch HEAD points to in a new repository: `init.defaultBranch`, or `main`
+pub fn default_branch() -> Result<String> {
+    Ok(get("init.defaultBranch")?.unwrap_or_else(|| repo::DEFAULT_BRANCH.to_string()))
+}
+
+/// Identity as written in commit objects: `Name <email>`
+pub fn user_identity() -> Result<String> {
+    Ok(format!("{} <{}>", user_name()?, user_email()?))
+}
+
+/// Checks an identity given on the command line is `Name <email>`, returns it normalized
+pub fn parse_identity(identity: &str) -> Result<String> {
+    let malformed = || anyhow!("fatal: --author '{}' is not 'Name <email>'", identity);
+
+    let (name, rest) = identity.split_once('<').ok_or_else(malformed)?;
+    let email = rest.trim_end().strip_suffix('>').ok_or_else(malformed)?;
+    let name = name.trim();
+    if name.is_empty() || email.trim().is_empty() || email.contains(['<', '>']) || name.contains('>') {
+        return Err(malformed());
+    }
+
+    Ok(format!("{} <{}>", name, email.trim()))
+}
@@ -0,0 +1,176 @@
+/// Number of unchanged lines shown around each change, like `git diff`
+const CONTEXT_LINES: usize = 3;
+
+/// A single line of a line-based diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    /// Line present in both versions
+    Context(&'a str),
+    /// Line only present in the old version
+    Removed(&'a str),
+    /// Line only present in the new version
+    Added(&'a str),
+}
+
+/// Computes a line diff between `old` and `new` using a longest common subsequence.
+///
+/// The common prefix and suffix are trimmed first so that the quadratic LCS table
+/// only covers the region that actually changed.
+pub fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffLine<'a>> {
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    // lcs[i][j] = length of the LCS of old_mid[i..] and new_mid[j..]
+    let mut lcs = vec![vec![0u32; new_mid.len() + 1]; old_mid.len() + 1];
+    for i in (0..old_mid.len()).rev() {
+        for j in (0..new_mid.len()).rev() {
+            lcs[i][j] = if old_mid[i] == new_mid[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::with_capacity(old.len().max(new.len()));
+    lines.extend(old[..prefix].iter().map(|l| DiffLine::Context(l)));
+
+    let (mut i, mut j) = (0, 0);
+    while i < old_mid.len() && j < new_mid.len() {
+        if old_mid[i] == new_mid[j] {
+            lines.push(DiffLine::Context(old_mid[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            lines.push(DiffLine::Removed(old_mid[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::Added(new_mid[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old_mid[i..].iter().map(|l| DiffLine::Removed(l)));
+    lines.extend(new_mid[j..].iter().map(|l| DiffLine::Added(l)));
+
+    lines.extend(old[old.len() - suffix..].iter().map(|l| DiffLine::Context(l)));
+    lines
+}
+
+/// Renders the `@@` hunks of a unified diff between two texts.
+/// Returns an empty string when both texts are identical.
+pub fn unified_hunks(old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let lines = diff_lines(&old_lines, &new_lines);
+
+    // Indices of changed lines, used to group them into hunks
+    let changes: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| !matches!(l, DiffLine::Context(_)))
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut output = String::new();
+    let mut idx = 0;
+
+    while idx < changes.len() {
+        let start = changes[idx].saturating_sub(CONTEXT_LINES);
+        let mut end = changes[idx];
+
+        // Merge following changes whose context would overlap this hunk
+        while idx + 1 < changes.len() && changes[idx + 1] <= end + 2 * CONTEXT_LINES + 1 {
+            idx += 1;
+            end = changes[idx];
+        }
+        let end = (end + CONTEXT_LINES + 1).min(lines.len());
+        idx += 1;
+
+        // Line numbers (1-based) of the hunk start in each version
+        let old_start = 1 + lines[..start]
+            .iter()
+            .filter(|l| !matches!(l, DiffLine::Added(_)))
+            .count();
+        let new_start = 1 + lines[..start]
+            .iter()
+            .filter(|l| !matches!(l, DiffLine::Removed(_)))
+            .count();
+
+        let hunk = &lines[start..end];
+        let old_count = hunk.iter().filter(|l| !matches!(l, DiffLine::Added(_))).count();
+        let new_count = hunk.iter().filter(|l| !matches!(l, DiffLine::Removed(_))).count();
+
+        output.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_count),
+            hunk_range(new_start, new_count)
+        ));
+        for line in hunk {
+            match line {
+                DiffLine::Context(l) => output.push_str(&format!(" {}\n", l)),
+                DiffLine::Removed(l) => output.push_str(&format!("-{}\n", l)),
+                DiffLine::Added(l) => output.push_str(&format!("+{}\n", l)),
+            }
+        }
+    }
+
+    output
+}
+
+/// Formats a hunk range the way git does: an empty side starts one line earlier
+fn hunk_range(start: usize, count: usize) -> String {
+    if count == 0 {
+        format!("{},0", start - 1)
+    } else if count == 1 {
+        start.to_string()
+    } else {
+        format!("{},{}", start, count)
+    }
+}
+
+/// Renders a complete git-style diff for one file.
+///
+/// `old` is `None` when the file is newly added and `new` is `None` when it was deleted;
+/// `mode` is the tree mode of that added or deleted file (e.g. `100755`).
+/// Contents that are not valid UTF-8 are reported as binary instead of being printed.
+pub fn diff_file(path: &str, old: Option<&[u8]>, new: Option<&[u8]>, mode: &str) -> String {
+    let mut output = format!("diff --git a/{} b/{}\n", path, path);
+    if old.is_none() {
+        output.push_str(&format!("new file mode {}\n", mode));
+    } else if new.is_none() {
+        output.push_str(&format!("deleted file mode {}\n", mode));
+    }
+
+    let old_label = if old.is_some() { format!("a/{}", path) } else { "/dev/null".to_string() };
+    let new_label = if new.is_some() { format!("b/{}", path) } else { "/dev/null".to_string() };
+
+    let old_text = old.map(std::str::from_utf8).transpose();
+    let new_text = new.map(std::str::from_utf8).transpose();
+
+    match (old_text, new_text) {
+        (Ok(old_text), Ok(new_text)) => {
+            output.push_str(&format!("--- {}\n+++ {}\n", old_label, new_label));
+            output.push_str(&unified_hunks(
+                old_text.unwrap_or(""),
+                new_text.unwrap_or(""),
+            ));
+        }
+        _ => {
+            output.push_str(&format!("Binary files {} and {} differ\n", old_label, new_label));
+        }
+    }
+
+    output
+}
@@ -1,12 +1,42 @@
-use std::fs;
-use std::path::PathBuf;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use anyhow::{Context, Result};
 use flate2::write::ZlibEncoder;
 use flate2::Compression;
 use sha1::{Digest, Sha1};
 
+use crate::core::attributes::CrlfToLf;
 use crate::core::object::GitObject;
 
+/// Files larger than this are hashed and compressed in chunks instead of being read at once
+pub const STREAMING_THRESHOLD: u64 = 8 * 1024 * 1024;
+
+/// Size of the chunks read when streaming a file
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Keeps temporary object names unique when several files are streamed at once
+static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Minimum length of an abbreviated object name
+pub const MIN_SHA_PREFIX_LEN: usize = 4;
+
+/// True for a full object name: 40 hexadecimal digits
+pub fn is_valid_sha(sha: &str) -> bool {
+    sha.len() == 40 && sha.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Checks an object name typed by the user before anything is looked up: a full SHA or
+/// an abbreviation of at least 4 hexadecimal digits. Returns it lowercased.
+pub fn normalize_sha(input: &str) -> Result<String> {
+    let sha = input.trim();
+    if sha.len() < MIN_SHA_PREFIX_LEN || sha.len() > 40 || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!("fatal: not a valid object name: {}", input);
+    }
+    Ok(sha.to_ascii_lowercase())
+}
+
 pub fn write_object(obj: &impl GitObject) -> Result<String> {
     // 1. Serialize the object (with header + content)
     let serialized = obj.serialize();
@@ -15,7 +45,7 @@ pub fn write_object(obj: &impl GitObject) -> Result<String> {
     let mut hasher = Sha1::new();
     hasher.update(&serialized);
     let hash = hasher.finalize();
-    let hex = hex::encode(&hash);
+    let hex = hex::encode(hash);
 
     // 3. Prepare storage path .git/objects/xx/yyyy...
     let (dir_name, file_name) = hex.split_at(2);
@@ -42,6 +72,103 @@ pub fn write_object(obj: &impl GitObject) -> Result<String> {
     Ok(hex)
 }
 
+/// Writes a file as a blob object without loading it fully into memory.
+/// The file is fed through the SHA-1 hasher and the zlib encoder chunk by chunk into a
+/// temporary object file, which is renamed once its hash is known.
+///
+/// With `crlf_to_lf` the line endings are converted like `Attributes::to_blob` does.
+/// The file is then read twice: the size in the header comes before the content.
+pub fn write_blob_streaming(file_path: &Path, crlf_to_lf: bool) -> Result<String> {
+    let size = if crlf_to_lf {
+        let mut size = 0u64;
+        for_each_chunk(file_path, true, |chunk| {
+            size += chunk.len() as u64;
+            Ok(())
+        })?;
+        size
+    } else {
+        fs::metadata(file_path)
+            .with_context(|| format!("failed to open file {:?}", file_path))?
+            .len()
+    };
+    let header = format!("blob {}\0", size);
+
+    let objects_dir = PathBuf::from(".git/objects");
+    fs::create_dir_all(&objects_dir).with_context(|| "failed to create object directory")?;
+    let tmp_path = objects_dir.join(format!(
+        "tmp_obj_{}_{}",
+        std::process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = (|| -> Result<String> {
+        let tmp_file = File::create(&tmp_path)
+            .with_context(|| format!("failed to create {:?}", tmp_path))?;
+        let mut encoder = ZlibEncoder::new(tmp_file, Compression::default());
+        let mut hasher = Sha1::new();
+
+        hasher.update(header.as_bytes());
+        encoder.write_all(header.as_bytes())?;
+
+        let mut written = 0u64;
+        for_each_chunk(file_path, crlf_to_lf, |chunk| {
+            hasher.update(chunk);
+            encoder.write_all(chunk)?;
+            written += chunk.len() as u64;
+            Ok(())
+        })?;
+        if written != size {
+            anyhow::bail!("file {:?} changed while it was being hashed", file_path);
+        }
+        encoder.finish()?.sync_all()?;
+
+        let hex = hex::encode(hasher.finalize());
+        let (dir_name, file_name) = hex.split_at(2);
+        let path = objects_dir.join(dir_name).join(file_name);
+        if !path.exists() {
+            fs::create_dir_all(objects_dir.join(dir_name))
+                .with_context(|| "failed to create object directory")?;
+            fs::rename(&tmp_path, &path)
+                .with_context(|| format!("failed to write object to {:?}", path))?;
+        }
+        Ok(hex)
+    })();
+
+    // Left over when the object already existed or something failed
+    let _ = fs::remove_file(&tmp_path);
+    result
+}
+
+/// Reads a file chunk by chunk, with its CRLF line endings turned into LF when asked
+fn for_each_chunk(file_path: &Path, crlf_to_lf: bool, mut f: impl FnMut(&[u8]) -> Result<()>) -> Result<()> {
+    let mut file = File::open(file_path)
+        .with_context(|| format!("failed to open file {:?}", file_path))?;
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut converter = CrlfToLf::default();
+    let mut converted = Vec::with_capacity(CHUNK_SIZE);
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("failed to read file {:?}", file_path))?;
+        if read == 0 {
+            break;
+        }
+        if crlf_to_lf {
+            converted.clear();
+            converter.convert(&buffer[..read], &mut converted);
+            f(&converted)?;
+        } else {
+            f(&buffer[..read])?;
+        }
+    }
+    if crlf_to_lf {
+        converted.clear();
+        converter.finish(&mut converted);
+        f(&converted)?;
+    }
+    Ok(())
+}
+
 /// Computes the SHA-1 hash of a blob with Git-style header.
 /// This is used to compare working directory files to their index versions.
 pub fn hash_blob(data: &[u8]) -> Result<String> {
@@ -54,3 +181,38 @@ pub fn hash_blob(data: &[u8]) -> Result<String> {
     let result = hasher.finalize();
     Ok(hex::encode(result))
 }
+
+/// Computes the SHA-1 of any object (header included) without writing it
+pub fn hash_object(obj: &impl GitObject) -> String {
+    hex::encode(Sha1::digest(obj.serialize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_sha_rejects_empty_short_and_non_hex_input() {
+        for input in ["", "abc", "zzzz", "0123456789abcdef0123456789abcdef012345678", "12 34"] {
+            let err = normalize_sha(input).unwrap_err();
+            assert_eq!(err.to_string(), format!("fatal: not a valid object name: {}", input));
+        }
+    }
+
+    #[test]
+    fn normalize_sha_accepts_prefixes_and_full_names() {
+        assert_eq!(normalize_sha("abcd").unwrap(), "abcd");
+        assert_eq!(
+            normalize_sha(" 0123456789ABCDEF0123456789abcdef01234567\n").unwrap(),
+            "0123456789abcdef0123456789abcdef01234567"
+        );
+    }
+
+    #[test]
+    fn is_valid_sha_only_accepts_full_names() {
+        assert!(is_valid_sha("0123456789abcdef0123456789abcdef01234567"));
+        assert!(!is_valid_sha(""));
+        assert!(!is_valid_sha("0123456"));
+        assert!(!is_valid_sha("g123456789abcdef0123456789abcdef01234567"));
+    }
+}
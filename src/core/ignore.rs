@@ -1,13 +1,32 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
-
-use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::path::{Path, PathBuf};
+
+use globset::{GlobBuilder, GlobMatcher};
+use walkdir::WalkDir;
+
+/// The line of an ignore file that decided whether a path is ignored
+#[derive(Debug, Clone, PartialEq)]
+pub struct IgnoreRule {
+    /// Ignore file the line comes from, relative to the repo root
+    pub source: PathBuf,
+    /// 1-based line number in `source`
+    pub line_number: usize,
+    /// The pattern as written, with its `!` and trailing `/`
+    pub pattern: String,
+    /// A `!` pattern: the path is explicitly not ignored
+    pub is_negation: bool,
+}
 
-/// Pattern that can be negated (for !pattern support)
+/// One line of an ignore file, compiled
 struct IgnorePattern {
-    glob_set: GlobSet,
-    is_negation: bool,
+    glob: GlobMatcher,
+    rule: IgnoreRule,
+    /// Trailing `/`: only directories match
+    dir_only: bool,
+    /// Directory of the ignore file the pattern comes from, relative to the repo root.
+    /// The pattern only applies below it and is matched against paths relative to it.
+    base: PathBuf,
 }
 
 /// .gutsignore and .gitignore support
@@ -16,96 +35,103 @@ pub struct IgnoreMatcher {
 }
 
 impl IgnoreMatcher {
+    /// Loads every ignore file of the repository: `.git/info/exclude`, the one at the
+    /// root and those in subdirectories. In each directory `.gutsignore` wins over
+    /// `.gitignore`. Ignored directories are not entered: their ignore files could not
+    /// bring anything back.
     pub fn from_gutsignore(repo_root: &Path) -> std::io::Result<Self> {
-        let guts_ignore_path = repo_root.join(".gutsignore");
-        let git_ignore_path = repo_root.join(".gitignore");
-
-        if !guts_ignore_path.exists() && !git_ignore_path.exists() {
-            return Ok(Self::empty());
+        // Patterns local to this clone come first: every ignore file can override them
+        let mut matcher = IgnoreMatcher::empty();
+        let exclude_path = repo_root.join(".git").join("info").join("exclude");
+        if exclude_path.is_file() {
+            let file = File::open(exclude_path)?;
+            matcher.patterns.extend(parse_patterns(BufReader::new(file), Path::new(""), Path::new(".git/info/exclude"))?);
         }
 
-        let ignore_path = if guts_ignore_path.exists() {
-            guts_ignore_path
-        } else {
-            git_ignore_path
-        };
-
-        let file = File::open(ignore_path)?;
-        let reader = BufReader::new(file);
-
-        let mut patterns = Vec::new();
-
-        for line in reader.lines() {
-            let line = line?;
-            let trimmed = line.trim();
+        // A directory is visited before its subdirectories, so deeper files come later
+        // and can override their parents
+        let mut walker = WalkDir::new(repo_root).sort_by_file_name().into_iter();
+        while let Some(entry) = walker.next() {
+            let entry = entry.map_err(std::io::Error::other)?;
+            if !entry.file_type().is_dir() {
+                continue;
+            }
 
-            if trimmed.is_empty() || trimmed.starts_with('#') {
+            let base = entry
+                .path()
+                .strip_prefix(repo_root)
+                .unwrap_or(Path::new(""))
+                .to_path_buf();
+            let is_ignored = entry.depth() > 0
+                && (entry.file_name() == ".git" || matcher.matches(&base, true).is_some_and(|rule| !rule.is_negation));
+            if is_ignored {
+                walker.skip_current_dir();
                 continue;
             }
 
-            let (pattern, is_negation) = if trimmed.starts_with('!') {
-                (&trimmed[1..], true)
+            let guts_ignore_path = entry.path().join(".gutsignore");
+            let git_ignore_path = entry.path().join(".gitignore");
+            let ignore_path = if guts_ignore_path.exists() {
+                guts_ignore_path
+            } else if git_ignore_path.exists() {
+                git_ignore_path
             } else {
-                (trimmed, false)
+                continue;
             };
 
-            let mut builder = GlobSetBuilder::new();
-
-            // Handle directory patterns (ending with /)
-            if pattern.ends_with('/') {
-                let dir_pattern = format!("{}**", pattern);
-                let glob = Glob::new(&dir_pattern)
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-                builder.add(glob);
-            } else {
-                // Add the pattern as-is
-                let glob = Glob::new(pattern)
-                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-                builder.add(glob);
-
-                // Also add a directory version for patterns that might match directories
-                if !pattern.contains('/') || !pattern.contains('*') {
-                    let dir_pattern = format!("{}/", pattern);
-                    if let Ok(dir_glob) = Glob::new(&dir_pattern) {
-                        builder.add(dir_glob);
-                    }
-                }
-            }
-
-            let glob_set = builder
-                .build()
-                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
-
-            patterns.push(IgnorePattern {
-                glob_set,
-                is_negation,
-            });
+            let source = ignore_path.strip_prefix(repo_root).unwrap_or(&ignore_path).to_path_buf();
+            let file = File::open(&ignore_path)?;
+            matcher.patterns.extend(parse_patterns(BufReader::new(file), &base, &source)?);
         }
 
-        Ok(IgnoreMatcher { patterns })
+        Ok(matcher)
     }
 
+    /// Whether `path` is ignored. As in git, everything below an ignored directory is
+    /// ignored too, and a negated pattern cannot bring such a file back.
     pub fn is_ignored(&self, path: &Path, repo_root: &Path) -> bool {
+        self.matching_rule(path, repo_root).is_some_and(|rule| !rule.is_negation)
+    }
+
+    /// The rule deciding whether `path` is ignored: the one ignoring a parent directory,
+    /// else the last one matching the path, which may be a `!` pattern. `None` when no
+    /// pattern matches.
+    pub fn matching_rule(&self, path: &Path, repo_root: &Path) -> Option<&IgnoreRule> {
         // Convert to relative path from repo root
         let relative_path = match path.strip_prefix(repo_root) {
             Ok(rel) => rel,
             Err(_) => path,
         };
 
-        let mut ignored = false;
+        // Every parent of the path is a directory
+        let mut parent = PathBuf::new();
+        let components: Vec<_> = relative_path.components().collect();
+        for component in components.iter().take(components.len().saturating_sub(1)) {
+            parent.push(component);
+            if let Some(rule) = self.matches(&parent, true).filter(|rule| !rule.is_negation) {
+                return Some(rule);
+            }
+        }
+
+        let is_dir = std::fs::symlink_metadata(path).is_ok_and(|m| m.is_dir());
+        self.matches(relative_path, is_dir)
+    }
 
-        // Process patterns in order
+    /// Applies the patterns in order to one path: the last one matching decides
+    fn matches(&self, relative_path: &Path, is_dir: bool) -> Option<&IgnoreRule> {
+        let mut decision = None;
         for pattern in &self.patterns {
-            if pattern.glob_set.is_match(relative_path) {
-                if pattern.is_negation {
-                    ignored = false;
-                } else {
-                    ignored = true;
-                }
+            if pattern.dir_only && !is_dir {
+                continue;
+            }
+            let Ok(scoped_path) = relative_path.strip_prefix(&pattern.base) else {
+                continue;
+            };
+            if pattern.glob.is_match(scoped_path) {
+                decision = Some(&pattern.rule);
             }
         }
-
-        ignored
+        decision
     }
 
     pub fn empty() -> Self {
@@ -114,3 +140,62 @@ impl IgnoreMatcher {
         }
     }
 }
+
+/// Parses the lines of one ignore file located in the `base` directory, `source` being
+/// the file itself.
+///
+/// Git's rules: a trailing `/` only matches directories; a pattern with a `/` at the
+/// start or in the middle is anchored to `base` (a leading `/` is dropped), otherwise
+/// it matches a name at any depth; `*` does not cross `/` while `**` does.
+fn parse_patterns(reader: impl BufRead, base: &Path, source: &Path) -> std::io::Result<Vec<IgnorePattern>> {
+    let mut patterns = Vec::new();
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        let (pattern, is_negation) = match trimmed.strip_prefix('!') {
+            Some(rest) => (rest, true),
+            None => (trimmed, false),
+        };
+        let (pattern, dir_only) = match pattern.strip_suffix('/') {
+            Some(rest) => (rest, true),
+            None => (pattern, false),
+        };
+        if pattern.is_empty() {
+            continue;
+        }
+        let glob = compile_pattern(pattern)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+
+        patterns.push(IgnorePattern {
+            glob,
+            rule: IgnoreRule {
+                source: source.to_path_buf(),
+                line_number: index + 1,
+                pattern: trimmed.to_string(),
+                is_negation,
+            },
+            dir_only,
+            base: base.to_path_buf(),
+        });
+    }
+
+    Ok(patterns)
+}
+
+/// Compiles a pattern matched against repo-relative paths: anchored when it holds a
+/// `/` (a leading one is dropped), otherwise matching a name at any depth
+pub(crate) fn compile_pattern(pattern: &str) -> Result<GlobMatcher, globset::Error> {
+    let glob = if pattern.contains('/') {
+        pattern.trim_start_matches('/').to_string()
+    } else {
+        format!("**/{}", pattern)
+    };
+
+    Ok(GlobBuilder::new(&glob).literal_separator(true).build()?.compile_matcher())
+}
@@ -0,0 +1,248 @@
+use crate::core::{build_tree, simple_index};
+use crate::core::cat::{self, ParsedObject};
+use crate::core::parse_tree::TreeEntry;
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Result of a 3-way tree merge
+#[derive(Debug, PartialEq)]
+pub enum MergeOutcome {
+    /// Every path merged cleanly; SHA of the written merged tree
+    Clean(String),
+    /// Paths changed differently on both sides, sorted. No tree is written.
+    Conflicts(Vec<String>),
+}
+
+/// Enumeration of possible merge decisions for a file
+/// This represents the outcome of comparing a file across the three trees
+#[derive(Debug, PartialEq)]
+pub enum MergeDecision {
+    /// Take the version from the current branch (HEAD)
+    TakeHead,
+    /// Take the version from the branch being merged
+    TakeOther,
+    /// There's a conflict that requires manual resolution
+    Conflict,
+}
+
+/// Decides what action to take for a file during merge based on 3-way comparison
+/// This implements the core Git merge logic for individual files
+///
+/// # Arguments
+/// * `base` - File entry from the merge base (common ancestor)
+/// * `head` - File entry from the current branch (HEAD)
+/// * `other` - File entry from the branch being merged
+///
+/// # Returns
+/// * `MergeDecision` - The decision for how to handle this file. Taking a side where
+///   the file is missing means the file is deleted.
+pub fn decide_merge_action(base: Option<&TreeEntry>, head: Option<&TreeEntry>, other: Option<&TreeEntry>) -> MergeDecision {
+    match (base, head, other) {
+        // Both branches have the same content - no conflict
+        (Some(_), Some(h), Some(o)) if h.sha == o.sha => MergeDecision::TakeHead,
+        // Current branch unchanged, other branch modified - take other
+        (Some(b), Some(h), Some(_)) if b.sha == h.sha => MergeDecision::TakeOther,
+        // Other branch unchanged, current branch modified - take head
+        (Some(b), Some(_), Some(o)) if b.sha == o.sha => MergeDecision::TakeHead,
+        // New file added in both branches with same content - no conflict
+        (None, Some(h), Some(o)) if h.sha == o.sha => MergeDecision::TakeHead,
+        // File added only in current branch - keep it
+        (None, Some(_), None) => MergeDecision::TakeHead,
+        // File added only in other branch - take it
+        (None, None, Some(_)) => MergeDecision::TakeOther,
+        // Deleted in other branch, unchanged in current branch - take the deletion
+        (Some(b), Some(h), None) if b.sha == h.sha => MergeDecision::TakeOther,
+        // Deleted in current branch, unchanged (or deleted) in other branch - keep the deletion
+        (Some(b), None, Some(o)) if b.sha == o.sha => MergeDecision::TakeHead,
+        (Some(_), None, None) => MergeDecision::TakeHead,
+        // All other cases are conflicts (different changes to same file, or modified and deleted)
+        _ => MergeDecision::Conflict,
+    }
+}
+
+/// Performs a 3-way merge of Git trees
+/// This is the core merge algorithm that combines changes from three tree states
+///
+/// # Arguments
+/// * `git_dir` - Path to the .git directory
+/// * `base` - SHA of the base tree (common ancestor)
+/// * `head` - SHA of the current branch's tree
+/// * `other` - SHA of the other branch's tree
+///
+/// # Returns
+/// * `Result<MergeOutcome>` - The merged tree SHA, or every conflicted path
+pub fn merge_trees(git_dir: &Path, base: &str, head: &str, other: &str) -> Result<MergeOutcome> {
+    // Load all three trees into flat maps for easier comparison
+    let base_entries = load_tree_map(git_dir, base)?;
+    let head_entries = load_tree_map(git_dir, head)?;
+    let other_entries = load_tree_map(git_dir, other)?;
+
+    // Merged files as `path -> blob SHA`, turned back into nested trees at the end
+    let mut merged_files: HashMap<String, String> = HashMap::new();
+    let mut merged_modes: HashMap<String, String> = HashMap::new();
+    let mut conflicts: Vec<String> = Vec::new();
+
+    // Collect all unique file paths from all three trees
+    let all_paths: HashSet<&PathBuf> = base_entries.keys()
+        .chain(head_entries.keys())
+        .chain(other_entries.keys())
+        .collect();
+
+    // Process each file path
+    for path in all_paths {
+        let base_entry = base_entries.get(path);
+        let head_entry = head_entries.get(path);
+        let other_entry = other_entries.get(path);
+        let key = path.to_string_lossy().to_string();
+
+        // Decide what to do with this file based on 3-way comparison
+        let taken = match decide_merge_action(base_entry, head_entry, other_entry) {
+            MergeDecision::TakeHead => head_entry,
+            MergeDecision::TakeOther => other_entry,
+            MergeDecision::Conflict => {
+                conflicts.push(key);
+                continue;
+            }
+        };
+        if let Some(entry) = taken {
+            if entry.mode != simple_index::MODE_FILE {
+                merged_modes.insert(key.clone(), entry.mode.clone());
+            }
+            merged_files.insert(key, entry.sha.clone());
+        }
+    }
+
+    if !conflicts.is_empty() {
+        conflicts.sort();
+        return Ok(MergeOutcome::Conflicts(conflicts));
+    }
+
+    // Create and write the new merged tree object
+    Ok(MergeOutcome::Clean(build_tree::write_tree_from_files(&merged_files, &merged_modes)?))
+}
+
+/// Loads a Git tree into a flat HashMap mapping file paths to tree entries
+/// This recursively traverses the tree structure and flattens it for easier processing
+///
+/// # Arguments
+/// * `git_dir` - Path to the .git directory
+/// * `sha` - SHA of the tree object to load
+///
+/// # Returns
+/// * `Result<HashMap<PathBuf, TreeEntry>>` - Map of file paths to tree entries
+pub fn load_tree_map(git_dir: &Path, sha: &str) -> Result<HashMap<PathBuf, TreeEntry>> {
+    let mut map = HashMap::new();
+    load_tree_map_recursive(git_dir, sha, PathBuf::new(), &mut map)?;
+    Ok(map)
+}
+
+/// Recursively loads tree entries into a flat map
+/// This handles the recursive nature of Git trees (directories contain subtrees)
+///
+/// # Arguments
+/// * `git_dir` - Path to the .git directory
+/// * `sha` - SHA of the current tree object
+/// * `prefix` - Current path prefix for nested directories
+/// * `map` - Mutable reference to the map being built
+///
+/// # Returns
+/// * `Result<()>` - Success or error
+fn load_tree_map_recursive(
+    git_dir: &Path,
+    sha: &str,
+    prefix: PathBuf,
+    map: &mut HashMap<PathBuf, TreeEntry>,
+) -> Result<()> {
+    let entries = match cat::read_object(git_dir, sha)? {
+        ParsedObject::Tree(entries) => entries,
+        _ => return Err(anyhow!("object {} is not a tree", sha)),
+    };
+
+    for entry in entries {
+        let full_path = prefix.join(&entry.name);
+        let sha = hex::encode(entry.hash);
+
+        if entry.mode == "40000" {
+            // Directory entry - recurse into subtree
+            load_tree_map_recursive(git_dir, &sha, full_path, map)?;
+        } else {
+            // File entry - add to map
+            map.insert(full_path, TreeEntry { mode: entry.mode, filename: entry.name, sha });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(sha: &str) -> TreeEntry {
+        TreeEntry {
+            mode: "100644".to_string(),
+            filename: "file.txt".to_string(),
+            sha: sha.to_string(),
+        }
+    }
+
+    #[test]
+    fn same_content_on_both_sides_takes_head() {
+        let (b, h, o) = (entry("b"), entry("x"), entry("x"));
+        assert_eq!(decide_merge_action(Some(&b), Some(&h), Some(&o)), MergeDecision::TakeHead);
+    }
+
+    #[test]
+    fn only_other_modified_takes_other() {
+        let (b, h, o) = (entry("b"), entry("b"), entry("o"));
+        assert_eq!(decide_merge_action(Some(&b), Some(&h), Some(&o)), MergeDecision::TakeOther);
+    }
+
+    #[test]
+    fn only_head_modified_takes_head() {
+        let (b, h, o) = (entry("b"), entry("h"), entry("b"));
+        assert_eq!(decide_merge_action(Some(&b), Some(&h), Some(&o)), MergeDecision::TakeHead);
+    }
+
+    #[test]
+    fn added_identically_on_both_sides_takes_head() {
+        let (h, o) = (entry("x"), entry("x"));
+        assert_eq!(decide_merge_action(None, Some(&h), Some(&o)), MergeDecision::TakeHead);
+    }
+
+    #[test]
+    fn added_only_on_head_takes_head() {
+        let h = entry("h");
+        assert_eq!(decide_merge_action(None, Some(&h), None), MergeDecision::TakeHead);
+    }
+
+    #[test]
+    fn added_only_on_other_takes_other() {
+        let o = entry("o");
+        assert_eq!(decide_merge_action(None, None, Some(&o)), MergeDecision::TakeOther);
+    }
+
+    #[test]
+    fn deleted_on_other_takes_the_deletion() {
+        let (b, h) = (entry("b"), entry("b"));
+        assert_eq!(decide_merge_action(Some(&b), Some(&h), None), MergeDecision::TakeOther);
+    }
+
+    #[test]
+    fn deleted_on_head_keeps_the_deletion() {
+        let (b, o) = (entry("b"), entry("b"));
+        assert_eq!(decide_merge_action(Some(&b), None, Some(&o)), MergeDecision::TakeHead);
+        assert_eq!(decide_merge_action(Some(&b), None, None), MergeDecision::TakeHead);
+    }
+
+    #[test]
+    fn diverging_changes_conflict() {
+        let (b, h, o) = (entry("b"), entry("h"), entry("o"));
+        assert_eq!(decide_merge_action(Some(&b), Some(&h), Some(&o)), MergeDecision::Conflict);
+        // Added differently on both sides
+        assert_eq!(decide_merge_action(None, Some(&h), Some(&o)), MergeDecision::Conflict);
+        // Modified on one side, deleted on the other
+        assert_eq!(decide_merge_action(Some(&b), Some(&h), None), MergeDecision::Conflict);
+        assert_eq!(decide_merge_action(Some(&b), None, Some(&o)), MergeDecision::Conflict);
+    }
+}
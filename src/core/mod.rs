@@ -10,4 +10,10 @@ pub mod read_head;
 pub mod resolve_parse;
 pub mod parse_tree;
 pub mod ignore;
+pub mod diff;
+pub mod config;
+pub mod reflog;
+pub mod merge_engine;
+pub mod color;
+pub mod attributes;
 //pub mod tree;
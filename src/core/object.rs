@@ -76,14 +76,32 @@ impl GitObject for Tree {
     }
 }
 
+/// Current UTC offset of the local timezone, formatted like in commit objects (`+0200`, `-0530`)
+pub fn local_timezone() -> String {
+    format_timezone(chrono::Local::now().offset().local_minus_utc())
+}
+
+/// Formats an offset east of UTC, in seconds, as `+HHMM` / `-HHMM`
+pub fn format_timezone(offset_seconds: i32) -> String {
+    let sign = if offset_seconds < 0 { '-' } else { '+' };
+    let minutes = offset_seconds.abs() / 60;
+    format!("{}{:02}{:02}", sign, minutes / 60, minutes % 60)
+}
+
+#[derive(Clone)]
 pub struct Commit {
     pub tree: String,
     pub parent: Option<Vec<String>>,
     pub message: String,
+    /// "Name <email>"
     pub author: String,
     pub committer: String,
+    /// Unix timestamps
     pub author_date: i64,
     pub committer_date: i64,
+    /// UTC offsets as written in the object, e.g. "+0000" or "-0530"
+    pub author_timezone: String,
+    pub committer_timezone: String,
 }
 
 impl GitObject for Commit {
@@ -102,15 +120,13 @@ impl GitObject for Commit {
             }
         }
 
-        let timezone = "+0000";
-
         let author_line = format!(
             "author {} {} {}\n",
-            self.author, self.author_date, timezone
+            self.author, self.author_date, self.author_timezone
         );
         let committer_line = format!(
             "committer {} {} {}\n",
-            self.committer, self.committer_date, timezone
+            self.committer, self.committer_date, self.committer_timezone
         );
 
         content.extend(author_line.as_bytes());
@@ -125,3 +141,43 @@ impl GitObject for Commit {
         content
     }
 }
+
+/// Annotated tag: a named pointer to another object (usually a commit),
+/// with its own author and message
+#[derive(Clone)]
+pub struct Tag {
+    /// SHA of the tagged object
+    pub object: String,
+    /// Type of the tagged object, e.g. "commit"
+    pub target_type: String,
+    /// Tag name, without `refs/tags/`
+    pub name: String,
+    /// "Name <email>"
+    pub tagger: String,
+    /// Unix timestamp
+    pub tagger_date: i64,
+    /// UTC offset as written in the object, e.g. "+0000"
+    pub tagger_timezone: String,
+    pub message: String,
+}
+
+impl GitObject for Tag {
+    fn object_type(&self) -> &str {
+        "tag"
+    }
+
+    fn content(&self) -> Vec<u8> {
+        let mut content = format!(
+            "object {}\ntype {}\ntag {}\ntagger {} {} {}\n\n",
+            self.object, self.target_type, self.name, self.tagger, self.tagger_date, self.tagger_timezone
+        )
+        .into_bytes();
+
+        content.extend(self.message.as_bytes());
+        if !self.message.ends_with('\n') {
+            content.extend(b"\n");
+        }
+
+        content
+    }
+}
@@ -31,8 +31,43 @@ pub fn read_head(guts_dir: &Path, head_input: &str) -> Result<String> {
     }
 }
 
+/// What HEAD points to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Head {
+    /// HEAD is a symbolic ref to `refs/heads/<name>` (the branch may have no commit yet)
+    Branch(String),
+    /// HEAD holds a commit SHA directly
+    Detached(String),
+}
+
+impl Head {
+    /// Short form used in messages: the branch name, or the abbreviated SHA
+    pub fn short_name(&self) -> String {
+        match self {
+            Head::Branch(name) => name.clone(),
+            Head::Detached(sha) => sha[..sha.len().min(7)].to_string(),
+        }
+    }
+}
+
+/// Reads `.git/HEAD` and tells whether it names a branch or a detached commit
+pub fn current_head(git_dir: &Path) -> Result<Head> {
+    let head_path = git_dir.join("HEAD");
+    let content = fs::read_to_string(&head_path)
+        .with_context(|| format!("Failed to read HEAD from {}", head_path.display()))?;
+    let content = content.trim();
+
+    match content.strip_prefix("ref: ") {
+        Some(target) => {
+            let name = target.trim().strip_prefix("refs/heads/").unwrap_or(target.trim());
+            Ok(Head::Branch(name.to_string()))
+        }
+        None => Ok(Head::Detached(content.to_string())),
+    }
+}
+
 /// Gets the current branch name from HEAD file
-/// Returns "main" as default if HEAD doesn't exist or isn't a symbolic ref
+/// Returns the configured default branch if HEAD doesn't exist, "HEAD" when detached
 pub fn get_current_branch() -> Result<String> {
     use crate::core::simple_index;
     
@@ -40,19 +75,13 @@ pub fn get_current_branch() -> Result<String> {
     let repo_root = simple_index::find_repo_root()
         .context("Not in a git repository")?;
     let git_dir = repo_root.join(".git");
-    let head_path = git_dir.join("HEAD");
-    
-    // Read HEAD file content
-    let content = match fs::read_to_string(&head_path) {
-        Ok(content) => content,
-        Err(_) => return Ok("main".to_string()), // Default to main if HEAD doesn't exist
-    };
-
-    // If it's a symbolic reference like "ref: refs/heads/branch-name"
-    if let Some(symbolic) = content.strip_prefix("ref: refs/heads/") {
-        Ok(symbolic.trim().to_string())
-    } else {
-        // If HEAD contains a direct SHA (detached HEAD), return a generic message
-        Ok("HEAD".to_string())
+    if !git_dir.join("HEAD").exists() {
+        return crate::core::config::default_branch();
+    }
+
+    match current_head(&git_dir)? {
+        Head::Branch(name) => Ok(name),
+        // Detached HEAD: return a generic name
+        Head::Detached(_) => Ok("HEAD".to_string()),
     }
 }
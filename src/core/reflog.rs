@@ -0,0 +1,128 @@
+use crate::core::{config, object};
+use anyhow::{anyhow, Context, Result};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Old SHA recorded when a ref is created
+pub const NULL_SHA: &str = "0000000000000000000000000000000000000000";
+
+/// One line of a reflog:
+/// `<old> <new> Name <email> <timestamp> <timezone>\t<message>`
+#[derive(Debug, Clone)]
+pub struct ReflogEntry {
+    pub old_sha: String,
+    pub new_sha: String,
+    pub identity: String,
+    pub timestamp: i64,
+    pub timezone: String,
+    /// `action: details`, e.g. `commit: Add README`
+    pub message: String,
+}
+
+impl ReflogEntry {
+    /// Parses one reflog line, None when it is malformed
+    pub fn parse(line: &str) -> Option<Self> {
+        let (header, message) = line.split_once('\t').unwrap_or((line, ""));
+        let (old_sha, rest) = header.split_once(' ')?;
+        let (new_sha, rest) = rest.split_once(' ')?;
+        // The identity may contain spaces: the last two fields are the date
+        let (rest, timezone) = rest.rsplit_once(' ')?;
+        let (identity, timestamp) = rest.rsplit_once(' ')?;
+
+        Some(ReflogEntry {
+            old_sha: old_sha.to_string(),
+            new_sha: new_sha.to_string(),
+            identity: identity.to_string(),
+            timestamp: timestamp.parse().ok()?,
+            timezone: timezone.to_string(),
+            message: message.to_string(),
+        })
+    }
+
+    /// Renders the entry back to its on-disk line, without newline
+    pub fn serialize(&self) -> String {
+        format!(
+            "{} {} {} {} {}\t{}",
+            self.old_sha, self.new_sha, self.identity, self.timestamp, self.timezone, self.message
+        )
+    }
+}
+
+/// Path of the reflog of a ref: `.git/logs/HEAD`, `.git/logs/refs/heads/main`
+pub fn log_path(git_dir: &Path, ref_name: &str) -> PathBuf {
+    git_dir.join("logs").join(ref_name)
+}
+
+/// Appends a movement of `ref_name` from `old_sha` (None when created) to `new_sha`
+pub fn append(
+    git_dir: &Path,
+    ref_name: &str,
+    old_sha: Option<&str>,
+    new_sha: &str,
+    message: &str,
+) -> Result<()> {
+    let entry = ReflogEntry {
+        old_sha: old_sha.unwrap_or(NULL_SHA).to_string(),
+        new_sha: new_sha.to_string(),
+        identity: config::user_identity()?,
+        timestamp: chrono::Utc::now().timestamp(),
+        timezone: object::local_timezone(),
+        // Keep each entry on a single line
+        message: message.lines().next().unwrap_or("").to_string(),
+    };
+
+    let path = log_path(git_dir, ref_name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create {}", parent.display()))?;
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("failed to open reflog {}", path.display()))?;
+    writeln!(file, "{}", entry.serialize())
+        .with_context(|| format!("failed to write reflog {}", path.display()))?;
+    Ok(())
+}
+
+/// Records a movement of HEAD, and of the branch it points to when it is not detached
+pub fn record_head_update(
+    git_dir: &Path,
+    old_sha: Option<&str>,
+    new_sha: &str,
+    message: &str,
+) -> Result<()> {
+    append(git_dir, "HEAD", old_sha, new_sha, message)?;
+
+    let head = fs::read_to_string(git_dir.join("HEAD")).context("cannot read HEAD")?;
+    if let Some(branch_ref) = head.trim().strip_prefix("ref: ") {
+        append(git_dir, branch_ref, old_sha, new_sha, message)?;
+    }
+    Ok(())
+}
+
+/// Reads the reflog of a ref, oldest entry first. A missing log is empty.
+pub fn read(git_dir: &Path, ref_name: &str) -> Result<Vec<ReflogEntry>> {
+    let path = log_path(git_dir, ref_name);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read reflog {}", path.display()))?;
+    Ok(content.lines().filter_map(ReflogEntry::parse).collect())
+}
+
+/// SHA a ref pointed to `n` movements ago (`HEAD@{n}`)
+pub fn nth_entry(git_dir: &Path, ref_name: &str, n: usize) -> Result<String> {
+    let entries = read(git_dir, ref_name)?;
+    entries
+        .iter()
+        .rev()
+        .nth(n)
+        .map(|entry| entry.new_sha.clone())
+        .ok_or_else(|| anyhow!("log for '{}' only has {} entries", ref_name, entries.len()))
+}
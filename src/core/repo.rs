@@ -1,30 +1,62 @@
 use std::fs;
 use std::path::Path;
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 
-/// Initialise a `.git` Repository in the given Directory
+/// Branch HEAD points to in a new repository
+pub const DEFAULT_BRANCH: &str = "main";
+
+/// Initialise a `.git` Repository in the given Directory, HEAD pointing to `main`
+pub fn init(path: &Path) -> Result<()> {
+    init_with_branch(path, DEFAULT_BRANCH)
+}
+
+/// Initialise a `.git` Repository in the given Directory, with the same layout as `git init`
 /// Create:
 /// - .git/
-/// - .git/objects/
+/// - .git/objects/ (with info/ and pack/)
 /// - .git/refs/heads/
-/// - .git/HEAD
+/// - .git/refs/tags/
+/// - .git/HEAD, pointing to `initial_branch`
 /// - .git/config
-pub fn init(path: &Path) -> Result<()> {
-    let guts_dir = path.join(".git");
-    let objects_dir = guts_dir.join("objects");
-    let refs_heads_dir = guts_dir.join("refs").join("heads");
+pub fn init_with_branch(path: &Path, initial_branch: &str) -> Result<()> {
+    init_layout(&path.join(".git"), initial_branch, false)
+}
+
+/// Initialise a bare Repository: the same layout directly in the given Directory,
+/// without `.git` subdirectory nor working tree, like `git init --bare`
+pub fn init_bare(path: &Path, initial_branch: &str) -> Result<()> {
+    init_layout(path, initial_branch, true)
+}
+
+fn init_layout(guts_dir: &Path, initial_branch: &str, bare: bool) -> Result<()> {
+    if initial_branch.is_empty()
+        || initial_branch.starts_with('-')
+        || initial_branch.contains("..")
+        || initial_branch.chars().any(|c| c.is_whitespace() || "~^:?*[\\".contains(c))
+    {
+        return Err(anyhow!("invalid initial branch name: '{}'", initial_branch));
+    }
+
     let head_file = guts_dir.join("HEAD");
     let config_file = guts_dir.join("config");
 
-    fs::create_dir_all(&objects_dir).with_context(|| "failed to create objects directory")?;
-    fs::create_dir_all(&refs_heads_dir).with_context(|| "failed to create refs/heads directory")?;
+    for dir in ["objects/info", "objects/pack", "refs/heads", "refs/tags"] {
+        fs::create_dir_all(guts_dir.join(dir))
+            .with_context(|| format!("failed to create {} directory", dir))?;
+    }
 
-    fs::write(&head_file, b"ref: refs/heads/main\n")
+    fs::write(&head_file, format!("ref: refs/heads/{}\n", initial_branch))
         .with_context(|| "failed to write HEAD file")?;
 
-    fs::write(&config_file, b"[core]\n\trepositoryformatversion = 0\n")
-        .with_context(|| "failed to write config file")?;
+    fs::write(
+        &config_file,
+        format!(
+            "[core]\n\trepositoryformatversion = 0\n\tfilemode = true\n\tbare = {}\n",
+            bare
+        ),
+    )
+    .with_context(|| "failed to write config file")?;
 
     Ok(())
 }
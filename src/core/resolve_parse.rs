@@ -1,24 +1,128 @@
-use anyhow::Result;
+use crate::core::cat::{self, ParsedObject};
+use crate::core::hash;
+use crate::core::reflog;
+use anyhow::{anyhow, Result};
 use std::path::Path;
 use std::fs;
 
+/// Resolves a revision to a commit SHA.
+///
+/// Accepts everything `resolve_name` does, followed by any chain of ancestry
+/// suffixes applied left to right:
+/// - `~<n>` walks `n` first parents (`~` alone means `~1`)
+/// - `^<n>` selects the nth parent (`^` alone means `^1`, `^0` is the commit itself)
+///
+/// e.g. `HEAD~2`, `main^`, `main~1^2`. Annotated tags resolve to the commit they tag.
 pub fn resolve_ref(guts_dir: &Path, head_input: &str) -> Result<String> {
+    let split = head_input.find(['~', '^']).unwrap_or(head_input.len());
+    let (base, mut suffix) = head_input.split_at(split);
+    if base.is_empty() {
+        anyhow::bail!("Invalid revision '{}'", head_input);
+    }
+
+    let mut sha = peel_tags(guts_dir, &resolve_name(guts_dir, base)?)?;
+
+    while let Some(op) = suffix.chars().next() {
+        let digits_len = suffix[1..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(suffix.len() - 1);
+        let digits = &suffix[1..1 + digits_len];
+        let n: usize = if digits.is_empty() {
+            1
+        } else {
+            digits
+                .parse()
+                .map_err(|_| anyhow!("Invalid revision '{}'", head_input))?
+        };
+        suffix = &suffix[1 + digits_len..];
+
+        match op {
+            '~' => {
+                for _ in 0..n {
+                    sha = nth_parent(guts_dir, &sha, 1, head_input)?;
+                }
+            }
+            '^' => {
+                if n > 0 {
+                    sha = nth_parent(guts_dir, &sha, n, head_input)?;
+                }
+            }
+            _ => anyhow::bail!("Invalid revision '{}'", head_input),
+        }
+    }
+
+    Ok(sha)
+}
+
+/// Follows annotated tags down to the object they point to; any other SHA
+/// (or one whose object is not there) is returned unchanged
+pub fn peel_tags(guts_dir: &Path, sha: &str) -> Result<String> {
+    let mut sha = sha.to_string();
+    while cat::get_object_path(guts_dir, &sha).exists() {
+        match cat::read_object(guts_dir, &sha)? {
+            ParsedObject::Tag(tag) => sha = tag.object,
+            _ => break,
+        }
+    }
+    Ok(sha)
+}
+
+/// Returns the `n`th (1-based) parent of a commit
+fn nth_parent(guts_dir: &Path, sha: &str, n: usize, revision: &str) -> Result<String> {
+    let commit = match cat::read_object(guts_dir, sha)? {
+        ParsedObject::Commit(commit) => commit,
+        _ => anyhow::bail!("'{}': object {} is not a commit", revision, sha),
+    };
+
+    let parents = commit.parent.unwrap_or_default();
+    match parents.get(n - 1) {
+        Some(parent) => Ok(parent.clone()),
+        None if parents.is_empty() => anyhow::bail!(
+            "'{}': commit {} is the root commit and has no parent",
+            revision,
+            &sha[..7]
+        ),
+        None => anyhow::bail!(
+            "'{}': commit {} has only {} parent(s)",
+            revision,
+            &sha[..7],
+            parents.len()
+        ),
+    }
+}
+
+/// Resolves a plain name (HEAD, full or abbreviated SHA, branch, tag, ref path or `<ref>@{n}`) to a SHA
+fn resolve_name(guts_dir: &Path, head_input: &str) -> Result<String> {
+    // `HEAD@{2}`: where the ref was two movements ago, read from its reflog
+    if let Some((name, rest)) = head_input.split_once("@{") {
+        let n: usize = rest
+            .strip_suffix('}')
+            .and_then(|n| n.parse().ok())
+            .ok_or_else(|| anyhow!("Invalid revision '{}'", head_input))?;
+        let ref_name = if name == "HEAD" || name.starts_with("refs/") {
+            name.to_string()
+        } else {
+            format!("refs/heads/{}", name)
+        };
+        return reflog::nth_entry(guts_dir, &ref_name, n);
+    }
+
     if head_input == "HEAD" {
         let head_path = guts_dir.join("HEAD");
         let content = fs::read_to_string(&head_path)?.trim().to_string();
 
         if content.starts_with("ref: ") {
             let ref_name = content.trim_start_matches("ref: ").trim();
-            return resolve_ref(guts_dir, ref_name);
-        } else if content.len() == 40 && content.chars().all(|c| c.is_ascii_hexdigit()) {
+            return resolve_name(guts_dir, ref_name);
+        } else if hash::is_valid_sha(&content) {
             return Ok(content);
         } else {
             anyhow::bail!("Invalid HEAD content: {}", content);
         }
     }
 
-    if head_input.len() == 40 && head_input.chars().all(|c| c.is_ascii_hexdigit()) {
-        return Ok(head_input.to_string());
+    if hash::is_valid_sha(head_input) {
+        return hash::normalize_sha(head_input);
     }
 
     let paths_to_try = [
@@ -30,9 +134,18 @@ pub fn resolve_ref(guts_dir: &Path, head_input: &str) -> Result<String> {
     for path in paths_to_try {
         if path.exists() {
             let sha = fs::read_to_string(path)?.trim().to_string();
-            return Ok(sha);
+            // An empty or damaged ref file must not reach the object store
+            if !hash::is_valid_sha(&sha) {
+                anyhow::bail!("fatal: not a valid object name: {}", head_input);
+            }
+            return Ok(sha.to_lowercase());
         }
     }
 
+    // Refs win over abbreviated SHAs, like in git
+    if hash::normalize_sha(head_input).is_ok() {
+        return cat::resolve_object_prefix(guts_dir, head_input);
+    }
+
     anyhow::bail!("Reference '{}' not found", head_input)
 }
@@ -1,12 +1,16 @@
 // Module for a simple Git index in JSON format
 // Educational alternative to Git's complex binary index
 
-use crate::core::{blob, cat, hash};
+use crate::core::attributes::Attributes;
+use crate::core::{blob, cat, hash, status_binary_index};
 use anyhow::{anyhow, Context, Result};
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Helper function to execute a closure with a temporary current directory
 /// This is the simple and safe way to handle directory context for TUI
@@ -25,12 +29,27 @@ where
     }
 }
 
+/// Mode of a regular file in tree objects
+pub const MODE_FILE: &str = "100644";
+/// Mode of an executable file in tree objects
+pub const MODE_EXECUTABLE: &str = "100755";
+/// Mode of a symbolic link in tree objects (the blob holds the link target)
+pub const MODE_SYMLINK: &str = "120000";
+
 /// Simple structure for Git index
-/// Stores only "staged" files with their SHA-1 hash
+/// Stores every file of the next commit with its SHA-1 hash (like Git's index,
+/// it is not cleared after a commit: it then matches the HEAD tree)
 #[derive(Serialize, Deserialize, Default, Debug)]
 pub struct SimpleIndex {
     /// Map: relative file path -> SHA-1 hash of content
     pub files: HashMap<String, String>,
+    /// Map: relative file path -> mode, only for files that are not plain `100644` files
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub modes: HashMap<String, String>,
+    /// Paths whose working tree file was just hashed (or written) with its staged blob:
+    /// `save` records their stat data without reading them again
+    #[serde(skip)]
+    pub hashed: HashSet<String>,
 }
 
 impl SimpleIndex {
@@ -53,7 +72,7 @@ impl SimpleIndex {
     }
 
 
-    /// Save index to .git/simple_index.json
+    /// Save index to .git/simple_index.json, and to .git/index in git's binary format
     pub fn save(&self) -> Result<()> {
         let index_path = get_simple_index_path()?;
 
@@ -63,33 +82,69 @@ impl SimpleIndex {
         fs::write(&index_path, content)
             .with_context(|| format!("unable to write {:?}", index_path))?;
 
+        // Keep git's own index in sync, so `git` commands see the same staged files
+        if let Some(git_dir) = index_path.parent() {
+            status_binary_index::write_git_index(git_dir, &self.files, &self.modes, &self.hashed)?;
+        }
+
         Ok(())
     }
 
     /// Add a file to the index (= "stage" it for next commit)
     pub fn add_file(&mut self, file_path: &Path) -> Result<()> {
-        // Convert to absolute path if necessary
-        let absolute_path = if file_path.is_absolute() {
-            file_path.to_path_buf()
-        } else {
-            std::env::current_dir()?.join(file_path)
-        };
+        self.add_files(&[file_path.to_path_buf()])
+    }
 
-        // Read file content
-        let content = fs::read(&absolute_path)
-            .with_context(|| format!("unable to read {:?}", absolute_path))?;
+    /// Add several files to the index, hashing them in parallel
+    pub fn add_files(&mut self, file_paths: &[PathBuf]) -> Result<()> {
+        self.add_files_with_progress(file_paths, |_| {})
+    }
 
-        // Create Git blob and calculate its SHA-1 hash
-        let blob = blob::Blob::new(content);
-        let file_hash = hash::write_object(&blob)?;
+    /// `add_files`, calling `progress` with the number of files stored so far after each
+    /// one. It is called from several threads at once.
+    pub fn add_files_with_progress(&mut self, file_paths: &[PathBuf], progress: impl Fn(usize) + Sync) -> Result<()> {
+        let repo_root = find_repo_root()?;
+        let current_dir = std::env::current_dir()?;
+        let attributes = Attributes::load(&repo_root)?;
+        let done = AtomicUsize::new(0);
+
+        // Blobs are written concurrently, the map is only touched once they are all stored
+        let staged = file_paths
+            .par_iter()
+            .map(|file_path| {
+                let staged = hash_worktree_file(&repo_root, &current_dir.join(file_path), &attributes);
+                progress(done.fetch_add(1, Ordering::Relaxed) + 1);
+                staged
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for (relative_path, file_hash, mode) in staged {
+            // Remember the executable bit or symlink
+            if mode == MODE_FILE {
+                self.modes.remove(&relative_path);
+            } else {
+                self.modes.insert(relative_path.clone(), mode.to_string());
+            }
+            self.hashed.insert(relative_path.clone());
+            self.files.insert(relative_path, file_hash);
+        }
 
-        // Convert to relative path from repo root
-        let relative_path = get_relative_path(&absolute_path)?;
+        Ok(())
+    }
 
-        // Add to our map
-        self.files.insert(relative_path, file_hash);
+    /// Tree mode of a staged file (`100644` unless recorded otherwise)
+    pub fn mode_of(&self, path: &str) -> &str {
+        self.modes.get(path).map(String::as_str).unwrap_or(MODE_FILE)
+    }
 
-        Ok(())
+    /// Build an index holding exactly the files of a tree object
+    /// Used whenever HEAD moves (reset, checkout, merge) so the staging area matches the new commit
+    pub fn from_tree(git_dir: &Path, tree_hash: &str) -> Result<Self> {
+        Ok(SimpleIndex {
+            files: get_files_from_tree(git_dir, tree_hash, "")?,
+            modes: get_modes_from_tree(git_dir, tree_hash)?,
+            hashed: HashSet::new(),
+        })
     }
 
     /// Check if a file is in the index (staged)
@@ -103,6 +158,67 @@ impl SimpleIndex {
     }
 }
 
+/// Tree mode for a file on disk: `120000` for a symlink, `100755` when any execute bit
+/// is set (Unix only), else `100644`
+pub fn file_mode(path: &Path) -> Result<&'static str> {
+    if is_symlink(path) {
+        return Ok(MODE_SYMLINK);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let metadata = fs::metadata(path).with_context(|| format!("unable to stat {:?}", path))?;
+        if metadata.permissions().mode() & 0o111 != 0 {
+            return Ok(MODE_EXECUTABLE);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(MODE_FILE)
+}
+
+/// True when `path` itself is a symbolic link (dangling or not)
+pub fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+/// Content a working tree file is stored with: its bytes, or the link target for a symlink.
+/// `relative_path`, from the repo root, selects the attributes that apply to the file.
+pub fn read_worktree_file(path: &Path, relative_path: &str, attributes: &Attributes) -> Result<Vec<u8>> {
+    if is_symlink(path) {
+        let target = fs::read_link(path).with_context(|| format!("unable to read link {:?}", path))?;
+        return Ok(target.to_string_lossy().into_owned().into_bytes());
+    }
+    let content = fs::read(path).with_context(|| format!("unable to read {:?}", path))?;
+
+    // Text files are stored with LF line endings when the attributes ask for it
+    Ok(attributes.to_blob(relative_path, content))
+}
+
+/// Writes a working tree file as a blob object and returns its SHA-1, with the same
+/// content as `read_worktree_file`. Large files are streamed instead of read in memory.
+pub fn write_worktree_blob(path: &Path, relative_path: &str, attributes: &Attributes) -> Result<String> {
+    if !is_symlink(path) {
+        let size = fs::metadata(path).with_context(|| format!("unable to stat {:?}", path))?.len();
+        if size > hash::STREAMING_THRESHOLD {
+            // `text=auto` only looks at the start of the content
+            let mut content_start = Vec::new();
+            fs::File::open(path)
+                .and_then(|file| file.take(8000).read_to_end(&mut content_start))
+                .with_context(|| format!("unable to read {:?}", path))?;
+            let crlf_to_lf = attributes.normalizes_to_blob(relative_path, &content_start);
+            return hash::write_blob_streaming(path, crlf_to_lf);
+        }
+    }
+
+    let blob = blob::Blob::new(read_worktree_file(path, relative_path, attributes)?);
+    hash::write_object(&blob)
+}
+
 /// Find Git repository root (directory containing .git/)
 pub fn find_repo_root() -> Result<PathBuf> {
     let mut current = std::env::current_dir().with_context(|| "unable to get current directory")?;
@@ -120,6 +236,15 @@ pub fn find_repo_root() -> Result<PathBuf> {
     }
 }
 
+/// Path relative to the repo root, as stored in the index
+pub fn repo_relative(repo_root: &Path, path: &Path) -> Result<String> {
+    let absolute_path = std::env::current_dir()?.join(path);
+    let relative = absolute_path
+        .strip_prefix(repo_root)
+        .map_err(|_| anyhow!("fatal: '{}' is outside repository", path.display()))?;
+    Ok(relative.to_string_lossy().to_string())
+}
+
 /// Return path to .git/simple_index.json
 fn get_simple_index_path() -> Result<PathBuf> {
     let repo_root = find_repo_root()?;
@@ -127,13 +252,19 @@ fn get_simple_index_path() -> Result<PathBuf> {
 }
 
 
-/// Convert absolute path to relative path from repo root
-fn get_relative_path(file_path: &Path) -> Result<String> {
-    let repo_root = find_repo_root()?;
-    let relative = file_path
-        .strip_prefix(&repo_root)
-        .with_context(|| "file is not in the repository")?;
-    Ok(relative.to_string_lossy().to_string())
+/// Writes the blob of a working tree file, returns its path relative to the repo root, SHA and mode
+fn hash_worktree_file(
+    repo_root: &Path,
+    absolute_path: &Path,
+    attributes: &Attributes,
+) -> Result<(String, String, &'static str)> {
+    let relative_path = absolute_path
+        .strip_prefix(repo_root)
+        .with_context(|| "file is not in the repository")?
+        .to_string_lossy()
+        .to_string();
+    let file_hash = write_worktree_blob(absolute_path, &relative_path, attributes)?;
+    Ok((relative_path, file_hash, file_mode(absolute_path)?))
 }
 
 /// Check if we're in a Git repository
@@ -145,10 +276,15 @@ pub fn is_git_repository() -> Result<bool> {
 }
 
 /// Public function to add a file to the index
-/// This is the function that the `guts add` command will call
 pub fn add_file_to_index(file_path: &Path) -> Result<()> {
+    add_files_to_index(&[file_path.to_path_buf()])
+}
+
+/// Adds files to the index with a single load and save of the index file
+/// This is the function that the `guts add` command will call
+pub fn add_files_to_index(file_paths: &[PathBuf]) -> Result<()> {
     let mut index = SimpleIndex::load()?;
-    index.add_file(file_path)?;
+    index.add_files(file_paths)?;
     index.save()?;
     Ok(())
 }
@@ -192,9 +328,7 @@ pub fn get_committed_files() -> Result<HashMap<String, String>> {
         return Ok(HashMap::new());
     }
     
-    let commit_data = fs::read(&commit_obj_path)?;
-    let decompressed = decompress_object(&commit_data)?;
-    let parsed = cat::parse_object(&decompressed)?;
+    let parsed = cat::read_object(&git_dir, &commit_hash)?;
     
     let tree_hash = match parsed {
         cat::ParsedObject::Commit(commit) => commit.tree,
@@ -207,7 +341,7 @@ pub fn get_committed_files() -> Result<HashMap<String, String>> {
 
 /// Recursively get all files from a tree object
 /// Returns a HashMap: relative file path -> SHA-1 hash
-fn get_files_from_tree(git_dir: &Path, tree_hash: &str, prefix: &str) -> Result<HashMap<String, String>> {
+pub fn get_files_from_tree(git_dir: &Path, tree_hash: &str, prefix: &str) -> Result<HashMap<String, String>> {
     let mut files = HashMap::new();
     
     let tree_obj_path = cat::get_object_path(git_dir, tree_hash);
@@ -215,9 +349,7 @@ fn get_files_from_tree(git_dir: &Path, tree_hash: &str, prefix: &str) -> Result<
         return Ok(files);
     }
     
-    let tree_data = fs::read(&tree_obj_path)?;
-    let decompressed = decompress_object(&tree_data)?;
-    let parsed = cat::parse_object(&decompressed)?;
+    let parsed = cat::read_object_cached(git_dir, tree_hash)?;
     
     let entries = match parsed {
         cat::ParsedObject::Tree(entries) => entries,
@@ -231,8 +363,8 @@ fn get_files_from_tree(git_dir: &Path, tree_hash: &str, prefix: &str) -> Result<
             format!("{}/{}", prefix, entry.name)
         };
         
-        if entry.mode == "100644" {
-            // Regular file
+        if entry.mode.starts_with("100") || entry.mode == MODE_SYMLINK {
+            // Regular file, executable file or symlink
             let hash_hex = hex::encode(entry.hash);
             files.insert(file_path, hash_hex);
         } else if entry.mode == "40000" {
@@ -246,22 +378,33 @@ fn get_files_from_tree(git_dir: &Path, tree_hash: &str, prefix: &str) -> Result<
     Ok(files)
 }
 
-/// Decompress Git object data (Git uses zlib compression)
-/// But our simple implementation stores objects uncompressed, so try both
-fn decompress_object(data: &[u8]) -> Result<Vec<u8>> {
-    // First try to decompress as zlib (standard Git format)
-    use std::io::Read;
-    
-    let mut decoder = flate2::read::ZlibDecoder::new(data);
-    let mut decompressed = Vec::new();
-    
-    match decoder.read_to_end(&mut decompressed) {
-        Ok(_) => Ok(decompressed),
-        Err(_) => {
-            // If decompression fails, assume data is already uncompressed
-            // (our simple implementation stores objects uncompressed)
-            Ok(data.to_vec())
+/// Modes of the files of a tree that are not plain `100644` files
+/// Returns a HashMap: relative file path -> mode
+pub fn get_modes_from_tree(git_dir: &Path, tree_hash: &str) -> Result<HashMap<String, String>> {
+    let mut modes = HashMap::new();
+    collect_modes(git_dir, tree_hash, "", &mut modes)?;
+    Ok(modes)
+}
+
+fn collect_modes(git_dir: &Path, tree_hash: &str, prefix: &str, modes: &mut HashMap<String, String>) -> Result<()> {
+    let entries = match cat::read_object(git_dir, tree_hash)? {
+        cat::ParsedObject::Tree(entries) => entries,
+        _ => return Err(anyhow!("Object is not a tree")),
+    };
+
+    for entry in entries {
+        let file_path = if prefix.is_empty() {
+            entry.name.clone()
+        } else {
+            format!("{}/{}", prefix, entry.name)
+        };
+
+        if entry.mode == "40000" {
+            collect_modes(git_dir, &hex::encode(entry.hash), &file_path, modes)?;
+        } else if entry.mode != MODE_FILE {
+            modes.insert(file_path, entry.mode);
         }
     }
+    Ok(())
 }
 
@@ -1,15 +1,65 @@
 use anyhow::{Context, Result};
-use std::collections::HashSet;
+use rayon::prelude::*;
+use sha1::{Digest, Sha1};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
-use crate::core::hash;
+use crate::core::{hash, simple_index};
 
 /// Represents a file entry from the Git index.
 pub struct IndexEntry {
     pub path: PathBuf,     // Relative file path
     pub blob_hash: String, // SHA-1 hash of the file content
+    pub mode: u32,         // Tree mode, e.g. 0o100644
+    pub stat: FileStat,    // File metadata when it was staged
+}
+
+/// Metadata git keeps for each index entry to notice changed files without hashing them.
+/// Every field is truncated to 32 bits, like in the on-disk format.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FileStat {
+    pub ctime_secs: u32,
+    pub ctime_nsecs: u32,
+    pub mtime_secs: u32,
+    pub mtime_nsecs: u32,
+    pub dev: u32,
+    pub ino: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub size: u32,
+}
+
+impl FileStat {
+    /// Stat data of a file (of the link itself for a symlink). All zero outside Unix.
+    pub fn from_metadata(metadata: &fs::Metadata) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            FileStat {
+                ctime_secs: metadata.ctime() as u32,
+                ctime_nsecs: metadata.ctime_nsec() as u32,
+                mtime_secs: metadata.mtime() as u32,
+                mtime_nsecs: metadata.mtime_nsec() as u32,
+                dev: metadata.dev() as u32,
+                ino: metadata.ino() as u32,
+                uid: metadata.uid(),
+                gid: metadata.gid(),
+                size: metadata.size() as u32,
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = metadata;
+            FileStat::default()
+        }
+    }
+}
+
+/// Reads the big-endian u32 at `pos`
+fn read_u32(data: &[u8], pos: usize) -> u32 {
+    u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
 }
 
 /// Recursively lists all files in the working directory, excluding .git folders.
@@ -39,7 +89,7 @@ pub fn parse_git_index(index_path: &Path) -> Result<Vec<IndexEntry>> {
     let index_path = index_path.join("index");
     let data = fs::read(&index_path).context("failed to read index")?;
 
-    if &data[0..4] != b"DIRC" {
+    if data.len() < 12 || &data[0..4] != b"DIRC" {
         return Err(anyhow::anyhow!("Invalid index file (Missing DIRC)"));
     }
 
@@ -81,9 +131,23 @@ pub fn parse_git_index(index_path: &Path) -> Result<Vec<IndexEntry>> {
         }
 
         let path = String::from_utf8_lossy(&data[flags_end..path_end]).to_string();
+        // ctime, mtime, dev, ino, mode, uid, gid and size come first, 4 bytes each
+        let field = |i: usize| read_u32(&data, pos + 4 * i);
         entries.push(IndexEntry {
             path: PathBuf::from(path),
             blob_hash,
+            mode: field(6),
+            stat: FileStat {
+                ctime_secs: field(0),
+                ctime_nsecs: field(1),
+                mtime_secs: field(2),
+                mtime_nsecs: field(3),
+                dev: field(4),
+                ino: field(5),
+                uid: field(7),
+                gid: field(8),
+                size: field(9),
+            },
         });
 
         // Go to the next entry: include null byte and padding
@@ -96,6 +160,86 @@ pub fn parse_git_index(index_path: &Path) -> Result<Vec<IndexEntry>> {
     Ok(entries)
 }
 
+/// Writes `.git/index` in git's version 2 `DIRC` format from the staged files, so that
+/// `git` sees what guts staged. Entries are sorted by path and the file ends with
+/// the SHA-1 of everything before it.
+///
+/// Nothing is hashed here: an entry keeps the stat data of the last write while its blob
+/// is unchanged, and only the `hashed` paths, whose file was just read, are stat'ed.
+pub fn write_git_index(
+    git_dir: &Path,
+    files: &HashMap<String, String>,
+    modes: &HashMap<String, String>,
+    hashed: &HashSet<String>,
+) -> Result<()> {
+    let repo_root = git_dir.parent().unwrap_or(Path::new("."));
+    // Entries of the last write whose stat data can be reused
+    let previous: HashMap<PathBuf, IndexEntry> = parse_git_index(git_dir)
+        .map(|entries| entries.into_iter().map(|entry| (entry.path.clone(), entry)).collect())
+        .unwrap_or_default();
+
+    let mut paths: Vec<&String> = files.keys().collect();
+    paths.sort();
+    let stats: Vec<FileStat> = paths
+        .par_iter()
+        .map(|path| {
+            entry_stat(repo_root, path, &files[*path], hashed.contains(*path), previous.get(Path::new(path.as_str())))
+        })
+        .collect();
+
+    let mut data = Vec::new();
+    data.extend_from_slice(b"DIRC");
+    data.extend_from_slice(&2u32.to_be_bytes());
+    data.extend_from_slice(&(paths.len() as u32).to_be_bytes());
+
+    for (path, stat) in paths.into_iter().zip(stats) {
+        let entry_start = data.len();
+        let mode = modes.get(path).map(String::as_str).unwrap_or(simple_index::MODE_FILE);
+        let mode = u32::from_str_radix(mode, 8)
+            .map_err(|_| anyhow::anyhow!("invalid mode {} for {}", mode, path))?;
+        let sha = hex::decode(&files[path])
+            .map_err(|_| anyhow::anyhow!("invalid SHA-1 {} for {}", files[path], path))?;
+
+        for field in [
+            stat.ctime_secs, stat.ctime_nsecs, stat.mtime_secs, stat.mtime_nsecs,
+            stat.dev, stat.ino, mode, stat.uid, stat.gid, stat.size,
+        ] {
+            data.extend_from_slice(&field.to_be_bytes());
+        }
+        data.extend_from_slice(&sha);
+        // Flags: the path length, capped to 12 bits
+        data.extend_from_slice(&(path.len().min(0xFFF) as u16).to_be_bytes());
+        data.extend_from_slice(path.as_bytes());
+
+        // At least one NUL, padding the entry to a multiple of 8 bytes
+        let entry_len = data.len() - entry_start;
+        data.resize(entry_start + (entry_len + 8) / 8 * 8, 0);
+    }
+
+    let checksum = Sha1::digest(&data);
+    data.extend_from_slice(&checksum);
+
+    let index_path = git_dir.join("index");
+    fs::write(&index_path, data).with_context(|| format!("unable to write {:?}", index_path))
+}
+
+/// Stat data to record for a staged file. It is only recorded when the file on disk
+/// is known to hold the staged content: zero makes git compare the contents instead,
+/// so a file changed since it was staged is never taken for clean.
+fn entry_stat(repo_root: &Path, path: &str, sha: &str, just_hashed: bool, previous: Option<&IndexEntry>) -> FileStat {
+    if just_hashed {
+        return fs::symlink_metadata(repo_root.join(path))
+            .map(|metadata| FileStat::from_metadata(&metadata))
+            .unwrap_or_default();
+    }
+
+    // Same blob as the last write: what was true of the file then still is
+    match previous {
+        Some(entry) if entry.blob_hash == sha => entry.stat,
+        _ => FileStat::default(),
+    }
+}
+
 /// Reads the current HEAD commit hash from .git/HEAD.
 /// If HEAD is a symbolic reference (e.g. `ref: refs/heads/main`), it resolves the actual hash.
 pub fn read_head_commit(gut_dir: &Path) -> Result<String> {
@@ -2,6 +2,7 @@ mod terminal;
 
 use anyhow::Result;
 use clap::Parser;
+use std::io::Write;
 use guts::cli::{Cli, Commands};
 
 fn main() -> Result<()> {
@@ -14,6 +15,7 @@ fn main() -> Result<()> {
     }
 
     let cli = Cli::parse();
+    cli.enter_repository()?;
 
     // refactored for TUI output
     match cli.command {
@@ -25,6 +27,10 @@ fn main() -> Result<()> {
             let output = guts::commands::hash_object::run(&args)?;
             println!("{}", output);
         }
+        Commands::CatFile(args) if args.batch => {
+            // Raw bytes: binary blobs must keep the size announced in their header
+            std::io::stdout().write_all(&guts::commands::cat_file::run_batch(&args)?)?;
+        }
         Commands::CatFile(args) => {
             let output = guts::commands::cat_file::run(&args)?;
             print!("{}", output);
@@ -39,7 +45,7 @@ fn main() -> Result<()> {
         }
         Commands::Status(args) => {
             let output = guts::commands::status::run(&args)?;
-            println!("{}", output);
+            print!("{}", output);
         }
         Commands::Add(args) => {
             let output = guts::commands::add::run(&args)?;
@@ -59,7 +65,9 @@ fn main() -> Result<()> {
         }
         Commands::Log(args) => {
             let output = guts::commands::log::run(&args)?;
-            println!("{}", output);
+            if !output.is_empty() {
+                println!("{}", output);
+            }
         }
         Commands::LsFiles(args) => {
             let output = guts::commands::ls_files::run(&args)?;
@@ -69,11 +77,11 @@ fn main() -> Result<()> {
         }
         Commands::LsTree(args) => {
             let output = guts::commands::ls_tree::run(&args)?;
-            println!("{}", output);
+            print!("{}", output);
         }
         Commands::ShowRef(args) => {
             let output = guts::commands::show_ref::run(&args)?;
-            println!("{}", output);
+            print!("{}", output);
         }
         Commands::Checkout(args) => {
             let output = guts::commands::checkout::run(&args)?;
@@ -83,6 +91,110 @@ fn main() -> Result<()> {
             let output = guts::commands::merge::run(&args)?;
             println!("{}", output);
         }
+        Commands::Diff(args) => {
+            let output = guts::commands::diff::run(&args)?;
+            print!("{}", output);
+        }
+        Commands::Reset(args) => {
+            let output = guts::commands::reset::run(&args)?;
+            println!("{}", output);
+        }
+        Commands::Config(args) => {
+            let output = guts::commands::config::run(&args)?;
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+        }
+        Commands::Show(args) => {
+            let output = guts::commands::show::run(&args)?;
+            print!("{}", output);
+        }
+        Commands::Reflog(args) => {
+            let output = guts::commands::reflog::run(&args)?;
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+        }
+        Commands::Revert(args) => {
+            let output = guts::commands::revert::run(&args)?;
+            println!("{}", output);
+        }
+        Commands::CherryPick(args) => {
+            let output = guts::commands::cherry_pick::run(&args)?;
+            println!("{}", output);
+        }
+        Commands::Mv(args) => {
+            let output = guts::commands::mv::run(&args)?;
+            println!("{}", output);
+        }
+        Commands::Clean(args) => {
+            let output = guts::commands::clean::run(&args)?;
+            print!("{}", output);
+        }
+        Commands::Stash(args) => {
+            let output = guts::commands::stash::run(&args)?;
+            println!("{}", output);
+        }
+        Commands::Clone(args) => {
+            let output = guts::commands::clone::run(&args)?;
+            println!("{}", output);
+        }
+        Commands::Describe(args) => {
+            let output = guts::commands::describe::run(&args)?;
+            println!("{}", output);
+        }
+        Commands::Blame(args) => {
+            let output = guts::commands::blame::run(&args)?;
+            print!("{}", output);
+        }
+        Commands::Gc(args) => {
+            let output = guts::commands::gc::run(&args)?;
+            print!("{}", output);
+        }
+        Commands::Fsck(args) => {
+            let output = guts::commands::fsck::run(&args)?;
+            print!("{}", output);
+        }
+        Commands::Switch(args) => {
+            let output = guts::commands::switch::run(&args)?;
+            println!("{}", output);
+        }
+        Commands::Restore(args) => {
+            let output = guts::commands::restore::run(&args)?;
+            println!("{}", output);
+        }
+        Commands::Remote(args) => {
+            let output = guts::commands::remote::run(&args)?;
+            print!("{}", output);
+        }
+        Commands::Fetch(args) => {
+            let output = guts::commands::fetch::run(&args)?;
+            print!("{}", output);
+        }
+        Commands::Push(args) => {
+            let output = guts::commands::push::run(&args)?;
+            print!("{}", output);
+        }
+        Commands::Tag(args) => {
+            let output = guts::commands::tag::run(&args)?;
+            print!("{}", output);
+        }
+        Commands::CountObjects(args) => {
+            let output = guts::commands::count_objects::run(&args)?;
+            print!("{}", output);
+        }
+        Commands::RevList(args) => {
+            let output = guts::commands::rev_list::run(&args)?;
+            print!("{}", output);
+        }
+        Commands::CheckIgnore(args) => {
+            let output = guts::commands::check_ignore::run(&args)?;
+            print!("{}", output);
+        }
+        Commands::Export(args) => {
+            let output = guts::commands::export::run(&args)?;
+            println!("{}", output);
+        }
         Commands::Tui => terminal::run_app()?,  
     }
 
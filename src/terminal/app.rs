@@ -1,10 +1,35 @@
 use anyhow::Result;
 use clap::Parser;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use guts::cli::{Cli, Commands};
+use guts::core::color::ColorWhen;
 use std::process::{Command, Stdio};
-use ratatui::{backend::CrosstermBackend, Terminal};
+use ratatui::{backend::CrosstermBackend, layout::Rect, Terminal};
 use std::io::Stdout;
+use crate::terminal::file_panel::{self, FileEntry};
+use crate::terminal::history;
+use crate::terminal::status_panel::{self, StatusView};
+use crate::terminal::tabs::{self, LogView, Tab};
+
+/// Lines scrolled by one notch of the mouse wheel
+const WHEEL_SCROLL_LINES: i32 = 3;
+
+/// Panel receiving the key presses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Console,
+    Files,
+    /// Scrolling the diff pane opened from the file browser
+    Diff,
+}
+
+/// Unified diff shown in place of the command history
+#[derive(Debug, Clone)]
+pub struct DiffView {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub scroll_offset: usize,
+}
 
 #[derive(Debug, Clone)]
 pub struct CommandResult {
@@ -27,8 +52,21 @@ pub struct App {
     pub show_autocomplete: bool,
     pub autocomplete_index: usize,
     pub force_redraw: bool,
-    pub last_executed_command: Option<String>
-
+    pub last_executed_command: Option<String>,
+    pub focus: Focus,
+    pub file_entries: Vec<FileEntry>, // file browser of current_dir
+    pub selected_file: usize,
+    pub diff_view: Option<DiffView>,
+    /// Status dashboard shown in place of the help pane (Ctrl+S)
+    pub status_view: Option<StatusView>,
+    /// View of the right-hand pane, with the data of the Status and Log tabs
+    pub tab: Tab,
+    pub tab_status: StatusView,
+    pub log_view: LogView,
+    // Screen areas of the last frame, to route mouse events
+    pub files_area: Rect,
+    pub history_area: Rect,
+    pub input_area: Rect,
 }
 
 impl Default for App {
@@ -50,14 +88,251 @@ impl Default for App {
             show_autocomplete: false,
             autocomplete_index: 0,
             force_redraw: false,
-            last_executed_command: None
+            last_executed_command: None,
+            focus: Focus::Console,
+            file_entries: Vec::new(),
+            selected_file: 0,
+            diff_view: None,
+            status_view: None,
+            tab: Tab::Console,
+            tab_status: StatusView::default(),
+            log_view: LogView::default(),
+            files_area: Rect::default(),
+            history_area: Rect::default(),
+            input_area: Rect::default(),
         }
     }
 }
 
 impl App {
     pub fn new() -> Self {
-        Self::default()
+        let mut app = Self::default();
+        // Up-arrow recall works across sessions
+        app.input_history = history::load();
+        app.input_history_index = app.input_history.len();
+        app.refresh_files();
+        app
+    }
+
+    /// Persists the console history to ~/.guts_history
+    pub fn save_history(&self) -> Result<()> {
+        history::save(&self.input_history)
+    }
+
+    // ======================= File browser =======================
+    /// Reloads the entries of current_dir and their `guts status` state
+    pub fn refresh_files(&mut self) {
+        self.file_entries = file_panel::load_entries(&self.current_dir);
+        if self.selected_file >= self.file_entries.len() {
+            self.selected_file = self.file_entries.len().saturating_sub(1);
+        }
+    }
+
+    // ======================= Status panel =======================
+    /// Opens the status dashboard, or closes it when it is shown
+    fn toggle_status_view(&mut self) {
+        self.status_view = match self.status_view {
+            Some(_) => None,
+            None => Some(status_panel::load(&self.current_dir)),
+        };
+    }
+
+    /// Reloads the status dashboard if it is open
+    pub fn refresh_status(&mut self) {
+        if self.status_view.is_some() {
+            self.status_view = Some(status_panel::load(&self.current_dir));
+        }
+    }
+
+    // ======================= Tabs =======================
+    /// Shows `tab`, reloading its data for current_dir
+    pub fn select_tab(&mut self, tab: Tab) {
+        self.tab = tab;
+        match tab {
+            Tab::Console => {}
+            Tab::Status => self.tab_status = status_panel::load(&self.current_dir),
+            Tab::Log => self.log_view = tabs::load_log(&self.current_dir),
+        }
+    }
+
+    /// Alt+←/→ and Alt+1-3 switch tabs from the console too, where the plain keys edit the input
+    fn tab_switch_key(&self, key: KeyEvent) -> Option<Tab> {
+        let plain = self.tab != Tab::Console;
+        if !plain && !key.modifiers.contains(KeyModifiers::ALT) {
+            return None;
+        }
+        match key.code {
+            KeyCode::Left => Some(self.tab.previous()),
+            KeyCode::Right => Some(self.tab.next()),
+            KeyCode::Char(c) => Tab::from_number(c),
+            _ => None,
+        }
+    }
+
+    fn handle_tab_key(&mut self, key: KeyEvent) -> Result<()> {
+        let page = self.max_visible_lines.max(1);
+        match key.code {
+            KeyCode::Esc => self.select_tab(Tab::Console),
+            KeyCode::Char('r') => self.select_tab(self.tab),
+            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.focus = Focus::Files;
+                self.refresh_files();
+            }
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.toggle_status_view();
+            }
+            KeyCode::Up => self.log_scroll(-1),
+            KeyCode::Down => self.log_scroll(1),
+            KeyCode::PageUp => self.log_scroll(-(page as i32)),
+            KeyCode::PageDown => self.log_scroll(page as i32),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Scrolls the Log tab by `delta` lines, up when negative
+    pub fn log_scroll(&mut self, delta: i32) {
+        let visible = self.history_area.height.saturating_sub(2) as usize;
+        let max_scroll = self.log_view.lines.len().saturating_sub(visible.max(1));
+        let offset = self.log_view.scroll_offset as i64 + delta as i64;
+        self.log_view.scroll_offset = (offset.max(0) as usize).min(max_scroll);
+    }
+
+    fn handle_file_panel_key(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc => self.focus = Focus::Console,
+            KeyCode::Up if self.selected_file > 0 => self.selected_file -= 1,
+            KeyCode::Down if self.selected_file + 1 < self.file_entries.len() => {
+                self.selected_file += 1
+            }
+            KeyCode::Char('r') => self.refresh_files(),
+            KeyCode::Char('a') => self.stage_selected_file(),
+            KeyCode::Char('d') => self.open_diff_view(),
+            KeyCode::Enter => {
+                // Enter a directory like `cd` would
+                if let Some(entry) = self.file_entries.get(self.selected_file) {
+                    if entry.is_dir {
+                        let command = format!("cd {}", entry.name);
+                        let result = self.handle_cd_command(&command);
+                        self.command_history.push(result);
+                        self.selected_file = 0;
+                        self.refresh_files();
+                        self.refresh_status();
+                        self.scroll_to_bottom();
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // ======================= Diff pane =======================
+    /// Opens the working tree diff of the highlighted file (or of every file in a directory)
+    fn open_diff_view(&mut self) {
+        let Some(entry) = self.file_entries.get(self.selected_file) else {
+            return;
+        };
+
+        let current_dir = std::path::PathBuf::from(&self.current_dir);
+        let diff_args = guts::commands::diff::DiffArgs {
+            cached: false,
+            name_only: false,
+            name_status: false,
+            commits: Vec::new(),
+        };
+        let diff = match guts::core::simple_index::with_dir(Some(&current_dir), || guts::commands::diff::run(&diff_args)) {
+            Ok(diff) => diff,
+            Err(e) => {
+                self.command_history.push(CommandResult {
+                    command: format!("guts diff {}", entry.name),
+                    output: String::new(),
+                    error: Some(e.to_string()),
+                });
+                self.scroll_to_bottom();
+                return;
+            }
+        };
+
+        // Diff paths are relative to the repository root
+        let repo_root = guts::core::simple_index::with_dir(Some(&current_dir), guts::core::simple_index::find_repo_root)
+            .unwrap_or_else(|_| current_dir.clone());
+        let relative = current_dir
+            .join(&entry.name)
+            .strip_prefix(&repo_root)
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_else(|_| entry.name.clone());
+
+        let lines = file_diff_lines(&diff, &relative, entry.is_dir);
+        let lines = if lines.is_empty() {
+            vec![format!("No changes in {}", relative)]
+        } else {
+            lines
+        };
+
+        self.diff_view = Some(DiffView {
+            title: format!("Diff {}", relative),
+            lines,
+            scroll_offset: 0,
+        });
+        // The diff pane replaces the command history
+        self.tab = Tab::Console;
+        self.focus = Focus::Diff;
+    }
+
+    fn handle_diff_key(&mut self, key: KeyEvent) -> Result<()> {
+        let page = self.max_visible_lines.max(1);
+        match key.code {
+            KeyCode::Esc => {
+                self.diff_view = None;
+                self.focus = Focus::Console;
+            }
+            KeyCode::Up => self.diff_scroll_up(1),
+            KeyCode::Down => self.diff_scroll_down(1),
+            KeyCode::PageUp => self.diff_scroll_up(page),
+            KeyCode::PageDown => self.diff_scroll_down(page),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    pub fn diff_scroll_up(&mut self, lines: usize) {
+        if let Some(view) = &mut self.diff_view {
+            view.scroll_offset = view.scroll_offset.saturating_sub(lines);
+        }
+    }
+
+    pub fn diff_scroll_down(&mut self, lines: usize) {
+        let max_visible = self.max_visible_lines;
+        if let Some(view) = &mut self.diff_view {
+            let max_scroll = view.lines.len().saturating_sub(max_visible);
+            view.scroll_offset = (view.scroll_offset + lines).min(max_scroll);
+        }
+    }
+
+    /// Runs `guts add` on the highlighted entry and logs it in the console
+    fn stage_selected_file(&mut self) {
+        let Some(entry) = self.file_entries.get(self.selected_file) else {
+            return;
+        };
+
+        let add_args = guts::commands::add::AddArgs {
+            files: vec![std::path::PathBuf::from(&entry.name)],
+            dry_run: false,
+            verbose: false,
+            quiet: true,
+        };
+        let command = format!("guts add {}", entry.name);
+        let current_dir = std::path::PathBuf::from(&self.current_dir);
+        let result = match guts::core::simple_index::with_dir(Some(&current_dir), || guts::commands::add::run(&add_args)) {
+            Ok(out) => CommandResult { command, output: out, error: None },
+            Err(e) => CommandResult { command, output: String::new(), error: Some(e.to_string()) },
+        };
+
+        self.command_history.push(result);
+        self.refresh_files();
+        self.refresh_status();
+        self.scroll_to_bottom();
     }
 
     // ======================= Line & Scroll =======================
@@ -110,6 +385,63 @@ impl App {
         self.max_visible_lines = if height > 8 { height - 6 } else { 2 };
     }
 
+    /// Scrolls the history pane (or the open diff) by `delta` lines, up when negative
+    pub fn apply_scroll(&mut self, delta: i32) {
+        if self.tab == Tab::Log {
+            self.log_scroll(delta);
+            return;
+        }
+        let lines = delta.unsigned_abs() as usize;
+        match (self.diff_view.is_some(), delta < 0) {
+            (true, true) => self.diff_scroll_up(lines),
+            (true, false) => self.diff_scroll_down(lines),
+            (false, true) => (0..lines).for_each(|_| self.scroll_up()),
+            (false, false) => (0..lines).for_each(|_| self.scroll_down()),
+        }
+    }
+
+    // ======================= Mouse =======================
+    /// Wheel over the history pane scrolls it; a click focuses the files panel or the
+    /// console, and in the input line moves the cursor under the pointer
+    pub fn handle_mouse_event(&mut self, mouse: MouseEvent) {
+        let (column, row) = (mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::ScrollUp if area_contains(self.history_area, column, row) => {
+                self.apply_scroll(-WHEEL_SCROLL_LINES)
+            }
+            MouseEventKind::ScrollDown if area_contains(self.history_area, column, row) => {
+                self.apply_scroll(WHEEL_SCROLL_LINES)
+            }
+            MouseEventKind::Down(MouseButton::Left) if self.focus != Focus::Diff => {
+                if area_contains(self.files_area, column, row) {
+                    self.focus = Focus::Files;
+                } else if area_contains(self.input_area, column, row) {
+                    self.focus = Focus::Console;
+                    // Text starts after the border and the prompt; the cursor is a byte index,
+                    // so the clicked column is mapped to the start of the character under it
+                    let text_start = self.input_area.x + 1 + self.prompt().chars().count() as u16;
+                    let clicked = column.saturating_sub(text_start) as usize;
+                    self.cursor_position = self
+                        .input
+                        .char_indices()
+                        .nth(clicked)
+                        .map_or(self.input.len(), |(index, _)| index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `<dir name>$ ` shown before the input
+    pub fn prompt(&self) -> String {
+        let current_dir = std::path::Path::new(&self.current_dir)
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        format!("{}$ ", current_dir)
+    }
+
     // ================= Auto complete: helpers =================
     fn update_autocomplete(&mut self) {
         use std::collections::HashSet;
@@ -121,6 +453,16 @@ impl App {
             return;
         }
 
+        // Past the command name, the token being typed is completed as a path
+        if let Some(paths) = self.path_suggestions() {
+            if !paths.is_empty() {
+                self.autocomplete_list = paths;
+                self.show_autocomplete = true;
+                self.autocomplete_index = 0;
+            }
+            return;
+        }
+
         let mut suggestions = HashSet::new();
 
         for history in &self.input_history {
@@ -154,7 +496,32 @@ impl App {
             "guts log",
             "guts ls-files",
             "guts show-ref",
-            "guts checkout"
+            "guts checkout",
+            "guts diff",
+            "guts reset",
+            "guts config",
+            "guts show",
+            "guts reflog",
+            "guts revert",
+            "guts cherry-pick",
+            "guts mv",
+            "guts clean",
+            "guts stash",
+            "guts clone",
+            "guts describe",
+            "guts blame",
+            "guts gc",
+            "guts fsck",
+            "guts switch",
+            "guts restore",
+            "guts remote",
+            "guts fetch",
+            "guts push",
+            "guts tag",
+            "guts count-objects",
+            "guts rev-list",
+            "guts check-ignore",
+            "guts export"
         ];
         for cmd in basic_cmds {
             if cmd.starts_with(&self.input) {
@@ -172,6 +539,51 @@ impl App {
         }
     }
 
+    /// Completions of the last whitespace-separated token as an entry of current_dir,
+    /// or None while the command itself (`cd`, `guts add`...) is still being typed
+    fn path_suggestions(&self) -> Option<Vec<String>> {
+        // Arguments are split the way the command will be run, quotes included
+        let (before_token, token) = self.input.split_at(current_argument_start(&self.input));
+        let previous = split_command(before_token).ok()?;
+        let command_tokens = if previous.first().is_some_and(|name| name == "guts") { 2 } else { 1 };
+        if previous.len() < command_tokens {
+            return None;
+        }
+
+        let Some(token) = partial_argument(token) else {
+            return Some(Vec::new());
+        };
+        // Options are not paths
+        if token.starts_with('-') {
+            return Some(Vec::new());
+        }
+
+        let (dir_part, name_part) = match token.rfind('/') {
+            Some(slash) => token.split_at(slash + 1),
+            None => ("", token.as_str()),
+        };
+        let Ok(entries) = std::fs::read_dir(std::path::Path::new(&self.current_dir).join(dir_part)) else {
+            return Some(Vec::new());
+        };
+
+        let mut suggestions: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                // Hidden entries only when explicitly asked for
+                if !name.starts_with(name_part) || (name.starts_with('.') && !name_part.starts_with('.')) {
+                    return None;
+                }
+                let suffix = if entry.path().is_dir() { "/" } else { "" };
+                // Names with spaces or quotes must stay one argument
+                let completed = format!("{}{}{}", dir_part, name, suffix);
+                Some(format!("{}{}", before_token, shell_words::quote(&completed)))
+            })
+            .collect();
+        suggestions.sort();
+        Some(suggestions)
+    }
+
     fn apply_autocomplete(&mut self) {
         if self.show_autocomplete && !self.autocomplete_list.is_empty() {
             if let Some(suggestion) = self.autocomplete_list.get(self.autocomplete_index) {
@@ -184,37 +596,48 @@ impl App {
 
     // ======================= EVENT KEY =======================
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Result<()> {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.should_quit = true;
+            return Ok(());
+        }
+        match self.focus {
+            Focus::Files => return self.handle_file_panel_key(key),
+            Focus::Diff => return self.handle_diff_key(key),
+            Focus::Console => {}
+        }
+        if let Some(tab) = self.tab_switch_key(key) {
+            self.select_tab(tab);
+            return Ok(());
+        }
+        if self.tab != Tab::Console {
+            return self.handle_tab_key(key);
+        }
+
         match key.code {
-            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.should_quit = true;
+            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.focus = Focus::Files;
+                self.refresh_files();
             }
-            KeyCode::Enter => {
-                if !self.input.trim().is_empty() {
-                    self.execute_command()?;
-                }
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.toggle_status_view();
             }
-            KeyCode::Backspace => {
-                if self.cursor_position > 0 {
-                    self.input.remove(self.cursor_position - 1);
-                    self.cursor_position -= 1;
-                    self.update_autocomplete();
-                }
+            KeyCode::Enter if !self.input.trim().is_empty() => {
+                self.execute_command()?;
             }
-            KeyCode::Delete => {
-                if self.cursor_position < self.input.len() {
-                    self.input.remove(self.cursor_position);
-                    self.update_autocomplete();
-                }
+            KeyCode::Backspace if self.cursor_position > 0 => {
+                self.input.remove(self.cursor_position - 1);
+                self.cursor_position -= 1;
+                self.update_autocomplete();
             }
-            KeyCode::Left => {
-                if self.cursor_position > 0 {
-                    self.cursor_position -= 1;
-                }
+            KeyCode::Delete if self.cursor_position < self.input.len() => {
+                self.input.remove(self.cursor_position);
+                self.update_autocomplete();
             }
-            KeyCode::Right => {
-                if self.cursor_position < self.input.len() {
-                    self.cursor_position += 1;
-                }
+            KeyCode::Left if self.cursor_position > 0 => {
+                self.cursor_position -= 1;
+            }
+            KeyCode::Right if self.cursor_position < self.input.len() => {
+                self.cursor_position += 1;
             }
             KeyCode::Up => {
                 // Ctrl+Up, scroll up
@@ -285,6 +708,9 @@ impl App {
         self.input.clear();
         self.cursor_position = 0;
         self.scroll_to_bottom();
+        // Any command may have changed files or their status
+        self.refresh_files();
+        self.refresh_status();
     }
 
     // ======================= EXECUTE COMMANDS =======================
@@ -293,10 +719,11 @@ impl App {
         self.last_executed_command = Some(command.clone());
 
 
-        if !command.is_empty() {
+        // Repeating the previous command does not add a new history entry
+        if !command.is_empty() && self.input_history.last() != Some(&command) {
             self.input_history.push(command.clone());
-            self.input_history_index = self.input_history.len();
         }
+        self.input_history_index = self.input_history.len();
 
         // interne command
         if command == "exit" || command == "quit" {
@@ -311,7 +738,7 @@ impl App {
             return Ok(());
         }
 
-        if command.starts_with("cd") {
+        if command == "cd" || command.starts_with("cd ") {
             let result = self.handle_cd_command(&command);
             self.command_history.push(result);
             self.finalize_command();
@@ -340,9 +767,18 @@ impl App {
 
     // ======================= CD Command Handler =======================
     fn handle_cd_command(&mut self, command: &str) -> CommandResult {
-        let parts: Vec<&str> = command.split_whitespace().collect();
+        let parts = match split_command(command) {
+            Ok(parts) => parts,
+            Err(e) => {
+                return CommandResult {
+                    command: command.to_string(),
+                    output: String::new(),
+                    error: Some(e),
+                }
+            }
+        };
         let target_dir = if parts.len() > 1 {
-            std::path::PathBuf::from(&self.current_dir).join(parts[1])
+            std::path::PathBuf::from(&self.current_dir).join(&parts[1])
         } else {
             std::env::var("HOME").unwrap_or_else(|_| self.current_dir.clone()).into()
         };
@@ -371,14 +807,13 @@ impl App {
         terminal: &mut Terminal<CrosstermBackend<Stdout>>,
         command: &str,
     ) -> Result<()> {
-        use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+        use crossterm::terminal::disable_raw_mode;
         use std::io::{self, Write};
         use std::path::PathBuf;
         use std::process::Command;
 
         // out of the terminal
         terminal.clear()?; // clear tui
-        drop(terminal);
         disable_raw_mode()?; // out raw mode
 
         // clear terminal
@@ -386,8 +821,8 @@ impl App {
         io::stdout().flush().unwrap();
 
         // command parse
-        let parts: Vec<&str> = command.split_whitespace().collect();
-        let editor = parts[0];
+        let parts = split_command(command).unwrap_or_else(|_| command.split_whitespace().map(String::from).collect());
+        let editor = &parts[0];
         let args = &parts[1..];
 
         // fix bug onedrive
@@ -404,7 +839,7 @@ impl App {
         let status = if cfg!(target_os = "windows") {
             let full_command = format!("{} {}", editor, args.join(" "));
             Command::new("cmd")
-                .args(&["/C", &full_command])
+                .args(["/C", &full_command])
                 .current_dir(&safe_dir)
                 .status()
         } else {
@@ -446,41 +881,45 @@ impl App {
 
     // ======================= Shell Command Handler =======================
     fn execute_shell_command(&self, command: &str) -> CommandResult {
-        let cleaned_dir = if self.current_dir.starts_with(r"\\?\") {
-            self.current_dir.trim_start_matches(r"\\?\\").to_string()
-        } else {
-            self.current_dir.clone()
-        };
+        // Canonicalized Windows paths carry a `\\?\` prefix the shells do not accept
+        let dir = self.current_dir.strip_prefix(r"\\?\").unwrap_or(&self.current_dir);
 
         #[cfg(target_os = "windows")]
-        let shell_result = Command::new("powershell")
-            .arg("-Command")
-            .arg(command)
-            .current_dir(&cleaned_dir)
-            .output();
-
+        let mut shell = {
+            let mut shell = Command::new("powershell");
+            shell.arg("-Command");
+            shell
+        };
         #[cfg(not(target_os = "windows"))]
-        let shell_result = Command::new("sh")
-            .arg("-c")
-            .arg(command)
-            .current_dir(&self.current_dir)
-            .output();
+        let mut shell = {
+            let mut shell = Command::new("sh");
+            shell.arg("-c");
+            shell
+        };
 
-        match shell_result {
+        match shell.arg(command).current_dir(dir).output() {
             Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+                let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
 
-                let combined_output = if !stderr.is_empty() {
-                    format!("{}\n{}", stdout, stderr)
+                // A failing command shows its stderr (or exit status) as the error
+                let error = if output.status.success() {
+                    None
+                } else if stderr.is_empty() {
+                    Some(format!("Command failed: {}", output.status))
+                } else {
+                    Some(stderr.clone())
+                };
+                let output = if error.is_none() && !stderr.is_empty() {
+                    format!("{}\n{}", stdout, stderr).trim().to_string()
                 } else {
                     stdout
                 };
 
                 CommandResult {
                     command: command.to_string(),
-                    output: combined_output.trim().to_string(),
-                    error: None,
+                    output,
+                    error,
                 }
             }
             Err(e) => CommandResult {
@@ -493,16 +932,41 @@ impl App {
 
     // ======================= Handles only guts subcommands =======================
     fn execute_guts_command(&mut self, command: &str) -> Result<CommandResult> {
-        let args: Vec<&str> = command.split_whitespace().collect();
+        let args = match split_command(command) {
+            Ok(args) => args,
+            Err(e) => {
+                return Ok(CommandResult {
+                    command: command.to_string(),
+                    output: String::new(),
+                    error: Some(e),
+                })
+            }
+        };
+
+        let cli = match Cli::try_parse_from(args) {
+            Ok(cli) => cli,
+            Err(e) => {
+                return Ok(CommandResult {
+                    command: command.to_string(),
+                    output: String::new(),
+                    error: Some(e.to_string()),
+                })
+            }
+        };
 
-        match Cli::try_parse_from(args) {
-            Ok(cli) => {
+        // Commands find the repository from the current directory: run them from the one
+        // of the TUI, then from wherever -C, --git-dir or --work-tree point
+        let original_dir = std::env::current_dir()?;
+        std::env::set_current_dir(&self.current_dir)?;
+        let result = match cli.enter_repository() {
+            Err(e) => Ok(CommandResult {
+                command: command.to_string(),
+                output: String::new(),
+                error: Some(e.to_string()),
+            }),
+            Ok(()) => {
                 match cli.command {
-                    Commands::Init(mut init_args) => {
-                        // Use TUI current directory if no directory specified
-                        if init_args.dir.is_none() {
-                            init_args.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        }
+                    Commands::Init(init_args) => {
                         match guts::commands::init::run(&init_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
@@ -516,10 +980,54 @@ impl App {
                             }),
                         }
                     }
-                    Commands::HashObject(mut hash_args) => {
-                        // Inject current TUI directory
-                        hash_args.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        match guts::commands::hash_object::run(&hash_args) {
+                    Commands::HashObject(hash_args) => {
+                        // --stdin would wait for stdin to close, which never happens in the TUI
+                        if hash_args.stdin {
+                            Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some("hash-object --stdin reads stdin, which the TUI does not have".to_string()),
+                            })
+                        } else {
+                            match guts::commands::hash_object::run(&hash_args) {
+                                Ok(out) => Ok(CommandResult {
+                                    command: command.to_string(),
+                                    output: out,
+                                    error: None,
+                                }),
+                                Err(e) => Ok(CommandResult {
+                                    command: command.to_string(),
+                                    output: String::new(),
+                                    error: Some(e.to_string()),
+                                }),
+                            }
+                        }
+                    }
+                    Commands::CatFile(cat_args) => {
+                        // --batch would wait for stdin to close, which never happens in the TUI
+                        if cat_args.batch {
+                            Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some("cat-file --batch reads stdin, which the TUI does not have".to_string()),
+                            })
+                        } else {
+                            match guts::commands::cat_file::run(&cat_args) {
+                                Ok(out) => Ok(CommandResult {
+                                    command: command.to_string(),
+                                    output: out,
+                                    error: None,
+                                }),
+                                Err(e) => Ok(CommandResult {
+                                    command: command.to_string(),
+                                    output: String::new(),
+                                    error: Some(e.to_string()),
+                                }),
+                            }
+                        }
+                    }
+                    Commands::WriteTree(tree_args) => {
+                        match guts::commands::write_tree::run(&tree_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
                                 output: out,
@@ -532,10 +1040,33 @@ impl App {
                             }),
                         }
                     }
-                    Commands::CatFile(mut cat_args) => {
-                        // Inject current TUI directory
-                        cat_args.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        match guts::commands::cat_file::run(&cat_args) {
+                    Commands::CommitTree(commit_args) => {
+                        // There is no stdin to read a message from in the TUI
+                        if commit_args.message.is_none() {
+                            Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some("commit-tree needs a message (-m) in the TUI".to_string()),
+                            })
+                        } else {
+                            match guts::commands::commit_tree::run(&commit_args) {
+                                Ok(out) => Ok(CommandResult {
+                                    command: command.to_string(),
+                                    output: out,
+                                    error: None,
+                                }),
+                                Err(e) => Ok(CommandResult {
+                                    command: command.to_string(),
+                                    output: String::new(),
+                                    error: Some(e.to_string()),
+                                }),
+                            }
+                        }
+                    }
+                    Commands::Status(mut status_args) => {
+                        // The output panel shows plain text
+                        status_args.color = ColorWhen::Never;
+                        match guts::commands::status::run(&status_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
                                 output: out,
@@ -548,10 +1079,10 @@ impl App {
                             }),
                         }
                     }
-                    Commands::WriteTree(mut tree_args) => {
-                        // Inject current TUI directory
-                        tree_args.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        match guts::commands::write_tree::run(&tree_args) {
+                    Commands::Add(mut add_args) => {
+                        // The output is captured: a progress line would land on the screen
+                        add_args.quiet = true;
+                        match guts::commands::add::run(&add_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
                                 output: out,
@@ -564,10 +1095,22 @@ impl App {
                             }),
                         }
                     }
-                    Commands::CommitTree(mut commit_args) => {
-                        // Inject current TUI directory
-                        commit_args.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        match guts::commands::commit_tree::run(&commit_args) {
+                    Commands::Rm(rm_args) => {
+                        match guts::commands::rm::run(&rm_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                error: Some(e.to_string()),
+                                output: String::new(),
+                            }),
+                        }
+                    }
+                    Commands::Commit(commit_args) => {
+                        match guts::commands::commit::run(&commit_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
                                 output: out,
@@ -580,10 +1123,8 @@ impl App {
                             }),
                         }
                     }
-                    Commands::Status(mut status_args) => {
-                        // Inject current TUI directory
-                        status_args.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        match guts::commands::status::run(&status_args) {
+                    Commands::RevParse(rev_parse_args) => {
+                        match guts::commands::rev_parse::run(&rev_parse_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
                                 output: out,
@@ -596,10 +1137,10 @@ impl App {
                             }),
                         }
                     }
-                    Commands::Add(mut add_args) => {
-                        // Inject current TUI directory
-                        add_args.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        match guts::commands::add::run(&add_args) {
+                    Commands::Log(mut log_args) => {
+                        // The output panel shows plain text
+                        log_args.color = ColorWhen::Never;
+                        match guts::commands::log::run(&log_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
                                 output: out,
@@ -612,10 +1153,8 @@ impl App {
                             }),
                         }
                     }
-                    Commands::Rm(mut rm_args) => {
-                        // Inject current TUI directory
-                        rm_args.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        match guts::commands::rm::run(&rm_args) {
+                    Commands::ShowRef(show_ref_args) => {
+                        match guts::commands::show_ref::run(&show_ref_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
                                 output: out,
@@ -623,15 +1162,28 @@ impl App {
                             }),
                             Err(e) => Ok(CommandResult {
                                 command: command.to_string(),
+                                output: String::new(),
                                 error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Checkout(checkout_object) => {
+                        match guts::commands::checkout::run(&checkout_object) {
+                            Ok(out) => Ok(CommandResult {
+
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
                                 output: String::new(),
+                                error: Some(e.to_string()),
                             }),
                         }
-                    }
-                    Commands::Commit(mut commit_args) => {
-                        // Inject current TUI directory
-                        commit_args.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        match guts::commands::commit::run(&commit_args) {
+                    },
+                    Commands::LsTree(ls_tree_args) => {
+                        match guts::commands::ls_tree::run(&ls_tree_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
                                 output: out,
@@ -643,9 +1195,9 @@ impl App {
                                 error: Some(e.to_string()),
                             }),
                         }
-                    }
-                    Commands::RevParse(rev_parse_args) => {
-                        match guts::commands::rev_parse::run(&rev_parse_args) {
+                    },
+                    Commands::LsFiles(ls_files_args) => {
+                        match guts::commands::ls_files::run(&ls_files_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
                                 output: out,
@@ -657,11 +1209,9 @@ impl App {
                                 error: Some(e.to_string()),
                             }),
                         }
-                    }
-                    Commands::Log(mut log_args) => {
-                        // Inject current TUI directory
-                        log_args.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        match guts::commands::log::run(&log_args) {
+                    },
+                    Commands::Merge(merge_args) => {
+                        match guts::commands::merge::run(&merge_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
                                 output: out,
@@ -673,11 +1223,9 @@ impl App {
                                 error: Some(e.to_string()),
                             }),
                         }
-                    }
-                    Commands::ShowRef(mut show_ref_args) => {
-                        // Inject current TUI directory
-                        show_ref_args.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        match guts::commands::show_ref::run(&show_ref_args) {
+                    },
+                    Commands::Diff(diff_args) => {
+                        match guts::commands::diff::run(&diff_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
                                 output: out,
@@ -690,11 +1238,9 @@ impl App {
                             }),
                         }
                     },
-                    Commands::Checkout(mut checkout_object) => {
-                        checkout_object.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        match guts::commands::checkout::run(&checkout_object) {
+                    Commands::Reset(reset_args) => {
+                        match guts::commands::reset::run(&reset_args) {
                             Ok(out) => Ok(CommandResult {
-
                                 command: command.to_string(),
                                 output: out,
                                 error: None,
@@ -706,9 +1252,8 @@ impl App {
                             }),
                         }
                     },
-                    Commands::LsTree(mut ls_tree_args) => {
-                        ls_tree_args.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        match guts::commands::ls_tree::run(&ls_tree_args) {
+                    Commands::Config(config_args) => {
+                        match guts::commands::config::run(&config_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
                                 output: out,
@@ -721,8 +1266,8 @@ impl App {
                             }),
                         }
                     },
-                    Commands::LsFiles(ls_files_args) => {
-                        match guts::commands::ls_files::run(&ls_files_args) {
+                    Commands::Show(show_args) => {
+                        match guts::commands::show::run(&show_args) {
                             Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
                                 output: out,
@@ -735,12 +1280,11 @@ impl App {
                             }),
                         }
                     },
-                    Commands::Merge(mut merge_args) => {
-                        merge_args.dir = Some(std::path::PathBuf::from(&self.current_dir));
-                        match guts::commands::merge::run(&merge_args) {
-                            Ok(_) => Ok(CommandResult { 
+                    Commands::Reflog(reflog_args) => {
+                        match guts::commands::reflog::run(&reflog_args) {
+                            Ok(out) => Ok(CommandResult {
                                 command: command.to_string(),
-                                output: format!("Merged branch {:?}", merge_args.name),
+                                output: out,
                                 error: None,
                             }),
                             Err(e) => Ok(CommandResult {
@@ -750,23 +1294,301 @@ impl App {
                             }),
                         }
                     },
-                    Commands::Tui => Ok(CommandResult {
-                        command: command.to_string(),
-                        output: String::new(),
-                        error: Some("Cannot launch TUI from within TUI".to_string()),
-                    }),
-                }
+                    Commands::Revert(revert_args) => {
+                        match guts::commands::revert::run(&revert_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::CherryPick(cherry_pick_args) => {
+                        match guts::commands::cherry_pick::run(&cherry_pick_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Mv(mv_args) => {
+                        match guts::commands::mv::run(&mv_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Clean(clean_args) => {
+                        match guts::commands::clean::run(&clean_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Stash(stash_args) => {
+                        match guts::commands::stash::run(&stash_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Clone(clone_args) => {
+                        match guts::commands::clone::run(&clone_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Describe(describe_args) => {
+                        match guts::commands::describe::run(&describe_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Blame(blame_args) => {
+                        match guts::commands::blame::run(&blame_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Gc(gc_args) => {
+                        match guts::commands::gc::run(&gc_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Fsck(fsck_args) => {
+                        match guts::commands::fsck::run(&fsck_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Switch(switch_args) => {
+                        match guts::commands::switch::run(&switch_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Restore(restore_args) => {
+                        match guts::commands::restore::run(&restore_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Remote(remote_args) => {
+                        match guts::commands::remote::run(&remote_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Fetch(fetch_args) => {
+                        match guts::commands::fetch::run(&fetch_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Push(push_args) => {
+                        match guts::commands::push::run(&push_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Tag(tag_args) => {
+                        match guts::commands::tag::run(&tag_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::CountObjects(count_objects_args) => {
+                        match guts::commands::count_objects::run(&count_objects_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::RevList(rev_list_args) => {
+                        match guts::commands::rev_list::run(&rev_list_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::CheckIgnore(check_ignore_args) => {
+                        match guts::commands::check_ignore::run(&check_ignore_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Export(export_args) => {
+                        match guts::commands::export::run(&export_args) {
+                            Ok(out) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: out,
+                                error: None,
+                            }),
+                            Err(e) => Ok(CommandResult {
+                                command: command.to_string(),
+                                output: String::new(),
+                                error: Some(e.to_string()),
+                            }),
+                        }
+                    },
+                    Commands::Tui => Ok(CommandResult {
+                        command: command.to_string(),
+                        output: String::new(),
+                        error: Some("Cannot launch TUI from within TUI".to_string()),
+                    }),
+                }
             }
-            Err(e) => Ok(CommandResult {
-                command: command.to_string(),
-                output: String::new(),
-                error: Some(e.to_string()),
-            }),
-        }
+        };
+        std::env::set_current_dir(&original_dir)?;
+        result
     }
 
     // ======================= System COMMANDS =======================
     // Executes shell/system-level commands
+    #[allow(dead_code)]
     fn execute_system_command(&mut self, command: &str) -> Result<CommandResult> {
         let parts: Vec<&str> = command.split_whitespace().collect();
         if parts.is_empty() {
@@ -806,4 +1628,288 @@ impl App {
             }),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Lines of the `diff --git` sections of `diff` about `path` (or about any file under it for a directory)
+fn file_diff_lines(diff: &str, path: &str, is_dir: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut in_section = false;
+
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("diff --git a/") {
+            let section_path = header.split(" b/").next().unwrap_or("");
+            in_section = section_path == path
+                || (is_dir && section_path.starts_with(&format!("{}/", path)));
+        }
+        if in_section {
+            lines.push(line.to_string());
+        }
+    }
+
+    lines
+}
+
+/// Splits a console line into arguments like a shell would: quotes group words
+/// (`-m "two words"`) and backslashes escape
+fn split_command(command: &str) -> std::result::Result<Vec<String>, String> {
+    shell_words::split(command).map_err(|e| format!("error: {}", e))
+}
+
+/// Byte offset where the argument being typed starts: after the last space that is
+/// neither quoted nor escaped, following the rules of `split_command`
+fn current_argument_start(input: &str) -> usize {
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in input.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match (quote, c) {
+            (Some('\''), '\'') => quote = None,
+            (Some('\''), _) => {}
+            (_, '\\') => escaped = true,
+            (Some(_), '"') => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, c) if c.is_whitespace() => start = index + c.len_utf8(),
+            _ => {}
+        }
+    }
+    start
+}
+
+/// Value of an argument still being typed, closing the quote it may have left open
+fn partial_argument(token: &str) -> Option<String> {
+    ["", "'", "\""].iter().find_map(|close| match split_command(&format!("{}{}", token, close)) {
+        Ok(words) if words.len() <= 1 => Some(words.into_iter().next().unwrap_or_default()),
+        _ => None,
+    })
+}
+
+fn area_contains(area: Rect, column: u16, row: u16) -> bool {
+    column >= area.x && column < area.x + area.width && row >= area.y && row < area.y + area.height
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app_with_history(commands: usize) -> App {
+        let mut app = App {
+            max_visible_lines: 5,
+            ..App::default()
+        };
+        for i in 0..commands {
+            app.command_history.push(CommandResult {
+                command: format!("guts log {}", i),
+                output: String::new(),
+                error: None,
+            });
+        }
+        app
+    }
+
+    #[test]
+    fn test_apply_scroll_moves_and_clamps_history() {
+        // Two lines per command: 20 in total, 15 of them above the visible ones at most
+        let mut app = app_with_history(10);
+
+        app.apply_scroll(3);
+        assert_eq!(app.scroll_offset, 3);
+        app.apply_scroll(-1);
+        assert_eq!(app.scroll_offset, 2);
+        app.apply_scroll(100);
+        assert_eq!(app.scroll_offset, 15);
+        app.apply_scroll(-100);
+        assert_eq!(app.scroll_offset, 0);
+
+        // Everything fits: nothing to scroll
+        let mut app = app_with_history(2);
+        app.apply_scroll(3);
+        assert_eq!(app.scroll_offset, 0);
+    }
+
+    fn run_console(app: &mut App, command: &str) {
+        app.input = command.to_string();
+        app.cursor_position = app.input.len();
+        app.execute_command().unwrap();
+    }
+
+    #[test]
+    fn test_execute_command_cd_and_failing_shell_command() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let root = temp.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("sub")).unwrap();
+        let mut app = App {
+            current_dir: root.join("sub").to_string_lossy().to_string(),
+            ..App::default()
+        };
+
+        run_console(&mut app, "cd ..");
+        assert_eq!(app.current_dir, root.to_string_lossy());
+        assert_eq!(app.command_history.len(), 1);
+        assert!(app.command_history[0].error.is_none());
+        assert!(app.input.is_empty());
+
+        run_console(&mut app, "guts-no-such-command-here");
+        let result = app.command_history.last().unwrap();
+        assert_eq!(app.command_history.len(), 2);
+        assert!(result.error.is_some(), "{:?}", result);
+        // The shell ran in the new directory
+        assert_eq!(app.current_dir, root.to_string_lossy());
+    }
+
+    #[test]
+    fn test_guts_command_keeps_quoted_arguments() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        std::fs::create_dir(temp.path().join("my dir")).unwrap();
+        std::fs::write(temp.path().join("file.txt"), "content\n").unwrap();
+        let mut app = App {
+            current_dir: temp.path().to_string_lossy().to_string(),
+            ..App::default()
+        };
+
+        run_console(&mut app, "guts init");
+        run_console(&mut app, "guts add file.txt");
+        run_console(&mut app, "guts commit -m \"two words\"");
+        let result = app.command_history.last().unwrap();
+        assert!(result.error.is_none(), "{:?}", result);
+        assert!(result.output.ends_with("] two words"), "{:?}", result);
+
+        run_console(&mut app, "cd 'my dir'");
+        assert!(app.current_dir.ends_with("my dir"), "{}", app.current_dir);
+
+        // An unclosed quote is reported instead of guessed at
+        run_console(&mut app, "guts commit -m \"oops");
+        assert!(app.command_history.last().unwrap().error.is_some());
+    }
+
+    #[test]
+    fn test_guts_commands_reading_stdin_are_refused() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        let mut app = App {
+            current_dir: temp.path().to_string_lossy().to_string(),
+            ..App::default()
+        };
+
+        run_console(&mut app, "guts init");
+        run_console(&mut app, "guts cat-file --batch");
+        let result = app.command_history.last().unwrap();
+        assert!(result.error.as_deref().unwrap_or_default().contains("--batch"), "{:?}", result);
+
+        run_console(&mut app, "guts hash-object --stdin");
+        let result = app.command_history.last().unwrap();
+        assert!(result.error.as_deref().unwrap_or_default().contains("--stdin"), "{:?}", result);
+    }
+
+    #[test]
+    fn test_click_in_input_lands_on_a_char_boundary() {
+        let mut app = App {
+            current_dir: "/tmp/dé".to_string(),
+            input: "héllo".to_string(),
+            input_area: Rect::new(0, 0, 40, 3),
+            ..App::default()
+        };
+        let click = |column| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row: 1,
+            modifiers: KeyModifiers::NONE,
+        };
+
+        // Border, then the 4 characters of `dé$ `, then `hé`
+        app.handle_mouse_event(click(1 + 4 + 2));
+        assert_eq!(app.cursor_position, "hé".len());
+        app.handle_mouse_event(click(30));
+        assert_eq!(app.cursor_position, app.input.len());
+    }
+
+    #[test]
+    fn test_merge_reports_the_command_output() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("file.txt"), "content\n").unwrap();
+        let mut app = App {
+            current_dir: temp.path().to_string_lossy().to_string(),
+            ..App::default()
+        };
+
+        run_console(&mut app, "guts init");
+        run_console(&mut app, "guts add file.txt");
+        run_console(&mut app, "guts commit -m first");
+        run_console(&mut app, "guts checkout -b other");
+        run_console(&mut app, "guts checkout main");
+        run_console(&mut app, "guts merge other");
+        let result = app.command_history.last().unwrap();
+        assert_eq!(result.output, "Already up to date.", "{:?}", result);
+    }
+
+    #[test]
+    fn test_path_completion_quotes_names_with_spaces() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("my file.txt"), "content\n").unwrap();
+        std::fs::create_dir(temp.path().join("my dir")).unwrap();
+        let mut app = App {
+            current_dir: temp.path().to_string_lossy().to_string(),
+            ..App::default()
+        };
+
+        app.input = "guts add my".to_string();
+        app.update_autocomplete();
+        assert_eq!(app.autocomplete_list, ["guts add 'my dir/'", "guts add 'my file.txt'"]);
+        assert_eq!(split_command(&app.autocomplete_list[1]).unwrap(), ["guts", "add", "my file.txt"]);
+
+        // A quote left open still completes, and earlier quoted arguments are kept as typed
+        app.input = "guts commit -m \"two words\" 'my f".to_string();
+        app.update_autocomplete();
+        assert_eq!(app.autocomplete_list, ["guts commit -m \"two words\" 'my file.txt'"]);
+    }
+
+    #[test]
+    fn test_tabs_switch_with_keys_and_load_live_data() {
+        let temp = assert_fs::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("file.txt"), "content\n").unwrap();
+        let mut app = App {
+            current_dir: temp.path().to_string_lossy().to_string(),
+            ..App::default()
+        };
+        run_console(&mut app, "guts init");
+        run_console(&mut app, "guts add file.txt");
+        run_console(&mut app, "guts commit -m first");
+        std::fs::write(temp.path().join("new.txt"), "new\n").unwrap();
+
+        let press = |app: &mut App, code: KeyCode, modifiers: KeyModifiers| {
+            app.handle_key_event(KeyEvent::new(code, modifiers)).unwrap();
+        };
+
+        // In the console, plain arrows and digits edit the input
+        app.input = "ab".to_string();
+        app.cursor_position = 2;
+        press(&mut app, KeyCode::Left, KeyModifiers::NONE);
+        press(&mut app, KeyCode::Char('3'), KeyModifiers::NONE);
+        assert_eq!((app.tab, app.input.as_str()), (Tab::Console, "a3b"));
+
+        press(&mut app, KeyCode::Char('3'), KeyModifiers::ALT);
+        assert_eq!(app.tab, Tab::Log);
+        assert_eq!(app.log_view.lines.len(), 1);
+        assert!(app.log_view.lines[0].ends_with(" first"), "{:?}", app.log_view);
+
+        press(&mut app, KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(app.tab, Tab::Status);
+        assert_eq!(app.tab_status.untracked, ["new.txt"]);
+
+        // A second commit shows up when the Log tab is selected again
+        run_console(&mut app, "guts add new.txt");
+        run_console(&mut app, "guts commit -m second");
+        press(&mut app, KeyCode::Right, KeyModifiers::NONE);
+        assert_eq!(app.tab, Tab::Log);
+        assert!(app.log_view.lines[0].ends_with(" second"), "{:?}", app.log_view);
+
+        press(&mut app, KeyCode::Right, KeyModifiers::NONE);
+        assert_eq!(app.tab, Tab::Console);
+        press(&mut app, KeyCode::Char('2'), KeyModifiers::ALT);
+        press(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(app.tab, Tab::Console);
+    }
+}
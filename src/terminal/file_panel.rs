@@ -0,0 +1,92 @@
+use guts::commands::status::{self, StatusObject};
+use guts::core::color::ColorWhen;
+use guts::core::simple_index::with_dir;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// State of a file (or of the most relevant file inside a directory) for `guts status`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FileStatus {
+    Clean,
+    Untracked,
+    Staged,
+    /// Changed in the working tree and not staged yet
+    Modified,
+}
+
+/// One row of the file browser
+#[derive(Debug, Clone)]
+pub struct FileEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub status: FileStatus,
+}
+
+/// Lists the entries of `dir` (directories first, `.git` hidden), colored by `guts status`
+pub fn load_entries(dir: &str) -> Vec<FileEntry> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let statuses = with_dir(Some(&PathBuf::from(dir)), || {
+        status::run(&StatusObject {
+            short: true,
+            color: ColorWhen::Never,
+            ignored: false,
+        })
+    })
+    .map(|output| parse_short_status(&output))
+    .unwrap_or_default();
+
+    let mut entries: Vec<FileEntry> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name() != ".git")
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let is_dir = entry.path().is_dir();
+            let status = if is_dir {
+                // A directory shows the most urgent state of the files below it
+                let prefix = format!("{}/", name);
+                statuses
+                    .iter()
+                    .filter(|(path, _)| path.starts_with(&prefix))
+                    .map(|(_, status)| *status)
+                    .max()
+                    .unwrap_or(FileStatus::Clean)
+            } else {
+                statuses.get(&name).copied().unwrap_or(FileStatus::Clean)
+            };
+            FileEntry { name, is_dir, status }
+        })
+        .collect();
+
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    entries
+}
+
+/// Reads `guts status --short` output into `path -> status`.
+/// Anything that is not an `XY path` line (e.g. outside a repository) is skipped.
+fn parse_short_status(output: &str) -> HashMap<String, FileStatus> {
+    let mut statuses = HashMap::new();
+
+    for line in output.lines() {
+        let mut codes = line.chars();
+        let (Some(index_code), Some(work_code), Some(' ')) = (codes.next(), codes.next(), codes.next())
+        else {
+            continue;
+        };
+        // A rename is reported as `old -> new`: the file now lives at the new path
+        let path = codes.as_str().rsplit(" -> ").next().unwrap_or_default();
+
+        let status = match (index_code, work_code) {
+            ('?', '?') => FileStatus::Untracked,
+            (_, 'M' | 'D') => FileStatus::Modified,
+            ('M' | 'A' | 'D' | 'R', ' ') => FileStatus::Staged,
+            _ => continue,
+        };
+        statuses.insert(path.to_string(), status);
+    }
+
+    statuses
+}
@@ -0,0 +1,62 @@
+use anyhow::{Context, Result};
+use guts::core::config;
+use std::fs;
+use std::path::PathBuf;
+
+/// Number of commands kept when `tui.historySize` is not configured
+pub const DEFAULT_HISTORY_SIZE: usize = 1000;
+
+/// File holding the console history between sessions, `~/.guts_history`
+pub fn history_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".guts_history"))
+}
+
+/// Maximum number of commands to keep, from the `tui.historySize` config key
+pub fn history_size() -> usize {
+    config::get("tui.historysize")
+        .ok()
+        .flatten()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(DEFAULT_HISTORY_SIZE)
+}
+
+/// Loads the saved history, oldest first. A missing or unreadable file gives an empty history.
+pub fn load() -> Vec<String> {
+    let Some(content) = history_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+
+    let lines: Vec<String> = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.to_string())
+        .collect();
+    trim(dedup_consecutive(lines), history_size())
+}
+
+/// Writes the history, without consecutive duplicates and capped to the configured size
+pub fn save(history: &[String]) -> Result<()> {
+    let Some(path) = history_path() else {
+        return Ok(());
+    };
+
+    let lines = trim(dedup_consecutive(history.to_vec()), history_size());
+    let mut content = lines.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    fs::write(&path, content).with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn dedup_consecutive(mut lines: Vec<String>) -> Vec<String> {
+    lines.dedup();
+    lines
+}
+
+/// Keeps the `max` most recent commands
+fn trim(mut lines: Vec<String>, max: usize) -> Vec<String> {
+    if lines.len() > max {
+        lines.drain(..lines.len() - max);
+    }
+    lines
+}
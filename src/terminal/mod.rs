@@ -1,4 +1,8 @@
 pub mod app;
+pub mod file_panel;
+pub mod history;
 pub mod run_app;
+pub mod status_panel;
+pub mod tabs;
 pub mod ui;
 pub use run_app::run_app;
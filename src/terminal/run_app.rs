@@ -19,6 +19,7 @@ pub fn run_app() -> Result<()> {
     // create app and run it
     let mut app = App::new();
     let res = run_app_loop(&mut terminal, &mut app);
+    let saved = app.save_history();
 
     // restore terminal
     disable_raw_mode()?;
@@ -29,7 +30,7 @@ pub fn run_app() -> Result<()> {
     )?;
     terminal.show_cursor()?;
 
-    if let Err(err) = res {
+    if let Err(err) = res.and(saved) {
         println!("{:?}", err);
     }
 
@@ -44,7 +45,11 @@ fn run_app_loop(
     loop {
         terminal.draw(|f| ui::render(f, app))?;
 
-        if let Event::Key(key) = event::read()? {
+        let event = event::read()?;
+        if let Event::Mouse(mouse) = event {
+            app.handle_mouse_event(mouse);
+        }
+        if let Event::Key(key) = event {
             if key.kind == KeyEventKind::Press {
                 app.handle_key_event(key)?;
 
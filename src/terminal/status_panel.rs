@@ -0,0 +1,61 @@
+use guts::commands::status::{self, StatusObject};
+use guts::core::color::ColorWhen;
+use guts::core::simple_index::with_dir;
+use std::path::PathBuf;
+
+/// The files of `guts status`, one list per section of its long format
+#[derive(Debug, Clone, Default)]
+pub struct StatusView {
+    /// `<code> <path>` lines, e.g. `M src/main.rs`
+    pub staged: Vec<String>,
+    pub unstaged: Vec<String>,
+    pub untracked: Vec<String>,
+    /// Why there is nothing to show, e.g. outside a repository
+    pub error: Option<String>,
+}
+
+/// Runs `guts status` in `dir` and sorts its files into the three sections
+pub fn load(dir: &str) -> StatusView {
+    let output = with_dir(Some(&PathBuf::from(dir)), || {
+        status::run(&StatusObject {
+            short: true,
+            color: ColorWhen::Never,
+            ignored: false,
+        })
+    });
+    match output {
+        Ok(output) => parse_short_status(&output),
+        Err(e) => StatusView {
+            error: Some(e.to_string()),
+            ..StatusView::default()
+        },
+    }
+}
+
+/// Reads `guts status --short` output: X is the index column, Y the working tree one.
+/// A file both staged and changed again (`MM`) is listed in both sections.
+fn parse_short_status(output: &str) -> StatusView {
+    let mut view = StatusView::default();
+
+    for line in output.lines() {
+        let mut codes = line.chars();
+        let (Some(index_code), Some(work_code), Some(' ')) = (codes.next(), codes.next(), codes.next())
+        else {
+            continue;
+        };
+        let path = codes.as_str();
+
+        if (index_code, work_code) == ('?', '?') {
+            view.untracked.push(path.to_string());
+            continue;
+        }
+        if index_code != ' ' {
+            view.staged.push(format!("{} {}", index_code, path));
+        }
+        if work_code != ' ' {
+            view.unstaged.push(format!("{} {}", work_code, path));
+        }
+    }
+
+    view
+}
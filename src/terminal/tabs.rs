@@ -0,0 +1,148 @@
+use crate::terminal::app::App;
+use crate::terminal::ui;
+use guts::commands::log::{self, LogArgs};
+use guts::core::color::ColorWhen;
+use guts::core::simple_index::with_dir;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Tabs, Wrap},
+    Frame,
+};
+use std::path::PathBuf;
+
+/// View shown in the right-hand pane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tab {
+    /// Command history and input line
+    Console,
+    /// `guts status` of the current directory
+    Status,
+    /// `guts log --oneline` of the current directory
+    Log,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 3] = [Tab::Console, Tab::Status, Tab::Log];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Tab::Console => "Console",
+            Tab::Status => "Status",
+            Tab::Log => "Log",
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Tab::ALL.iter().position(|tab| *tab == self).unwrap_or(0)
+    }
+
+    /// Tab of the number key `1`, `2` or `3`
+    pub fn from_number(key: char) -> Option<Tab> {
+        let number = key.to_digit(10)? as usize;
+        Tab::ALL.get(number.checked_sub(1)?).copied()
+    }
+
+    pub fn next(self) -> Tab {
+        Tab::ALL[(self.index() + 1) % Tab::ALL.len()]
+    }
+
+    pub fn previous(self) -> Tab {
+        Tab::ALL[(self.index() + Tab::ALL.len() - 1) % Tab::ALL.len()]
+    }
+}
+
+/// Commits of the Log tab, newest first
+#[derive(Debug, Clone, Default)]
+pub struct LogView {
+    /// `<short sha> <subject>` lines
+    pub lines: Vec<String>,
+    /// Why there is nothing to show, e.g. no commit yet
+    pub error: Option<String>,
+    pub scroll_offset: usize,
+}
+
+/// Runs `guts log --oneline` in `dir`
+pub fn load_log(dir: &str) -> LogView {
+    let args = LogArgs {
+        oneline: true,
+        graph: false,
+        all: false,
+        max_count: None,
+        depth: None,
+        format: None,
+        color: ColorWhen::Never,
+    };
+    match with_dir(Some(&PathBuf::from(dir)), || log::run(&args)) {
+        Ok(output) => LogView {
+            lines: output.lines().map(str::to_string).collect(),
+            ..LogView::default()
+        },
+        Err(e) => LogView {
+            error: Some(e.to_string()),
+            ..LogView::default()
+        },
+    }
+}
+
+/// Tab titles, numbered after the key selecting them
+pub fn render_tab_bar(f: &mut Frame, area: Rect, active: Tab) {
+    let titles: Vec<Line> = Tab::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, tab)| Line::from(format!("{} {}", i + 1, tab.title())))
+        .collect();
+    let tabs = Tabs::new(titles)
+        .block(Block::default().borders(Borders::ALL).title("Team UNFAIR"))
+        .select(active.index())
+        .style(Style::default().fg(Color::White))
+        .highlight_style(Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+
+    f.render_widget(tabs, area);
+}
+
+/// Content of the Status or Log tab; the Console one is drawn by `ui`
+pub fn render_tab(f: &mut Frame, area: Rect, app: &App) {
+    match app.tab {
+        Tab::Console => {}
+        Tab::Status => ui::render_status_lists(f, area, &app.tab_status, "r to refresh"),
+        Tab::Log => render_log(f, area, app),
+    }
+}
+
+fn render_log(f: &mut Frame, area: Rect, app: &App) {
+    let view = &app.log_view;
+    if let Some(error) = &view.error {
+        let paragraph = Paragraph::new(error.as_str())
+            .block(Block::default().borders(Borders::ALL).title("Log - r to refresh"))
+            .style(Style::default().fg(Color::LightRed))
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = view
+        .lines
+        .iter()
+        .skip(view.scroll_offset)
+        .take(area.height.saturating_sub(2) as usize)
+        .map(|line| {
+            let (sha, subject) = line.split_once(' ').unwrap_or((line.as_str(), ""));
+            ListItem::new(Line::from(vec![
+                Span::styled(sha, Style::default().fg(Color::Yellow)),
+                Span::raw(" "),
+                Span::styled(subject, Style::default().fg(Color::White)),
+            ]))
+        })
+        .collect();
+
+    let title = format!(
+        "Log ({}/{}) - ↑/↓ scroll, r to refresh",
+        (view.scroll_offset + 1).min(view.lines.len()),
+        view.lines.len()
+    );
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
+
+    f.render_widget(list, area);
+}
@@ -1,4 +1,7 @@
-use crate::terminal::app::App;
+use crate::terminal::app::{App, Focus};
+use crate::terminal::file_panel::FileStatus;
+use crate::terminal::status_panel::StatusView;
+use crate::terminal::tabs::{self, Tab};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -15,8 +18,18 @@ pub fn render(f: &mut Frame, app: &mut App) {
         .direction(Direction::Horizontal)
         .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
         .split(f.size());
-    // left panel - ASCII Art
-    render_ascii_art(f, chunks[0]);
+    // left panel - file browser above the ASCII Art
+    let left = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[0]);
+    app.files_area = left[0];
+    render_file_panel(f, left[0], app);
+    if app.status_view.is_some() {
+        render_status_panel(f, left[1], app);
+    } else {
+        render_ascii_art(f, left[1]);
+    }
     // right panel - CLI Interface
     render_cli_interface(f, chunks[1], app);
 }
@@ -50,8 +63,20 @@ fn render_ascii_art(f: &mut Frame, area: Rect) {
     Navigation:
     • ↑/↓ - Command history
     • Ctrl+↑/↓ - Scroll output
+    • Ctrl+S - Status panel
+    • Alt+←/→, Alt+1-3 - Switch tab
     • Ctrl+C - Quit
     • Enter - Execute command
+
+    Files (Ctrl+F):
+    • ↑/↓ - Select, Enter - Open dir
+    • a - Stage, d - Diff, r - Refresh
+    • Esc - Back to console
+
+    Status and Log tabs:
+    • ←/→, 1-3 - Switch tab
+    • ↑/↓ - Scroll, r - Refresh
+    • Esc - Back to console
 "#;
 
     let paragraph = Paragraph::new(ascii_art)
@@ -63,39 +88,142 @@ fn render_ascii_art(f: &mut Frame, area: Rect) {
     f.render_widget(paragraph, area);
 }
 
-fn render_cli_interface(f: &mut Frame, area: Rect, app: &mut App) {
+fn render_file_panel(f: &mut Frame, area: Rect, app: &App) {
+    let focused = app.focus == Focus::Files;
+
+    let items: Vec<ListItem> = app
+        .file_entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let color = match entry.status {
+                FileStatus::Staged => Color::Green,
+                FileStatus::Modified => Color::Red,
+                FileStatus::Untracked => Color::DarkGray,
+                FileStatus::Clean => Color::White,
+            };
+            let name = if entry.is_dir {
+                format!("{}/", entry.name)
+            } else {
+                entry.name.clone()
+            };
+
+            let mut style = Style::default().fg(color);
+            if focused && i == app.selected_file {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            ListItem::new(Line::from(Span::styled(name, style)))
+        })
+        .collect();
+
+    // Keep the highlighted entry in view
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = (app.selected_file + 1).saturating_sub(visible);
+    let items: Vec<ListItem> = items.into_iter().skip(skip).collect();
+
+    let border_style = if focused {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    };
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .title("Files"),
+    );
+
+    f.render_widget(list, area);
+}
+
+fn render_status_panel(f: &mut Frame, area: Rect, app: &App) {
+    if let Some(view) = &app.status_view {
+        render_status_lists(f, area, view, "Ctrl+S to close");
+    }
+}
+
+/// Staged, unstaged and untracked files as three stacked lists, `hint` in the first title
+pub fn render_status_lists(f: &mut Frame, area: Rect, view: &StatusView, hint: &str) {
+    if let Some(error) = &view.error {
+        let paragraph = Paragraph::new(error.as_str())
+            .block(Block::default().borders(Borders::ALL).title(format!("Status - {}", hint)))
+            .style(Style::default().fg(Color::LightRed))
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let sections = [
+        ("Staged", &view.staged, Color::Green),
+        ("Not staged", &view.unstaged, Color::Red),
+        ("Untracked", &view.untracked, Color::DarkGray),
+    ];
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Banner
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+        ])
+        .split(area);
+
+    for ((title, files, color), chunk) in sections.into_iter().zip(chunks.iter()) {
+        let items: Vec<ListItem> = files
+            .iter()
+            .map(|file| ListItem::new(Line::from(Span::styled(file.as_str(), Style::default().fg(color)))))
+            .collect();
+        let title = if title == "Staged" {
+            format!("{} ({}) - {}", title, files.len(), hint)
+        } else {
+            format!("{} ({})", title, files.len())
+        };
+        let list = List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(color))
+                .title(title),
+        );
+        f.render_widget(list, *chunk);
+    }
+}
+
+fn render_cli_interface(f: &mut Frame, area: Rect, app: &mut App) {
+    // Only the Console tab has an input line
+    let constraints = if app.tab == Tab::Console {
+        vec![
+            Constraint::Length(3), // Tabs
             Constraint::Min(0),    // Command history
             Constraint::Length(3), // Input area
-        ])
+        ]
+    } else {
+        vec![Constraint::Length(3), Constraint::Min(0)]
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
         .split(area);
 
     app.update_visible_lines(chunks[1].height as usize);
+    app.history_area = chunks[1];
+
+    tabs::render_tab_bar(f, chunks[0], app.tab);
+    if app.tab != Tab::Console {
+        app.input_area = Rect::default();
+        tabs::render_tab(f, chunks[1], app);
+        return;
+    }
+    app.input_area = chunks[2];
 
-    // banner
-    render_banner(f, chunks[0]);
-    // command hystory
-    render_command_history_with_scroll(f, chunks[1], app);
+    // command hystory, or the diff opened from the file browser
+    if app.diff_view.is_some() {
+        render_diff_view(f, chunks[1], app);
+    } else {
+        render_command_history_with_scroll(f, chunks[1], app);
+    }
     // input area
     render_input_area(f, chunks[2], app);
 }
 
-fn render_banner(f: &mut Frame, area: Rect) {
-    let banner = Paragraph::new("Team UNFAIR")
-        .block(Block::default().borders(Borders::ALL))
-        .style(
-            Style::default()
-                .fg(Color::White)
-                .add_modifier(Modifier::BOLD),
-        )
-        .alignment(Alignment::Center);
-
-    f.render_widget(banner, area);
-}
-
 fn render_command_history_with_scroll(f: &mut Frame, area: Rect, app: &App) {
     let mut items = Vec::new();
 
@@ -193,13 +321,50 @@ fn render_command_history_with_scroll(f: &mut Frame, area: Rect, app: &App) {
     }
 }
 
-fn render_input_area(f: &mut Frame, area: Rect, app: &App) {
-    let current_dir = std::path::Path::new(&app.current_dir)
-        .file_name()
-        .unwrap_or_default()
-        .to_string_lossy();
+fn render_diff_view(f: &mut Frame, area: Rect, app: &App) {
+    let Some(view) = &app.diff_view else {
+        return;
+    };
 
-    let prompt = format!("{}$ ", current_dir);
+    let items: Vec<ListItem> = view
+        .lines
+        .iter()
+        .skip(view.scroll_offset)
+        .take(app.max_visible_lines)
+        .map(|line| {
+            let style = if line.starts_with("+++") || line.starts_with("---") || line.starts_with("diff --git") {
+                Style::default().fg(Color::White).add_modifier(Modifier::BOLD)
+            } else if line.starts_with('+') {
+                Style::default().fg(Color::Green)
+            } else if line.starts_with('-') {
+                Style::default().fg(Color::Red)
+            } else if line.starts_with("@@") {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            ListItem::new(Line::from(Span::styled(line.as_str(), style)))
+        })
+        .collect();
+
+    let title = format!(
+        "{} ({}/{}) - PgUp/PgDn scroll, Esc back",
+        view.title,
+        (view.scroll_offset + 1).min(view.lines.len()),
+        view.lines.len()
+    );
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow))
+            .title(title),
+    );
+
+    f.render_widget(list, area);
+}
+
+fn render_input_area(f: &mut Frame, area: Rect, app: &App) {
+    let prompt = app.prompt();
     let input_text = format!("{}{}", prompt, app.input);
 
     let input = Paragraph::new(input_text)
@@ -209,7 +374,8 @@ fn render_input_area(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(input, area);
 
     // Input cursor position
-    let cursor_x = area.x + 1 + prompt.len() as u16 + app.cursor_position as u16;
+    let cursor_column = app.input[..app.cursor_position].chars().count();
+    let cursor_x = area.x + 1 + prompt.chars().count() as u16 + cursor_column as u16;
     let cursor_y = area.y + 1;
     f.set_cursor(cursor_x, cursor_y);
 }
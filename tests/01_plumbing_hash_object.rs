@@ -1,5 +1,6 @@
 use assert_cmd::Command;
 use assert_fs::prelude::*;
+use predicates::prelude::*;
 use std::process::Command as StdCommand;
 
 // Test que guts hash-object produit exactement le même hash que git hash-object
@@ -34,6 +35,7 @@ fn test_hash_object_compatibility_with_git() {
     let guts_output = cmd
         .current_dir(temp.path())
         .arg("hash-object")
+        .arg("-w")
         .arg("test.txt")
         .assert()
         .success()
@@ -60,3 +62,65 @@ fn test_hash_object_compatibility_with_git() {
         "Guts hash-object must produce identical hash to Git"
     );
 }
+
+/// Test hash-object --stdin hashes standard input, and only writes the object with -w
+#[test]
+fn test_hash_object_stdin() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let _ = guts::core::repo::init(temp.path());
+
+    // `echo 'hello world' | git hash-object --stdin`
+    let sha = "3b18e512dba79e4c8300dd08aeb37f8e728b8dad";
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["hash-object", "--stdin"])
+        .write_stdin("hello world\n")
+        .assert()
+        .success()
+        .stdout(format!("{}\n", sha));
+    let object_path = temp.path().join(".git/objects").join(&sha[..2]).join(&sha[2..]);
+    assert!(!object_path.exists(), "hash-object without -w must not write the object");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["hash-object", "-w", "--stdin"])
+        .write_stdin("hello world\n")
+        .assert()
+        .success()
+        .stdout(format!("{}\n", sha));
+    assert!(object_path.exists());
+
+    // -t hashes the same bytes as another type of object
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["hash-object", "-t", "commit", "--stdin"])
+        .write_stdin("hello world\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(sha).not());
+}
+
+/// Test hash-object prints one SHA per file, in order
+#[test]
+fn test_hash_object_multiple_files() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("a.txt").write_str("a\n").unwrap();
+    temp.child("b.txt").write_str("b\n").unwrap();
+
+    let git_output = StdCommand::new("git")
+        .current_dir(temp.path())
+        .args(["hash-object", "a.txt", "b.txt"])
+        .output()
+        .expect("Failed to run git hash-object - Git must be installed");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["hash-object", "a.txt", "b.txt"])
+        .assert()
+        .success()
+        .stdout(String::from_utf8_lossy(&git_output.stdout).to_string());
+}
@@ -1,5 +1,6 @@
 use assert_cmd::Command;
 use assert_fs::prelude::*;
+use predicates::prelude::*;
 use std::process::Command as StdCommand;
 
 /// Test que guts cat-file produit exactement la même sortie que git cat-file
@@ -54,3 +55,239 @@ fn test_cat_file_compatibility_with_git() {
         "Guts cat-file must produce identical output to Git"
     );
 }
+
+/// Test cat-file -t and -s match git for blobs, trees and commits
+#[test]
+fn test_cat_file_type_and_size() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("test.txt").write_str("Hello, type and size!\n").unwrap();
+
+    for args in [
+        &["init"][..],
+        &["add", "test.txt"],
+        &["-c", "user.name=Test", "-c", "user.email=test@example.com", "commit", "-m", "Initial"],
+    ] {
+        StdCommand::new("git").current_dir(temp.path()).args(args).output().unwrap();
+    }
+
+    for rev in ["HEAD", "HEAD^{tree}", "HEAD:test.txt"] {
+        let sha_output = StdCommand::new("git")
+            .current_dir(temp.path())
+            .args(["rev-parse", rev])
+            .output()
+            .unwrap();
+        let sha = String::from_utf8_lossy(&sha_output.stdout).trim().to_string();
+
+        for flag in ["-t", "-s"] {
+            let git_output = StdCommand::new("git")
+                .current_dir(temp.path())
+                .args(["cat-file", flag, &sha])
+                .output()
+                .unwrap();
+
+            Command::cargo_bin("guts")
+                .unwrap()
+                .current_dir(temp.path())
+                .args(["cat-file", flag, &sha])
+                .assert()
+                .success()
+                .stdout(String::from_utf8_lossy(&git_output.stdout).to_string());
+        }
+    }
+}
+
+/// Test cat-file -p prints tree entries exactly like git: <mode> <type> <sha>\t<name>
+#[test]
+fn test_cat_file_pretty_prints_trees_like_git() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("top.txt").write_str("top\n").unwrap();
+    temp.child("nested/inner.txt").write_str("inner\n").unwrap();
+
+    for args in [&["init"][..], &["add", "."]] {
+        StdCommand::new("git").current_dir(temp.path()).args(args).output().unwrap();
+    }
+    let tree_output = StdCommand::new("git").current_dir(temp.path()).arg("write-tree").output().unwrap();
+    let tree = String::from_utf8_lossy(&tree_output.stdout).trim().to_string();
+
+    let git_output = StdCommand::new("git")
+        .current_dir(temp.path())
+        .args(["cat-file", "-p", &tree])
+        .output()
+        .unwrap();
+    let git_content = String::from_utf8_lossy(&git_output.stdout).to_string();
+    assert!(git_content.contains(" tree "));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["cat-file", "-p", &tree])
+        .assert()
+        .success()
+        .stdout(git_content);
+}
+
+/// Test cat-file refuses more than one mode flag
+#[test]
+fn test_cat_file_conflicting_flags() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["cat-file", "-t", "-s", "0123456789abcdef0123456789abcdef01234567"])
+        .assert()
+        .failure();
+}
+
+/// Test cat-file reports a truncated object file as corrupt
+#[test]
+fn test_cat_file_truncated_object() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("file.txt").write_str(&"some content to compress\n".repeat(20)).unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["hash-object", "-w", "file.txt"])
+        .output()
+        .unwrap();
+    let sha = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    // Cut the object file in half, like an interrupted write
+    let object_path = temp.path().join(".git/objects").join(&sha[..2]).join(&sha[2..]);
+    let data = std::fs::read(&object_path).unwrap();
+    std::fs::write(&object_path, &data[..data.len() / 2]).unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["cat-file", "-p", &sha])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!("object {} is corrupt", sha)));
+}
+
+/// Test cat-file outside a repository fails clearly
+#[test]
+fn test_cat_file_outside_repository() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["cat-file", "-p", "0123456789abcdef0123456789abcdef01234567"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not a git repository"));
+}
+
+/// Test cat-file --batch frames each object and flags unknown ones, byte for byte like git
+#[test]
+fn test_cat_file_batch() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("a.txt").write_str("first blob\n").unwrap();
+    temp.child("b.bin").write_binary(&[0xff, 0x00, 0xfe, b'\n', 0x80]).unwrap();
+    StdCommand::new("git").current_dir(temp.path()).arg("init").output().unwrap();
+
+    let mut shas = Vec::new();
+    for file in ["a.txt", "b.bin"] {
+        let output = StdCommand::new("git")
+            .current_dir(temp.path())
+            .args(["hash-object", "-w", file])
+            .output()
+            .unwrap();
+        shas.push(String::from_utf8_lossy(&output.stdout).trim().to_string());
+    }
+    let missing = "0123456789012345678901234567890123456789";
+    let input = format!("{}\n{}\n{}\n", shas[0], shas[1], missing);
+
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["cat-file", "--batch"])
+        .write_stdin(input.clone())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let mut expected = format!("{} blob 11\nfirst blob\n\n", shas[0]).into_bytes();
+    expected.extend_from_slice(format!("{} blob 5\n", shas[1]).as_bytes());
+    expected.extend_from_slice(&[0xff, 0x00, 0xfe, b'\n', 0x80, b'\n']);
+    expected.extend_from_slice(format!("{} missing\n", missing).as_bytes());
+    assert_eq!(output.stdout, expected);
+
+    let git_output = StdCommand::new("git")
+        .current_dir(temp.path())
+        .args(["cat-file", "--batch"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            use std::io::Write;
+            child.stdin.take().unwrap().write_all(input.as_bytes())?;
+            child.wait_with_output()
+        })
+        .unwrap();
+    assert_eq!(output.stdout, git_output.stdout);
+}
+
+/// Test cat-file rejects malformed object names before looking anything up
+#[test]
+fn test_cat_file_rejects_invalid_object_names() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    for name in ["", "abc", "zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz"] {
+        Command::cargo_bin("guts")
+            .unwrap()
+            .current_dir(temp.path())
+            .args(["cat-file", "-p", name])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!("not a valid object name: {}", name)));
+    }
+}
+
+/// Test cat-file explains that an object only found in a pack cannot be read yet
+#[test]
+fn test_cat_file_packed_object_is_reported() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("file.txt").write_str("packed content\n").unwrap();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .current_dir(temp.path())
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    git(&["init", "-q"]);
+    git(&["add", "file.txt"]);
+    git(&["commit", "-q", "-m", "Packed"]);
+    let blob = git(&["rev-parse", "HEAD:file.txt"]);
+    // Moves every object into a pack and deletes the loose copies
+    git(&["repack", "-a", "-d", "-q"]);
+    assert!(!temp.path().join(".git/objects").join(&blob[..2]).join(&blob[2..]).exists());
+
+    for name in [blob.as_str(), &blob[..8]] {
+        Command::cargo_bin("guts")
+            .unwrap()
+            .current_dir(temp.path())
+            .args(["cat-file", "-p", name])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!("object {} is in a packfile (unsupported)", blob)));
+    }
+
+    // An object that is nowhere keeps its usual error
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["cat-file", "-p", "0123456789abcdef0123456789abcdef01234567"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("packfile").not());
+}
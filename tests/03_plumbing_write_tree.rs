@@ -75,3 +75,63 @@ fn test_write_tree_compatibility_with_git() {
         "Guts write-tree must produce identical tree hash to Git"
     );
 }
+
+/// Test a file above the streaming threshold is stored with git's blob hash
+#[test]
+fn test_add_large_file_streams_blob() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    // 20 MiB of zeros, sparse so the test stays cheap on disk
+    let file = std::fs::File::create(temp.path().join("big.bin")).unwrap();
+    file.set_len(20 * 1024 * 1024).unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "big.bin"]).assert().success();
+
+    let tree = Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("write-tree").output().unwrap();
+    let listing = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("ls-tree")
+        .arg(String::from_utf8_lossy(&tree.stdout).trim())
+        .output()
+        .unwrap();
+
+    // `git hash-object` of the same file
+    assert_eq!(
+        String::from_utf8_lossy(&listing.stdout),
+        "100644 blob 6c5effe7efd33b24ca7ee229ae57cbb5ee9d4e8c\tbig.bin\n"
+    );
+    // The temporary object file does not stay behind
+    let leftovers = std::fs::read_dir(temp.path().join(".git/objects"))
+        .unwrap()
+        .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().starts_with("tmp_obj_"))
+        .count();
+    assert_eq!(leftovers, 0);
+}
+
+/// Test write-tree --from-dir hashes the directory on disk to the same tree as the index,
+/// skipping `.git` and ignored files
+#[test]
+fn test_write_tree_from_dir_matches_index() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child(".gutsignore").write_str("*.log\n").unwrap();
+    temp.child("README.md").write_str("# Project\n").unwrap();
+    temp.child("src/main.rs").write_str("fn main() {}\n").unwrap();
+    temp.child("src/nested/lib.rs").write_str("pub fn f() {}\n").unwrap();
+    temp.child("debug.log").write_str("ignored\n").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "."]).assert().success();
+
+    let from_index = Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("write-tree").output().unwrap();
+    let from_dir = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["write-tree", "--from-dir", "."])
+        .output()
+        .unwrap();
+
+    assert!(from_dir.status.success());
+    assert_eq!(String::from_utf8_lossy(&from_dir.stdout), String::from_utf8_lossy(&from_index.stdout));
+}
@@ -1,5 +1,6 @@
 use assert_cmd::Command;
 use assert_fs::prelude::*;
+use predicates::prelude::*;
 use std::process::Command as StdCommand;
 
 #[test]
@@ -89,3 +90,154 @@ fn test_commit_tree_compatibility_with_git() {
         println!("✅ Formats identiques, seul le timestamp diffère (normal)");
     }
 }
+
+/// Test every field of a commit written by commit-tree survives parsing it back
+#[test]
+fn test_commit_tree_round_trip() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let _ = guts::core::repo::init(temp.path());
+
+    let tree_hash = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+    // Parents must exist
+    let parent = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["commit-tree", tree_hash, "-m", "Parent"])
+        .output()
+        .unwrap();
+    let parent_hash = String::from_utf8_lossy(&parent.stdout).trim().to_string();
+
+    // Dates are written in the local timezone
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .env("TZ", "UTC")
+        .args([
+            "commit-tree",
+            tree_hash,
+            "-p",
+            &parent_hash,
+            "-m",
+            "Subject line\n\nBody paragraph",
+            "--author",
+            "Ada Lovelace <ada@example.com>",
+            "--committer",
+            "Grace Hopper <grace@example.com>",
+            "--author-date",
+            "1700000000",
+            "--committer-date",
+            "1700000100",
+        ])
+        .output()
+        .unwrap();
+    let commit_hash = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let expected = format!(
+        "tree {}\nparent {}\nauthor Ada Lovelace <ada@example.com> 1700000000 +0000\ncommitter Grace Hopper <grace@example.com> 1700000100 +0000\n\nSubject line\n\nBody paragraph\n",
+        tree_hash, parent_hash
+    );
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["cat-file", "-p", &commit_hash])
+        .assert()
+        .success()
+        .stdout(expected.clone());
+
+    // Git reads the same object identically
+    let git_output = StdCommand::new("git")
+        .current_dir(temp.path())
+        .args(["cat-file", "-p", &commit_hash])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&git_output.stdout), expected);
+}
+
+/// Test commit timezones come from the local zone or the options and round-trip through cat-file
+#[test]
+fn test_commit_tree_timezones() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let _ = guts::core::repo::init(temp.path());
+    let tree_hash = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+    let commit_with = |extra: &[&str]| {
+        let output = Command::cargo_bin("guts")
+            .unwrap()
+            .current_dir(temp.path())
+            .env("TZ", "Asia/Kolkata")
+            .args(["commit-tree", tree_hash, "-m", "Zoned", "--author-date", "1700000000"])
+            .args(extra)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let sha = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let object = Command::cargo_bin("guts")
+            .unwrap()
+            .current_dir(temp.path())
+            .args(["cat-file", "-p", &sha])
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&object.stdout).to_string()
+    };
+
+    // Local offset by default
+    let local = commit_with(&[]);
+    assert!(local.contains(" 1700000000 +0530\ncommitter "), "{}", local);
+
+    // Explicit offsets are kept as given
+    let explicit = commit_with(&["--author-timezone", "-0800", "--committer-timezone", "+0100"]);
+    assert!(explicit.contains(" 1700000000 -0800\ncommitter "), "{}", explicit);
+    assert!(explicit.contains(" 1700000000 +0100\n\nZoned"), "{}", explicit);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["commit-tree", tree_hash, "-m", "Bad", "--author-timezone", "0800"])
+        .assert()
+        .failure();
+}
+
+/// Test repeated -p flags write every parent and a missing message is read from stdin
+#[test]
+fn test_commit_tree_merge_parents_and_stdin_message() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let _ = guts::core::repo::init(temp.path());
+    let tree_hash = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+    let commit_tree = |args: &[&str], stdin: &str| {
+        let output = Command::cargo_bin("guts")
+            .unwrap()
+            .current_dir(temp.path())
+            .arg("commit-tree")
+            .arg(tree_hash)
+            .args(args)
+            .write_stdin(stdin)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    };
+    let a = commit_tree(&["-m", "A"], "");
+    let b = commit_tree(&["-m", "B"], "");
+    let merge = commit_tree(&["-p", &a, "-p", &b], "Merge A and B\n");
+
+    let content = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["cat-file", "-p", &merge])
+        .output()
+        .unwrap();
+    let content = String::from_utf8_lossy(&content.stdout);
+    assert!(content.contains(&format!("parent {}\nparent {}\n", a, b)), "{}", content);
+    assert!(content.ends_with("\nMerge A and B\n"), "{}", content);
+
+    // A parent that is not in the object store is refused
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["commit-tree", tree_hash, "-p", "1111111111111111111111111111111111111111", "-m", "x"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not a valid object name"));
+}
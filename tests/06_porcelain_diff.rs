@@ -0,0 +1,217 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+/// Test diff of a modified working file against HEAD
+#[test]
+fn test_diff_working_tree() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let file = temp.child("notes.txt");
+    file.write_str("line one\nline two\nline three\n").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("notes.txt").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg("Initial").assert().success();
+
+    // No changes yet: diff is empty
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("diff")
+        .assert()
+        .success()
+        .stdout("");
+
+    file.write_str("line one\nline 2\nline three\n").unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("diff")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--- a/notes.txt"))
+        .stdout(predicate::str::contains("+++ b/notes.txt"))
+        .stdout(predicate::str::contains("@@ -1,3 +1,3 @@"))
+        .stdout(predicate::str::contains("-line two\n+line 2\n"));
+}
+
+/// Test diff --cached shows staged changes only
+#[test]
+fn test_diff_cached() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("tracked.txt").write_str("tracked\n").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("tracked.txt").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg("Initial").assert().success();
+
+    temp.child("staged.txt").write_str("staged content\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("staged.txt").assert().success();
+    temp.child("tracked.txt").write_str("unstaged edit\n").unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("diff")
+        .arg("--cached")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--- /dev/null"))
+        .stdout(predicate::str::contains("+++ b/staged.txt"))
+        .stdout(predicate::str::contains("+staged content"))
+        .stdout(predicate::str::contains("tracked.txt").not());
+}
+
+/// Test binary content is not printed
+#[test]
+fn test_diff_binary_file() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let file = temp.child("image.bin");
+    file.write_binary(&[0xff, 0xfe, 0x00, 0x01]).unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("image.bin").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg("Binary").assert().success();
+
+    file.write_binary(&[0xff, 0xfe, 0x00, 0x02]).unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("diff")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Binary files a/image.bin and b/image.bin differ"));
+}
+
+/// Test diff --name-only and --name-status list added, modified and deleted files
+#[test]
+fn test_diff_name_only_and_name_status() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("changed.txt").write_str("v1\n").unwrap();
+    temp.child("deleted.txt").write_str("bye\n").unwrap();
+    temp.child("kept.txt").write_str("same\n").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "."]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["commit", "-m", "Initial"]).assert().success();
+
+    temp.child("changed.txt").write_str("v2\n").unwrap();
+    std::fs::remove_file(temp.path().join("deleted.txt")).unwrap();
+    temp.child("added.txt").write_str("new\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "added.txt"]).assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["diff", "--name-status"])
+        .assert()
+        .success()
+        .stdout("A\tadded.txt\nM\tchanged.txt\nD\tdeleted.txt\n");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["diff", "--name-only"])
+        .assert()
+        .success()
+        .stdout("added.txt\nchanged.txt\ndeleted.txt\n");
+
+    // Only the new file is staged
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["diff", "--cached", "--name-status"])
+        .assert()
+        .success()
+        .stdout("A\tadded.txt\n");
+}
+
+/// Test diff between two commits, as two arguments or as a range
+#[test]
+fn test_diff_two_commits() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("notes.txt").write_str("line one\nline two\n").unwrap();
+    temp.child("old.txt").write_str("removed later\n").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "."]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["commit", "-m", "First"]).assert().success();
+
+    temp.child("notes.txt").write_str("line one\nline 2\n").unwrap();
+    temp.child("new.txt").write_str("added\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "notes.txt", "new.txt"]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["rm", "old.txt"]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["commit", "-m", "Second"]).assert().success();
+
+    // Uncommitted changes do not show up between two commits
+    temp.child("notes.txt").write_str("dirty\n").unwrap();
+
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["diff", "HEAD~1", "HEAD"])
+        .output()
+        .unwrap();
+    let diff = String::from_utf8_lossy(&output.stdout).to_string();
+    assert!(diff.contains("-line two\n+line 2\n"), "{}", diff);
+    assert!(diff.contains("+++ b/new.txt"));
+    assert!(diff.contains("+added\n"));
+    assert!(diff.contains("--- a/old.txt"));
+    assert!(diff.contains("-removed later\n"));
+    assert!(!diff.contains("dirty"));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["diff", "HEAD~1..HEAD"])
+        .assert()
+        .success()
+        .stdout(diff);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["diff", "--name-status", "HEAD", "HEAD~1"])
+        .assert()
+        .success()
+        .stdout("D\tnew.txt\nM\tnotes.txt\nA\told.txt\n");
+}
+
+/// Test added and deleted executables and symlinks get their own mode in the header
+#[cfg(unix)]
+#[test]
+fn test_diff_new_and_deleted_file_modes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let guts = |args: &[&str]| {
+        let output = Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(args).output().unwrap();
+        assert!(output.status.success(), "guts {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    temp.child("file.txt").write_str("content\n").unwrap();
+    guts(&["init"]);
+    guts(&["add", "file.txt"]);
+    guts(&["commit", "-m", "Initial"]);
+
+    temp.child("run.sh").write_str("#!/bin/sh\n").unwrap();
+    std::fs::set_permissions(temp.path().join("run.sh"), std::fs::Permissions::from_mode(0o755)).unwrap();
+    std::os::unix::fs::symlink("file.txt", temp.path().join("link")).unwrap();
+    guts(&["add", "run.sh", "link"]);
+
+    let cached = guts(&["diff", "--cached"]);
+    assert!(cached.contains("diff --git a/run.sh b/run.sh\nnew file mode 100755\n"), "{}", cached);
+    assert!(cached.contains("diff --git a/link b/link\nnew file mode 120000\n"), "{}", cached);
+
+    guts(&["commit", "-m", "Script and link"]);
+    assert!(guts(&["show", "HEAD"]).contains("new file mode 100755\n"));
+
+    std::fs::remove_file(temp.path().join("run.sh")).unwrap();
+    std::fs::remove_file(temp.path().join("link")).unwrap();
+    let working = guts(&["diff"]);
+    assert!(working.contains("deleted file mode 100755\n"), "{}", working);
+    assert!(working.contains("deleted file mode 120000\n"), "{}", working);
+    assert!(!working.contains("100644"), "{}", working);
+}
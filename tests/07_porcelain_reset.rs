@@ -0,0 +1,195 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::fs;
+
+/// Create a repo with two commits and return the SHA of the first one
+fn repo_with_two_commits(temp: &assert_fs::TempDir) -> String {
+    let file = temp.child("file.txt");
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    file.write_str("version 1\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("file.txt").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg("First").assert().success();
+
+    let first = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("rev-parse")
+        .arg("HEAD")
+        .output()
+        .unwrap();
+
+    file.write_str("version 2\n").unwrap();
+    temp.child("second.txt").write_str("added later\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg(".").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg("Second").assert().success();
+
+    String::from_utf8_lossy(&first.stdout).trim().to_string()
+}
+
+fn head_sha(temp: &assert_fs::TempDir) -> String {
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("rev-parse")
+        .arg("HEAD")
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Test reset --soft only moves the branch
+#[test]
+fn test_reset_soft() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let first = repo_with_two_commits(&temp);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["reset", "--soft", &first])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("HEAD is now at"));
+
+    assert_eq!(head_sha(&temp), first);
+
+    // Changes of the second commit are still staged
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Changes to be committed"))
+        .stdout(predicate::str::contains("new file:   second.txt"))
+        .stdout(predicate::str::contains("modified:   file.txt"));
+}
+
+/// Test reset (mixed by default) unstages but keeps working files
+#[test]
+fn test_reset_mixed() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let first = repo_with_two_commits(&temp);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["reset", &first])
+        .assert()
+        .success();
+
+    assert_eq!(head_sha(&temp), first);
+    assert_eq!(fs::read_to_string(temp.path().join("file.txt")).unwrap(), "version 2\n");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Changes to be committed").not())
+        .stdout(predicate::str::contains("modified:   file.txt"))
+        .stdout(predicate::str::contains("Untracked files"))
+        .stdout(predicate::str::contains("second.txt"));
+}
+
+/// Test reset --hard restores the working tree and refuses to drop local changes without -f
+#[test]
+fn test_reset_hard() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let first = repo_with_two_commits(&temp);
+
+    temp.child("file.txt").write_str("uncommitted edit\n").unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["reset", "--hard", &first])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("uncommitted changes"));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["reset", "--hard", "-f", &first])
+        .assert()
+        .success();
+
+    assert_eq!(head_sha(&temp), first);
+    assert_eq!(fs::read_to_string(temp.path().join("file.txt")).unwrap(), "version 1\n");
+    assert!(!temp.path().join("second.txt").exists());
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("nothing to commit, working tree clean"));
+}
+
+/// Test reset HEAD <file> unstages a change and leaves the working tree alone
+#[test]
+fn test_reset_path_unstages_file() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    repo_with_two_commits(&temp);
+    let head = head_sha(&temp);
+
+    temp.child("file.txt").write_str("version 3\n").unwrap();
+    temp.child("new.txt").write_str("brand new\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "."]).assert().success();
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "-s"])
+        .assert()
+        .stdout("M  file.txt\nA  new.txt\n");
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["reset", "HEAD", "file.txt"]).assert().success();
+    // The lone path form uses HEAD
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["reset", "new.txt"]).assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "-s"])
+        .assert()
+        .stdout(" M file.txt\n?? new.txt\n");
+    assert_eq!(fs::read_to_string(temp.path().join("file.txt")).unwrap(), "version 3\n");
+    assert_eq!(head_sha(&temp), head);
+}
+
+/// Test reset with paths refuses --hard and unknown paths
+#[test]
+fn test_reset_path_errors() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    repo_with_two_commits(&temp);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["reset", "--hard", "HEAD", "file.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot do hard reset with paths"));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["reset", "--hard", "file.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot do hard reset with paths"));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["reset", "HEAD", "missing.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pathspec 'missing.txt' did not match"));
+}
@@ -0,0 +1,138 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+/// Commit three versions of a file and return their SHAs, oldest first
+fn repo_with_three_commits(temp: &assert_fs::TempDir) -> Vec<String> {
+    let file = temp.child("file.txt");
+    let mut shas = Vec::new();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    for i in 1..=3 {
+        file.write_str(&format!("version {}\n", i)).unwrap();
+        Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("file.txt").assert().success();
+        Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg(format!("Commit {}", i)).assert().success();
+        shas.push(rev_parse(temp, "HEAD"));
+    }
+
+    shas
+}
+
+fn rev_parse(temp: &assert_fs::TempDir, revision: &str) -> String {
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("rev-parse")
+        .arg(revision)
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Test `~<n>` and `^` ancestry suffixes
+#[test]
+fn test_rev_parse_ancestry() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let shas = repo_with_three_commits(&temp);
+
+    assert_eq!(rev_parse(&temp, "HEAD~0"), shas[2]);
+    assert_eq!(rev_parse(&temp, "HEAD~"), shas[1]);
+    assert_eq!(rev_parse(&temp, "HEAD~2"), shas[0]);
+    assert_eq!(rev_parse(&temp, "HEAD^"), shas[1]);
+    assert_eq!(rev_parse(&temp, "main^1"), shas[1]);
+    assert_eq!(rev_parse(&temp, "main~1^"), shas[0]);
+    assert_eq!(rev_parse(&temp, "HEAD^^0"), shas[1]);
+    assert_eq!(rev_parse(&temp, &format!("{}~1", shas[1])), shas[0]);
+}
+
+/// Test walking past the root commit or a missing parent fails clearly
+#[test]
+fn test_rev_parse_ancestry_errors() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    repo_with_three_commits(&temp);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("rev-parse")
+        .arg("HEAD~3")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("root commit"));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("rev-parse")
+        .arg("HEAD^2")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("has only 1 parent"));
+}
+
+/// Test an empty or damaged ref file is reported instead of crashing
+#[test]
+fn test_rev_parse_invalid_ref_content() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    repo_with_three_commits(&temp);
+    temp.child(".git/refs/heads/broken").write_str("").unwrap();
+    temp.child(".git/refs/heads/short").write_str("ab\n").unwrap();
+
+    for branch in ["broken", "short"] {
+        Command::cargo_bin("guts")
+            .unwrap()
+            .current_dir(temp.path())
+            .arg("rev-parse")
+            .arg(branch)
+            .assert()
+            .code(1)
+            .stderr(predicate::str::contains(format!("fatal: not a valid object name: {}", branch)));
+    }
+}
+
+/// Test abbreviated SHAs resolve in rev-parse (with ancestry suffixes) and cat-file
+#[test]
+fn test_rev_parse_sha_prefix() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let shas = repo_with_three_commits(&temp);
+
+    assert_eq!(rev_parse(&temp, &shas[2][..7]), shas[2]);
+    assert_eq!(rev_parse(&temp, &format!("{}~1", &shas[2][..7])), shas[1]);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["cat-file", "-p", &shas[0][..7]])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Commit 1"));
+
+    // A prefix matching nothing is an error
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["cat-file", "-p", "ffffffff"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no object matches"));
+}
+
+/// Test rev-parse --short abbreviates to 7 chars, or to n chars with --short=n
+#[test]
+fn test_rev_parse_short() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let shas = repo_with_three_commits(&temp);
+
+    assert_eq!(rev_parse(&temp, "HEAD"), shas[2]);
+
+    for (flag, len) in [("--short", 7), ("--short=10", 10)] {
+        Command::cargo_bin("guts")
+            .unwrap()
+            .current_dir(temp.path())
+            .args(["rev-parse", flag, "HEAD~1"])
+            .assert()
+            .success()
+            .stdout(format!("{}\n", &shas[1][..len]));
+    }
+}
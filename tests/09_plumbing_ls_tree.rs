@@ -0,0 +1,169 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+/// Stage a small nested layout and return the SHA of its tree
+fn nested_tree(temp: &assert_fs::TempDir) -> String {
+    temp.child("apple.txt").write_str("apple").unwrap();
+    temp.child("fruits/cherry.txt").write_str("cherry").unwrap();
+    temp.child("fruits/red/strawberry.txt").write_str("strawberry").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg(".").assert().success();
+
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("write-tree")
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Test ls-tree only lists the top level by default
+#[test]
+fn test_ls_tree_top_level() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let tree = nested_tree(&temp);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("ls-tree")
+        .arg(&tree)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("100644 blob"))
+        .stdout(predicate::str::contains("\tapple.txt"))
+        .stdout(predicate::str::contains("040000 tree"))
+        .stdout(predicate::str::contains("\tfruits\n"))
+        .stdout(predicate::str::contains("cherry.txt").not());
+}
+
+/// Test ls-tree -r prints full blob paths and no tree entries
+#[test]
+fn test_ls_tree_recursive() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let tree = nested_tree(&temp);
+
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("ls-tree")
+        .arg("-r")
+        .arg(&tree)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let paths: Vec<&str> = stdout
+        .lines()
+        .map(|line| line.split('\t').nth(1).unwrap())
+        .collect();
+    assert_eq!(paths, ["apple.txt", "fruits/cherry.txt", "fruits/red/strawberry.txt"]);
+    assert!(!stdout.contains(" tree "));
+}
+
+/// Test ls-tree -r -t also shows the trees it descends into
+#[test]
+fn test_ls_tree_recursive_with_trees() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let tree = nested_tree(&temp);
+
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("ls-tree")
+        .arg("-r")
+        .arg("-t")
+        .arg(&tree)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let paths: Vec<&str> = stdout
+        .lines()
+        .map(|line| line.split('\t').nth(1).unwrap())
+        .collect();
+    assert_eq!(
+        paths,
+        ["apple.txt", "fruits", "fruits/cherry.txt", "fruits/red", "fruits/red/strawberry.txt"]
+    );
+}
+
+/// Run ls-tree with `args` on the nested tree and return the listed paths
+fn listed_paths(temp: &assert_fs::TempDir, args: &[&str]) -> Vec<String> {
+    let output = Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("ls-tree").args(args).output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.split('\t').nth(1).unwrap().to_string())
+        .collect()
+}
+
+/// Test a path after `--` limits the listing to that directory or file
+#[test]
+fn test_ls_tree_path_filter() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let tree = nested_tree(&temp);
+
+    assert_eq!(listed_paths(&temp, &[&tree, "--", "fruits/"]), ["fruits/cherry.txt", "fruits/red"]);
+    assert_eq!(
+        listed_paths(&temp, &["-r", &tree, "--", "fruits"]),
+        ["fruits/cherry.txt", "fruits/red/strawberry.txt"]
+    );
+    assert_eq!(listed_paths(&temp, &[&tree, "--", "fruits/cherry.txt"]), ["fruits/cherry.txt"]);
+    assert!(listed_paths(&temp, &[&tree, "--", "vegetables"]).is_empty());
+}
+
+/// Test an executable file is stored with mode 100755
+#[cfg(unix)]
+#[test]
+fn test_ls_tree_executable_mode() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let script = temp.child("script.sh");
+    script.write_str("#!/bin/sh\necho hi\n").unwrap();
+    std::fs::set_permissions(script.path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+    let tree = nested_tree(&temp);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("ls-tree")
+        .arg(&tree)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("100755 blob [0-9a-f]{40}\tscript.sh").unwrap())
+        .stdout(predicate::str::is_match("100644 blob [0-9a-f]{40}\tapple.txt").unwrap());
+}
+
+/// Test ls-tree rejects malformed object names and expands valid abbreviations
+#[test]
+fn test_ls_tree_validates_object_names() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("file.txt").write_str("content\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "file.txt"]).assert().success();
+    let output = Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("write-tree").output().unwrap();
+    let tree = String::from_utf8(output.stdout).unwrap().trim().to_string();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["ls-tree", "not-a-sha"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not a valid object name: not-a-sha"));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["ls-tree", &tree[..8]])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("file.txt"));
+}
@@ -0,0 +1,117 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+
+/// Commit a single file and return the pretty-printed commit object
+fn commit_and_cat(temp: &assert_fs::TempDir, home: &std::path::Path) -> String {
+    temp.child("file.txt").write_str("content").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).env("HOME", home).arg("add").arg("file.txt").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).env("HOME", home).arg("commit").arg("-m").arg("Initial").assert().success();
+
+    let head = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("rev-parse")
+        .arg("HEAD")
+        .output()
+        .unwrap();
+    let head = String::from_utf8_lossy(&head.stdout).trim().to_string();
+
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("cat-file")
+        .arg(&head)
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// Test commits use the identity from .git/config
+#[test]
+fn test_commit_uses_repo_identity() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let home = assert_fs::TempDir::new().unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    let config = temp.child(".git/config");
+    let mut content = std::fs::read_to_string(config.path()).unwrap();
+    content.push_str("[user]\n\tname = Ada Lovelace\n\temail = ada@example.com\n");
+    config.write_str(&content).unwrap();
+
+    let commit = commit_and_cat(&temp, home.path());
+    assert!(commit.contains("author Ada Lovelace <ada@example.com> "));
+    assert!(commit.contains("committer Ada Lovelace <ada@example.com> "));
+}
+
+/// Test ~/.gutsconfig is used when the repository sets no identity
+#[test]
+fn test_commit_uses_global_identity() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let home = assert_fs::TempDir::new().unwrap();
+    home.child(".gutsconfig")
+        .write_str("[user]\n    name = \"Grace Hopper\"\n    email = grace@example.com\n")
+        .unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    let commit = commit_and_cat(&temp, home.path());
+    assert!(commit.contains("author Grace Hopper <grace@example.com> "));
+}
+
+/// Test the default identity is kept when nothing is configured
+#[test]
+fn test_commit_default_identity() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let home = assert_fs::TempDir::new().unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    let commit = commit_and_cat(&temp, home.path());
+    assert!(commit.contains("author guts <guts@example.com> "));
+}
+
+/// Test guts config writes, reads and lists keys
+#[test]
+fn test_config_set_get_list() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let home = assert_fs::TempDir::new().unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).env("HOME", home.path()).args(["config", "user.name", "Ada Lovelace"]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).env("HOME", home.path()).args(["config", "user.email", "ada@example.com"]).assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .env("HOME", home.path())
+        .args(["config", "user.name"])
+        .assert()
+        .success()
+        .stdout("Ada Lovelace\n");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .env("HOME", home.path())
+        .args(["config", "--list"])
+        .assert()
+        .success()
+        .stdout("core.repositoryformatversion=0\ncore.filemode=true\ncore.bare=false\nuser.name=Ada Lovelace\nuser.email=ada@example.com\n");
+
+    // Stored in standard git INI format
+    let config = std::fs::read_to_string(temp.path().join(".git/config")).unwrap();
+    assert!(config.contains("[user]\n\tname = Ada Lovelace\n\temail = ada@example.com\n"));
+
+    // Unset keys are an error
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .env("HOME", home.path())
+        .args(["config", "user.signingkey"])
+        .assert()
+        .failure();
+
+    let commit = commit_and_cat(&temp, home.path());
+    assert!(commit.contains("author Ada Lovelace <ada@example.com> "));
+}
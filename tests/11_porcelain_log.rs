@@ -0,0 +1,232 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::guts_stdout_trimmed;
+
+/// Build `main` as: base -> left -> merge, where merge also has `right` (child of base) as parent.
+/// Returns the SHAs (base, left, right, merge).
+fn repo_with_merge(temp: &assert_fs::TempDir) -> (String, String, String, String) {
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    temp.child("file.txt").write_str("base").unwrap();
+    guts_stdout_trimmed(temp, &["add", "file.txt"]);
+    guts_stdout_trimmed(temp, &["commit", "-m", "Base"]);
+    let base = guts_stdout_trimmed(temp, &["rev-parse", "HEAD"]);
+
+    temp.child("file.txt").write_str("left").unwrap();
+    guts_stdout_trimmed(temp, &["add", "file.txt"]);
+    guts_stdout_trimmed(temp, &["commit", "-m", "Left"]);
+    let left = guts_stdout_trimmed(temp, &["rev-parse", "HEAD"]);
+
+    let tree = guts_stdout_trimmed(temp, &["write-tree"]);
+    let right = guts_stdout_trimmed(temp, &["commit-tree", &tree, "-p", &base, "-m", "Right"]);
+    let merge = guts_stdout_trimmed(temp, &["commit-tree", &tree, "-p", &left, "-p", &right, "-m", "Merge right"]);
+    temp.child(".git/refs/heads/main").write_str(&format!("{}\n", merge)).unwrap();
+
+    (base, left, right, merge)
+}
+
+/// Test a merge commit keeps both parents
+#[test]
+fn test_merge_commit_parents() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let (_, left, right, merge) = repo_with_merge(&temp);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["cat-file", "-p", &merge])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("parent {}\nparent {}\n", left, right)));
+
+    assert_eq!(guts_stdout_trimmed(&temp, &["rev-parse", "HEAD^2"]), right);
+}
+
+/// Test log follows the first parent of merge commits
+#[test]
+fn test_log_follows_first_parent() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let (base, left, right, merge) = repo_with_merge(&temp);
+
+    let log = guts_stdout_trimmed(&temp, &["log"]);
+    let shas: Vec<&str> = log
+        .lines()
+        .filter_map(|line| line.strip_prefix("commit "))
+        .collect();
+    assert_eq!(shas, [merge.as_str(), left.as_str(), base.as_str()]);
+    assert!(!log.contains(&right));
+}
+
+/// Test log --oneline abbreviates SHAs
+#[test]
+fn test_log_oneline() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let (base, left, _, merge) = repo_with_merge(&temp);
+
+    assert_eq!(
+        guts_stdout_trimmed(&temp, &["log", "--oneline"]),
+        format!("{} Merge right\n{} Left\n{} Base", &merge[..7], &left[..7], &base[..7])
+    );
+}
+
+/// Test log --graph draws the fork and join of a merge
+#[test]
+fn test_log_graph() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let (base, left, right, merge) = repo_with_merge(&temp);
+
+    let expected = format!(
+        "* {} Merge right\n|\\\n| * {} Right\n* | {} Left\n|/\n* {} Base",
+        &merge[..7],
+        &right[..7],
+        &left[..7],
+        &base[..7]
+    );
+    assert_eq!(guts_stdout_trimmed(&temp, &["log", "--graph", "--oneline"]), expected);
+}
+
+/// Test the default format shows author, date and the indented message
+#[test]
+fn test_log_verbose_format() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    temp.child("file.txt").write_str("content").unwrap();
+    guts_stdout_trimmed(&temp, &["add", "file.txt"]);
+    let tree = guts_stdout_trimmed(&temp, &["write-tree"]);
+    let commit = guts_stdout_trimmed(
+        &temp,
+        &[
+            "commit-tree",
+            &tree,
+            "-m",
+            "Subject\n\nBody line",
+            "--author",
+            "Ada Lovelace <ada@example.com>",
+            "--author-date",
+            "1700000000",
+        ],
+    );
+    temp.child(".git/refs/heads/main").write_str(&format!("{}\n", commit)).unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("log")
+        .assert()
+        .success()
+        .stdout(format!(
+            "commit {}\nAuthor: Ada Lovelace <ada@example.com>\nDate:   Tue Nov 14 22:13:20 2023 +0000\n\n    Subject\n\n    Body line\n",
+            commit
+        ));
+}
+
+/// Test -n limits the number of commits shown
+#[test]
+fn test_log_max_count() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let (_, left, _, merge) = repo_with_merge(&temp);
+
+    assert_eq!(
+        guts_stdout_trimmed(&temp, &["log", "--oneline", "-n", "2"]),
+        format!("{} Merge right\n{} Left", &merge[..7], &left[..7])
+    );
+    assert_eq!(guts_stdout_trimmed(&temp, &["log", "--graph", "--oneline", "--max-count", "1"]), format!("* {} Merge right", &merge[..7]));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["log", "-n", "0"])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+/// Test --depth stops the walk after that many commits and a larger depth shows everything
+#[test]
+fn test_log_depth() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout_trimmed(&temp, &["init"]);
+    for i in 1..=5 {
+        temp.child("file.txt").write_str(&format!("version {}\n", i)).unwrap();
+        guts_stdout_trimmed(&temp, &["add", "file.txt"]);
+        guts_stdout_trimmed(&temp, &["commit", "-m", &format!("Commit {}", i)]);
+    }
+
+    let subjects = |args: &[&str]| -> Vec<String> {
+        guts_stdout_trimmed(&temp, args).lines().map(|line| line.rsplit("Commit ").next().unwrap().to_string()).collect()
+    };
+    assert_eq!(subjects(&["log", "--oneline", "--depth", "3"]), ["5", "4", "3"]);
+    assert_eq!(subjects(&["log", "--oneline", "--depth", "10"]), ["5", "4", "3", "2", "1"]);
+
+    // The graph ends at the cut without a dangling line
+    let graph = guts_stdout_trimmed(&temp, &["log", "--graph", "--oneline", "--depth", "3"]);
+    assert_eq!(graph.lines().count(), 3, "{}", graph);
+    assert!(graph.lines().all(|line| line.starts_with("* ")), "{}", graph);
+}
+
+/// Test --format expands placeholders and keeps unknown ones literally
+#[test]
+fn test_log_format() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let (_base, left, right, merge) = repo_with_merge(&temp);
+
+    let stdout = guts_stdout_trimmed(&temp, &["log", "-n", "2", "--format=%h %s"]);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    for line in &lines {
+        let (sha, _) = line.split_once(' ').unwrap();
+        assert_eq!(sha.len(), 7);
+        assert!(sha.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+    assert_eq!(lines[0], format!("{} Merge right", &merge[..7]));
+
+    assert_eq!(
+        guts_stdout_trimmed(&temp, &["log", "-n", "1", "--format=%H|%P|%q|100%%"]),
+        format!("{}|{} {}|%q|100%", merge, left, right)
+    );
+
+    let author = guts_stdout_trimmed(&temp, &["log", "-n", "1", "--format=%an <%ae>"]);
+    assert!(author.ends_with('>') && author.contains(" <"), "unexpected author: {}", author);
+}
+
+/// Test --color=always highlights SHAs and --color=never writes no escape codes
+#[test]
+fn test_log_color() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let (_, _, _, merge) = repo_with_merge(&temp);
+
+    let colored = guts_stdout_trimmed(&temp, &["log", "--color=always"]);
+    assert!(colored.starts_with(&format!("\x1b[33mcommit {}\x1b[m\n", merge)), "{}", colored);
+    let oneline = guts_stdout_trimmed(&temp, &["log", "--oneline", "--color=always"]);
+    assert!(oneline.starts_with(&format!("\x1b[33m{}\x1b[m ", &merge[..7])), "{}", oneline);
+
+    assert!(!guts_stdout_trimmed(&temp, &["log", "--color=never"]).contains('\x1b'));
+    // Not a terminal: auto means no color
+    assert!(!guts_stdout_trimmed(&temp, &["log", "--graph"]).contains('\x1b'));
+}
+
+/// Test log --all shows the commits of both sides of a merge and of other branches, each once
+#[test]
+fn test_log_all_follows_every_parent_once() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let (base, left, right, merge) = repo_with_merge(&temp);
+    let tree = guts_stdout_trimmed(&temp, &["write-tree"]);
+    let side = guts_stdout_trimmed(&temp, &["commit-tree", &tree, "-p", &right, "-m", "Side"]);
+    temp.child(".git/refs/heads/side").write_str(&format!("{}\n", side)).unwrap();
+
+    let log = guts_stdout_trimmed(&temp, &["log", "--all", "--format=%H"]);
+    let mut shas: Vec<&str> = log.lines().collect();
+    assert_eq!(shas.len(), 5, "{}", log);
+    assert_eq!(shas.last(), Some(&base.as_str()));
+    shas.sort();
+    let mut expected = [base.as_str(), left.as_str(), right.as_str(), merge.as_str(), side.as_str()];
+    expected.sort();
+    assert_eq!(shas, expected);
+
+    // Without --all the merged-in branch stays hidden
+    assert!(!guts_stdout_trimmed(&temp, &["log", "--format=%H"]).contains(&right));
+}
@@ -0,0 +1,219 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+/// Test status --short prints XY codes for index and working tree
+#[test]
+fn test_status_short() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("modified.txt").write_str("v1").unwrap();
+    temp.child("removed.txt").write_str("gone soon").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg(".").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg("Initial").assert().success();
+
+    // Staged, then modified again
+    temp.child("modified.txt").write_str("v2").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("modified.txt").assert().success();
+    temp.child("modified.txt").write_str("v3").unwrap();
+
+    temp.child("added.txt").write_str("new").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("added.txt").assert().success();
+
+    std::fs::remove_file(temp.path().join("removed.txt")).unwrap();
+    temp.child("untracked.txt").write_str("?").unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("status")
+        .arg("--short")
+        .assert()
+        .success()
+        .stdout("A  added.txt\nMM modified.txt\n D removed.txt\n?? untracked.txt\n");
+}
+
+/// Test status --short prints nothing on a clean tree
+#[test]
+fn test_status_short_clean() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("file.txt").write_str("content").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("file.txt").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg("Initial").assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "-s"])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+/// Test a staged deletion and a staged new file with the same content show as one rename
+#[test]
+fn test_status_detects_rename() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("a.txt").write_str("same content\n").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "a.txt"]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["commit", "-m", "Initial"]).assert().success();
+
+    std::fs::rename(temp.path().join("a.txt"), temp.path().join("b.txt")).unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["rm", "--cached", "a.txt"]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "b.txt"]).assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("renamed:   a.txt -> b.txt\n"))
+        .stdout(predicates::str::contains("deleted").not())
+        .stdout(predicates::str::contains("new file").not());
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "--short"])
+        .assert()
+        .success()
+        .stdout("R  a.txt -> b.txt\n");
+}
+
+/// Test --color=always colors the status codes and --color=never writes no escape codes
+#[test]
+fn test_status_color() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    temp.child("staged.txt").write_str("staged").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("staged.txt").assert().success();
+    temp.child("untracked.txt").write_str("?").unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "--short", "--color=always"])
+        .assert()
+        .success()
+        .stdout("\x1b[32mA\x1b[m  staged.txt\n\x1b[31m??\x1b[m untracked.txt\n");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "--color=always"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[32mnew file:   staged.txt\x1b[m"))
+        .stdout(predicate::str::contains("\x1b[31muntracked.txt\x1b[m"));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "--color=never"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b").not());
+}
+
+/// Test status counts the commits the branch is ahead of its upstream
+#[test]
+fn test_status_ahead_of_upstream() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let guts = |args: &[&str]| {
+        let output = Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(args).output().unwrap();
+        assert!(output.status.success(), "guts {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    guts(&["init"]);
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        temp.child(name).write_str(name).unwrap();
+        guts(&["add", name]);
+        guts(&["commit", "-m", name]);
+        if name == "a.txt" {
+            // No upstream configured yet: nothing about it is printed
+            assert!(!guts(&["status"]).contains("Your branch"));
+            let sha = guts(&["rev-parse", "HEAD"]);
+            temp.child(".git/refs/remotes/origin/main").write_str(&sha).unwrap();
+            guts(&["config", "branch.main.remote", "origin"]);
+            guts(&["config", "branch.main.merge", "refs/heads/main"]);
+            assert!(guts(&["status"]).contains("Your branch is up to date with 'origin/main'."));
+        }
+    }
+
+    assert!(guts(&["status"]).contains("Your branch is ahead of 'origin/main' by 2 commits."));
+}
+
+/// Test status skips the files of a directory that still holds its committed tree
+#[test]
+fn test_status_skips_unchanged_subtrees() {
+    use guts::commands::status::{self, StatusObject};
+    use guts::core::color::ColorWhen;
+    use guts::core::simple_index::with_dir;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    for i in 0..200 {
+        temp.child(format!("big/nested/file{}.txt", i)).write_str(&format!("content {}\n", i)).unwrap();
+    }
+    temp.child("changed.txt").write_str("v1\n").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "."]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["commit", "-m", "Initial"]).assert().success();
+    temp.child("changed.txt").write_str("version 2\n").unwrap();
+
+    let before = status::files_hashed();
+    let output = with_dir(Some(&temp.path().to_path_buf()), || {
+        status::run(&StatusObject {
+            short: true,
+            color: ColorWhen::Never,
+            ignored: false,
+        })
+    })
+    .unwrap();
+
+    assert_eq!(output, " M changed.txt\n");
+    assert_eq!(status::files_hashed() - before, 1);
+}
+
+/// Test ignored files only show up with --ignored
+#[test]
+fn test_status_ignored() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child(".gutsignore").write_str("*.log\nbuild/\n").unwrap();
+    temp.child("debug.log").write_str("noise").unwrap();
+    temp.child("build/out.bin").write_str("binary").unwrap();
+    temp.child("kept.txt").write_str("kept").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "-s"])
+        .assert()
+        .success()
+        .stdout("?? .gutsignore\n?? kept.txt\n");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "-s", "--ignored"])
+        .assert()
+        .success()
+        .stdout("?? .gutsignore\n?? kept.txt\n!! build/\n!! debug.log\n");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "--ignored"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Ignored files:\n"))
+        .stdout(predicate::str::contains("        debug.log\n"));
+}
@@ -0,0 +1,190 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+
+fn short_status(temp: &assert_fs::TempDir) -> String {
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "--short"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// Test a .gutsignore in a subdirectory only applies below it
+#[test]
+fn test_nested_gutsignore() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    temp.child("logs/.gutsignore").write_str("*.tmp\n").unwrap();
+    temp.child("logs/x.tmp").write_str("scratch").unwrap();
+    temp.child("logs/app.log").write_str("log").unwrap();
+    temp.child("root.tmp").write_str("kept").unwrap();
+
+    assert_eq!(
+        short_status(&temp),
+        "?? logs/.gutsignore\n?? logs/app.log\n?? root.tmp\n"
+    );
+}
+
+/// Test a nested negation re-includes a file ignored by the root file
+#[test]
+fn test_nested_gutsignore_negation() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    temp.child(".gutsignore").write_str("*.log\n").unwrap();
+    temp.child("keep/.gutsignore").write_str("!important.log\n").unwrap();
+    temp.child("keep/important.log").write_str("keep me").unwrap();
+    temp.child("keep/debug.log").write_str("drop me").unwrap();
+    temp.child("important.log").write_str("drop me too").unwrap();
+
+    assert_eq!(
+        short_status(&temp),
+        "?? .gutsignore\n?? keep/.gutsignore\n?? keep/important.log\n"
+    );
+}
+
+/// Test a `build/` pattern ignores direct and nested files of the directory
+#[test]
+fn test_directory_pattern_ignores_descendants() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    temp.child(".gutsignore").write_str("build/\n").unwrap();
+    temp.child("build/a.js").write_str("a").unwrap();
+    temp.child("build/sub/b.js").write_str("b").unwrap();
+    temp.child("src/main.js").write_str("main").unwrap();
+
+    assert_eq!(short_status(&temp), "?? .gutsignore\n?? src/main.js\n");
+
+    // add . skips the ignored directory as well
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "."]).assert().success();
+    let ls_files = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("ls-files")
+        .output()
+        .unwrap();
+    let ls_files = String::from_utf8_lossy(&ls_files.stdout);
+    assert!(ls_files.contains("src/main.js"));
+    assert!(!ls_files.contains("build/"));
+}
+
+/// Test add . stages every file of a large tree in one go and still skips ignored files
+#[test]
+fn test_add_many_files_respects_gutsignore() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    temp.child(".gutsignore").write_str("*.log\n").unwrap();
+    for i in 0..200 {
+        temp.child(format!("dir{}/file{}.txt", i % 8, i)).write_str(&i.to_string()).unwrap();
+        temp.child(format!("dir{}/file{}.log", i % 8, i)).write_str("log").unwrap();
+    }
+
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["add", "."])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    // The 200 text files plus .gutsignore
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("Added 201 files:"));
+
+    let status = short_status(&temp);
+    assert!(!status.contains("??"), "unexpected untracked files:\n{}", status);
+    assert_eq!(status.lines().filter(|line| line.starts_with("A ")).count(), 201);
+}
+
+/// Test patterns in .git/info/exclude hide files, and a .gutsignore can re-include them
+#[test]
+fn test_info_exclude() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    temp.child(".git/info/exclude").write_str("# local only\n*.swp\nnotes/\n").unwrap();
+    temp.child("main.rs.swp").write_str("swap").unwrap();
+    temp.child("notes/todo.txt").write_str("todo").unwrap();
+    temp.child("keep.swp").write_str("keep").unwrap();
+    temp.child("main.rs").write_str("fn main() {}").unwrap();
+
+    assert_eq!(short_status(&temp), "?? main.rs\n");
+
+    // Shared ignore files win over the local exclude file
+    temp.child(".gutsignore").write_str("!keep.swp\n").unwrap();
+    assert_eq!(short_status(&temp), "?? .gutsignore\n?? keep.swp\n?? main.rs\n");
+}
+
+/// Test a leading `/` anchors a pattern to the root while a bare name matches at any depth
+#[test]
+fn test_anchored_and_floating_patterns() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    temp.child(".gutsignore").write_str("/build\n*.log\n").unwrap();
+    temp.child("build/out.o").write_str("root build").unwrap();
+    temp.child("src/build/gen.rs").write_str("nested build").unwrap();
+    temp.child("app.log").write_str("log").unwrap();
+    temp.child("src/deep/debug.log").write_str("log").unwrap();
+    temp.child("src/main.rs").write_str("fn main() {}").unwrap();
+
+    assert_eq!(
+        short_status(&temp),
+        "?? .gutsignore\n?? src/build/gen.rs\n?? src/main.rs\n"
+    );
+}
+
+/// Test `**` matches any number of directories, including none
+#[test]
+fn test_deep_glob_pattern() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    temp.child(".gutsignore").write_str("doc/**/*.pdf\n").unwrap();
+    temp.child("doc/manual.pdf").write_str("pdf").unwrap();
+    temp.child("doc/api/v1/ref.pdf").write_str("pdf").unwrap();
+    temp.child("doc/api/v1/ref.md").write_str("md").unwrap();
+    temp.child("other/doc/x.pdf").write_str("pdf").unwrap();
+
+    assert_eq!(
+        short_status(&temp),
+        "?? .gutsignore\n?? doc/api/v1/ref.md\n?? other/doc/x.pdf\n"
+    );
+}
+
+/// Test a negation re-includes one file of an ignored directory's content, but not of an ignored directory
+#[test]
+fn test_negation_inside_ignored_directory() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    temp.child(".gutsignore")
+        .write_str("logs/*\n!logs/keep.log\ncache/\n!cache/keep.txt\n")
+        .unwrap();
+    temp.child("logs/keep.log").write_str("kept").unwrap();
+    temp.child("logs/drop.log").write_str("dropped").unwrap();
+    temp.child("cache/keep.txt").write_str("still ignored").unwrap();
+
+    // Like git: the content of `logs` is ignored but `logs` itself is not, while
+    // nothing below the ignored `cache` directory can be re-included
+    assert_eq!(short_status(&temp), "?? .gutsignore\n?? logs/keep.log\n");
+}
+
+/// Test ignore files inside an ignored directory are never read
+#[test]
+fn test_ignore_files_in_ignored_directory_are_skipped() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    temp.child(".gutsignore").write_str("target/\n").unwrap();
+    // An invalid pattern would make status fail if this file were parsed
+    temp.child("target/debug/.gutsignore").write_str("[\n").unwrap();
+    temp.child("target/debug/app").write_str("binary").unwrap();
+    temp.child("main.rs").write_str("fn main() {}\n").unwrap();
+
+    assert_eq!(short_status(&temp), "?? .gutsignore\n?? main.rs\n");
+}
@@ -0,0 +1,125 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+fn ls_files(temp: &assert_fs::TempDir) -> String {
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("ls-files")
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// Test rm refuses directories without -r
+#[test]
+fn test_rm_directory_requires_recursive() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("docs/guide.md").write_str("guide").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg(".").assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["rm", "docs"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("without -r"));
+}
+
+/// Test rm -r removes tracked files of a directory but keeps untracked ones
+#[test]
+fn test_rm_recursive() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("docs/guide.md").write_str("guide").unwrap();
+    temp.child("docs/api/index.md").write_str("api").unwrap();
+    temp.child("readme.md").write_str("readme").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg(".").assert().success();
+    temp.child("docs/notes.txt").write_str("untracked").unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["rm", "-r", "docs"])
+        .assert()
+        .success()
+        .stdout("rm 'docs/api/index.md'\nrm 'docs/guide.md'\n");
+
+    assert_eq!(ls_files(&temp), "readme.md\n");
+    assert!(!temp.path().join("docs/guide.md").exists());
+    assert!(!temp.path().join("docs/api").exists());
+    assert!(temp.path().join("docs/notes.txt").exists());
+}
+
+/// Test rm -r fails when nothing tracked is inside the directory
+#[test]
+fn test_rm_recursive_untracked_directory() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    temp.child("scratch/tmp.txt").write_str("untracked").unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["rm", "-r", "scratch"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("did not match any files"));
+
+    assert!(temp.path().join("scratch/tmp.txt").exists());
+}
+
+/// Test rm --cached stops tracking a file but keeps it on disk
+#[test]
+fn test_rm_cached() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("secret.env").write_str("TOKEN=123").unwrap();
+    temp.child("app.rs").write_str("fn main() {}").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg(".").assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["rm", "--cached", "secret.env"])
+        .assert()
+        .success()
+        .stdout("rm 'secret.env'\n");
+
+    assert!(temp.path().join("secret.env").exists());
+    assert_eq!(ls_files(&temp), "app.rs\n");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "--short"])
+        .assert()
+        .success()
+        .stdout("A  app.rs\n?? secret.env\n");
+}
+
+/// Test rm -r --cached untracks a whole directory without deleting it
+#[test]
+fn test_rm_recursive_cached() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("vendor/lib/a.rs").write_str("a").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg(".").assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["rm", "-r", "--cached", "vendor"])
+        .assert()
+        .success();
+
+    assert!(temp.path().join("vendor/lib/a.rs").exists());
+    assert_eq!(ls_files(&temp), "");
+}
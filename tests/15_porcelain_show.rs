@@ -0,0 +1,55 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+/// Test show prints the commit header and the diff against its parent
+#[test]
+fn test_show_commit() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let file = temp.child("notes.txt");
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    file.write_str("one\ntwo\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("notes.txt").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg("First").assert().success();
+
+    file.write_str("one\n2\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("notes.txt").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg("Second").assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("show")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("commit "))
+        .stdout(predicate::str::contains("Author: "))
+        .stdout(predicate::str::contains("\n    Second\n\ndiff --git a/notes.txt b/notes.txt\n"))
+        .stdout(predicate::str::contains("@@ -1,2 +1,2 @@\n one\n-two\n+2\n"));
+}
+
+/// Test show on the root commit lists every file as added
+#[test]
+fn test_show_root_commit() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("a.txt").write_str("alpha\n").unwrap();
+    temp.child("dir/b.txt").write_str("beta\n").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg(".").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg("Root").assert().success();
+    temp.child("a.txt").write_str("changed\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("a.txt").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg("Change").assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["show", "HEAD~1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("    Root\n"))
+        .stdout(predicate::str::contains("new file mode 100644\n--- /dev/null\n+++ b/a.txt\n@@ -0,0 +1 @@\n+alpha\n"))
+        .stdout(predicate::str::contains("+++ b/dir/b.txt\n"));
+}
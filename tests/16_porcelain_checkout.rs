@@ -0,0 +1,267 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::fs;
+
+mod common;
+use common::commit_file;
+
+/// Test checkout -- <file> restores one file from HEAD and leaves the others alone
+#[test]
+fn test_checkout_restore_file() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("README.md").write_str("# Project\n").unwrap();
+    temp.child("src/lib.rs").write_str("pub fn lib() {}\n").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg(".").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg("Initial").assert().success();
+
+    temp.child("README.md").write_str("scribbles\n").unwrap();
+    temp.child("src/lib.rs").write_str("// work in progress\n").unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["checkout", "--", "README.md"])
+        .assert()
+        .success();
+
+    assert_eq!(fs::read_to_string(temp.path().join("README.md")).unwrap(), "# Project\n");
+    assert_eq!(fs::read_to_string(temp.path().join("src/lib.rs")).unwrap(), "// work in progress\n");
+
+    // Nested paths work too
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["checkout", "--", "src/lib.rs"])
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(temp.path().join("src/lib.rs")).unwrap(), "pub fn lib() {}\n");
+}
+
+/// Test checkout -- <file> refuses untracked paths
+#[test]
+fn test_checkout_restore_untracked_file() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("tracked.txt").write_str("tracked\n").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("add").arg("tracked.txt").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("commit").arg("-m").arg("Initial").assert().success();
+
+    temp.child("notes.txt").write_str("mine\n").unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["checkout", "--", "notes.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("did not match any file(s) known to guts"));
+
+    assert_eq!(fs::read_to_string(temp.path().join("notes.txt")).unwrap(), "mine\n");
+}
+
+/// Test switching branches keeps untracked files and removes files only tracked on the old branch
+#[test]
+fn test_checkout_keeps_untracked_files() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    commit_file(&temp, "main.txt", "main\n");
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["checkout", "-b", "feature"]).assert().success();
+    commit_file(&temp, "feature/new.txt", "feature\n");
+
+    temp.child("notes.txt").write_str("mine\n").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["checkout", "main"]).assert().success();
+
+    assert_eq!(fs::read_to_string(temp.path().join("notes.txt")).unwrap(), "mine\n");
+    assert_eq!(fs::read_to_string(temp.path().join("main.txt")).unwrap(), "main\n");
+    assert!(!temp.path().join("feature").exists());
+}
+
+/// Test checkout refuses to overwrite an untracked file and lists it
+#[test]
+fn test_checkout_untracked_file_conflict() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    commit_file(&temp, "main.txt", "main\n");
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["checkout", "-b", "feature"]).assert().success();
+    commit_file(&temp, "notes.txt", "from feature\n");
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["checkout", "main"]).assert().success();
+
+    temp.child("notes.txt").write_str("mine\n").unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["checkout", "feature"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("would be overwritten by checkout:\n\tnotes.txt"));
+
+    assert_eq!(fs::read_to_string(temp.path().join("notes.txt")).unwrap(), "mine\n");
+    assert_eq!(fs::read_to_string(temp.path().join(".git/HEAD")).unwrap().trim(), "ref: refs/heads/main");
+}
+
+/// Test checkout prints no debugging output
+#[test]
+fn test_checkout_output_has_no_debug() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    commit_file(&temp, "main.txt", "main\n");
+    commit_file(&temp, "src/lib.rs", "pub fn lib() {}\n");
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["checkout", "-b", "feature"]).assert().success();
+    commit_file(&temp, "feature.txt", "feature\n");
+
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["checkout", "main"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("DEBUG"));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("DEBUG"));
+}
+
+/// Test a symlink is committed as a 120000 link and recreated as a link on checkout
+#[cfg(unix)]
+#[test]
+fn test_checkout_round_trips_symlink() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    commit_file(&temp, "target.txt", "target\n");
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["checkout", "-b", "feature"]).assert().success();
+    std::os::unix::fs::symlink("target.txt", temp.path().join("link")).unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "link"]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["commit", "-m", "Add link"]).assert().success();
+
+    let tree = Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("write-tree").output().unwrap();
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("ls-tree")
+        .arg(String::from_utf8_lossy(&tree.stdout).trim())
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("120000 blob [0-9a-f]{40}\tlink").unwrap());
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["checkout", "main"]).assert().success();
+    assert!(fs::symlink_metadata(temp.path().join("link")).is_err());
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["checkout", "feature"]).assert().success();
+    let link = temp.path().join("link");
+    assert!(fs::symlink_metadata(&link).unwrap().file_type().is_symlink());
+    assert_eq!(fs::read_link(&link).unwrap(), std::path::PathBuf::from("target.txt"));
+    assert_eq!(fs::read_to_string(&link).unwrap(), "target\n");
+
+    // The restored link is not reported as a change
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "--short"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("link").not());
+}
+
+/// Test checkout -- <path> restores links as links, keeps the exec bit and never writes through a link
+#[cfg(unix)]
+#[test]
+fn test_checkout_paths_restores_links_and_modes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let outside = assert_fs::TempDir::new().unwrap();
+    outside.child("secret.txt").write_str("secret\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    temp.child("run.sh").write_str("#!/bin/sh\n").unwrap();
+    fs::set_permissions(temp.path().join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+    std::os::unix::fs::symlink("run.sh", temp.path().join("link")).unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "run.sh", "link"]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["commit", "-m", "Script"]).assert().success();
+
+    // The script becomes a link to a file outside the repository, the link a plain file
+    fs::remove_file(temp.path().join("run.sh")).unwrap();
+    std::os::unix::fs::symlink(outside.path().join("secret.txt"), temp.path().join("run.sh")).unwrap();
+    fs::remove_file(temp.path().join("link")).unwrap();
+    temp.child("link").write_str("not a link\n").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["checkout", "--", "run.sh", "link"]).assert().success();
+
+    assert_eq!(fs::read_to_string(outside.path().join("secret.txt")).unwrap(), "secret\n");
+    let script = fs::symlink_metadata(temp.path().join("run.sh")).unwrap();
+    assert!(script.file_type().is_file());
+    assert_eq!(script.permissions().mode() & 0o111, 0o111);
+    assert_eq!(fs::read_link(temp.path().join("link")).unwrap(), std::path::PathBuf::from("run.sh"));
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "--short"])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+/// Test the uncommitted changes check stays fast and accurate on a few hundred files
+#[test]
+fn test_checkout_change_check_many_files() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    for i in 0..300 {
+        temp.child(format!("dir{}/file{}.txt", i % 10, i)).write_str(&format!("content {}\n", i)).unwrap();
+    }
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "."]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["commit", "-m", "Many files"]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["checkout", "-b", "feature"]).assert().success();
+
+    let start = std::time::Instant::now();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["checkout", "main"]).assert().success();
+    assert!(start.elapsed() < std::time::Duration::from_secs(10), "checkout took {:?}", start.elapsed());
+
+    // A single modified file deep in the list still blocks the switch
+    temp.child("dir9/file299.txt").write_str("changed\n").unwrap();
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["checkout", "feature"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("You have uncommitted changes"));
+}
+
+/// Test a staged new file counts as an uncommitted change and survives a refused switch
+#[test]
+fn test_checkout_refuses_with_staged_new_file() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("README.md").write_str("# Project\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "."]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["commit", "-m", "Initial"]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["checkout", "-b", "feature"]).assert().success();
+
+    temp.child("new.txt").write_str("staged only\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "new.txt"]).assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["checkout", "main"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("You have uncommitted changes"));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("ls-files")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("new.txt"));
+}
@@ -0,0 +1,60 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+mod common;
+use common::{commit_file_with_message, guts_stdout_trimmed};
+
+/// Test commits, checkouts and resets are listed newest first
+#[test]
+fn test_reflog_records_head_movements() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    let first = commit_file_with_message(&temp, "a.txt", "a", "First");
+    let second = commit_file_with_message(&temp, "b.txt", "b", "Second");
+    guts_stdout_trimmed(&temp, &["checkout", "-b", "feature"]);
+    guts_stdout_trimmed(&temp, &["reset", "--hard", &first]);
+
+    let expected = format!(
+        "{first} HEAD@{{0}}: reset: moving to {first_full}\n\
+         {second} HEAD@{{1}}: checkout: moving from main to feature\n\
+         {second} HEAD@{{2}}: commit: Second\n\
+         {first} HEAD@{{3}}: commit (initial): First",
+        first = &first[..7],
+        second = &second[..7],
+        first_full = first,
+    );
+    assert_eq!(guts_stdout_trimmed(&temp, &["reflog"]), expected);
+
+    // Each line follows git's on-disk format
+    let log = std::fs::read_to_string(temp.path().join(".git/logs/HEAD")).unwrap();
+    assert!(log.starts_with(&format!("{} {} guts <guts@example.com> ", "0".repeat(40), first)));
+    assert!(log.lines().next().unwrap().ends_with(" +0000\tcommit (initial): First"));
+
+    // The branch keeps its own log
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["reflog", "feature"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("feature@{1}: branch: Created from main"));
+}
+
+/// Test a commit lost by reset --hard can be recovered through HEAD@{n}
+#[test]
+fn test_reflog_recovers_reset_commit() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    let first = commit_file_with_message(&temp, "a.txt", "a", "First");
+    let second = commit_file_with_message(&temp, "b.txt", "b", "Second");
+    guts_stdout_trimmed(&temp, &["reset", "--hard", &first]);
+    assert!(!temp.path().join("b.txt").exists());
+
+    assert_eq!(guts_stdout_trimmed(&temp, &["rev-parse", "HEAD@{1}"]), second);
+    guts_stdout_trimmed(&temp, &["reset", "--hard", "HEAD@{1}"]);
+
+    assert_eq!(guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]), second);
+    assert_eq!(std::fs::read_to_string(temp.path().join("b.txt")).unwrap(), "b");
+}
@@ -0,0 +1,69 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+mod common;
+use common::{commit_file_with_message, guts_stdout_trimmed};
+
+/// Test reverting a commit that added a file removes the file in a new commit
+#[test]
+fn test_revert_added_file() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    commit_file_with_message(&temp, "README.md", "# Project\n", "Initial");
+    let added = commit_file_with_message(&temp, "src/extra.rs", "fn extra() {}\n", "Add extra");
+    commit_file_with_message(&temp, "README.md", "# Project\n\nMore\n", "Extend README");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["revert", &added])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Revert \"Add extra\""));
+
+    assert!(!temp.path().join("src/extra.rs").exists());
+    // Later changes are kept
+    assert_eq!(std::fs::read_to_string(temp.path().join("README.md")).unwrap(), "# Project\n\nMore\n");
+
+    let head = guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]);
+    let message = guts_stdout_trimmed(&temp, &["cat-file", "-p", &head]);
+    assert!(message.contains(&format!("Revert \"Add extra\"\n\nThis reverts commit {}.", added)));
+    assert!(!guts_stdout_trimmed(&temp, &["ls-files"]).contains("extra.rs"));
+    assert_eq!(guts_stdout_trimmed(&temp, &["status", "--short"]), "");
+}
+
+/// Test reverting a modification restores the parent content
+#[test]
+fn test_revert_modification() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    commit_file_with_message(&temp, "file.txt", "v1\n", "Initial");
+    commit_file_with_message(&temp, "file.txt", "v2\n", "Change");
+
+    guts_stdout_trimmed(&temp, &["revert", "HEAD"]);
+    assert_eq!(std::fs::read_to_string(temp.path().join("file.txt")).unwrap(), "v1\n");
+}
+
+/// Test revert refuses when the file changed again since the commit
+#[test]
+fn test_revert_conflict() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    commit_file_with_message(&temp, "file.txt", "v1\n", "Initial");
+    let change = commit_file_with_message(&temp, "file.txt", "v2\n", "Change");
+    let head = commit_file_with_message(&temp, "file.txt", "v3\n", "Change again");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["revert", &change])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("CONFLICT (content): Merge conflict in file.txt\nerror: could not revert"));
+
+    assert_eq!(guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]), head);
+    assert_eq!(std::fs::read_to_string(temp.path().join("file.txt")).unwrap(), "v3\n");
+}
@@ -0,0 +1,69 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+mod common;
+use common::{commit_file_with_message, guts_stdout_trimmed};
+
+/// Test a commit from another branch is replayed on the current branch
+#[test]
+fn test_cherry_pick_from_other_branch() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    commit_file_with_message(&temp, "README.md", "# Project\n", "Initial");
+    guts_stdout_trimmed(&temp, &["checkout", "-b", "feature"]);
+    commit_file_with_message(&temp, "feature/unwanted.txt", "no\n", "Unwanted");
+    let picked = commit_file_with_message(&temp, "src/fix.rs", "fn fix() {}\n", "Fix bug");
+
+    guts_stdout_trimmed(&temp, &["checkout", "main"]);
+    let main_head = commit_file_with_message(&temp, "main.txt", "main\n", "Main work");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["cherry-pick", &picked])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Fix bug"));
+
+    assert_eq!(std::fs::read_to_string(temp.path().join("src/fix.rs")).unwrap(), "fn fix() {}\n");
+    assert!(temp.path().join("main.txt").exists());
+    assert!(!temp.path().join("feature/unwanted.txt").exists());
+
+    // New commit on top of main reusing the message
+    assert_eq!(guts_stdout_trimmed(&temp, &["rev-parse", "HEAD^"]), main_head);
+    assert_eq!(guts_stdout_trimmed(&temp, &["log", "--oneline", "-n", "1"])[8..], *"Fix bug");
+    assert_eq!(guts_stdout_trimmed(&temp, &["status", "--short"]), "");
+
+    // The picked file is stored in a subtree
+    let head = guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]);
+    let commit = guts_stdout_trimmed(&temp, &["cat-file", "-p", &head]);
+    let tree_sha = commit.lines().next().unwrap().strip_prefix("tree ").unwrap();
+    let tree = guts_stdout_trimmed(&temp, &["ls-tree", "-r", tree_sha]);
+    assert!(tree.contains("\tsrc/fix.rs"));
+}
+
+/// Test conflicting changes are reported and nothing is committed
+#[test]
+fn test_cherry_pick_conflict() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+
+    commit_file_with_message(&temp, "file.txt", "base\n", "Initial");
+    guts_stdout_trimmed(&temp, &["checkout", "-b", "feature"]);
+    let picked = commit_file_with_message(&temp, "file.txt", "feature\n", "Feature change");
+
+    guts_stdout_trimmed(&temp, &["checkout", "main"]);
+    let main_head = commit_file_with_message(&temp, "file.txt", "main\n", "Main change");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["cherry-pick", &picked])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("CONFLICT (content): Merge conflict in file.txt"));
+
+    assert_eq!(guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]), main_head);
+    assert_eq!(std::fs::read_to_string(temp.path().join("file.txt")).unwrap(), "main\n");
+}
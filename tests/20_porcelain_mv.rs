@@ -0,0 +1,81 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+fn ls_files(temp: &assert_fs::TempDir) -> String {
+    let output = Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("ls-files").output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// Test mv renames the file on disk and in the index
+#[test]
+fn test_mv_renames_tracked_file() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("old.txt").write_str("content\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "old.txt"]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["commit", "-m", "Initial"]).assert().success();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["mv", "old.txt", "new.txt"]).assert().success();
+
+    temp.child("old.txt").assert(predicate::path::missing());
+    temp.child("new.txt").assert("content\n");
+    let files = ls_files(&temp);
+    assert!(files.contains("new.txt"));
+    assert!(!files.contains("old.txt"));
+
+    // Same blob on both sides: status sees a rename
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["status", "--short"])
+        .assert()
+        .success()
+        .stdout("R  old.txt -> new.txt\n");
+}
+
+/// Test mv into a directory keeps the file name
+#[test]
+fn test_mv_into_directory() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("a.txt").write_str("a\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "a.txt"]).assert().success();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["mv", "a.txt", "dir/"]).assert().success();
+
+    temp.child("dir/a.txt").assert("a\n");
+    let files = ls_files(&temp);
+    assert!(files.contains("dir/a.txt"));
+    assert!(!files.lines().any(|line| line == "a.txt"));
+}
+
+/// Test mv refuses untracked sources and existing destinations
+#[test]
+fn test_mv_errors() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("tracked.txt").write_str("t\n").unwrap();
+    temp.child("other.txt").write_str("o\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "tracked.txt"]).assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["mv", "other.txt", "moved.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not under version control"));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["mv", "tracked.txt", "other.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("destination exists"));
+
+    temp.child("tracked.txt").assert("t\n");
+    temp.child("other.txt").assert("o\n");
+}
@@ -0,0 +1,68 @@
+use assert_fs::prelude::*;
+
+mod common;
+use common::guts_stdout;
+
+/// Test ls-files --stage prints `<mode> <sha> 0\t<path>` for each entry
+#[test]
+fn test_ls_files_stage() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("a.txt").write_str("a\n").unwrap();
+    temp.child("dir/b.txt").write_str("b\n").unwrap();
+    guts_stdout(&temp, &["init"]);
+    guts_stdout(&temp, &["add", "."]);
+
+    let a_sha = guts_stdout(&temp, &["hash-object", "a.txt"]);
+    let b_sha = guts_stdout(&temp, &["hash-object", "dir/b.txt"]);
+    assert_eq!(
+        guts_stdout(&temp, &["ls-files", "--stage"]),
+        format!("100644 {} 0\ta.txt\n100644 {} 0\tdir/b.txt\n", a_sha.trim(), b_sha.trim())
+    );
+
+    // The default output stays path-only
+    assert_eq!(guts_stdout(&temp, &["ls-files"]), "a.txt\ndir/b.txt\n");
+}
+
+/// Commit `kept.txt`, `changed.txt` and `deleted.txt`, then modify, delete and add files
+fn repo_with_changes(temp: &assert_fs::TempDir) {
+    temp.child(".gutsignore").write_str("*.log\n").unwrap();
+    temp.child("kept.txt").write_str("kept\n").unwrap();
+    temp.child("changed.txt").write_str("v1\n").unwrap();
+    temp.child("deleted.txt").write_str("bye\n").unwrap();
+    guts_stdout(temp, &["init"]);
+    guts_stdout(temp, &["add", "."]);
+    guts_stdout(temp, &["commit", "-m", "Initial"]);
+
+    temp.child("changed.txt").write_str("v2\n").unwrap();
+    std::fs::remove_file(temp.path().join("deleted.txt")).unwrap();
+    temp.child("new/untracked.txt").write_str("new\n").unwrap();
+    temp.child("debug.log").write_str("ignored\n").unwrap();
+}
+
+/// Test ls-files -o lists untracked files and skips ignored ones
+#[test]
+fn test_ls_files_others() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    repo_with_changes(&temp);
+
+    assert_eq!(guts_stdout(&temp, &["ls-files", "-o"]), "new/untracked.txt\n");
+}
+
+/// Test ls-files -m lists modified and deleted tracked files
+#[test]
+fn test_ls_files_modified() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    repo_with_changes(&temp);
+
+    assert_eq!(guts_stdout(&temp, &["ls-files", "-m"]), "changed.txt\ndeleted.txt\n");
+
+    // Both filters together, and the default listing is unchanged
+    assert_eq!(
+        guts_stdout(&temp, &["ls-files", "-m", "-o"]),
+        "changed.txt\ndeleted.txt\nnew/untracked.txt\n"
+    );
+    assert_eq!(
+        guts_stdout(&temp, &["ls-files"]),
+        ".gutsignore\nchanged.txt\ndeleted.txt\nkept.txt\n"
+    );
+}
@@ -0,0 +1,93 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use std::fs;
+use std::process::Command as StdCommand;
+
+/// Test init creates the same core layout as git init
+#[test]
+fn test_init_layout_matches_git() {
+    let guts_repo = assert_fs::TempDir::new().unwrap();
+    let git_repo = assert_fs::TempDir::new().unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(guts_repo.path()).arg("init").assert().success();
+    StdCommand::new("git")
+        .current_dir(git_repo.path())
+        .args(["init", "--initial-branch=main"])
+        .output()
+        .unwrap();
+
+    for path in ["HEAD", "config", "objects", "objects/info", "objects/pack", "refs/heads", "refs/tags"] {
+        assert!(git_repo.path().join(".git").join(path).exists(), "git has no .git/{}", path);
+        assert!(guts_repo.path().join(".git").join(path).exists(), "guts has no .git/{}", path);
+    }
+
+    assert_eq!(fs::read_to_string(guts_repo.path().join(".git/HEAD")).unwrap(), "ref: refs/heads/main\n");
+
+    // git itself reads the config written by guts
+    for (key, value) in [("core.repositoryformatversion", "0"), ("core.filemode", "true"), ("core.bare", "false")] {
+        let output = StdCommand::new("git")
+            .current_dir(guts_repo.path())
+            .args(["config", "--get", key])
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), value);
+    }
+}
+
+/// Test --initial-branch points HEAD (and the first commit) to another branch
+#[test]
+fn test_init_initial_branch() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["init", "--initial-branch=trunk"])
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(temp.path().join(".git/HEAD")).unwrap(), "ref: refs/heads/trunk\n");
+
+    temp.child("file.txt").write_str("content\n").unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["add", "file.txt"]).assert().success();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["commit", "-m", "Initial"]).assert().success();
+    temp.child(".git/refs/heads/trunk").assert(predicates::path::exists());
+
+    // Invalid names are refused
+    let other = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(other.path())
+        .args(["init", "--initial-branch=bad name"])
+        .assert()
+        .failure();
+}
+
+/// Test init.defaultBranch from ~/.gutsconfig names the first branch, --initial-branch still wins
+#[test]
+fn test_init_default_branch_from_config() {
+    let home = assert_fs::TempDir::new().unwrap();
+    home.child(".gutsconfig").write_str("[init]\n\tdefaultBranch = trunk\n").unwrap();
+
+    let temp = assert_fs::TempDir::new().unwrap();
+    let guts = |args: &[&str]| {
+        Command::cargo_bin("guts").unwrap().current_dir(temp.path()).env("HOME", home.path()).args(args).assert()
+    };
+    guts(&["init"]).success();
+    assert_eq!(fs::read_to_string(temp.path().join(".git/HEAD")).unwrap(), "ref: refs/heads/trunk\n");
+
+    temp.child("file.txt").write_str("content\n").unwrap();
+    guts(&["add", "file.txt"]).success();
+    guts(&["commit", "-m", "Initial"]).success();
+    temp.child(".git/refs/heads/trunk").assert(predicates::path::exists());
+    temp.child(".git/refs/heads/main").assert(predicates::path::missing());
+    guts(&["status"]).success().stdout(predicates::str::starts_with("On branch trunk\n"));
+
+    let other = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(other.path())
+        .env("HOME", home.path())
+        .args(["init", "--initial-branch=main"])
+        .assert()
+        .success();
+    assert_eq!(fs::read_to_string(other.path().join(".git/HEAD")).unwrap(), "ref: refs/heads/main\n");
+}
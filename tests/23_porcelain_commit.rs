@@ -0,0 +1,144 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::guts_stdout_trimmed;
+
+/// Test --amend replaces the tip commit, keeping its parent
+#[test]
+fn test_commit_amend() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout_trimmed(&temp, &["init"]);
+    temp.child("file.txt").write_str("v1\n").unwrap();
+    guts_stdout_trimmed(&temp, &["add", "file.txt"]);
+    guts_stdout_trimmed(&temp, &["commit", "-m", "First"]);
+    let first = guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]);
+
+    temp.child("file.txt").write_str("v2\n").unwrap();
+    guts_stdout_trimmed(&temp, &["add", "file.txt"]);
+    guts_stdout_trimmed(&temp, &["commit", "-m", "Secnod"]);
+    let typo = guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]);
+
+    // Unchanged tree, new message: still a new commit
+    guts_stdout_trimmed(&temp, &["commit", "--amend", "-m", "Second"]);
+    let amended = guts_stdout_trimmed(&temp, &["rev-parse", "main"]);
+    assert_ne!(amended, typo);
+    assert_eq!(guts_stdout_trimmed(&temp, &["rev-parse", "HEAD~1"]), first);
+    assert_eq!(guts_stdout_trimmed(&temp, &["log", "--format=%s"]), "Second\nFirst");
+
+    // Without -m the message is reused, with the newly staged content
+    temp.child("other.txt").write_str("other\n").unwrap();
+    guts_stdout_trimmed(&temp, &["add", "other.txt"]);
+    guts_stdout_trimmed(&temp, &["commit", "--amend"]);
+    assert_eq!(guts_stdout_trimmed(&temp, &["log", "--format=%s"]), "Second\nFirst");
+    assert_ne!(guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]), amended);
+}
+
+/// Test --amend works on the root commit and needs a commit to amend
+#[test]
+fn test_commit_amend_root() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout_trimmed(&temp, &["init"]);
+    temp.child("file.txt").write_str("v1\n").unwrap();
+    guts_stdout_trimmed(&temp, &["add", "file.txt"]);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["commit", "--amend", "-m", "Nothing yet"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("nothing to amend"));
+
+    guts_stdout_trimmed(&temp, &["commit", "-m", "Root"]);
+    let root = guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]);
+    guts_stdout_trimmed(&temp, &["commit", "--amend", "-m", "New root"]);
+
+    assert_ne!(guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]), root);
+    assert_eq!(guts_stdout_trimmed(&temp, &["log", "--format=%s|%P"]), "New root|");
+}
+
+/// Test committing with nothing staged tells a clean tree from unstaged changes
+#[test]
+fn test_commit_nothing_staged() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout_trimmed(&temp, &["init"]);
+    temp.child("file.txt").write_str("v1\n").unwrap();
+    guts_stdout_trimmed(&temp, &["add", "file.txt"]);
+    guts_stdout_trimmed(&temp, &["commit", "-m", "First"]);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["commit", "-m", "Again"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("nothing to commit, working tree clean"));
+
+    temp.child("file.txt").write_str("v2\n").unwrap();
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["commit", "-m", "Unstaged"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no changes added to commit"))
+        .stderr(predicate::str::contains("working tree clean").not());
+}
+
+/// Test --author overrides the author line only
+#[test]
+fn test_commit_author_override() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout_trimmed(&temp, &["init"]);
+    guts_stdout_trimmed(&temp, &["config", "user.name", "Committer"]);
+    guts_stdout_trimmed(&temp, &["config", "user.email", "committer@example.com"]);
+    temp.child("file.txt").write_str("v1\n").unwrap();
+    guts_stdout_trimmed(&temp, &["add", "file.txt"]);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["commit", "-m", "Imported", "--author", "Ada Lovelace"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not 'Name <email>'"));
+
+    guts_stdout_trimmed(&temp, &["commit", "-m", "Imported", "--author=Ada Lovelace <ada@example.com>"]);
+    let head = guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]);
+    let object = guts_stdout_trimmed(&temp, &["cat-file", "-p", &head]);
+
+    assert!(object.contains("\nauthor Ada Lovelace <ada@example.com> "), "{}", object);
+    assert!(object.contains("\ncommitter Committer <committer@example.com> "), "{}", object);
+}
+
+/// Test --allow-empty records a commit with the same tree as HEAD, even on an empty index
+#[test]
+fn test_commit_allow_empty() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout_trimmed(&temp, &["init"]);
+    guts_stdout_trimmed(&temp, &["commit", "--allow-empty", "-m", "Root"]);
+    let root = guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]);
+    assert!(guts_stdout_trimmed(&temp, &["cat-file", "-p", &root]).contains("tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904"));
+
+    temp.child("file.txt").write_str("v1\n").unwrap();
+    guts_stdout_trimmed(&temp, &["add", "file.txt"]);
+    guts_stdout_trimmed(&temp, &["commit", "-m", "First"]);
+    let first = guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["commit", "-m", "Same tree"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("nothing to commit"));
+    assert_eq!(guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]), first);
+
+    guts_stdout_trimmed(&temp, &["commit", "--allow-empty", "-m", "Same tree"]);
+    assert_eq!(guts_stdout_trimmed(&temp, &["rev-parse", "HEAD~1"]), first);
+    let tree_line = |rev: &str| guts_stdout_trimmed(&temp, &["cat-file", "-p", rev]).lines().next().unwrap().to_string();
+    let head = guts_stdout_trimmed(&temp, &["rev-parse", "HEAD"]);
+    assert_eq!(tree_line(&head), tree_line(&first));
+}
@@ -0,0 +1,79 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::guts_stdout;
+
+/// Commit `tracked.txt` and `src/lib.rs`, then add untracked and ignored files
+fn repo_with_untracked(temp: &assert_fs::TempDir) {
+    temp.child(".gutsignore").write_str("*.log\n").unwrap();
+    temp.child("tracked.txt").write_str("tracked\n").unwrap();
+    temp.child("src/lib.rs").write_str("lib\n").unwrap();
+    guts_stdout(temp, &["init"]);
+    guts_stdout(temp, &["add", "."]);
+    guts_stdout(temp, &["commit", "-m", "initial"]);
+
+    temp.child("scratch.txt").write_str("scratch\n").unwrap();
+    temp.child("src/tmp.rs").write_str("tmp\n").unwrap();
+    temp.child("build/out.bin").write_str("out\n").unwrap();
+    temp.child("debug.log").write_str("log\n").unwrap();
+}
+
+/// Test clean does nothing without -f or -n
+#[test]
+fn test_clean_requires_force_or_dry_run() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    repo_with_untracked(&temp);
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("clean")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("refusing to clean"));
+    temp.child("scratch.txt").assert(predicate::path::exists());
+}
+
+/// Test clean -n lists untracked files and deletes nothing
+#[test]
+fn test_clean_dry_run() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    repo_with_untracked(&temp);
+
+    assert_eq!(
+        guts_stdout(&temp, &["clean", "-n"]),
+        "Would remove scratch.txt\nWould remove src/tmp.rs\n"
+    );
+    assert_eq!(
+        guts_stdout(&temp, &["clean", "-n", "-d"]),
+        "Would remove build/\nWould remove scratch.txt\nWould remove src/tmp.rs\n"
+    );
+    temp.child("scratch.txt").assert(predicate::path::exists());
+    temp.child("build/out.bin").assert(predicate::path::exists());
+}
+
+/// Test clean -f deletes untracked files, and -d untracked directories, keeping the rest
+#[test]
+fn test_clean_force() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    repo_with_untracked(&temp);
+
+    assert_eq!(
+        guts_stdout(&temp, &["clean", "-f"]),
+        "Removing scratch.txt\nRemoving src/tmp.rs\n"
+    );
+    temp.child("scratch.txt").assert(predicate::path::missing());
+    temp.child("src/tmp.rs").assert(predicate::path::missing());
+    temp.child("build/out.bin").assert(predicate::path::exists());
+
+    assert_eq!(guts_stdout(&temp, &["clean", "-f", "-d"]), "Removing build/\n");
+    temp.child("build").assert(predicate::path::missing());
+
+    // Tracked and ignored files are never touched
+    temp.child("tracked.txt").assert(predicate::path::exists());
+    temp.child("src/lib.rs").assert(predicate::path::exists());
+    temp.child("debug.log").assert(predicate::path::exists());
+    assert_eq!(guts_stdout(&temp, &["status", "--short"]), "");
+}
@@ -0,0 +1,53 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::guts_stdout;
+
+/// Test stash resets a modified file to HEAD and pop brings the modification back
+#[test]
+fn test_stash_push_and_pop() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("file.txt").write_str("committed\n").unwrap();
+    guts_stdout(&temp, &["init"]);
+    guts_stdout(&temp, &["add", "file.txt"]);
+    guts_stdout(&temp, &["commit", "-m", "initial"]);
+
+    temp.child("file.txt").write_str("work in progress\n").unwrap();
+    temp.child("new.txt").write_str("new\n").unwrap();
+    guts_stdout(&temp, &["add", "new.txt"]);
+
+    let output = guts_stdout(&temp, &["stash"]);
+    assert!(output.contains("WIP on main:"), "unexpected output: {}", output);
+    temp.child("file.txt").assert("committed\n");
+    temp.child("new.txt").assert(predicate::path::missing());
+    assert_eq!(guts_stdout(&temp, &["status", "--short"]), "");
+
+    guts_stdout(&temp, &["stash", "pop"]);
+    temp.child("file.txt").assert("work in progress\n");
+    temp.child("new.txt").assert("new\n");
+    assert_eq!(guts_stdout(&temp, &["status", "--short"]), " M file.txt\nA  new.txt\n");
+
+    // The entry was dropped
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["stash", "pop"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No stash entries found."));
+}
+
+/// Test stash with a clean working tree saves nothing
+#[test]
+fn test_stash_without_changes() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("file.txt").write_str("committed\n").unwrap();
+    guts_stdout(&temp, &["init"]);
+    guts_stdout(&temp, &["add", "file.txt"]);
+    guts_stdout(&temp, &["commit", "-m", "initial"]);
+
+    assert_eq!(guts_stdout(&temp, &["stash", "push"]), "No local changes to save\n");
+    temp.child(".git/stash").assert(predicate::path::missing());
+}
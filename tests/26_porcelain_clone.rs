@@ -0,0 +1,59 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::guts_stdout;
+
+/// Test clone copies the history and checks out the files of the current branch
+#[test]
+fn test_clone_local_repository() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let source = temp.child("source");
+    source.child("README.md").write_str("# Project\n").unwrap();
+    source.child("src/main.rs").write_str("fn main() {}\n").unwrap();
+    guts_stdout(source.path(), &["init", "-b", "trunk"]);
+    guts_stdout(source.path(), &["add", "."]);
+    guts_stdout(source.path(), &["commit", "-m", "first"]);
+    source.child("README.md").write_str("# Project\n\nMore.\n").unwrap();
+    guts_stdout(source.path(), &["add", "README.md"]);
+    guts_stdout(source.path(), &["commit", "-m", "second"]);
+
+    guts_stdout(temp.path(), &["clone", "source", "copy"]);
+
+    let copy = temp.child("copy");
+    copy.child("README.md").assert("# Project\n\nMore.\n");
+    copy.child("src/main.rs").assert("fn main() {}\n");
+    copy.child(".git/HEAD").assert("ref: refs/heads/trunk\n");
+    assert_eq!(
+        guts_stdout(copy.path(), &["log", "--oneline"]),
+        guts_stdout(source.path(), &["log", "--oneline"])
+    );
+    assert_eq!(guts_stdout(copy.path(), &["status", "--short"]), "");
+}
+
+/// Test clone refuses an existing destination and a source without commits
+#[test]
+fn test_clone_errors() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("empty").create_dir_all().unwrap();
+    guts_stdout(temp.path().join("empty"), &["init"]);
+    temp.child("taken").create_dir_all().unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["clone", "empty", "taken"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["clone", "empty", "copy"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("has no commits"));
+    temp.child("copy").assert(predicate::path::missing());
+}
@@ -0,0 +1,97 @@
+use assert_fs::prelude::*;
+
+mod common;
+use common::guts_stdout;
+
+fn git_stdout(temp: &assert_fs::TempDir, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .current_dir(temp.path())
+        .args(args)
+        .output()
+        .expect("git must be installed");
+    assert!(output.status.success(), "git {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// Test git sees the files staged by `guts add` through `.git/index`
+#[test]
+fn test_add_writes_git_index() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("a.txt").write_str("a\n").unwrap();
+    temp.child("dir/b.txt").write_str("b\n").unwrap();
+    temp.child("untracked.txt").write_str("not staged\n").unwrap();
+    guts_stdout(&temp, &["init"]);
+    guts_stdout(&temp, &["add", "a.txt", "dir"]);
+
+    assert_eq!(git_stdout(&temp, &["ls-files"]), guts_stdout(&temp, &["ls-files"]));
+    assert_eq!(git_stdout(&temp, &["ls-files"]), "a.txt\ndir/b.txt\n");
+
+    // Removing a file from the index is seen as well
+    guts_stdout(&temp, &["rm", "--cached", "a.txt"]);
+    assert_eq!(git_stdout(&temp, &["ls-files"]), "dir/b.txt\n");
+}
+
+/// Test the entries written to `.git/index` read back with their mode, SHA and stat data,
+/// and match `git ls-files --stage`
+#[test]
+fn test_git_index_round_trip() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("a.txt").write_str("a\n").unwrap();
+    temp.child("dir/run.sh").write_str("#!/bin/sh\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let path = temp.child("dir/run.sh");
+        std::fs::set_permissions(path.path(), std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    guts_stdout(&temp, &["init"]);
+    guts_stdout(&temp, &["add", "."]);
+
+    let entries = guts::core::status_binary_index::parse_git_index(&temp.path().join(".git")).unwrap();
+    let stage: String = entries
+        .iter()
+        .map(|entry| format!("{:o} {} 0\t{}\n", entry.mode, entry.blob_hash, entry.path.display()))
+        .collect();
+    assert_eq!(stage, git_stdout(&temp, &["ls-files", "--stage"]));
+    assert_eq!(stage, guts_stdout(&temp, &["ls-files", "--stage"]));
+
+    // Stat data describes the file on disk
+    let metadata = std::fs::metadata(temp.child("a.txt").path()).unwrap();
+    assert_eq!(entries[0].stat.size, metadata.len() as u32);
+    assert_ne!(entries[0].stat.mtime_secs, 0);
+
+    // A file changed after staging is not taken for clean by git
+    assert_eq!(git_stdout(&temp, &["status", "--short"]), "A  a.txt\nA  dir/run.sh\n");
+    temp.child("a.txt").write_str("changed\n").unwrap();
+    assert_eq!(git_stdout(&temp, &["status", "--short"]), "AM a.txt\nA  dir/run.sh\n");
+}
+
+/// Test saving the index keeps the stat data of unchanged entries and records it for
+/// the files a checkout writes
+#[test]
+fn test_git_index_stat_reused_between_writes() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("a.txt").write_str("a\n").unwrap();
+    guts_stdout(&temp, &["init"]);
+    guts_stdout(&temp, &["add", "a.txt"]);
+    let git_dir = temp.path().join(".git");
+    let staged_stat = guts::core::status_binary_index::parse_git_index(&git_dir).unwrap()[0].stat;
+
+    // Staging another file leaves the entry of a.txt as it was, even once a.txt changed
+    temp.child("a.txt").write_str("changed\n").unwrap();
+    temp.child("b.txt").write_str("b\n").unwrap();
+    guts_stdout(&temp, &["add", "b.txt"]);
+    let entries = guts::core::status_binary_index::parse_git_index(&git_dir).unwrap();
+    assert_eq!(entries[0].stat, staged_stat);
+    assert_ne!(entries[1].stat.mtime_secs, 0);
+    assert_eq!(git_stdout(&temp, &["status", "--short"]), "AM a.txt\nA  b.txt\n");
+
+    // Every file written by a switch gets its stat data
+    guts_stdout(&temp, &["add", "a.txt"]);
+    guts_stdout(&temp, &["commit", "-m", "Initial"]);
+    guts_stdout(&temp, &["checkout", "-b", "feature"]);
+    guts_stdout(&temp, &["checkout", "main"]);
+    let entries = guts::core::status_binary_index::parse_git_index(&git_dir).unwrap();
+    assert!(entries.iter().all(|entry| entry.stat.mtime_secs != 0));
+    assert_eq!(git_stdout(&temp, &["status", "--short"]), "");
+}
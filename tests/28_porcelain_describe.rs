@@ -0,0 +1,42 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::{commit_file_with_message, guts_stdout};
+
+/// Test describe counts the commits since the nearest tag
+#[test]
+fn test_describe_counts_commits_since_tag() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    commit_file_with_message(&temp, "file.txt", "v1\n", "first");
+
+    // A lightweight tag on the first commit
+    let tagged = guts_stdout(&temp, &["rev-parse", "HEAD"]);
+    temp.child(".git/refs/tags/v1.0").write_str(&tagged).unwrap();
+    assert_eq!(guts_stdout(&temp, &["describe"]), "v1.0\n");
+
+    commit_file_with_message(&temp, "file.txt", "v2\n", "second");
+    commit_file_with_message(&temp, "file.txt", "v3\n", "third");
+
+    let short = guts_stdout(&temp, &["rev-parse", "--short", "HEAD"]);
+    assert_eq!(guts_stdout(&temp, &["describe"]), format!("v1.0-2-g{}", short));
+    assert_eq!(guts_stdout(&temp, &["describe", "HEAD~2"]), "v1.0\n");
+}
+
+/// Test describe fails when no tag is reachable
+#[test]
+fn test_describe_without_tags() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    commit_file_with_message(&temp, "file.txt", "v1\n", "first");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("describe")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No tags can describe"));
+}
@@ -0,0 +1,45 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+
+fn guts_stdout(temp: &assert_fs::TempDir, args: &[&str]) -> String {
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .env("TZ", "UTC")
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "guts {:?} failed", args);
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// Test blame attributes kept lines to the first commit and changed lines to the second
+#[test]
+fn test_blame_two_commits() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    guts_stdout(&temp, &["config", "user.name", "Alice"]);
+    guts_stdout(&temp, &["config", "user.email", "alice@example.com"]);
+
+    temp.child("poem.txt").write_str("roses are red\nviolets are blue\nsugar is sweet\n").unwrap();
+    guts_stdout(&temp, &["add", "poem.txt"]);
+    guts_stdout(&temp, &["commit", "-m", "first"]);
+    let first = guts_stdout(&temp, &["rev-parse", "--short", "HEAD"]).trim().to_string();
+
+    temp.child("poem.txt").write_str("roses are red\nviolets are purple\nsugar is sweet\nand so are you\n").unwrap();
+    guts_stdout(&temp, &["add", "poem.txt"]);
+    guts_stdout(&temp, &["commit", "-m", "second", "--author", "Bob <bob@example.com>"]);
+    let second = guts_stdout(&temp, &["rev-parse", "--short", "HEAD"]).trim().to_string();
+
+    let blame = guts_stdout(&temp, &["blame", "poem.txt"]);
+    let lines: Vec<&str> = blame.lines().collect();
+    assert_eq!(lines.len(), 4);
+
+    assert!(lines[0].starts_with(&format!("{} (Alice ", first)), "{}", lines[0]);
+    assert!(lines[0].ends_with(") roses are red"));
+    assert!(lines[1].starts_with(&format!("{} (Bob   ", second)), "{}", lines[1]);
+    assert!(lines[1].ends_with(") violets are purple"));
+    assert!(lines[2].starts_with(&format!("{} (Alice ", first)));
+    assert!(lines[3].starts_with(&format!("{} (Bob   ", second)));
+    assert!(lines[3].ends_with(") and so are you"));
+}
@@ -0,0 +1,100 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::guts_stdout;
+
+fn object_path(temp: &assert_fs::TempDir, sha: &str) -> std::path::PathBuf {
+    temp.path().join(".git/objects").join(&sha[..2]).join(&sha[2..])
+}
+
+/// Test gc removes a dangling blob and keeps committed and staged objects
+#[test]
+fn test_gc_removes_dangling_objects() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("file.txt").write_str("committed\n").unwrap();
+    guts_stdout(&temp, &["init"]);
+    guts_stdout(&temp, &["add", "file.txt"]);
+    guts_stdout(&temp, &["commit", "-m", "initial"]);
+
+    temp.child("dangling.txt").write_str("nobody points here\n").unwrap();
+    let dangling = guts_stdout(&temp, &["hash-object", "-w", "dangling.txt"]).trim().to_string();
+    temp.child("staged.txt").write_str("only in the index\n").unwrap();
+    guts_stdout(&temp, &["add", "staged.txt"]);
+    let staged = guts_stdout(&temp, &["hash-object", "staged.txt"]).trim().to_string();
+
+    // The dry run only lists it
+    assert_eq!(guts_stdout(&temp, &["gc", "--dry-run"]), format!("Would remove {}\n", dangling));
+    assert!(object_path(&temp, &dangling).exists());
+
+    assert_eq!(guts_stdout(&temp, &["gc"]), format!("Removing {}\n", dangling));
+    assert!(!object_path(&temp, &dangling).exists());
+    assert!(object_path(&temp, &staged).exists());
+
+    // The history is intact
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["log", "--oneline"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("initial"));
+    assert_eq!(guts_stdout(&temp, &["gc"]), "");
+}
+
+/// Test gc deletes nothing when an object reachable from HEAD cannot be read
+#[test]
+fn test_gc_refuses_with_unreadable_reachable_object() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("file.txt").write_str("committed\n").unwrap();
+    guts_stdout(&temp, &["init"]);
+    guts_stdout(&temp, &["add", "file.txt"]);
+    guts_stdout(&temp, &["commit", "-m", "initial"]);
+    let tree = guts_stdout(&temp, &["write-tree"]).trim().to_string();
+    temp.child("dangling.txt").write_str("nobody points here\n").unwrap();
+    let dangling = guts_stdout(&temp, &["hash-object", "-w", "dangling.txt"]).trim().to_string();
+
+    // Whatever the lost tree pointed to is unknown, so nothing at all is removed
+    std::fs::remove_file(object_path(&temp, &tree)).unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("gc")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!("cannot read reachable object(s) {}", tree)));
+    assert!(object_path(&temp, &dangling).exists());
+}
+
+/// Test gc runs in a repository whose tree holds a submodule (160000) entry
+#[test]
+fn test_gc_skips_submodule_entries() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("file.txt").write_str("committed\n").unwrap();
+    guts_stdout(&temp, &["init"]);
+    let blob = guts_stdout(&temp, &["hash-object", "-w", "file.txt"]).trim().to_string();
+
+    // A tree pointing to a commit of another repository, which is never stored here
+    let raw_sha = |sha: &str| -> Vec<u8> {
+        (0..40).step_by(2).map(|i| u8::from_str_radix(&sha[i..i + 2], 16).unwrap()).collect()
+    };
+    let mut tree = b"100644 file.txt\0".to_vec();
+    tree.extend(raw_sha(&blob));
+    tree.extend(b"160000 sub\0");
+    tree.extend(raw_sha("0123456789abcdef0123456789abcdef01234567"));
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["hash-object", "-w", "-t", "tree", "--stdin"])
+        .write_stdin(tree)
+        .output()
+        .unwrap();
+    let tree = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let commit = guts_stdout(&temp, &["commit-tree", &tree, "-m", "with submodule"]).trim().to_string();
+    temp.child(".git/refs/heads/main").write_str(&format!("{}\n", commit)).unwrap();
+
+    assert_eq!(guts_stdout(&temp, &["gc"]), "");
+    assert!(object_path(&temp, &blob).exists());
+}
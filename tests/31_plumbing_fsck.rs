@@ -0,0 +1,63 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::guts_stdout;
+
+fn committed_repo(temp: &assert_fs::TempDir) -> String {
+    temp.child("file.txt").write_str(&"some committed content\n".repeat(10)).unwrap();
+    guts_stdout(temp, &["init"]);
+    guts_stdout(temp, &["add", "file.txt"]);
+    guts_stdout(temp, &["commit", "-m", "initial"]);
+    guts_stdout(temp, &["hash-object", "file.txt"]).trim().to_string()
+}
+
+/// Test fsck is quiet on a sound repository and reports dangling objects
+#[test]
+fn test_fsck_sound_repository() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    committed_repo(&temp);
+    assert_eq!(guts_stdout(&temp, &["fsck"]), "");
+
+    temp.child("loose.txt").write_str("dangling\n").unwrap();
+    let dangling = guts_stdout(&temp, &["hash-object", "-w", "loose.txt"]).trim().to_string();
+    assert_eq!(guts_stdout(&temp, &["fsck"]), format!("dangling blob {}\n", dangling));
+}
+
+/// Test fsck flags an object whose file was altered
+#[test]
+fn test_fsck_flags_corrupt_object() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let blob = committed_repo(&temp);
+
+    let object_path = temp.path().join(".git/objects").join(&blob[..2]).join(&blob[2..]);
+    let mut data = std::fs::read(&object_path).unwrap();
+    let middle = data.len() / 2;
+    data[middle] ^= 0xff;
+    std::fs::write(&object_path, data).unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("fsck")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!("corrupt object {}", blob)));
+}
+
+/// Test fsck reports a blob a tree points to but that is gone
+#[test]
+fn test_fsck_reports_missing_object() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let blob = committed_repo(&temp);
+    std::fs::remove_file(temp.path().join(".git/objects").join(&blob[..2]).join(&blob[2..])).unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .arg("fsck")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!("missing blob {}", blob)));
+}
@@ -0,0 +1,143 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::fs;
+
+mod common;
+use common::{commit_file, guts_stdout};
+
+/// Repository where `feature` is one commit ahead of `main`, with `main` checked out
+fn feature_ahead_of_main(temp: &assert_fs::TempDir) {
+    guts_stdout(temp, &["init"]);
+    commit_file(temp, "main.txt", "main\n");
+    guts_stdout(temp, &["checkout", "-b", "feature"]);
+    commit_file(temp, "feature.txt", "feature\n");
+    guts_stdout(temp, &["checkout", "main"]);
+}
+
+fn parent_count(temp: &assert_fs::TempDir, rev: &str) -> usize {
+    let sha = guts_stdout(temp, &["rev-parse", rev]).trim().to_string();
+    guts_stdout(temp, &["cat-file", "-p", &sha])
+        .lines()
+        .filter(|line| line.starts_with("parent "))
+        .count()
+}
+
+/// Test merging a branch HEAD is an ancestor of only moves the ref
+#[test]
+fn test_merge_fast_forward() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    feature_ahead_of_main(&temp);
+    let feature = guts_stdout(&temp, &["rev-parse", "feature"]).trim().to_string();
+
+    let output = guts_stdout(&temp, &["merge", "feature"]);
+    assert!(output.contains("Fast-forward"), "{}", output);
+
+    assert_eq!(guts_stdout(&temp, &["rev-parse", "HEAD"]).trim(), feature);
+    assert_eq!(parent_count(&temp, "HEAD"), 1);
+    assert_eq!(fs::read_to_string(temp.path().join("feature.txt")).unwrap(), "feature\n");
+    assert_eq!(guts_stdout(&temp, &["status", "--short"]), "");
+}
+
+/// Test --no-ff records a merge commit even when a fast-forward is possible
+#[test]
+fn test_merge_no_ff_creates_merge_commit() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    feature_ahead_of_main(&temp);
+    let feature = guts_stdout(&temp, &["rev-parse", "feature"]).trim().to_string();
+
+    guts_stdout(&temp, &["merge", "--no-ff", "feature"]);
+
+    assert_ne!(guts_stdout(&temp, &["rev-parse", "HEAD"]).trim(), feature);
+    assert_eq!(parent_count(&temp, "HEAD"), 2);
+    assert_eq!(fs::read_to_string(temp.path().join("feature.txt")).unwrap(), "feature\n");
+}
+
+/// Test the merge base of a history holding a merge commit is the nearest common ancestor
+#[test]
+fn test_merge_base_after_a_merge_commit() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    commit_file(&temp, "shared.txt", "v1\n");
+    guts_stdout(&temp, &["checkout", "-b", "feature"]);
+    commit_file(&temp, "shared.txt", "v2\n");
+    guts_stdout(&temp, &["checkout", "main"]);
+    commit_file(&temp, "main.txt", "main\n");
+    guts_stdout(&temp, &["merge", "--no-ff", "feature"]);
+
+    // `topic` starts at the merge commit, which is the base of the next merge
+    guts_stdout(&temp, &["checkout", "-b", "topic"]);
+    commit_file(&temp, "shared.txt", "v3\n");
+    commit_file(&temp, "topic1.txt", "topic\n");
+    commit_file(&temp, "topic2.txt", "topic\n");
+    guts_stdout(&temp, &["checkout", "main"]);
+    commit_file(&temp, "after.txt", "after\n");
+
+    guts_stdout(&temp, &["merge", "topic"]);
+    assert_eq!(parent_count(&temp, "HEAD"), 2);
+    assert_eq!(fs::read_to_string(temp.path().join("shared.txt")).unwrap(), "v3\n");
+    assert_eq!(fs::read_to_string(temp.path().join("after.txt")).unwrap(), "after\n");
+}
+
+/// Test merging a branch that is already part of HEAD does nothing
+#[test]
+fn test_merge_already_up_to_date() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    feature_ahead_of_main(&temp);
+    guts_stdout(&temp, &["checkout", "feature"]);
+    let head = guts_stdout(&temp, &["rev-parse", "HEAD"]).trim().to_string();
+
+    assert_eq!(guts_stdout(&temp, &["merge", "main"]).trim(), "Already up to date.");
+    assert_eq!(guts_stdout(&temp, &["rev-parse", "HEAD"]).trim(), head);
+}
+
+/// Test a conflicting merge fails, lists every conflict and leaves the working tree as it was
+#[test]
+fn test_merge_conflict_leaves_working_tree_untouched() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    commit_file(&temp, "a.txt", "base a\n");
+    commit_file(&temp, "b.txt", "base b\n");
+
+    guts_stdout(&temp, &["checkout", "-b", "feature"]);
+    commit_file(&temp, "a.txt", "feature a\n");
+    commit_file(&temp, "b.txt", "feature b\n");
+    commit_file(&temp, "only-feature.txt", "feature\n");
+
+    guts_stdout(&temp, &["checkout", "main"]);
+    commit_file(&temp, "a.txt", "main a\n");
+    commit_file(&temp, "b.txt", "main b\n");
+    let head = guts_stdout(&temp, &["rev-parse", "HEAD"]).trim().to_string();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["merge", "feature"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Merge conflict in a.txt"))
+        .stderr(predicate::str::contains("Merge conflict in b.txt"));
+
+    assert_eq!(guts_stdout(&temp, &["rev-parse", "HEAD"]).trim(), head);
+    assert_eq!(fs::read_to_string(temp.path().join("a.txt")).unwrap(), "main a\n");
+    assert_eq!(fs::read_to_string(temp.path().join("b.txt")).unwrap(), "main b\n");
+    assert!(!temp.path().join("only-feature.txt").exists());
+    assert_eq!(guts_stdout(&temp, &["status", "--short"]), "");
+}
+
+/// Test merge refuses to run over uncommitted changes
+#[test]
+fn test_merge_refuses_local_changes() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    feature_ahead_of_main(&temp);
+    temp.child("main.txt").write_str("edited\n").unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["merge", "feature"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("local changes would be overwritten"));
+    assert_eq!(fs::read_to_string(temp.path().join("main.txt")).unwrap(), "edited\n");
+}
@@ -0,0 +1,55 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::fs;
+
+mod common;
+use common::{commit_file, guts_stdout};
+
+/// Test switch -c creates a branch and switch goes back to an existing one
+#[test]
+fn test_switch_create_and_back() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    commit_file(&temp, "main.txt", "main\n");
+
+    assert_eq!(guts_stdout(&temp, &["switch", "-c", "feature"]).trim(), "Switched to a new branch 'feature'");
+    assert_eq!(fs::read_to_string(temp.path().join(".git/HEAD")).unwrap().trim(), "ref: refs/heads/feature");
+    commit_file(&temp, "feature.txt", "feature\n");
+
+    assert_eq!(guts_stdout(&temp, &["switch", "main"]).trim(), "Switched to branch 'main'");
+    assert_eq!(fs::read_to_string(temp.path().join(".git/HEAD")).unwrap().trim(), "ref: refs/heads/main");
+    assert!(!temp.path().join("feature.txt").exists());
+}
+
+/// Test switch only takes branches, so a file name is not silently accepted
+#[test]
+fn test_switch_rejects_non_branch() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    commit_file(&temp, "main.txt", "main\n");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["switch", "main.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid reference: main.txt"));
+}
+
+/// Test restore brings back the committed content of a file and leaves HEAD alone
+#[test]
+fn test_restore_file() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    commit_file(&temp, "notes.txt", "committed\n");
+    let head = guts_stdout(&temp, &["rev-parse", "HEAD"]);
+
+    temp.child("notes.txt").write_str("scribbled over\n").unwrap();
+    guts_stdout(&temp, &["restore", "notes.txt"]);
+
+    assert_eq!(fs::read_to_string(temp.path().join("notes.txt")).unwrap(), "committed\n");
+    assert_eq!(guts_stdout(&temp, &["rev-parse", "HEAD"]), head);
+    assert_eq!(guts_stdout(&temp, &["status", "--short"]), "");
+}
@@ -0,0 +1,47 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+mod common;
+use common::{commit_file, guts_stdout};
+
+/// Test checking out a raw commit detaches HEAD, and status, commit, log and merge cope with it
+#[test]
+fn test_detached_head_commands() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    commit_file(&temp, "first.txt", "first\n");
+    let first = guts_stdout(&temp, &["rev-parse", "HEAD"]).trim().to_string();
+    commit_file(&temp, "second.txt", "second\n");
+    let main = guts_stdout(&temp, &["rev-parse", "main"]).trim().to_string();
+
+    guts_stdout(&temp, &["checkout", &first]);
+    assert_eq!(fs::read_to_string(temp.path().join(".git/HEAD")).unwrap().trim(), first);
+    assert!(!temp.path().join("second.txt").exists());
+
+    let status = guts_stdout(&temp, &["status"]);
+    assert!(status.starts_with(&format!("HEAD detached at {}\n", &first[..7])), "{}", status);
+
+    // Committing moves HEAD itself and leaves the branch alone
+    commit_file(&temp, "detached.txt", "detached\n");
+    let detached = guts_stdout(&temp, &["rev-parse", "HEAD"]).trim().to_string();
+    assert_ne!(detached, first);
+    assert_eq!(fs::read_to_string(temp.path().join(".git/HEAD")).unwrap().trim(), detached);
+    assert_eq!(guts_stdout(&temp, &["rev-parse", "main"]).trim(), main);
+
+    let log = guts_stdout(&temp, &["log", "--oneline"]);
+    assert_eq!(log.lines().count(), 2, "{}", log);
+    assert!(log.contains("detached.txt") && log.contains("first.txt"));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["merge", "main"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("HEAD is detached"));
+
+    // Back on the branch
+    guts_stdout(&temp, &["checkout", "main"]);
+    assert!(guts_stdout(&temp, &["status"]).starts_with("On branch main\n"));
+}
@@ -0,0 +1,50 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+mod common;
+use common::guts_stdout;
+
+/// Test remotes can be added, listed and removed, and are stored like git stores them
+#[test]
+fn test_remote_add_list_remove() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    assert_eq!(guts_stdout(&temp, &["remote"]), "");
+
+    guts_stdout(&temp, &["remote", "add", "origin", "https://example.com/repo.git"]);
+    guts_stdout(&temp, &["remote", "add", "backup", "/srv/backup.git"]);
+
+    assert_eq!(guts_stdout(&temp, &["remote"]), "origin\nbackup\n");
+    assert_eq!(
+        guts_stdout(&temp, &["remote", "-v"]),
+        "origin\thttps://example.com/repo.git (fetch)\norigin\thttps://example.com/repo.git (push)\n\
+         backup\t/srv/backup.git (fetch)\nbackup\t/srv/backup.git (push)\n"
+    );
+
+    // git reads the same config
+    let git_url = std::process::Command::new("git")
+        .current_dir(temp.path())
+        .args(["config", "--get", "remote.origin.url"])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&git_url.stdout).trim(), "https://example.com/repo.git");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["remote", "add", "origin", "/elsewhere"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("remote origin already exists"));
+
+    guts_stdout(&temp, &["remote", "remove", "origin"]);
+    assert_eq!(guts_stdout(&temp, &["remote"]), "backup\n");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["remote", "rm", "origin"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No such remote"));
+}
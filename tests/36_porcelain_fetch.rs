@@ -0,0 +1,96 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::{commit_file, guts_stdout};
+
+/// Test fetch copies the remote's objects and updates the remote-tracking refs
+#[test]
+fn test_fetch_from_sibling_repository() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let upstream = temp.child("upstream");
+    let local = temp.child("local");
+    upstream.create_dir_all().unwrap();
+    local.create_dir_all().unwrap();
+
+    guts_stdout(upstream.path(), &["init"]);
+    commit_file(&upstream, "readme.txt", "hello\n");
+    let first = guts_stdout(upstream.path(), &["rev-parse", "HEAD"]).trim().to_string();
+
+    guts_stdout(local.path(), &["init"]);
+    guts_stdout(local.path(), &["remote", "add", "origin", "../upstream"]);
+
+    let output = guts_stdout(local.path(), &["fetch"]);
+    assert!(output.contains("[new branch]      main -> origin/main"), "{}", output);
+    assert_eq!(
+        std::fs::read_to_string(local.path().join(".git/refs/remotes/origin/main")).unwrap().trim(),
+        first
+    );
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(local.path())
+        .arg("show-ref")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("{} refs/remotes/origin/main", first)));
+    // The whole history is here: commit, tree and blob
+    let commit = guts_stdout(local.path(), &["cat-file", "-p", &first]);
+    let tree = commit.lines().next().unwrap().strip_prefix("tree ").unwrap().to_string();
+    let entries = guts_stdout(local.path(), &["cat-file", "-p", &tree]);
+    let blob = entries.split_whitespace().nth(2).unwrap().to_string();
+    assert_eq!(guts_stdout(local.path(), &["cat-file", "-p", &blob]), "hello\n");
+
+    // Nothing new: nothing printed
+    assert_eq!(guts_stdout(local.path(), &["fetch", "origin"]), "");
+
+    commit_file(&upstream, "more.txt", "more\n");
+    let second = guts_stdout(upstream.path(), &["rev-parse", "HEAD"]).trim().to_string();
+    let output = guts_stdout(local.path(), &["fetch", "origin"]);
+    assert!(output.contains(&format!("{}..{}  main -> origin/main", &first[..7], &second[..7])), "{}", output);
+    guts_stdout(local.path(), &["fsck"]);
+}
+
+/// Test fetching an unknown remote fails
+#[test]
+fn test_fetch_unknown_remote() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(temp.path(), &["init"]);
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["fetch", "nowhere"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("'nowhere' does not appear to be a guts repository"));
+}
+
+/// Test a fetch reaching a missing object copies nothing and updates no ref
+#[test]
+fn test_fetch_missing_object_copies_nothing() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let upstream = temp.child("upstream");
+    let local = temp.child("local");
+    upstream.create_dir_all().unwrap();
+    local.create_dir_all().unwrap();
+
+    guts_stdout(upstream.path(), &["init"]);
+    commit_file(&upstream, "readme.txt", "hello\n");
+    let head = guts_stdout(upstream.path(), &["rev-parse", "HEAD"]).trim().to_string();
+    let blob = guts_stdout(upstream.path(), &["hash-object", "readme.txt"]).trim().to_string();
+    std::fs::remove_file(upstream.path().join(".git/objects").join(&blob[..2]).join(&blob[2..])).unwrap();
+
+    guts_stdout(local.path(), &["init"]);
+    guts_stdout(local.path(), &["remote", "add", "origin", "../upstream"]);
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(local.path())
+        .arg("fetch")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!("missing object {} in the remote", blob)));
+
+    // Neither the commit nor its tree made it, so the next fetch starts over
+    assert!(!local.path().join(".git/objects").join(&head[..2]).join(&head[2..]).exists());
+    assert!(!local.path().join(".git/refs/remotes/origin/main").exists());
+}
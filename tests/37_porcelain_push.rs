@@ -0,0 +1,129 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::process::Command as StdCommand;
+
+mod common;
+use common::{commit_file, guts_stdout};
+
+/// `refs/heads/main` of the bare repository, as git reads it
+fn remote_main(temp: &assert_fs::TempDir) -> String {
+    let output = StdCommand::new("git")
+        .current_dir(temp.path().join("upstream.git"))
+        .args(["rev-parse", "refs/heads/main"])
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+/// Test push creates then fast-forwards a branch in a bare sibling, and refuses to rewrite it without -f
+#[test]
+fn test_push_to_bare_sibling() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    StdCommand::new("git")
+        .current_dir(temp.path())
+        .args(["init", "--bare", "upstream.git"])
+        .output()
+        .unwrap();
+    let local = temp.child("local");
+    local.create_dir_all().unwrap();
+    guts_stdout(local.path(), &["init"]);
+    guts_stdout(local.path(), &["remote", "add", "origin", "../upstream.git"]);
+
+    commit_file(&local, "first.txt", "first\n");
+    let first = guts_stdout(local.path(), &["rev-parse", "HEAD"]).trim().to_string();
+    let output = guts_stdout(local.path(), &["push", "origin", "main"]);
+    assert!(output.contains(" * [new branch]      main -> main"), "{}", output);
+    assert_eq!(remote_main(&temp), first);
+
+    commit_file(&local, "second.txt", "second\n");
+    let second = guts_stdout(local.path(), &["rev-parse", "HEAD"]).trim().to_string();
+    guts_stdout(local.path(), &["push"]);
+    assert_eq!(remote_main(&temp), second);
+    assert_eq!(
+        std::fs::read_to_string(local.path().join(".git/refs/remotes/origin/main")).unwrap().trim(),
+        second
+    );
+    assert_eq!(guts_stdout(local.path(), &["push"]), "Everything up-to-date\n");
+
+    // git finds every object it needs
+    let fsck = StdCommand::new("git")
+        .current_dir(temp.path().join("upstream.git"))
+        .args(["fsck", "--strict"])
+        .output()
+        .unwrap();
+    assert!(fsck.status.success(), "{}", String::from_utf8_lossy(&fsck.stderr));
+
+    // Rewritten history is only pushed with -f
+    guts_stdout(local.path(), &["reset", "--hard", &first]);
+    commit_file(&local, "other.txt", "other\n");
+    let rewritten = guts_stdout(local.path(), &["rev-parse", "HEAD"]).trim().to_string();
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(local.path())
+        .arg("push")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("non-fast-forward"));
+    assert_eq!(remote_main(&temp), second);
+
+    let output = guts_stdout(local.path(), &["push", "-f"]);
+    assert!(output.contains("(forced update)"), "{}", output);
+    assert_eq!(remote_main(&temp), rewritten);
+}
+
+/// Test push refuses to move the branch checked out in a non-bare repository
+#[test]
+fn test_push_refuses_checked_out_branch() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let upstream = temp.child("upstream");
+    let local = temp.child("local");
+    upstream.create_dir_all().unwrap();
+    local.create_dir_all().unwrap();
+    guts_stdout(upstream.path(), &["init"]);
+    guts_stdout(local.path(), &["init"]);
+    commit_file(&local, "file.txt", "content\n");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(local.path())
+        .args(["push", "../upstream", "main"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("refusing to update checked out branch"));
+}
+
+/// Test a repository made with `guts init --bare` has no working tree and accepts pushes
+#[test]
+fn test_push_to_guts_bare_repository() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(temp.path(), &["init", "--bare", "upstream.git"]);
+    let bare = temp.path().join("upstream.git");
+    assert!(bare.join("HEAD").is_file());
+    assert!(bare.join("objects").is_dir());
+    assert!(!bare.join(".git").exists());
+    assert!(std::fs::read_to_string(bare.join("config")).unwrap().contains("bare = true"));
+
+    let local = temp.child("local");
+    local.create_dir_all().unwrap();
+    guts_stdout(local.path(), &["init"]);
+    guts_stdout(local.path(), &["remote", "add", "origin", "../upstream.git"]);
+    commit_file(&local, "first.txt", "first\n");
+    let head = guts_stdout(local.path(), &["rev-parse", "HEAD"]).trim().to_string();
+
+    guts_stdout(local.path(), &["push", "origin", "main"]);
+    assert_eq!(remote_main(&temp), head);
+
+    // git sees a sound repository
+    let fsck = StdCommand::new("git").current_dir(&bare).args(["fsck", "--strict"]).output().unwrap();
+    assert!(fsck.status.success(), "{}", String::from_utf8_lossy(&fsck.stderr));
+
+    // Initializing it again is refused
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["init", "--bare", "upstream.git"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already exists"));
+}
@@ -0,0 +1,72 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::process::Command as StdCommand;
+
+mod common;
+use common::guts_stdout;
+
+fn committed_repo(temp: &assert_fs::TempDir) -> String {
+    guts_stdout(temp, &["init"]);
+    guts_stdout(temp, &["config", "user.name", "Alice"]);
+    guts_stdout(temp, &["config", "user.email", "alice@example.com"]);
+    temp.child("file.txt").write_str("content\n").unwrap();
+    guts_stdout(temp, &["add", "file.txt"]);
+    guts_stdout(temp, &["commit", "-m", "initial"]);
+    guts_stdout(temp, &["rev-parse", "HEAD"]).trim().to_string()
+}
+
+/// Test an annotated tag is a tag object that cat-file and git can read back
+#[test]
+fn test_annotated_tag() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let head = committed_repo(&temp);
+
+    guts_stdout(&temp, &["tag", "-a", "v1.0", "-m", "First release"]);
+    let tag_sha = std::fs::read_to_string(temp.path().join(".git/refs/tags/v1.0")).unwrap().trim().to_string();
+    assert_ne!(tag_sha, head);
+
+    assert_eq!(guts_stdout(&temp, &["cat-file", "-t", &tag_sha]), "tag\n");
+    let pretty = guts_stdout(&temp, &["cat-file", "-p", &tag_sha]);
+    let lines: Vec<&str> = pretty.lines().collect();
+    assert_eq!(lines[0], format!("object {}", head));
+    assert_eq!(lines[1], "type commit");
+    assert_eq!(lines[2], "tag v1.0");
+    assert!(lines[3].starts_with("tagger Alice <alice@example.com> "), "{}", lines[3]);
+    assert_eq!(&lines[4..], ["", "First release"]);
+
+    let git_pretty = StdCommand::new("git")
+        .current_dir(temp.path())
+        .args(["cat-file", "-p", &tag_sha])
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&git_pretty.stdout), pretty);
+
+    // Revisions and describe see through the tag object
+    assert_eq!(guts_stdout(&temp, &["rev-parse", "v1.0"]).trim(), head);
+    assert_eq!(guts_stdout(&temp, &["describe"]).trim(), "v1.0");
+    guts_stdout(&temp, &["fsck"]);
+}
+
+/// Test lightweight tags point at the commit, and tags are listed by name
+#[test]
+fn test_lightweight_tag_and_list() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let head = committed_repo(&temp);
+
+    guts_stdout(&temp, &["tag", "snapshot"]);
+    guts_stdout(&temp, &["tag", "-m", "annotated", "release"]);
+    assert_eq!(
+        std::fs::read_to_string(temp.path().join(".git/refs/tags/snapshot")).unwrap().trim(),
+        head
+    );
+    assert_eq!(guts_stdout(&temp, &["tag"]), "release\nsnapshot\n");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["tag", "snapshot"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("tag 'snapshot' already exists"));
+}
@@ -0,0 +1,51 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+mod common;
+use common::guts_stdout;
+
+/// Repository with the `main` and `feature` branches and a `v1` tag, all on one commit
+fn repo_with_branch_and_tag(temp: &assert_fs::TempDir) -> String {
+    guts_stdout(temp, &["init"]);
+    temp.child("file.txt").write_str("content\n").unwrap();
+    guts_stdout(temp, &["add", "file.txt"]);
+    guts_stdout(temp, &["commit", "-m", "initial"]);
+    guts_stdout(temp, &["checkout", "-b", "feature"]);
+    guts_stdout(temp, &["tag", "v1"]);
+    guts_stdout(temp, &["rev-parse", "HEAD"]).trim().to_string()
+}
+
+/// Test show-ref lists every ref, and --heads / --tags keep one namespace each
+#[test]
+fn test_show_ref_filters() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let sha = repo_with_branch_and_tag(&temp);
+
+    let heads = format!("{sha} refs/heads/feature\n{sha} refs/heads/main\n");
+    let tags = format!("{sha} refs/tags/v1\n");
+    assert_eq!(guts_stdout(&temp, &["show-ref"]), format!("{}{}", heads, tags));
+    assert_eq!(guts_stdout(&temp, &["show-ref", "--heads"]), heads);
+    assert_eq!(guts_stdout(&temp, &["show-ref", "--tags"]), tags);
+    assert_eq!(guts_stdout(&temp, &["show-ref", "--heads", "--tags"]), format!("{}{}", heads, tags));
+}
+
+/// Test --verify shows one exact ref and fails on anything else
+#[test]
+fn test_show_ref_verify() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let sha = repo_with_branch_and_tag(&temp);
+
+    assert_eq!(guts_stdout(&temp, &["show-ref", "--verify", "refs/tags/v1"]), format!("{} refs/tags/v1\n", sha));
+    assert_eq!(guts_stdout(&temp, &["show-ref", "--verify", "HEAD"]), format!("{} HEAD\n", sha));
+
+    for name in ["main", "refs/heads/missing"] {
+        Command::cargo_bin("guts")
+            .unwrap()
+            .current_dir(temp.path())
+            .args(["show-ref", "--verify", name])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!("'{}' - not a valid ref", name)));
+    }
+}
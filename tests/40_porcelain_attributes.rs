@@ -0,0 +1,66 @@
+use assert_fs::prelude::*;
+use std::fs;
+
+mod common;
+use common::guts_stdout;
+
+/// Test a CRLF file marked as text is stored with LF, as git stores it
+#[test]
+fn test_add_normalizes_crlf_for_text_files() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    temp.child(".gutsattributes").write_str("*.txt text\n*.bin -text\n").unwrap();
+    temp.child("notes.txt").write_str("one\r\ntwo\r\n").unwrap();
+    temp.child("data.bin").write_str("one\r\ntwo\r\n").unwrap();
+    guts_stdout(&temp, &["add", "."]);
+
+    let tree = guts_stdout(&temp, &["write-tree"]).trim().to_string();
+    let listing = guts_stdout(&temp, &["ls-tree", &tree]);
+    // `printf 'one\ntwo\n' | git hash-object --stdin`
+    assert!(listing.contains("814f4a422927b82f5f8a43f8fab6d3839e3983f2\tnotes.txt"), "{}", listing);
+    assert_eq!(guts_stdout(&temp, &["cat-file", "-p", "814f4a422927b82f5f8a43f8fab6d3839e3983f2"]), "one\ntwo\n");
+    // Files that are not text keep their bytes
+    assert!(!listing.contains("814f4a422927b82f5f8a43f8fab6d3839e3983f2\tdata.bin"), "{}", listing);
+
+    // The CRLF file on disk matches what was staged
+    assert_eq!(
+        guts_stdout(&temp, &["status", "--short"]),
+        "A  .gutsattributes\nA  data.bin\nA  notes.txt\n"
+    );
+}
+
+/// Test eol=crlf writes CRLF line endings back on checkout
+#[test]
+fn test_checkout_writes_crlf_for_eol_crlf() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    temp.child(".gutsattributes").write_str("*.txt eol=crlf\n").unwrap();
+    temp.child("notes.txt").write_str("one\ntwo\n").unwrap();
+    guts_stdout(&temp, &["add", "."]);
+    guts_stdout(&temp, &["commit", "-m", "notes"]);
+
+    fs::remove_file(temp.path().join("notes.txt")).unwrap();
+    guts_stdout(&temp, &["checkout", "--", "notes.txt"]);
+    assert_eq!(fs::read_to_string(temp.path().join("notes.txt")).unwrap(), "one\r\ntwo\r\n");
+    assert_eq!(guts_stdout(&temp, &["status", "--short"]), "");
+}
+
+/// Test a text file too large to be read at once is normalized like a small one
+#[test]
+fn test_add_normalizes_crlf_for_large_text_files() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    temp.child(".gutsattributes").write_str("*.txt text\n").unwrap();
+    // Over the 8 MiB streaming threshold, with CRs falling on chunk boundaries
+    temp.child("big.txt").write_str(&"line\r\n".repeat(1_500_000)).unwrap();
+    guts_stdout(&temp, &["add", "."]);
+
+    let tree = guts_stdout(&temp, &["write-tree"]).trim().to_string();
+    let listing = guts_stdout(&temp, &["ls-tree", &tree]);
+    let blob = listing.lines().find(|line| line.ends_with("\tbig.txt")).unwrap().split_whitespace().nth(2).unwrap().to_string();
+    assert_eq!(guts_stdout(&temp, &["cat-file", "-s", &blob]).trim(), (5 * 1_500_000).to_string());
+    assert!(!guts_stdout(&temp, &["cat-file", "-p", &blob]).contains('\r'));
+
+    // What status hashes matches what was staged
+    assert_eq!(guts_stdout(&temp, &["status", "--short"]), "A  .gutsattributes\nA  big.txt\n");
+}
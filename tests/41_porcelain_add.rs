@@ -0,0 +1,41 @@
+use assert_fs::prelude::*;
+use std::fs;
+
+mod common;
+use common::guts_stdout;
+
+/// Test add --dry-run lists the files it would stage and leaves the index unchanged
+#[test]
+fn test_add_dry_run_leaves_index_unchanged() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    temp.child(".gutsignore").write_str("*.log\n").unwrap();
+    temp.child("src/main.rs").write_str("fn main() {}\n").unwrap();
+    temp.child("debug.log").write_str("noise\n").unwrap();
+    let index_before = fs::read(temp.path().join(".git/simple_index.json")).ok();
+
+    let output = guts_stdout(&temp, &["add", "--dry-run", "."]);
+    assert!(output.contains("add 'src/main.rs'\n"), "{}", output);
+    assert!(!output.contains("debug.log"), "{}", output);
+    assert_eq!(fs::read(temp.path().join(".git/simple_index.json")).ok(), index_before);
+    assert_eq!(guts_stdout(&temp, &["ls-files"]), "");
+
+    // -v also names the ignored files
+    let output = guts_stdout(&temp, &["add", "-n", "-v", "."]);
+    assert!(output.contains("skip 'debug.log' (ignored)\n"), "{}", output);
+    assert!(output.contains("add 'src/main.rs'\n"), "{}", output);
+}
+
+/// Test add --quiet on many files still stages them all and prints the summary
+#[test]
+fn test_add_quiet_many_files_prints_summary() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    for i in 0..250 {
+        temp.child(format!("files/file{:03}.txt", i)).write_str(&format!("content {}\n", i)).unwrap();
+    }
+
+    let output = guts_stdout(&temp, &["add", "--quiet", "."]);
+    assert!(output.starts_with("Added 250 files:"), "{}", output);
+    assert_eq!(guts_stdout(&temp, &["ls-files"]).lines().count(), 250);
+}
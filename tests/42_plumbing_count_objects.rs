@@ -0,0 +1,36 @@
+use assert_fs::prelude::*;
+
+mod common;
+use common::guts_stdout;
+
+/// Total size of the files under `.git/objects/xx/`
+fn objects_size(temp: &assert_fs::TempDir) -> u64 {
+    walkdir::WalkDir::new(temp.path().join(".git/objects"))
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.metadata().unwrap().len())
+        .sum()
+}
+
+/// Test count-objects counts every loose object and -v splits them by type
+#[test]
+fn test_count_objects_after_commits() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    assert_eq!(guts_stdout(&temp, &["count-objects"]), "count: 0, size: 0 bytes\n");
+
+    for content in ["one\n", "two\n"] {
+        temp.child("file.txt").write_str(content).unwrap();
+        guts_stdout(&temp, &["add", "file.txt"]);
+        guts_stdout(&temp, &["commit", "-m", content.trim()]);
+    }
+
+    // Each commit brought a blob, a tree and the commit itself
+    assert_eq!(
+        guts_stdout(&temp, &["count-objects"]),
+        format!("count: 6, size: {} bytes\n", objects_size(&temp))
+    );
+    let verbose = guts_stdout(&temp, &["count-objects", "-v"]);
+    assert!(verbose.ends_with("blob: 2\ncommit: 2\ntree: 2\n"), "{}", verbose);
+}
@@ -0,0 +1,42 @@
+use std::process::Command as StdCommand;
+
+mod common;
+use common::{commit_file, guts_stdout};
+
+/// Test rev-list --count counts every commit made, across both sides of a merge
+#[test]
+fn test_rev_list_count() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    commit_file(&temp, "a.txt", "a.txt");
+    commit_file(&temp, "b.txt", "b.txt");
+    assert_eq!(guts_stdout(&temp, &["rev-list", "--count", "HEAD"]), "2\n");
+
+    guts_stdout(&temp, &["checkout", "-b", "feature"]);
+    commit_file(&temp, "feature.txt", "feature.txt");
+    guts_stdout(&temp, &["checkout", "main"]);
+    commit_file(&temp, "c.txt", "c.txt");
+    guts_stdout(&temp, &["merge", "feature"]);
+
+    // 4 commits plus the merge commit
+    assert_eq!(guts_stdout(&temp, &["rev-list", "--count", "HEAD"]), "5\n");
+    assert_eq!(guts_stdout(&temp, &["rev-list", "--count", "feature"]), "3\n");
+    assert_eq!(guts_stdout(&temp, &["rev-list", "--count", "--max-count", "2", "HEAD"]), "2\n");
+
+    let head = guts_stdout(&temp, &["rev-parse", "HEAD"]);
+    let listed = guts_stdout(&temp, &["rev-list", "-n", "1", "HEAD"]);
+    assert_eq!(listed, head);
+}
+
+/// Test rev-list prints the same SHAs as git on a linear history
+#[test]
+fn test_rev_list_matches_git() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    guts_stdout(&temp, &["init"]);
+    for name in ["a.txt", "b.txt", "c.txt"] {
+        commit_file(&temp, name, name);
+    }
+
+    let git_output = StdCommand::new("git").current_dir(temp.path()).args(["rev-list", "HEAD"]).output().unwrap();
+    assert_eq!(guts_stdout(&temp, &["rev-list", "HEAD"]), String::from_utf8_lossy(&git_output.stdout));
+}
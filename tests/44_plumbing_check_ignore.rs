@@ -0,0 +1,51 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+/// Test check-ignore prints only the ignored paths
+#[test]
+fn test_check_ignore_lists_ignored_paths() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    temp.child(".gutsignore").write_str("# logs\n*.log\n!keep.log\n").unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["check-ignore", "foo.log", "keep.log", "main.rs"])
+        .assert()
+        .success()
+        .stdout("foo.log\n");
+}
+
+/// Test check-ignore -v names the file, line and pattern of the matching rule
+#[test]
+fn test_check_ignore_verbose_names_the_rule() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).arg("init").assert().success();
+    temp.child(".gutsignore").write_str("# logs\n*.log\n!keep.log\n").unwrap();
+    temp.child("sub/.gutsignore").write_str("build/\n").unwrap();
+    temp.child("sub/build").create_dir_all().unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["check-ignore", "-v", "foo.log", "keep.log", "sub/build/out.o", "main.rs"])
+        .assert()
+        .success()
+        .stdout(".gutsignore:2:*.log\tfoo.log\n.gutsignore:3:!keep.log\tkeep.log\nsub/.gutsignore:1:build/\tsub/build/out.o\n");
+}
+
+/// Test check-ignore fails outside a repository
+#[test]
+fn test_check_ignore_outside_repository() {
+    let temp = assert_fs::TempDir::new().unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["check-ignore", "foo.log"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not a git repository"));
+}
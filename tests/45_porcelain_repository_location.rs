@@ -0,0 +1,94 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+
+/// Test -C runs a whole workflow from outside the repository
+#[test]
+fn test_dash_c_runs_commands_in_another_directory() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let repo = temp.child("repo");
+    repo.create_dir_all().unwrap();
+    repo.child("file.txt").write_str("content\n").unwrap();
+
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["-C", "repo", "init"]).assert().success();
+    assert!(repo.path().join(".git/HEAD").is_file());
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["-C", "repo", "add", "file.txt"])
+        .assert()
+        .success();
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["-C", "repo", "commit", "-m", "From outside"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["-C", "repo", "log"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("From outside"));
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["-C", "repo", "status", "-s"])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+/// Test --git-dir finds the repository of its working tree
+#[test]
+fn test_git_dir_locates_the_repository() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    let repo = temp.child("repo");
+    repo.create_dir_all().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(repo.path()).arg("init").assert().success();
+    repo.child("new.txt").write_str("new\n").unwrap();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["--git-dir", "repo/.git", "status", "-s"])
+        .assert()
+        .success()
+        .stdout("?? new.txt\n");
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["--git-dir", "repo/.git", "--work-tree", "repo", "status", "-s"])
+        .assert()
+        .success()
+        .stdout("?? new.txt\n");
+}
+
+/// Test repository location flags that cannot be honored fail clearly
+#[test]
+fn test_repository_location_errors() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("repo").create_dir_all().unwrap();
+    temp.child("other").create_dir_all().unwrap();
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(["-C", "repo", "init"]).assert().success();
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["-C", "missing", "status"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot change to 'missing'"));
+
+    Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(temp.path())
+        .args(["--git-dir", "repo/.git", "--work-tree", "other", "status"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is not the .git directory of 'other'"));
+}
@@ -0,0 +1,58 @@
+use assert_cmd::Command;
+use assert_fs::prelude::*;
+use predicates::prelude::*;
+use std::fs;
+
+fn guts(temp: &assert_fs::TempDir, args: &[&str]) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("guts").unwrap().current_dir(temp.path()).args(args).assert()
+}
+
+/// Test export writes an old commit into a new directory and leaves the repository alone
+#[test]
+fn test_export_root_commit_into_snapshot() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("file.txt").write_str("v1\n").unwrap();
+    temp.child("src/lib.rs").write_str("// first\n").unwrap();
+    guts(&temp, &["init"]).success();
+    guts(&temp, &["add", "."]).success();
+    guts(&temp, &["commit", "-m", "Root"]).success();
+
+    temp.child("file.txt").write_str("v2\n").unwrap();
+    temp.child("later.txt").write_str("later\n").unwrap();
+    guts(&temp, &["add", "."]).success();
+    guts(&temp, &["commit", "-m", "Second"]).success();
+    let head = fs::read_to_string(temp.path().join(".git/refs/heads/main")).unwrap();
+
+    guts(&temp, &["export", "HEAD~1", "snapshot"])
+        .success()
+        .stdout(predicate::str::contains("into 'snapshot'"));
+
+    assert_eq!(fs::read_to_string(temp.path().join("snapshot/file.txt")).unwrap(), "v1\n");
+    assert_eq!(fs::read_to_string(temp.path().join("snapshot/src/lib.rs")).unwrap(), "// first\n");
+    assert!(!temp.path().join("snapshot/later.txt").exists());
+
+    // HEAD and the working tree still are on the second commit
+    assert_eq!(fs::read_to_string(temp.path().join(".git/refs/heads/main")).unwrap(), head);
+    assert_eq!(fs::read_to_string(temp.path().join("file.txt")).unwrap(), "v2\n");
+}
+
+/// Test export refuses a directory that already holds files
+#[test]
+fn test_export_refuses_non_empty_directory() {
+    let temp = assert_fs::TempDir::new().unwrap();
+    temp.child("file.txt").write_str("v1\n").unwrap();
+    guts(&temp, &["init"]).success();
+    guts(&temp, &["add", "file.txt"]).success();
+    guts(&temp, &["commit", "-m", "Root"]).success();
+    temp.child("busy/keep.txt").write_str("mine\n").unwrap();
+
+    guts(&temp, &["export", "HEAD", "busy"])
+        .failure()
+        .stderr(predicate::str::contains("'busy' is not empty"));
+    assert_eq!(fs::read_to_string(temp.path().join("busy/keep.txt")).unwrap(), "mine\n");
+
+    // An empty directory is fine
+    temp.child("empty").create_dir_all().unwrap();
+    guts(&temp, &["export", "HEAD", "empty"]).success();
+    assert_eq!(fs::read_to_string(temp.path().join("empty/file.txt")).unwrap(), "v1\n");
+}
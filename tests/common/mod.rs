@@ -0,0 +1,39 @@
+//! Helpers shared by the integration tests, included with `mod common;`
+// Each test file is its own crate and only uses some of them
+#![allow(dead_code)]
+
+use assert_cmd::Command;
+use assert_fs::fixture::ChildPath;
+use assert_fs::prelude::*;
+use std::path::Path;
+
+/// Runs `guts <args>` in `dir`, checks it succeeded and returns its stdout
+pub fn guts_stdout(dir: impl AsRef<Path>, args: &[&str]) -> String {
+    let output = Command::cargo_bin("guts")
+        .unwrap()
+        .current_dir(dir.as_ref())
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "guts {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// `guts_stdout` without the surrounding whitespace, e.g. a lone SHA
+pub fn guts_stdout_trimmed(dir: impl AsRef<Path>, args: &[&str]) -> String {
+    guts_stdout(dir, args).trim().to_string()
+}
+
+/// Commit `name` with `content` on the current branch, the file name being the message
+pub fn commit_file(dir: impl AsRef<Path>, name: &str, content: &str) {
+    commit_file_with_message(dir, name, content, name);
+}
+
+/// Commit `name` with `content` on the current branch and return the new HEAD
+pub fn commit_file_with_message(dir: impl AsRef<Path>, name: &str, content: &str, message: &str) -> String {
+    let dir = dir.as_ref();
+    ChildPath::new(dir.join(name)).write_str(content).unwrap();
+    guts_stdout(dir, &["add", name]);
+    guts_stdout(dir, &["commit", "-m", message]);
+    guts_stdout_trimmed(dir, &["rev-parse", "HEAD"])
+}